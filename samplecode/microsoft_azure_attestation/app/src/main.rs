@@ -17,28 +17,22 @@
 
 #![allow(non_snake_case)]
 
-extern crate base64;
 extern crate base64_url;
-extern crate jsonwebkey;
-extern crate jsonwebtoken;
 extern crate libloading;
 extern crate serde;
 extern crate serde_json;
+extern crate sgx_ratls;
 extern crate sgx_types;
 extern crate sgx_urts;
 extern crate sha2;
-extern crate x509_certificate;
 
-use std::convert::{TryFrom, TryInto};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use base64::{engine::general_purpose, Engine as _};
-use jsonwebkey::{JsonWebKey, Key, PublicExponent, RsaPublic};
-use jsonwebtoken as jwt;
 use serde::{Deserialize, Serialize};
+use sgx_ratls::{Jwks, MaaClaims, MaaError, MaaVerifier};
 use sgx_types::*;
 use sgx_urts::SgxEnclave;
 use sha2::{Digest, Sha256};
-use x509_certificate::{X509Certificate, X509CertificateError};
 
 static ENCLAVE_FILE: &'static str = "enclave.signed.so";
 const ATTESTATION_PROVIDER_URL: &'static str = "https://sharedeus.eus.attest.azure.net";
@@ -203,97 +197,17 @@ impl SgxRuntimeData {
 struct JwtResponse {
     pub token: String,
 }
-#[derive(Deserialize)]
-struct RawJsonWebKey {
-    pub x5c: Vec<String>,
-    pub kid: String,
-    pub kty: String,
-    // pub alg: String,
-}
-#[derive(Deserialize)]
-struct RawJsonWebKeySet {
-    pub keys: Vec<RawJsonWebKey>,
-}
 
-#[derive(Debug)]
-struct JsonWebKeySet {
-    pub keys: Vec<JsonWebKey>,
-}
-
-impl TryFrom<RawJsonWebKeySet> for JsonWebKeySet {
-    type Error = X509CertificateError;
-    // This method only works for RS256 algorithm using by Azure Attestation
-    fn try_from(raw_set: RawJsonWebKeySet) -> Result<Self, X509CertificateError> {
-        let mut keys = Vec::new();
-        for key in raw_set.keys {
-            if key.kty != "RSA" {
-                return Err(X509CertificateError::UnknownKeyAlgorithm(key.kty));
-            }
-            let raw_cert = general_purpose::STANDARD
-                .decode(key.x5c[0].clone())
-                .unwrap();
-            let x509 = X509Certificate::from_der(&raw_cert)?;
-            let pubkey = x509.rsa_public_key_data()?;
-            let rsa_pub = RsaPublic {
-                e: PublicExponent,
-                n: pubkey.modulus.as_slice().into(),
-            };
-            let rsa_key = Key::RSA {
-                public: rsa_pub,
-                private: None,
-            };
-            let mut jwk = JsonWebKey::new(rsa_key);
-            jwk.key_id = Some(key.kid);
-            keys.push(jwk);
-        }
-        Ok(JsonWebKeySet { keys })
-    }
-}
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-struct TokenClaims {
-    pub x_ms_sgx_is_debuggable: bool,
-    pub x_ms_sgx_mrenclave: String,
-    pub x_ms_sgx_mrsigner: String,
-    // pub x_ms_sgx_product_id: u64,
-    pub x_ms_sgx_svn: u64,
-    pub x_ms_sgx_ehd: String,
+/// Validate JsonWebToken with JsonWebKeySet,
+/// only works for token from default attestation provider.
+fn validate_json_web_token(jwt: &str, jwks: &[u8], now: i64) -> Result<MaaClaims, MaaError> {
+    let mut verifier = MaaVerifier::new(Jwks::from_json(jwks)?);
+    verifier.issuer(ATTESTATION_PROVIDER_URL);
+    verifier.verify(jwt, now)
 }
 
-/// Validate JsonWebToken with JsonWebKeySet,
-/// only works for RS256 algorithm and token from default attestation provider.
-fn validate_json_web_token(jwt: String, jwks: JsonWebKeySet) -> jwt::errors::Result<TokenClaims> {
-    let header = jwt::decode_header(&jwt)?;
-    if header.kid.is_none() {
-        return Err(jwt::errors::Error::from(
-            jwt::errors::ErrorKind::InvalidToken,
-        ));
-    }
-    // find the corresponding key
-    let mut idx: Option<usize> = None;
-    for (i, key) in jwks.keys.iter().enumerate() {
-        if key.key_id.is_some() {
-            if key.key_id == header.kid {
-                idx = Some(i);
-            }
-        }
-    }
-    if idx.is_none() {
-        // cannot find corresponding pubkey
-        return Err(jwt::errors::Error::from(
-            jwt::errors::ErrorKind::InvalidRsaKey,
-        ));
-    }
-    let pem = jwks.keys[idx.unwrap()].key.try_to_pem().unwrap();
-    // println!("\n{pem}");
-    let key = jwt::DecodingKey::from_rsa_pem(pem.as_bytes())?;
-    // prepare validation
-    let algo = jwt::Algorithm::RS256;
-    let mut validation = jwt::Validation::new(algo);
-    validation.validate_exp = false;
-    validation.iss = Some(ATTESTATION_PROVIDER_URL.to_string());
-    // decode JWT with the public key
-    Ok(jwt::decode::<TokenClaims>(&jwt, &key, &validation)?.claims)
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 fn main() {
@@ -323,27 +237,20 @@ fn main() {
         .get(format!("{}/certs", ATTESTATION_PROVIDER_URL))
         .send()
         .unwrap();
-    let resp_text = res.text().unwrap();
-    // println!("{resp_text}");
-    let raw_key_set: RawJsonWebKeySet = serde_json::from_str(&resp_text).unwrap();
-    let jwk_set: JsonWebKeySet = raw_key_set.try_into().unwrap();
-    let claims = validate_json_web_token(jwt, jwk_set).unwrap();
-    println!(
-        "Verified SGX debuggable status: {}",
-        claims.x_ms_sgx_is_debuggable
-    );
-    println!(
-        "Verified SGX enclave measurement: {}",
-        claims.x_ms_sgx_mrenclave
-    );
-    println!(
-        "Verified SGX signer measurement: {}",
-        claims.x_ms_sgx_mrsigner
-    );
-    println!("Verified SGX SGX SVN: {}", claims.x_ms_sgx_svn);
+    let jwks = res.bytes().unwrap();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let claims = validate_json_web_token(&jwt, &jwks, now).unwrap();
+    assert_eq!(claims.enclave_held_data, runtime_data);
+    println!("Verified SGX debuggable status: {}", claims.is_debuggable);
+    println!("Verified SGX enclave measurement: {}", hex(&claims.mr_enclave));
+    println!("Verified SGX signer measurement: {}", hex(&claims.mr_signer));
+    println!("Verified SGX SGX SVN: {}", claims.svn);
     println!(
         "Verified SGX runtime data: {}",
-        std::str::from_utf8(&base64_url::decode(&claims.x_ms_sgx_ehd).unwrap()).unwrap()
+        std::str::from_utf8(&claims.enclave_held_data).unwrap()
     );
 }
 
@@ -385,30 +292,29 @@ mod tests {
     .eyJhYXMtZWhkIjoiVkdocGN5QnBjeUJ6YjIxbElISjFiblJwYldVZ1pHRjBZUSIsImV4cCI6MTY5NTc2NjU0NywiaWF0IjoxNjk1NzM3NzQ3LCJpcy1kZWJ1Z2dhYmxlIjpmYWxzZSwiaXNzIjoiaHR0cHM6Ly9zaGFyZWRldXMuZXVzLmF0dGVzdC5henVyZS5uZXQiLCJqdGkiOiJmM2Q3NDU2ZjIwOGVhNzc5MTkxY2U0ZGVkMDY2YWI3ZmUyY2I3NTVhY2Y1MDYzOThiMzIzOGVmMjY3ZjgzZDlmIiwibWFhLWF0dGVzdGF0aW9uY29sbGF0ZXJhbCI6eyJxZWlkY2VydHNoYXNoIjoiYTY0ZDY0OTE5ODUwN2Q4YjU3ZTMzZjYzYWIyNjY4MzhmNDNmMzI3YmQ0YWFjYzc4NTEwYjY5NzZlZDA0NmUxMCIsInFlaWRjcmxoYXNoIjoiMTMxMTNlZWQ1NTEyZTBmMTcwYjVhY2RkNjkwM2VkNTcxYmU0MGFjOGJkMTVlNzhhYzYwZmI3YWZiOTE2YjFiYiIsInFlaWRoYXNoIjoiNzcwMWY2NDcwMGI3ZjUwNWQ3YjRiN2E5M2U0NWQ1Y2RlOGNmYzg2NWI2MGYxZGQ0OWVjYmVlOTc5MGMzMzcyZSIsInF1b3RlaGFzaCI6Ijg5ZWUxMWE4ODNhMDgwYWFiNmUyNjI2MmMxMDUwMzk4YjY3NWVkYzI0YWMzNGUyMzcwNDg1MWM0NjUzNzBmMTAiLCJ0Y2JpbmZvY2VydHNoYXNoIjoiYTY0ZDY0OTE5ODUwN2Q4YjU3ZTMzZjYzYWIyNjY4MzhmNDNmMzI3YmQ0YWFjYzc4NTEwYjY5NzZlZDA0NmUxMCIsInRjYmluZm9jcmxoYXNoIjoiMTMxMTNlZWQ1NTEyZTBmMTcwYjVhY2RkNjkwM2VkNTcxYmU0MGFjOGJkMTVlNzhhYzYwZmI3YWZiOTE2YjFiYiIsInRjYmluZm9oYXNoIjoiODJkMTA5ZmIzMDhmMjRhOTBlNDM5MzZlYTllMTJiNTViMDUyNTAyMjFmZGEyMjk0Zjc0YWI1ODE3ZTcxYmVhNCJ9LCJtYWEtZWhkIjoiVkdocGN5QnBjeUJ6YjIxbElISjFiblJwYldVZ1pHRjBZUSIsIm5iZiI6MTY5NTczNzc0NywicHJvZHVjdC1pZCI6MSwic2d4LW1yZW5jbGF2ZSI6ImY1NjczNWFhNDI1NjM2MjdhODMyZTBjN2JhOTkxMTM4MmViNjhhZmVkNzU4MzBiM2Y5NzI2NmYzZTY3YmRjOTkiLCJzZ3gtbXJzaWduZXIiOiJhNTk1YzZjNTgwNWRhMGM5YzRjYjkyMDMzNGQzNTRhZWFlZTIyMDdlNDc5ZGZmNjc5ZDVmMzYwMzc1ZjU1N2RkIiwic3ZuIjoxLCJ0ZWUiOiJzZ3giLCJ4LW1zLWF0dGVzdGF0aW9uLXR5cGUiOiJzZ3giLCJ4LW1zLXBvbGljeSI6eyJpcy1kZWJ1Z2dhYmxlIjpmYWxzZSwicHJvZHVjdC1pZCI6MSwic2d4LW1yZW5jbGF2ZSI6ImY1NjczNWFhNDI1NjM2MjdhODMyZTBjN2JhOTkxMTM4MmViNjhhZmVkNzU4MzBiM2Y5NzI2NmYzZTY3YmRjOTkiLCJzZ3gtbXJzaWduZXIiOiJhNTk1YzZjNTgwNWRhMGM5YzRjYjkyMDMzNGQzNTRhZWFlZTIyMDdlNDc5ZGZmNjc5ZDVmMzYwMzc1ZjU1N2RkIiwic3ZuIjoxLCJ0ZWUiOiJzZ3gifSwieC1tcy1wb2xpY3ktaGFzaCI6Ik93RXZwU1ZFV0E1ZWlzQ0VuY0J0OE5TWkZMWURSS29MYW9PTlByWmdvZVkiLCJ4LW1zLXNneC1jb2xsYXRlcmFsIjp7InFlaWRjZXJ0c2hhc2giOiJhNjRkNjQ5MTk4NTA3ZDhiNTdlMzNmNjNhYjI2NjgzOGY0M2YzMjdiZDRhYWNjNzg1MTBiNjk3NmVkMDQ2ZTEwIiwicWVpZGNybGhhc2giOiIxMzExM2VlZDU1MTJlMGYxNzBiNWFjZGQ2OTAzZWQ1NzFiZTQwYWM4YmQxNWU3OGFjNjBmYjdhZmI5MTZiMWJiIiwicWVpZGhhc2giOiI3NzAxZjY0NzAwYjdmNTA1ZDdiNGI3YTkzZTQ1ZDVjZGU4Y2ZjODY1YjYwZjFkZDQ5ZWNiZWU5NzkwYzMzNzJlIiwicXVvdGVoYXNoIjoiODllZTExYTg4M2EwODBhYWI2ZTI2MjYyYzEwNTAzOThiNjc1ZWRjMjRhYzM0ZTIzNzA0ODUxYzQ2NTM3MGYxMCIsInRjYmluZm9jZXJ0c2hhc2giOiJhNjRkNjQ5MTk4NTA3ZDhiNTdlMzNmNjNhYjI2NjgzOGY0M2YzMjdiZDRhYWNjNzg1MTBiNjk3NmVkMDQ2ZTEwIiwidGNiaW5mb2NybGhhc2giOiIxMzExM2VlZDU1MTJlMGYxNzBiNWFjZGQ2OTAzZWQ1NzFiZTQwYWM4YmQxNWU3OGFjNjBmYjdhZmI5MTZiMWJiIiwidGNiaW5mb2hhc2giOiI4MmQxMDlmYjMwOGYyNGE5MGU0MzkzNmVhOWUxMmI1NWIwNTI1MDIyMWZkYTIyOTRmNzRhYjU4MTdlNzFiZWE0In0sIngtbXMtc2d4LWVoZCI6IlZHaHBjeUJwY3lCemIyMWxJSEoxYm5ScGJXVWdaR0YwWVEiLCJ4LW1zLXNneC1pcy1kZWJ1Z2dhYmxlIjpmYWxzZSwieC1tcy1zZ3gtbXJlbmNsYXZlIjoiZjU2NzM1YWE0MjU2MzYyN2E4MzJlMGM3YmE5OTExMzgyZWI2OGFmZWQ3NTgzMGIzZjk3MjY2ZjNlNjdiZGM5OSIsIngtbXMtc2d4LW1yc2lnbmVyIjoiYTU5NWM2YzU4MDVkYTBjOWM0Y2I5MjAzMzRkMzU0YWVhZWUyMjA3ZTQ3OWRmZjY3OWQ1ZjM2MDM3NWY1NTdkZCIsIngtbXMtc2d4LXByb2R1Y3QtaWQiOjEsIngtbXMtc2d4LXJlcG9ydC1kYXRhIjoiOTRiYTQ0ZjM5OWI5YzRhZGM4MzBkNzhjNjdmNDkxNGNiYmMzYTM4MzhmNzk2ZDJlNzY2NjU5NDc1NGMwNjdkOTAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAiLCJ4LW1zLXNneC1zdm4iOjEsIngtbXMtc2d4LXRjYmlkZW50aWZpZXIiOiIxMCIsIngtbXMtdmVyIjoiMS4wIn0\
     .QJyc2Ka98fiy6r_FDbfzjgV3TCTFmODe-32FiGSiAyCz_ZO5Bmw9XnQI2Rzs-Yrq6b4bDV4WlMRmJePRXzI1i2cR3xtWhnJKQjTz_EYp63OfH8SsiWci_BQpTnzoiAbUi5EdrbYz3CXQtThTy_XHyYmJVEY8qLZ0dzSO4QmBxz6q8BfcEp7fhwuKzibetQlJ3zdz-TwIK0l0WbZ1jBG93oXPnQy9KhDAyDX533DvYjDjAE3FPnjV5cMZfjmcLVxTL6DROEIlZtm_yn5zJSWlQBrFRDxoYxoYtQlEeaOn-klKZj4ECJF498mACo5fYW20UhXv5ZZNdEMYVNb4dEVf-w";
 
+    // The time the sample JWT was issued at.
+    const RAW_TOKEN_ISSUED_AT: i64 = 1695737747;
+
     #[test]
     fn raw_key_conversion() {
-        let raw_set: RawJsonWebKeySet = serde_json::from_str(&RAW_KEY_SET).unwrap();
-        let jwk_set: JsonWebKeySet = raw_set.try_into().unwrap();
-        assert_eq!(jwk_set.keys.len(), 3);
+        let jwk_set = Jwks::from_json(RAW_KEY_SET.as_bytes()).unwrap();
+        assert_eq!(jwk_set.len(), 3);
     }
 
     #[test]
     fn token_validation() {
-        let raw_set: RawJsonWebKeySet = serde_json::from_str(&RAW_KEY_SET).unwrap();
-        let jwks: JsonWebKeySet = raw_set.try_into().unwrap();
-        let claims = validate_json_web_token(RAW_TOKEN.to_string(), jwks).unwrap();
-        assert!(!claims.x_ms_sgx_is_debuggable);
+        let claims =
+            validate_json_web_token(RAW_TOKEN, RAW_KEY_SET.as_bytes(), RAW_TOKEN_ISSUED_AT)
+                .unwrap();
+        assert!(!claims.is_debuggable);
         assert_eq!(
-            claims.x_ms_sgx_mrenclave,
+            hex(&claims.mr_enclave),
             "f56735aa42563627a832e0c7ba9911382eb68afed75830b3f97266f3e67bdc99"
         );
         assert_eq!(
-            claims.x_ms_sgx_mrsigner,
+            hex(&claims.mr_signer),
             "a595c6c5805da0c9c4cb920334d354aeaee2207e479dff679d5f360375f557dd"
         );
-        assert_eq!(
-            base64_url::decode(&claims.x_ms_sgx_ehd).unwrap(),
-            b"This is some runtime data",
-        )
+        assert_eq!(claims.enclave_held_data, b"This is some runtime data")
     }
 }
@@ -21,7 +21,7 @@
 extern crate sgx_types;
 extern crate sgx_urts;
 use sgx_types::*;
-use sgx_urts::SgxEnclave;
+use sgx_urts::{LaunchTokenCache, SgxEnclave};
 
 use std::os::unix::io::{IntoRawFd, AsRawFd};
 use std::env;
@@ -31,7 +31,7 @@ use std::str;
 const BUFFER_SIZE: usize = 1024;
 
 static ENCLAVE_FILE: &'static str = "enclave.signed.so";
-static ENCLAVE_TOKEN: &'static str = "enclave.token";
+static ENCLAVE_TOKEN_DIR: &'static str = ".";
 
 extern {
     fn run_server(eid: sgx_enclave_id_t, retval: *mut sgx_status_t,
@@ -136,17 +136,16 @@ fn ocall_get_update_info (platform_blob: * const sgx_platform_info_t,
 }
 
 fn init_enclave() -> SgxResult<SgxEnclave> {
-    let mut launch_token: sgx_launch_token_t = [0; 1024];
-    let mut launch_token_updated: i32 = 0;
+    // The launch token is cached in the working directory, and rewritten when it is updated.
+    let token_cache = LaunchTokenCache::new(ENCLAVE_TOKEN_DIR);
     // call sgx_create_enclave to initialize an enclave instance
     // Debug Support: set 2nd parameter to 1
     let debug = 1;
     let mut misc_attr = sgx_misc_attribute_t {secs_attr: sgx_attributes_t { flags:0, xfrm:0}, misc_select:0};
-    SgxEnclave::create(ENCLAVE_FILE,
-                       debug,
-                       &mut launch_token,
-                       &mut launch_token_updated,
-                       &mut misc_attr)
+    SgxEnclave::create_with_token_cache(ENCLAVE_FILE,
+                                        debug,
+                                        &mut misc_attr,
+                                        &token_cache)
 }
 
 enum Mode {
@@ -20,7 +20,7 @@
 extern crate sgx_types;
 extern crate sgx_urts;
 use sgx_types::*;
-use sgx_urts::SgxEnclave;
+use sgx_urts::{EnclaveBuilder, SgxEnclave, SwitchlessConfig};
 
 use std::time::Instant;
 
@@ -36,19 +36,18 @@ extern {
 }
 
 fn init_enclave(num_uworker : u32, num_tworker : u32) -> SgxResult<SgxEnclave> {
-    let mut launch_token: sgx_launch_token_t = [0; 1024];
-    let mut launch_token_updated: i32 = 0;
-    // call sgx_create_enclave to initialize an enclave instance
-    // Debug Support: set 2nd parameter to 1
-    let debug = 1;
-    let mut misc_attr = sgx_misc_attribute_t {secs_attr: sgx_attributes_t { flags:0, xfrm:0}, misc_select:0};
-    SgxEnclave::create_with_workers(ENCLAVE_FILE,
-                                    debug,
-                                    &mut launch_token,
-                                    &mut launch_token_updated,
-                                    &mut misc_attr,
-                                    num_uworker,
-                                    num_tworker)
+    let mut config = SwitchlessConfig::new();
+    config.num_uworkers(num_uworker)
+          .num_tworkers(num_tworker)
+          .on_worker_miss(|worker_type, stats| {
+              println!("[-] {:?} workers missed {} tasks", worker_type, stats.missed);
+          });
+    // call sgx_create_enclave_ex to initialize an enclave instance
+    // Debug Support: set debug to true
+    EnclaveBuilder::new(ENCLAVE_FILE)
+        .debug(true)
+        .switchless(&config)
+        .build()
 }
 
 #[no_mangle]
@@ -117,6 +116,8 @@ fn main() {
         },
     };
 
+    let counters = enclave.switchless_counters().unwrap();
+
     benchmark_empty_ocall(enclave.geteid(),0);
     benchmark_empty_ocall(enclave.geteid(),1);
     benchmark_empty_ecall(enclave.geteid(),0);
@@ -125,4 +126,9 @@ fn main() {
     println!("[+] say_something success...");
 
     enclave.destroy();
+
+    // The workers report their counters when they exit.
+    let stats = counters.snapshot();
+    println!("[+] switchless ocalls: {:?}", stats.ocalls);
+    println!("[+] switchless ecalls: {:?}", stats.ecalls);
 }
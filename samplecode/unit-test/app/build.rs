@@ -28,6 +28,7 @@ fn main () {
 
     println!("cargo:rustc-link-search=native={}/lib64", sdk_dir);
     println!("cargo:rustc-link-lib=static=sgx_uprotected_fs");
+    println!("cargo:rustc-link-lib=static=sgx_ukey_exchange");
     match is_sim.as_ref() {
         "SW" => {
             println!("cargo:rustc-link-lib=dylib=sgx_urts_sim");
@@ -15,15 +15,97 @@
 // specific language governing permissions and limitations
 // under the License..
 
+extern crate sgx_ra_sp;
 extern crate sgx_types;
+extern crate sgx_ucrypto;
 extern crate sgx_urts;
+use sgx_ra_sp::*;
 use sgx_types::*;
-use sgx_urts::SgxEnclave;
+use sgx_ucrypto::{rsgx_rijndael128_cmac_slice, SgxEccHandle};
+use sgx_urts::{rsgx_get_extended_epid_group_id, rsgx_ra_get_msg1, rsgx_ra_proc_msg2, SgxEnclave};
+use std::mem;
+use std::slice;
 
 static ENCLAVE_FILE: &'static str = "enclave.signed.so";
 
+// The CONFIGID and CONFIGSVN the enclave is created with and checked against.
+const CONFIG_ID: sgx_config_id_t = [0xa5; SGX_CONFIGID_SIZE];
+const CONFIG_SVN: sgx_config_svn_t = 0x0102;
+
 extern {
     fn test_main_entrance(eid: sgx_enclave_id_t, retval: *mut size_t) -> sgx_status_t;
+    fn test_ra_session_init(eid: sgx_enclave_id_t, retval: *mut sgx_status_t,
+                            sp_pub_key: *const sgx_ec256_public_t,
+                            context: *mut sgx_ra_context_t) -> sgx_status_t;
+    fn test_ra_session_proc_msg4(eid: sgx_enclave_id_t, retval: *mut sgx_status_t,
+                                 msg4: *const u8, msg4_size: size_t) -> sgx_status_t;
+    fn test_ra_session_mac(eid: sgx_enclave_id_t, retval: *mut sgx_status_t,
+                           data: *const u8, data_size: size_t,
+                           mac: *mut sgx_mac_t) -> sgx_status_t;
+    fn test_ra_session_close(eid: sgx_enclave_id_t) -> sgx_status_t;
+    fn test_enclave_identity(eid: sgx_enclave_id_t, retval: *mut sgx_status_t,
+                             config_id: *const u8, config_svn: u16) -> sgx_status_t;
+    fn sgx_ra_proc_msg2_trusted(eid: sgx_enclave_id_t, retval: *mut sgx_status_t,
+                                context: sgx_ra_context_t, p_msg2: *const sgx_ra_msg2_t,
+                                p_qe_target: *const sgx_target_info_t, p_report: *mut sgx_report_t,
+                                nonce: *mut sgx_quote_nonce_t) -> sgx_status_t;
+    fn sgx_ra_get_msg3_trusted(eid: sgx_enclave_id_t, retval: *mut sgx_status_t,
+                               context: sgx_ra_context_t, quote_size: u32,
+                               qe_report: *mut sgx_report_t, p_msg3: *mut sgx_ra_msg3_t,
+                               msg3_size: u32) -> sgx_status_t;
+}
+
+fn ecall_status(result: sgx_status_t, retval: sgx_status_t) -> SgxError {
+    match (result, retval) {
+        (sgx_status_t::SGX_SUCCESS, sgx_status_t::SGX_SUCCESS) => Ok(()),
+        (sgx_status_t::SGX_SUCCESS, e) | (e, _) => Err(e),
+    }
+}
+
+// Runs the remote attestation and key exchange between the enclave and a stand-in service
+// provider. The quote is not sent to the Intel Attestation Service: in simulation mode it
+// is not signed by a real platform.
+fn test_remote_attestation(eid: sgx_enclave_id_t) -> SgxError {
+    let ecc = SgxEccHandle::new();
+    ecc.open()?;
+    let (sp_key, _) = ecc.create_secret_key_pair()?;
+    let sp = RaServiceProvider::new(sp_key, sgx_spid_t::default(),
+                                    sgx_quote_sign_type_t::SGX_LINKABLE_SIGNATURE)?;
+
+    let mut retval = sgx_status_t::SGX_SUCCESS;
+    let mut context: sgx_ra_context_t = 0;
+    let result = unsafe { test_ra_session_init(eid, &mut retval, sp.public_key(), &mut context) };
+    ecall_status(result, retval)?;
+
+    let outcome = (|| -> SgxError {
+        let mut session = sp.proc_msg0(rsgx_get_extended_epid_group_id()?)?;
+        let msg1 = rsgx_ra_get_msg1(context, eid, sgx_ra_get_ga)?;
+        let msg2 = session.proc_msg1(&msg1, &[])?;
+        let msg3 = rsgx_ra_proc_msg2(context, eid, sgx_ra_proc_msg2_trusted,
+                                     sgx_ra_get_msg3_trusted, &msg2)?;
+        let report = session.proc_msg3(&msg3)?;
+        assert_eq!(report.isv_prod_id, 0);
+        assert_eq!(session.secret_key().err(), Some(sgx_status_t::SGX_ERROR_INVALID_STATE));
+
+        let msg4 = session.gen_msg4(true)?;
+        let msg4 = unsafe {
+            slice::from_raw_parts(&msg4 as *const sgx_ra_msg4_t as *const u8,
+                                  mem::size_of::<sgx_ra_msg4_t>())
+        };
+        let result = unsafe { test_ra_session_proc_msg4(eid, &mut retval, msg4.as_ptr(), msg4.len()) };
+        ecall_status(result, retval)?;
+
+        // Both sides derived the same MK.
+        let data = b"remote attestation";
+        let mut mac = sgx_mac_t::default();
+        let result = unsafe { test_ra_session_mac(eid, &mut retval, data.as_ptr(), data.len(), &mut mac) };
+        ecall_status(result, retval)?;
+        assert_eq!(mac, rsgx_rijndael128_cmac_slice(session.mac_key()?.as_raw(), &data[..])?);
+        Ok(())
+    })();
+
+    unsafe { test_ra_session_close(eid) };
+    outcome
 }
 
 fn init_enclave() -> SgxResult<SgxEnclave> {
@@ -33,11 +115,21 @@ fn init_enclave() -> SgxResult<SgxEnclave> {
     // Debug Support: set 2nd parameter to 1
     let debug = 1;
     let mut misc_attr = sgx_misc_attribute_t {secs_attr: sgx_attributes_t { flags:0, xfrm:0}, misc_select:0};
-    SgxEnclave::create(ENCLAVE_FILE,
-                       debug,
-                       &mut launch_token,
-                       &mut launch_token_updated,
-                       &mut misc_attr)
+    SgxEnclave::create_with_kss_config(ENCLAVE_FILE,
+                                       debug,
+                                       &mut launch_token,
+                                       &mut launch_token_updated,
+                                       &mut misc_attr,
+                                       &CONFIG_ID,
+                                       CONFIG_SVN)
+}
+
+fn test_kss_identity(eid: sgx_enclave_id_t) -> SgxError {
+    let mut retval = sgx_status_t::SGX_SUCCESS;
+    let result = unsafe {
+        test_enclave_identity(eid, &mut retval, CONFIG_ID.as_ptr(), CONFIG_SVN)
+    };
+    ecall_status(result, retval)
 }
 
 fn main() {
@@ -69,6 +161,22 @@ fn main() {
     }
     assert_eq!(retval, 0);
 
+    match test_remote_attestation(enclave.geteid()) {
+        Ok(()) => println!("[+] remote attestation test passed!"),
+        Err(x) => {
+            println!("[-] remote attestation test failed {}!", x.as_str());
+            return;
+        }
+    }
+
+    match test_kss_identity(enclave.geteid()) {
+        Ok(()) => println!("[+] enclave identity test passed!"),
+        Err(x) => {
+            println!("[-] enclave identity test failed {}!", x.as_str());
+            return;
+        }
+    }
+
     println!("[+] unit_test ended!");
 
     enclave.destroy();
@@ -38,6 +38,9 @@ extern crate sgx_alloc;
 extern crate sgx_rand;
 extern crate sgx_trts;
 extern crate sgx_tseal;
+extern crate sgx_tse;
+extern crate sgx_tdh;
+extern crate sgx_tkey_exchange;
 #[macro_use]
 extern crate memoffset;
 extern crate sgx_serialize;
@@ -68,6 +71,12 @@ use test_rts::*;
 mod test_seal;
 use test_seal::*;
 
+mod test_dh;
+use test_dh::*;
+
+mod test_ra;
+use test_ra::*;
+
 mod test_rand;
 use test_rand::*;
 
@@ -118,6 +127,17 @@ pub extern "C" fn test_main_entrance() -> size_t {
         // tcrypto
         test_rsgx_sha256_slice,
         test_rsgx_sha256_handle,
+        test_rsgx_aes256gcm,
+        test_rsgx_chacha20_poly1305,
+        test_crypto_traits,
+        test_rsgx_hkdf,
+        test_rsgx_pbkdf2,
+        test_rsgx_sp800_108_cmac_kdf,
+        test_rsgx_ed25519,
+        test_rsgx_x25519,
+        test_key_encoding,
+        test_rsgx_rsa_signatures,
+        test_secret_keys,
         // assert
         foo_panic,
         foo_should,
@@ -155,6 +175,15 @@ pub extern "C" fn test_main_entrance() -> size_t {
         test_array_sealing,  // Thanks to @silvanegli
         test_mac_aadata_slice,
         test_mac_aadata_number,
+        test_reseal_data,
+        test_stream_sealing,
+        test_key_request_builder,
+        test_report_data_builder,
+        // tdh
+        test_dh_session,
+        test_dh_identity_policy,
+        // tkey_exchange
+        test_ra_session_state,
         // rand
         test_rand_os_sgxrng,
         test_rand_distributions,
@@ -16,7 +16,9 @@
 // under the License..
 
 use sgx_tcrypto::*;
+use sgx_types::*;
 use std::string::String;
+use std::vec::Vec;
 use utils::*;
 
 static HASH_TEST_VEC: &'static [&'static str] = &[
@@ -52,3 +54,379 @@ pub fn test_rsgx_sha256_handle() {
         assert_eq!(hex_to_bytes(HASH_SHA256_TRUTH[i]), hash);
     }
 }
+
+pub fn test_rsgx_aes256gcm() {
+    // NIST GCM test case 16
+    let mut key = sgx_aes_gcm_256bit_key_t::default();
+    key.copy_from_slice(&hex_to_bytes(
+        "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+    ));
+    let plaintext = hex_to_bytes("d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39");
+    let aad = hex_to_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+    let iv = hex_to_bytes("cafebabefacedbaddecaf888");
+
+    let mut ciphertext = vec![0_u8; plaintext.len()];
+    let mut mac = sgx_aes_gcm_128bit_tag_t::default();
+    rsgx_aes256GCM_encrypt(&key, &plaintext, &iv, &aad, &mut ciphertext, &mut mac).unwrap();
+    assert_eq!(hex_to_bytes("522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662"), ciphertext);
+    assert_eq!(hex_to_bytes("76fc6ece0f4e1768cddf8853bb2d551b"), mac);
+
+    let aesh = SgxAes256Handle::new();
+    aesh.init(&key, &iv, &aad).unwrap();
+    let mut streamed = vec![0_u8; plaintext.len()];
+    aesh.update(&plaintext[..20], &mut streamed[..20]).unwrap();
+    aesh.update(&plaintext[20..], &mut streamed[20..]).unwrap();
+    assert_eq!(aesh.get_mac().unwrap(), mac);
+    aesh.close().unwrap();
+    assert_eq!(streamed, ciphertext);
+
+    let mut decrypted = vec![0_u8; ciphertext.len()];
+    rsgx_aes256GCM_decrypt(&key, &ciphertext, &iv, &aad, &mac, &mut decrypted).unwrap();
+    assert_eq!(decrypted, plaintext);
+
+    mac[0] ^= 0x01;
+    assert_eq!(
+        rsgx_aes256GCM_decrypt(&key, &ciphertext, &iv, &aad, &mac, &mut decrypted),
+        Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH)
+    );
+}
+
+pub fn test_rsgx_chacha20_poly1305() {
+    // RFC 8439 section 2.8.2
+    let mut key = sgx_chacha20_poly1305_key_t::default();
+    key.copy_from_slice(&hex_to_bytes(
+        "808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f",
+    ));
+    let nonce = hex_to_bytes("070000004041424344454647");
+    let aad = hex_to_bytes("50515253c0c1c2c3c4c5c6c7");
+    let plaintext = "Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.".as_bytes();
+
+    let mut ciphertext = vec![0_u8; plaintext.len()];
+    let mut mac = sgx_chacha20_poly1305_tag_t::default();
+    rsgx_chacha20_poly1305_encrypt(&key, plaintext, &nonce, &aad, &mut ciphertext, &mut mac)
+        .unwrap();
+    assert_eq!(hex_to_bytes("d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d63dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b3692ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc3ff4def08e4b7a9de576d26586cec64b6116"), ciphertext);
+    assert_eq!(hex_to_bytes("1ae10b594f09e26a7e902ecbd0600691"), mac);
+
+    let mut decrypted = vec![0_u8; ciphertext.len()];
+    rsgx_chacha20_poly1305_decrypt(&key, &ciphertext, &nonce, &aad, &mac, &mut decrypted)
+        .unwrap();
+    assert_eq!(decrypted, plaintext);
+}
+
+fn digest_all<D: Digest>(chunks: &[&[u8]]) -> D::Output {
+    let handle = D::default();
+    handle.init().unwrap();
+    for chunk in chunks {
+        handle.update(chunk).unwrap();
+    }
+    let hash = handle.finalize().unwrap();
+    handle.close().unwrap();
+    hash
+}
+
+fn aead_roundtrip<A: Aead>(key: &A::Key, nonce: &[u8]) {
+    let aad = b"header";
+    let plaintext = b"The quick brown fox jumps over the lazy dog";
+    let mut ciphertext = vec![0_u8; plaintext.len()];
+    let tag = A::encrypt(key, nonce, aad, plaintext, &mut ciphertext).unwrap();
+    assert_eq!(tag.as_ref().len(), A::TAG_SIZE);
+
+    let handle = A::default();
+    handle.init(key, nonce, aad).unwrap();
+    let mut streamed = vec![0_u8; plaintext.len()];
+    handle.update(&plaintext[..7], &mut streamed[..7]).unwrap();
+    handle.update(&plaintext[7..], &mut streamed[7..]).unwrap();
+    assert_eq!(handle.get_mac().unwrap().as_ref(), tag.as_ref());
+    handle.close().unwrap();
+    assert_eq!(streamed, ciphertext);
+
+    let mut decrypted = vec![0_u8; ciphertext.len()];
+    A::decrypt(key, nonce, aad, &ciphertext, &tag, &mut decrypted).unwrap();
+    assert_eq!(&decrypted[..], &plaintext[..]);
+}
+
+pub fn test_crypto_traits() {
+    let input = HASH_TEST_VEC[1].as_bytes();
+    let hash = digest_all::<SgxShaHandle>(&[&input[..10], &[], &input[10..]]);
+    assert_eq!(hex_to_bytes(HASH_SHA256_TRUTH[1]), hash);
+    assert_eq!(SgxShaHandle::digest(input).unwrap(), hash);
+    assert_eq!(<SgxShaHandle as Digest>::OUTPUT_SIZE, SGX_SHA256_HASH_SIZE);
+
+    // RFC 4231 test case 2, with the key zero-padded to 32 bytes
+    let mut key = sgx_hmac_256bit_key_t::default();
+    key[..4].copy_from_slice(b"Jefe");
+    let data = b"what do ya want for nothing?";
+    let truth = hex_to_bytes("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    let tag = SgxHmacHandle::mac(&key, data).unwrap();
+    assert_eq!(truth, tag);
+    SgxHmacHandle::verify(&key, data, &truth).unwrap();
+    assert_eq!(
+        SgxHmacHandle::verify(&key, data, &truth[..16]),
+        Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH)
+    );
+
+    aead_roundtrip::<SgxAesHandle>(&[0x11_u8; SGX_AESGCM_KEY_SIZE], &[0x22_u8; 12]);
+    aead_roundtrip::<SgxAes256Handle>(&[0x11_u8; SGX_AESGCM256_KEY_SIZE], &[0x22_u8; 12]);
+    aead_roundtrip::<SgxChaCha20Poly1305Handle>(
+        &[0x11_u8; SGX_CHACHA20POLY1305_KEY_SIZE],
+        &[0x22_u8; 12],
+    );
+}
+
+pub fn test_rsgx_hkdf() {
+    // RFC 5869 test case 1
+    let ikm = hex_to_bytes("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b");
+    let salt = hex_to_bytes("000102030405060708090a0b0c");
+    let info = hex_to_bytes("f0f1f2f3f4f5f6f7f8f9");
+    let prk = rsgx_hkdf_sha256_extract(&salt, &ikm).unwrap();
+    assert_eq!(
+        hex_to_bytes("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"),
+        prk
+    );
+    let mut okm = [0_u8; 42];
+    rsgx_hkdf_sha256_expand(&prk, &info, &mut okm).unwrap();
+    assert_eq!(hex_to_bytes("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"), okm.to_vec());
+
+    // RFC 5869 test case 2
+    let ikm: Vec<u8> = (0x00..0x50).collect();
+    let salt: Vec<u8> = (0x60..0xb0).collect();
+    let info: Vec<u8> = (0xb0..=0xff).collect();
+    let mut okm = [0_u8; 82];
+    rsgx_hkdf_sha256(&salt, &ikm, &info, &mut okm).unwrap();
+    assert_eq!(hex_to_bytes("b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71cc30c58179ec3e87c14c01d5c1f3434f1d87"), okm.to_vec());
+
+    // RFC 5869 test case 3
+    let ikm = hex_to_bytes("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b");
+    let mut okm = [0_u8; 42];
+    rsgx_hkdf_sha256(&[], &ikm, &[], &mut okm).unwrap();
+    assert_eq!(hex_to_bytes("8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"), okm.to_vec());
+
+    // RFC 5869 test case 1 inputs with SHA384
+    let salt = hex_to_bytes("000102030405060708090a0b0c");
+    let info = hex_to_bytes("f0f1f2f3f4f5f6f7f8f9");
+    rsgx_hkdf_sha384(&salt, &ikm, &info, &mut okm).unwrap();
+    assert_eq!(hex_to_bytes("9b5097a86038b805309076a44b3a9f38063e25b516dcbf369f394cfab43685f748b6457763e4f0204fc5"), okm.to_vec());
+
+    let mut too_long = vec![0_u8; 255 * SGX_SHA256_HASH_SIZE + 1];
+    assert_eq!(
+        rsgx_hkdf_sha256(&salt, &ikm, &info, &mut too_long),
+        Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    );
+}
+
+pub fn test_rsgx_pbkdf2() {
+    // RFC 7914 section 11
+    let mut dk = [0_u8; 64];
+    rsgx_pbkdf2_hmac_sha256(b"passwd", b"salt", 1, &mut dk).unwrap();
+    assert_eq!(hex_to_bytes("55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"), dk.to_vec());
+    rsgx_pbkdf2_hmac_sha256(b"Password", b"NaCl", 80000, &mut dk).unwrap();
+    assert_eq!(hex_to_bytes("4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56a1d425a1225833549adb841b51c9b3176a272bdebba1d078478f62b397f33c8d"), dk.to_vec());
+
+    assert_eq!(
+        rsgx_pbkdf2_hmac_sha256(b"passwd", b"salt", 0, &mut dk),
+        Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    );
+}
+
+pub fn test_rsgx_sp800_108_cmac_kdf() {
+    // Not a published vector: it was generated with KBKDFCMAC from pyca/cryptography,
+    // an independent implementation, in counter mode with the counter before the
+    // fixed input and r = 32, L = 32 bits.
+    let mut key = sgx_cmac_128bit_key_t::default();
+    key.copy_from_slice(&hex_to_bytes("000102030405060708090a0b0c0d0e0f"));
+    let mut okm = [0_u8; 40];
+    rsgx_sp800_108_cmac_kdf(&key, b"sgx label", b"context data", &mut okm).unwrap();
+    assert_eq!(hex_to_bytes("cf0cee49e1185e788b217abb7354578918964fc0e3afc5a65fa0d384590e3d86b452588efd058c02"), okm.to_vec());
+}
+
+pub fn test_rsgx_ed25519() {
+    // RFC 8032 section 7.1, test 2
+    let mut private = sgx_ed25519_private_t::default();
+    private.seed.copy_from_slice(&hex_to_bytes(
+        "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+    ));
+    let public = rsgx_ed25519_public_from_private(&private).unwrap();
+    assert_eq!(
+        hex_to_bytes("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c"),
+        public.key
+    );
+    let msg = [0x72_u8];
+    let signature = rsgx_ed25519_sign_slice(&msg, &private).unwrap();
+    assert_eq!(
+        hex_to_bytes("92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da"),
+        signature.r
+    );
+    assert_eq!(
+        hex_to_bytes("085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"),
+        signature.s
+    );
+    assert!(rsgx_ed25519_verify_slice(&msg, &public, &signature).unwrap());
+    assert!(!rsgx_ed25519_verify_slice(&[0x73_u8], &public, &signature).unwrap());
+
+    let (private, public) = rsgx_ed25519_create_key_pair().unwrap();
+    let signature = rsgx_ed25519_sign_slice(HASH_TEST_VEC[2].as_bytes(), &private).unwrap();
+    assert!(rsgx_ed25519_verify_slice(HASH_TEST_VEC[2].as_bytes(), &public, &signature).unwrap());
+}
+
+pub fn test_rsgx_x25519() {
+    // RFC 7748 section 6.1
+    let mut alice = sgx_x25519_private_t::default();
+    alice.scalar.copy_from_slice(&hex_to_bytes(
+        "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+    ));
+    let mut bob = sgx_x25519_private_t::default();
+    bob.scalar.copy_from_slice(&hex_to_bytes(
+        "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb",
+    ));
+    let alice_public = rsgx_x25519_public_from_private(&alice).unwrap();
+    let bob_public = rsgx_x25519_public_from_private(&bob).unwrap();
+    assert_eq!(
+        hex_to_bytes("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"),
+        alice_public.u
+    );
+    let shared = rsgx_x25519_compute_shared_key(&alice, &bob_public).unwrap();
+    assert_eq!(
+        hex_to_bytes("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742"),
+        shared.s
+    );
+    assert_eq!(rsgx_x25519_compute_shared_key(&bob, &alice_public).unwrap().s, shared.s);
+
+    assert_eq!(
+        rsgx_x25519_compute_shared_key(&alice, &sgx_x25519_public_t::default()).err(),
+        Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    );
+}
+
+pub fn test_key_encoding() {
+    // P-256 key and signature exported by OpenSSL
+    let spki = hex_to_bytes(
+        "3059301306072a8648ce3d020106082a8648ce3d030107034200047e285f5ece7a0cc61f60925dff711116\
+         cc96d55671a80db4a87a02138a1f956e004c4fcca7c7c7bd021de3002190bb105a609acfe49e76ea1807b2\
+         7a58b74748",
+    );
+    let pkcs8 = hex_to_bytes(
+        "308187020100301306072a8648ce3d020106082a8648ce3d030107046d306b0201010420b9d6cc65a0ca88\
+         f4814ad19b4d75712b711bc419ccff1eb2144e4e5b36de7cfea144034200047e285f5ece7a0cc61f60925d\
+         ff711116cc96d55671a80db4a87a02138a1f956e004c4fcca7c7c7bd021de3002190bb105a609acfe49e76\
+         ea1807b27a58b74748",
+    );
+    let pem = "-----BEGIN PUBLIC KEY-----\n\
+               MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEfihfXs56DMYfYJJd/3ERFsyW1VZx\n\
+               qA20qHoCE4oflW4ATE/Mp8fHvQId4wAhkLsQWmCaz+SeduoYB7J6WLdHSA==\n\
+               -----END PUBLIC KEY-----\n";
+
+    let public = rsgx_ec256_public_from_der(&spki).unwrap();
+    assert_eq!(
+        hex_to_bytes("6e951f8a13027aa8b40da87156d596cc161171ff5d92601fc60c7ace5e5f287e"),
+        public.gx
+    );
+    assert_eq!(
+        hex_to_bytes("4847b7587ab20718ea769ee4cf9a605a10bb902100e31d02bdc7c7a7cc4f4c00"),
+        public.gy
+    );
+    let private = rsgx_ec256_private_from_der(&pkcs8).unwrap();
+    assert_eq!(
+        hex_to_bytes("fe7cde365b4e4e14b21effcc19c41b712b71754d9bd14a81f488caa065ccd6b9"),
+        private.r
+    );
+    assert_eq!(rsgx_ec256_public_to_der(&public).unwrap(), spki);
+    assert_eq!(rsgx_ec256_private_to_der(&private, &public).unwrap(), pkcs8);
+    assert_eq!(rsgx_pem_encode(SGX_PEM_PUBLIC_KEY_LABEL, &spki), pem);
+    assert_eq!(rsgx_pem_decode(pem, SGX_PEM_PUBLIC_KEY_LABEL).unwrap(), spki);
+    assert!(rsgx_pem_decode(pem, SGX_PEM_PRIVATE_KEY_LABEL).is_err());
+    assert!(rsgx_ec256_public_from_der(&pkcs8).is_err());
+
+    let der = hex_to_bytes(
+        "30440220671149a16a168a7fcfbb4eef1d77f21ef9b2dc4ed57fdee92850a29fee410f7b02205fd410ea91\
+         f29a89090718d40c85b6ff98cebc2d32e48573496db60fd9073f09",
+    );
+    let signature = rsgx_ecdsa_signature_from_der(&der).unwrap();
+    assert_eq!(signature.x[0], 0xee410f7b);
+    assert_eq!(signature.y[0], 0xd9073f09);
+    assert_eq!(rsgx_ecdsa_signature_to_der(&signature).unwrap(), der);
+
+    // RSA round trip through the SGX 3072-bit layout
+    let mut key = sgx_rsa3072_public_key_t::default();
+    key.modulus.iter_mut().enumerate().for_each(|(i, b)| *b = i as u8 | 0x80);
+    key.exponent = [0x01, 0x00, 0x01, 0x00];
+    let components = SgxRsaPublicKeyComponents::from_rsa3072(&key);
+    let der = rsgx_rsa_public_to_der(&components).unwrap();
+    let decoded = rsgx_rsa_public_from_der(&der).unwrap().to_rsa3072().unwrap();
+    assert_eq!(decoded.modulus.to_vec(), key.modulus.to_vec());
+    assert_eq!(decoded.exponent, key.exponent);
+}
+
+pub fn test_rsgx_rsa_signatures() {
+    // 1024-bit key and signatures of "abc" generated with pyca/cryptography
+    let key = rsgx_rsa_private_from_der(&hex_to_bytes(
+        "30820277020100300d06092a864886f70d0101010500048202613082025d02010002818100ac44ed9073d7\
+         7e0d19f091941c1381fab37f4a108fa3ece29c7b89f343e6ed4f9a2c2912213d62daed0aad919843caf791\
+         03b6658c45a25f072e134ef2cbe997ab7ebadf37376ecc0ac8326152d8e1f9bd704025991b93c354b58986\
+         ec9951b26d0bd334fd14d438849fab03ed49d1ea43cc21f64647a35a0f2d53a3b519d2a302030100010281\
+         810084a35db6c73ab857260dd0491584ffa323e97201665774e0d6535857f184305c05fcd2729f1d40ac07\
+         af1d0ce90be98ecc1ceba72c65c12b21c01c25c153b7ba486d65ca3a165943a0bbd08eedeabd6e50d4372f\
+         6b80534fd3e4ccdf2e0f7f29fa1acd2ba5dfe69291042db4dd31cbbbcb6a7f48be5e3644298b25f87795a2\
+         81024100d4460e462b46c401c54fce421bcd741961c57ff83ab3beea2cca3cc1f432acdda54d9159ae44ce\
+         74047db315a49ded4cc157114fe2007e98d0dde4166e7747b1024100cfc14c3a1319c8e00401c52bbe647a\
+         c1ddb6c3a633a61e1b36f40adb5bf7735e718db8277935244be646765a4f89928553052ca366745fc6a9a5\
+         23071be82893024100c32a51ef63d4a15bbb6cbf877a86704af1e0a2e2b81e10d6bf2d85a5117f11fe8b00\
+         0136420a93028a2a83dfcf45402b44cb676859b2f8b249bee8f952c12a51024015fa57eb08dc4070da5ba9\
+         952016171a6d2f09718aa3372a1fdd1f4ce764bd12afa69365b19fda9020f331500a7bfe41167044b1a084\
+         0d21c20e932f0c57b9fb02404d4508c471e42d36189b08905a573f56ded62f61150d8f486fa00610539b53\
+         c9342be986bf328522b3614060f68ede1c62108500be6496ad7df8d1db9ace863a",
+    ))
+    .unwrap();
+    let public = key.public_key();
+    let data = "abc".as_bytes();
+
+    let signature = hex_to_bytes(
+        "9652715d0082adf1307347c2ee89768c729cb7112a819561013dcf7b7e258391f88ccaee0d326e960ffed6\
+         1da2a5c4ac2c4865d4a8c787cf8f9662a0e4c7df92f6de32c3680180bc8b85e60b2b45d70a1f62de6f1b15\
+         772ea997598f9cbd1e6ff824aef9fd70bc75c7c7d5380823c8e0649aa757620baa3c6e3bb8e0d517545a",
+    );
+    assert_eq!(rsgx_rsa_sign_pkcs1v15_sha256(data, &key).unwrap(), signature);
+    assert!(rsgx_rsa_verify_pkcs1v15_sha256(data, &public, &signature).unwrap());
+    assert!(!rsgx_rsa_verify_pkcs1v15_sha256(&data[..2], &public, &signature).unwrap());
+    assert!(!rsgx_rsa_verify_pss_sha256(data, &public, &signature).unwrap());
+
+    let signature = hex_to_bytes(
+        "a06971e3efa26e7c2bfd0453087d1b57b831121dba4432c789a95c1067f5ca2e736e8725ac352f0e096019\
+         cb7dd71ac8fd3617ec01efacfffad39f93e278167fc673628ea665796530c96ff3d99c67d8136b04694ee0\
+         cc8425aad789d3fb40bed31fb566db1ee65ff9568631c7495e9769d117ae04173c28073238d419abfee6",
+    );
+    assert!(rsgx_rsa_verify_pss_sha256(data, &public, &signature).unwrap());
+    assert!(!rsgx_rsa_verify_pss_sha256(&data[..2], &public, &signature).unwrap());
+    assert!(!rsgx_rsa_verify_pss_sha256(data, &public, &signature[1..]).unwrap());
+
+    let signature = rsgx_rsa_sign_pss_sha256(&[], &key).unwrap();
+    assert_eq!(signature.len(), 128);
+    assert!(rsgx_rsa_verify_pss_sha256(&[], &public, &signature).unwrap());
+    assert_ne!(rsgx_rsa_sign_pss_sha256(&[], &key).unwrap(), signature);
+
+    let mut broken = key.clone();
+    broken.dmq1[0] ^= 1;
+    assert_eq!(
+        rsgx_rsa_sign_pkcs1v15_sha256(data, &broken).err(),
+        Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    );
+}
+
+pub fn test_secret_keys() {
+    let key = SecretKey128::new([0x2b_u8; 16]);
+    assert_eq!(key.as_bytes(), &[0x2b_u8; 16]);
+    assert!(key == SecretKey128::from([0x2b_u8; 16]));
+    assert!(!key.ct_eq(&SecretKey128::default()));
+
+    let ecc = SgxEccHandle::new();
+    ecc.open().unwrap();
+    let (private_a, public_a) = ecc.create_secret_key_pair().unwrap();
+    let (private_b, public_b) = ecc.create_secret_key_pair().unwrap();
+    let shared_a = ecc.compute_shared_secret(&private_a, &public_b).unwrap();
+    let shared_b = ecc.compute_shared_secret(&private_b, &public_a).unwrap();
+    assert!(shared_a == shared_b);
+    let raw = ecc.compute_shared_dhkey(private_a.as_raw(), &public_b).unwrap();
+    assert_eq!(shared_a.as_raw().s, raw.s);
+    assert!(private_a != private_b);
+    ecc.close().unwrap();
+}
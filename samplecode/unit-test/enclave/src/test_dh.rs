@@ -0,0 +1,196 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use sgx_tdh::*;
+use sgx_tse::*;
+use sgx_types::*;
+use std::string::String;
+use std::vec::Vec;
+
+fn establish_sessions(rekey_interval: u64) -> (SgxDhSession, SgxDhSession) {
+    let mut responder = SgxDhResponder::init_session();
+    let mut initiator = SgxDhInitiator::init_session();
+    let mut msg1 = SgxDhMsg1::default();
+    let mut msg2 = SgxDhMsg2::default();
+    let mut msg3 = SgxDhMsg3::new();
+    let mut identity = sgx_dh_session_enclave_identity_t::default();
+
+    responder.gen_msg1(&mut msg1).unwrap();
+    initiator.proc_msg1(&msg1, &mut msg2).unwrap();
+    let aek = responder
+        .proc_msg2_secret(&msg2, &mut msg3, &mut identity)
+        .unwrap();
+    let initiator_aek = initiator.proc_msg3_secret(&msg3, &mut identity).unwrap();
+    assert!(aek == initiator_aek);
+
+    let initiator_session =
+        SgxDhSession::with_rekey_interval(SgxDhSessionRole::Initiator, &aek, rekey_interval)
+            .unwrap();
+    let responder_session =
+        SgxDhSession::with_rekey_interval(SgxDhSessionRole::Responder, &aek, rekey_interval)
+            .unwrap();
+    (initiator_session, responder_session)
+}
+
+pub fn test_dh_session() {
+    let (mut initiator, mut responder) = establish_sessions(4);
+
+    for i in 0..10_u8 {
+        let request = initiator.seal_message(&[i; 33], b"request").unwrap();
+        assert_eq!(request.len(), 33 + SGX_DH_SESSION_MESSAGE_OVERHEAD);
+        assert_eq!(
+            responder.open_message(&request, b"request").unwrap(),
+            vec![i; 33]
+        );
+        assert_eq!(
+            responder.open_message(&request, b"request").err(),
+            Some(sgx_status_t::SGX_ERROR_INVALID_STATE)
+        );
+
+        let response = responder.seal_message(&[], &[]).unwrap();
+        assert_eq!(
+            initiator.open_message(&response, &[]).unwrap(),
+            Vec::<u8>::new()
+        );
+    }
+    assert_eq!(initiator.send_sequence(), 10);
+    assert_eq!(responder.recv_sequence(), 10);
+
+    let message = initiator.seal_message(b"message", &[]).unwrap();
+    assert_eq!(
+        initiator.open_message(&message, &[]).err(),
+        Some(sgx_status_t::SGX_ERROR_INVALID_STATE)
+    );
+    let mut modified = message.clone();
+    modified[SGX_DH_SESSION_HEADER_SIZE] ^= 1;
+    assert_eq!(
+        responder.open_message(&modified, &[]).err(),
+        Some(sgx_status_t::SGX_ERROR_MAC_MISMATCH)
+    );
+    assert_eq!(
+        responder.open_message(&message, b"aad").err(),
+        Some(sgx_status_t::SGX_ERROR_MAC_MISMATCH)
+    );
+    assert_eq!(
+        responder
+            .open_message(&message[..SGX_DH_SESSION_MESSAGE_OVERHEAD - 1], &[])
+            .err(),
+        Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    );
+
+    let state = responder.to_bytes();
+    assert_eq!(state.len(), SGX_DH_SESSION_STATE_SIZE);
+    let mut restored = SgxDhSession::from_bytes(&state).unwrap();
+    assert_eq!(restored.role(), SgxDhSessionRole::Responder);
+    assert_eq!(restored.open_message(&message, &[]).unwrap(), b"message");
+    let response = restored.seal_message(b"response", &[]).unwrap();
+    assert_eq!(initiator.open_message(&response, &[]).unwrap(), b"response");
+    assert!(SgxDhSession::from_bytes(&state[1..]).is_err());
+
+    assert!(SgxDhSession::with_rekey_interval(
+        SgxDhSessionRole::Initiator,
+        &SecretKey128::default(),
+        0
+    )
+    .is_err());
+}
+
+pub fn test_dh_identity_policy() {
+    let report = rsgx_self_report();
+    let is_debug = (report.body.attributes.flags & SGX_FLAGS_DEBUG) != 0;
+
+    let handshake = |policy: &EnclaveIdentityPolicy| {
+        let mut responder = SgxDhResponder::init_session();
+        let mut initiator = SgxDhInitiator::init_session();
+        let mut msg1 = SgxDhMsg1::default();
+        let mut msg2 = SgxDhMsg2::default();
+        let mut msg3 = SgxDhMsg3::new();
+        let mut identity = sgx_dh_session_enclave_identity_t::default();
+
+        responder.gen_msg1(&mut msg1).unwrap();
+        initiator.proc_msg1(&msg1, &mut msg2).unwrap();
+        let responder_aek =
+            responder.proc_msg2_with_policy(&msg2, &mut msg3, policy, &mut identity)?;
+        let initiator_aek = initiator.proc_msg3_with_policy(&msg3, policy, &mut identity)?;
+        assert!(responder_aek == initiator_aek);
+        Ok::<(), SgxDhError>(())
+    };
+
+    let mut policy = EnclaveIdentityPolicy::new();
+    assert_eq!(
+        handshake(&policy).err(),
+        Some(SgxDhError::IdentityPolicy(
+            IdentityPolicyError::Unconstrained
+        ))
+    );
+
+    policy
+        .mr_signer(&report.body.mr_signer)
+        .allow_mr_enclave(&report.body.mr_enclave)
+        .isv_prod_id(report.body.isv_prod_id)
+        .min_isv_svn(report.body.isv_svn);
+    if is_debug {
+        assert_eq!(
+            handshake(&policy).err(),
+            Some(SgxDhError::IdentityPolicy(IdentityPolicyError::Debug))
+        );
+        policy.allow_debug(true);
+    }
+    assert!(handshake(&policy).is_ok());
+
+    let mut other_enclave = policy.clone();
+    other_enclave.allow_mr_enclave(&sgx_measurement_t {
+        m: [0xff; SGX_HASH_SIZE],
+    });
+    assert!(handshake(&other_enclave).is_ok());
+    let mut other_signer = policy.clone();
+    other_signer.mr_signer(&sgx_measurement_t {
+        m: [0xff; SGX_HASH_SIZE],
+    });
+    assert_eq!(
+        handshake(&other_signer).err(),
+        Some(SgxDhError::IdentityPolicy(IdentityPolicyError::MrSigner))
+    );
+    if let Some(isv_svn) = report.body.isv_svn.checked_add(1) {
+        let mut newer = policy.clone();
+        newer.min_isv_svn(isv_svn);
+        assert_eq!(
+            handshake(&newer).err(),
+            Some(SgxDhError::IdentityPolicy(IdentityPolicyError::IsvSvn))
+        );
+    }
+
+    let config = format!(
+        "# trusted signer\nmr_signer = {}\nmr_enclave = {}\nallow_debug = {}\n",
+        hex(&report.body.mr_signer.m),
+        hex(&[0xff; SGX_HASH_SIZE]),
+        is_debug
+    );
+    let from_config = EnclaveIdentityPolicy::from_config(&config).unwrap();
+    assert_eq!(
+        handshake(&from_config).err(),
+        Some(SgxDhError::IdentityPolicy(IdentityPolicyError::MrEnclave))
+    );
+    assert_eq!(
+        EnclaveIdentityPolicy::from_config("min_isv_svn = 1").err(),
+        Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    );
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,106 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use sgx_tcrypto::*;
+use sgx_tkey_exchange::*;
+use sgx_types::*;
+use std::mem;
+use std::ptr;
+use std::slice;
+use std::sync::SgxMutex;
+
+// The session of the end-to-end remote attestation the app runs against its stand-in
+// service provider, after test_main_entrance.
+static RA_SESSION: SgxMutex<Option<RaSession>> = SgxMutex::new(None);
+
+#[no_mangle]
+pub extern "C" fn test_ra_session_init(
+    sp_pub_key: &sgx_ec256_public_t,
+    context: &mut sgx_ra_context_t,
+) -> sgx_status_t {
+    match RaSession::new(sp_pub_key) {
+        Ok(session) => {
+            *context = session.context();
+            *RA_SESSION.lock().unwrap() = Some(session);
+            sgx_status_t::SGX_SUCCESS
+        }
+        Err(e) => e,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn test_ra_session_proc_msg4(msg4: *const u8, msg4_size: usize) -> sgx_status_t {
+    if msg4_size != mem::size_of::<sgx_ra_msg4_t>() {
+        return sgx_status_t::SGX_ERROR_INVALID_PARAMETER;
+    }
+    let msg4 = unsafe { ptr::read_unaligned(msg4 as *const sgx_ra_msg4_t) };
+    match RA_SESSION.lock().unwrap().as_mut() {
+        Some(session) => match session.proc_msg4(&msg4) {
+            Ok(()) => sgx_status_t::SGX_SUCCESS,
+            Err(e) => e,
+        },
+        None => sgx_status_t::SGX_ERROR_INVALID_STATE,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn test_ra_session_mac(
+    data: *const u8,
+    data_size: usize,
+    mac: &mut sgx_mac_t,
+) -> sgx_status_t {
+    let data = unsafe { slice::from_raw_parts(data, data_size) };
+    let guard = RA_SESSION.lock().unwrap();
+    let session = match guard.as_ref() {
+        Some(session) => session,
+        None => return sgx_status_t::SGX_ERROR_INVALID_STATE,
+    };
+    match session
+        .mac_key()
+        .and_then(|mk| rsgx_rijndael128_cmac_slice(mk.as_raw(), data))
+    {
+        Ok(tag) => {
+            *mac = tag;
+            sgx_status_t::SGX_SUCCESS
+        }
+        Err(e) => e,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn test_ra_session_close() {
+    RA_SESSION.lock().unwrap().take();
+}
+
+pub fn test_ra_session_state() {
+    let ecc = SgxEccHandle::new();
+    ecc.open().unwrap();
+    let (_, sp_pub_key) = ecc.create_secret_key_pair().unwrap();
+    let mut session = RaSession::new(&sp_pub_key).unwrap();
+    assert_eq!(session.state(), RaSessionState::Pending);
+    assert_eq!(
+        session.secret_key().err(),
+        Some(sgx_status_t::SGX_ERROR_INVALID_STATE)
+    );
+    assert_eq!(
+        session.mac_key().err(),
+        Some(sgx_status_t::SGX_ERROR_INVALID_STATE)
+    );
+    // Message 2 has not been processed, so there is no MK to check message 4 with.
+    assert!(session.proc_msg4(&sgx_ra_msg4_t::default()).is_err());
+    assert_eq!(session.state(), RaSessionState::Pending);
+}
@@ -16,10 +16,12 @@
 // under the License..
 
 use sgx_rand::*;
+use sgx_tse::*;
 use sgx_tseal::*;
 use sgx_types::marker::*;
 use sgx_types::*;
 use std::prelude::v1::*;
+use std::slice;
 
 fn to_sealed_log<T: Copy + ContiguousMemory>(
     sealed_data: &SgxSealedData<T>,
@@ -113,3 +115,237 @@ pub fn test_mac_aadata_slice() {
     let inner_slice = unsafe { slice::from_raw_parts(inner as *mut u8, 10) };
     assert_eq!(inner_slice, aad_data);
 }
+
+pub fn test_reseal_data() {
+    let data: [u8; 10] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+    let aad: [u8; 4] = [11, 12, 13, 14];
+    let attribute_mask = sgx_attributes_t {
+        flags: TSEAL_DEFAULT_FLAGSMASK,
+        xfrm: 0,
+    };
+    let sealed_data = SgxSealedData::<[u8]>::seal_data_ex(
+        SGX_KEYPOLICY_MRENCLAVE,
+        attribute_mask,
+        TSEAL_DEFAULT_MISCMASK,
+        &aad,
+        &data,
+    )
+    .expect("error while sealing array");
+    assert_eq!(sealed_data.get_key_policy(), SGX_KEYPOLICY_MRENCLAVE);
+
+    let resealed_data = sealed_data
+        .reseal_with(SGX_KEYPOLICY_MRSIGNER, attribute_mask, TSEAL_DEFAULT_MISCMASK)
+        .expect("error while resealing array");
+    assert_eq!(resealed_data.get_key_policy(), SGX_KEYPOLICY_MRSIGNER);
+    assert_ne!(
+        resealed_data.get_key_request().key_id.id,
+        sealed_data.get_key_request().key_id.id
+    );
+
+    let upgraded_data = resealed_data
+        .upgrade_to_current_svn()
+        .expect("error while upgrading array");
+    assert_eq!(upgraded_data.get_key_policy(), SGX_KEYPOLICY_MRSIGNER);
+
+    let unsealed_data = upgraded_data
+        .unseal_data()
+        .expect("error while unsealing array");
+    assert_eq!(unsealed_data.get_decrypt_txt(), data);
+    assert_eq!(unsealed_data.get_additional_txt(), aad);
+}
+
+pub fn test_stream_sealing() {
+    use std::io::{Cursor, Read, Write};
+
+    let mut data = vec![0_u8; 100000];
+    let mut rand = StdRng::new().unwrap();
+    rand.fill_bytes(&mut data);
+
+    let attribute_mask = sgx_attributes_t {
+        flags: TSEAL_DEFAULT_FLAGSMASK,
+        xfrm: 0,
+    };
+    let mut writer = SealingWriter::new_ex(
+        SGX_KEYPOLICY_MRSIGNER,
+        attribute_mask,
+        TSEAL_DEFAULT_MISCMASK,
+        4096,
+        Vec::new(),
+    )
+    .expect("error while creating sealing writer");
+    writer.write_all(&data).unwrap();
+    let sealed = writer.finish().unwrap();
+
+    let mut reader = UnsealingReader::new(Cursor::new(sealed.clone())).unwrap();
+    let mut unsealed = Vec::new();
+    reader.read_to_end(&mut unsealed).unwrap();
+    assert_eq!(unsealed, data);
+
+    // a truncated stream must be rejected
+    let truncated = sealed[..sealed.len() - 100].to_vec();
+    let mut reader = UnsealingReader::new(Cursor::new(truncated)).unwrap();
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+
+    // a tampered chunk must be rejected
+    let mut tampered = sealed;
+    let len = tampered.len();
+    tampered[len / 2] ^= 0x01;
+    let mut reader = UnsealingReader::new(Cursor::new(tampered)).unwrap();
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+}
+
+pub fn test_key_request_builder() {
+    let report = rsgx_self_report();
+    let request = KeyRequestBuilder::new(KeyName::Seal).build().unwrap();
+    assert_eq!(request.key_name, SGX_KEYSELECT_SEAL);
+    assert_ne!(request.key_policy & SGX_KEYPOLICY_MRSIGNER, 0);
+    assert_eq!(request.isv_svn, report.body.isv_svn);
+    assert_eq!(request.misc_mask, TSEAL_DEFAULT_MISCMASK);
+
+    let mut builder = KeyRequestBuilder::new(KeyName::Seal);
+    builder.policy(KeyPolicy::MRENCLAVE | KeyPolicy::NOISVPRODID);
+    let key = builder.get_key().unwrap();
+    assert!(key == rsgx_get_secret_key(&builder.build().unwrap()).unwrap());
+    let subkey = builder.derive_key(b"database").unwrap();
+    assert!(subkey == builder.derive_key(b"database").unwrap());
+    assert!(subkey != builder.derive_key(b"network").unwrap());
+    assert!(subkey != key);
+    assert!(builder.random_key_id().unwrap().get_key().unwrap() != key);
+
+    let stored = KeyRequestBuilder::from_request(&builder.build().unwrap()).unwrap();
+    assert!(stored.get_key().unwrap() == builder.get_key().unwrap());
+
+    assert_eq!(KeyPolicy::from_bits(0x0040), None);
+    assert_eq!(
+        KeyRequestBuilder::new(KeyName::Seal)
+            .policy(KeyPolicy::NOISVPRODID)
+            .build()
+            .err(),
+        Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    );
+    assert_eq!(
+        KeyRequestBuilder::new(KeyName::Seal)
+            .attribute_mask(sgx_attributes_t { flags: SGX_FLAGS_INITTED, xfrm: 0 })
+            .build()
+            .err(),
+        Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    );
+    if let Some(isv_svn) = report.body.isv_svn.checked_add(1) {
+        assert_eq!(
+            KeyRequestBuilder::new(KeyName::Seal)
+                .isv_svn(isv_svn)
+                .build()
+                .err(),
+            Some(sgx_status_t::SGX_ERROR_INVALID_ISVSVN)
+        );
+    }
+    assert!(KeyRequestBuilder::new(KeyName::Report)
+        .policy(KeyPolicy::default())
+        .get_key()
+        .is_ok());
+}
+
+pub fn test_report_data_builder() {
+    let public_key = [0x04_u8; 65];
+    let nonce = [0x5a_u8; 16];
+    let report_data = ReportDataBuilder::new(ReportDataHash::Sha256)
+        .public_key(&public_key)
+        .nonce(&nonce)
+        .build()
+        .unwrap();
+    assert_eq!(report_data.d[SGX_SHA256_HASH_SIZE..], [0_u8; 32]);
+    // The order of the fields does not matter, their labels do.
+    let reordered = ReportDataBuilder::new(ReportDataHash::Sha256)
+        .nonce(&nonce)
+        .public_key(&public_key)
+        .build()
+        .unwrap();
+    assert_eq!(report_data.d, reordered.d);
+    let relabeled = ReportDataBuilder::new(ReportDataHash::Sha256)
+        .user_data(&public_key)
+        .nonce(&nonce)
+        .build()
+        .unwrap();
+    assert_ne!(report_data.d, relabeled.d);
+
+    let sha384 = ReportDataBuilder::new(ReportDataHash::Sha384)
+        .public_key(&public_key)
+        .nonce(&nonce)
+        .build()
+        .unwrap();
+    assert_ne!(sha384.d[SGX_SHA256_HASH_SIZE..SGX_SHA384_HASH_SIZE], [0_u8; 16]);
+    assert_eq!(sha384.d[SGX_SHA384_HASH_SIZE..], [0_u8; 16]);
+
+    let report = ReportDataBuilder::new(ReportDataHash::Sha256)
+        .public_key(&public_key)
+        .nonce(&nonce)
+        .create_report(&sgx_target_info_t::default())
+        .unwrap();
+    assert!(ReportDataBuilder::new(ReportDataHash::Sha256)
+        .nonce(&nonce)
+        .public_key(&public_key)
+        .verify(&report.body)
+        .is_ok());
+    assert_eq!(
+        ReportDataBuilder::new(ReportDataHash::Sha256)
+            .public_key(&public_key)
+            .nonce(&[0_u8; 16])
+            .verify(&report.body)
+            .err(),
+        Some(sgx_status_t::SGX_ERROR_MAC_MISMATCH)
+    );
+    assert_eq!(
+        ReportDataBuilder::new(ReportDataHash::Sha384)
+            .public_key(&public_key)
+            .nonce(&nonce)
+            .verify(&report.body)
+            .err(),
+        Some(sgx_status_t::SGX_ERROR_MAC_MISMATCH)
+    );
+
+    assert_eq!(
+        ReportDataBuilder::new(ReportDataHash::Sha256).build().err(),
+        Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    );
+    assert_eq!(
+        ReportDataBuilder::new(ReportDataHash::Sha256)
+            .nonce(&nonce)
+            .nonce(&nonce)
+            .build()
+            .err(),
+        Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    );
+    assert_eq!(
+        ReportDataBuilder::new(ReportDataHash::Sha256)
+            .field("", &nonce)
+            .build()
+            .err(),
+        Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    );
+}
+
+/// Checks that the enclave sees the CONFIGID and CONFIGSVN the app created it
+/// with through `create_with_kss_config`.
+#[no_mangle]
+pub extern "C" fn test_enclave_identity(
+    config_id: *const u8,
+    config_svn: sgx_config_svn_t,
+) -> sgx_status_t {
+    let config_id = unsafe { slice::from_raw_parts(config_id, SGX_CONFIGID_SIZE) };
+    let identity = rsgx_self_identity();
+    if !identity.kss_enabled() {
+        return sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED;
+    }
+    if identity.config_id[..] != *config_id || identity.config_svn != config_svn {
+        return sgx_status_t::SGX_ERROR_UNEXPECTED;
+    }
+    // A self report carries the same CONFIGID/CONFIGSVN as the identity.
+    let report = rsgx_self_report();
+    if report.body.attributes.flags & SGX_FLAGS_KSS == 0
+        || report.body.config_id[..] != *config_id
+        || report.body.config_svn != config_svn
+    {
+        return sgx_status_t::SGX_ERROR_UNEXPECTED;
+    }
+    sgx_status_t::SGX_SUCCESS
+}
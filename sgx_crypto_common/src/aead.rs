@@ -0,0 +1,1348 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! AES-256-GCM and ChaCha20-Poly1305 Authenticated Encryption
+//!
+//! The Intel(R) SGX cryptography library only provides 128-bit AES-GCM. These
+//! algorithms are implemented in Rust: AES uses the AES-NI instructions when the
+//! processor has them and a software implementation otherwise, and AES, GHASH,
+//! ChaCha20 and Poly1305 are implemented without secret dependent branches or
+//! table lookups.
+//!
+use core::arch::x86_64::*;
+use core::cell::{Cell, RefCell};
+use core::ptr;
+use sgx_types::*;
+
+const AES256_ROUNDS: usize = 14;
+const AEAD_BLOCK_SIZE: usize = 16;
+const CHACHA20_BLOCK_SIZE: usize = 64;
+
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a
+        .iter()
+        .zip(b.iter())
+        .fold(0_u8, |acc, (x, y)| acc | (x ^ y));
+    unsafe { ptr::read_volatile(&diff) == 0 }
+}
+
+pub(crate) fn zeroize(buf: &mut [u8]) {
+    for b in buf.iter_mut() {
+        unsafe { ptr::write_volatile(b, 0) };
+    }
+}
+
+fn load_le32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+//
+// AES-256, with AES-NI when the processor has it and a constant time software
+// implementation otherwise
+//
+#[derive(Clone)]
+enum Aes256 {
+    Ni([__m128i; AES256_ROUNDS + 1]),
+    Soft([[u8; AEAD_BLOCK_SIZE]; AES256_ROUNDS + 1]),
+}
+
+macro_rules! aes256_expand_even {
+    ($prev:expr, $last:expr, $rcon:expr) => {{
+        let mut t = _mm_aeskeygenassist_si128($last, $rcon);
+        t = _mm_shuffle_epi32(t, 0xff);
+        let mut k = $prev;
+        k = _mm_xor_si128(k, _mm_slli_si128(k, 4));
+        k = _mm_xor_si128(k, _mm_slli_si128(k, 4));
+        k = _mm_xor_si128(k, _mm_slli_si128(k, 4));
+        _mm_xor_si128(k, t)
+    }};
+}
+
+macro_rules! aes256_expand_odd {
+    ($prev:expr, $last:expr) => {{
+        let mut t = _mm_aeskeygenassist_si128($last, 0x00);
+        t = _mm_shuffle_epi32(t, 0xaa);
+        let mut k = $prev;
+        k = _mm_xor_si128(k, _mm_slli_si128(k, 4));
+        k = _mm_xor_si128(k, _mm_slli_si128(k, 4));
+        k = _mm_xor_si128(k, _mm_slli_si128(k, 4));
+        _mm_xor_si128(k, t)
+    }};
+}
+
+impl Aes256 {
+    fn new(key: &sgx_aes_gcm_256bit_key_t) -> Aes256 {
+        if crate::has_aes_ni() {
+            // Safe: the processor supports the instructions enabled by expand_aesni.
+            unsafe { Self::expand_aesni(key) }
+        } else {
+            Self::expand_soft(key)
+        }
+    }
+
+    #[target_feature(enable = "aes,sse2")]
+    unsafe fn expand_aesni(key: &sgx_aes_gcm_256bit_key_t) -> Aes256 {
+        let mut rk = [_mm_setzero_si128(); AES256_ROUNDS + 1];
+        rk[0] = _mm_loadu_si128(key.as_ptr() as *const __m128i);
+        rk[1] = _mm_loadu_si128(key[16..].as_ptr() as *const __m128i);
+        rk[2] = aes256_expand_even!(rk[0], rk[1], 0x01);
+        rk[3] = aes256_expand_odd!(rk[1], rk[2]);
+        rk[4] = aes256_expand_even!(rk[2], rk[3], 0x02);
+        rk[5] = aes256_expand_odd!(rk[3], rk[4]);
+        rk[6] = aes256_expand_even!(rk[4], rk[5], 0x04);
+        rk[7] = aes256_expand_odd!(rk[5], rk[6]);
+        rk[8] = aes256_expand_even!(rk[6], rk[7], 0x08);
+        rk[9] = aes256_expand_odd!(rk[7], rk[8]);
+        rk[10] = aes256_expand_even!(rk[8], rk[9], 0x10);
+        rk[11] = aes256_expand_odd!(rk[9], rk[10]);
+        rk[12] = aes256_expand_even!(rk[10], rk[11], 0x20);
+        rk[13] = aes256_expand_odd!(rk[11], rk[12]);
+        rk[14] = aes256_expand_even!(rk[12], rk[13], 0x40);
+        Aes256::Ni(rk)
+    }
+
+    fn expand_soft(key: &sgx_aes_gcm_256bit_key_t) -> Aes256 {
+        let mut w = [[0_u8; 4]; 4 * (AES256_ROUNDS + 1)];
+        for (i, word) in w.iter_mut().take(8).enumerate() {
+            word.copy_from_slice(&key[4 * i..4 * i + 4]);
+        }
+        let mut rcon = 0x01_u8;
+        for i in 8..w.len() {
+            let mut t = w[i - 1];
+            if i % 8 == 0 {
+                t = [
+                    aes_sbox(t[1]) ^ rcon,
+                    aes_sbox(t[2]),
+                    aes_sbox(t[3]),
+                    aes_sbox(t[0]),
+                ];
+                rcon = gf_mul(rcon, 2);
+            } else if i % 8 == 4 {
+                t = [
+                    aes_sbox(t[0]),
+                    aes_sbox(t[1]),
+                    aes_sbox(t[2]),
+                    aes_sbox(t[3]),
+                ];
+            }
+            let prev = w[i - 8];
+            for (b, (p, t)) in w[i].iter_mut().zip(prev.iter().zip(t.iter())) {
+                *b = p ^ t;
+            }
+        }
+        let mut rk = [[0_u8; AEAD_BLOCK_SIZE]; AES256_ROUNDS + 1];
+        for (i, k) in rk.iter_mut().enumerate() {
+            for c in 0..4 {
+                k[4 * c..4 * c + 4].copy_from_slice(&w[4 * i + c]);
+            }
+        }
+        for word in w.iter_mut() {
+            zeroize(word);
+        }
+        Aes256::Soft(rk)
+    }
+
+    fn encrypt_block(&self, block: &mut [u8; AEAD_BLOCK_SIZE]) {
+        match self {
+            // Safe: Aes256::Ni is only constructed when the processor has AES-NI.
+            Aes256::Ni(rk) => unsafe { Self::encrypt_block_aesni(rk, block) },
+            Aes256::Soft(rk) => Self::encrypt_block_soft(rk, block),
+        }
+    }
+
+    #[target_feature(enable = "aes,sse2")]
+    unsafe fn encrypt_block_aesni(
+        round_keys: &[__m128i; AES256_ROUNDS + 1],
+        block: &mut [u8; AEAD_BLOCK_SIZE],
+    ) {
+        let mut b = _mm_loadu_si128(block.as_ptr() as *const __m128i);
+        b = _mm_xor_si128(b, round_keys[0]);
+        for rk in &round_keys[1..AES256_ROUNDS] {
+            b = _mm_aesenc_si128(b, *rk);
+        }
+        b = _mm_aesenclast_si128(b, round_keys[AES256_ROUNDS]);
+        _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, b);
+    }
+
+    fn encrypt_block_soft(
+        round_keys: &[[u8; AEAD_BLOCK_SIZE]; AES256_ROUNDS + 1],
+        block: &mut [u8; AEAD_BLOCK_SIZE],
+    ) {
+        let mut s = *block;
+        xor_block(&mut s, &round_keys[0]);
+        for (round, rk) in round_keys.iter().enumerate().skip(1) {
+            for b in s.iter_mut() {
+                *b = aes_sbox(*b);
+            }
+            // ShiftRows, the state is stored column by column.
+            let mut t = [0_u8; AEAD_BLOCK_SIZE];
+            for c in 0..4 {
+                for r in 0..4 {
+                    t[r + 4 * c] = s[r + 4 * ((c + r) % 4)];
+                }
+            }
+            if round != AES256_ROUNDS {
+                for col in t.chunks_exact_mut(4) {
+                    let (a0, a1, a2, a3) = (col[0], col[1], col[2], col[3]);
+                    let all = a0 ^ a1 ^ a2 ^ a3;
+                    col[0] = a0 ^ all ^ gf_mul(a0 ^ a1, 2);
+                    col[1] = a1 ^ all ^ gf_mul(a1 ^ a2, 2);
+                    col[2] = a2 ^ all ^ gf_mul(a2 ^ a3, 2);
+                    col[3] = a3 ^ all ^ gf_mul(a3 ^ a0, 2);
+                }
+            }
+            s = t;
+            zeroize(&mut t);
+            xor_block(&mut s, rk);
+        }
+        *block = s;
+        zeroize(&mut s);
+    }
+}
+
+impl Drop for Aes256 {
+    fn drop(&mut self) {
+        match self {
+            Aes256::Ni(round_keys) => {
+                for rk in round_keys.iter_mut() {
+                    unsafe { ptr::write_volatile(rk, _mm_setzero_si128()) };
+                }
+            }
+            Aes256::Soft(round_keys) => {
+                for rk in round_keys.iter_mut() {
+                    zeroize(rk);
+                }
+            }
+        }
+    }
+}
+
+fn xor_block(dst: &mut [u8; AEAD_BLOCK_SIZE], src: &[u8; AEAD_BLOCK_SIZE]) {
+    for (d, s) in dst.iter_mut().zip(src.iter()) {
+        *d ^= s;
+    }
+}
+
+// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1, using masks instead
+// of branches.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut r = 0_u8;
+    for _ in 0..8 {
+        r ^= a & 0_u8.wrapping_sub(b & 1);
+        let carry = 0_u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    r
+}
+
+// The S-box is computed rather than looked up so that no memory access depends
+// on secret data: the inverse is x^254, followed by the affine transformation.
+fn aes_sbox(x: u8) -> u8 {
+    let x2 = gf_mul(x, x);
+    let x3 = gf_mul(x2, x);
+    let x6 = gf_mul(x3, x3);
+    let x12 = gf_mul(x6, x6);
+    let x14 = gf_mul(x12, x2);
+    let x15 = gf_mul(x12, x3);
+    let x30 = gf_mul(x15, x15);
+    let x60 = gf_mul(x30, x30);
+    let x120 = gf_mul(x60, x60);
+    let x240 = gf_mul(x120, x120);
+    let inv = gf_mul(x240, x14);
+    inv ^ inv.rotate_left(1) ^ inv.rotate_left(2) ^ inv.rotate_left(3) ^ inv.rotate_left(4) ^ 0x63
+}
+
+//
+// GHASH, bitwise multiplication in GF(2^128) with masks instead of branches
+//
+#[derive(Clone)]
+struct GHash {
+    h: u128,
+    y: u128,
+    buf: [u8; AEAD_BLOCK_SIZE],
+    buf_len: usize,
+}
+
+impl GHash {
+    fn new(h: &[u8; AEAD_BLOCK_SIZE]) -> GHash {
+        GHash {
+            h: u128::from_be_bytes(*h),
+            y: 0,
+            buf: [0; AEAD_BLOCK_SIZE],
+            buf_len: 0,
+        }
+    }
+
+    fn gf_mul(x: u128, h: u128) -> u128 {
+        const R: u128 = 0xe1 << 120;
+        let mut z = 0_u128;
+        let mut v = h;
+        for i in 0..128 {
+            let bit = (x >> (127 - i)) & 1;
+            z ^= v & 0_u128.wrapping_sub(bit);
+            let lsb = v & 1;
+            v = (v >> 1) ^ (R & 0_u128.wrapping_sub(lsb));
+        }
+        z
+    }
+
+    fn block(&mut self, block: &[u8; AEAD_BLOCK_SIZE]) {
+        self.y = Self::gf_mul(self.y ^ u128::from_be_bytes(*block), self.h);
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        if self.buf_len > 0 {
+            let n = core::cmp::min(AEAD_BLOCK_SIZE - self.buf_len, data.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+            if self.buf_len < AEAD_BLOCK_SIZE {
+                return;
+            }
+            let block = self.buf;
+            self.block(&block);
+            self.buf_len = 0;
+        }
+        let mut chunks = data.chunks_exact(AEAD_BLOCK_SIZE);
+        for chunk in &mut chunks {
+            let mut block = [0_u8; AEAD_BLOCK_SIZE];
+            block.copy_from_slice(chunk);
+            self.block(&block);
+        }
+        let rem = chunks.remainder();
+        self.buf[..rem.len()].copy_from_slice(rem);
+        self.buf_len = rem.len();
+    }
+
+    /// Zero-pad the pending data to a whole block.
+    fn pad(&mut self) {
+        if self.buf_len > 0 {
+            let mut block = [0_u8; AEAD_BLOCK_SIZE];
+            block[..self.buf_len].copy_from_slice(&self.buf[..self.buf_len]);
+            self.block(&block);
+            self.buf_len = 0;
+        }
+    }
+
+    fn finalize(&mut self, aad_len: u64, text_len: u64) -> [u8; AEAD_BLOCK_SIZE] {
+        self.pad();
+        let mut block = [0_u8; AEAD_BLOCK_SIZE];
+        block[..8].copy_from_slice(&(aad_len * 8).to_be_bytes());
+        block[8..].copy_from_slice(&(text_len * 8).to_be_bytes());
+        self.block(&block);
+        self.y.to_be_bytes()
+    }
+}
+
+impl Drop for GHash {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::write_volatile(&mut self.h, 0);
+            ptr::write_volatile(&mut self.y, 0);
+        }
+        zeroize(&mut self.buf);
+    }
+}
+
+#[derive(Clone)]
+struct Aes256Gcm {
+    cipher: Aes256,
+    ghash: GHash,
+    tag_mask: [u8; AEAD_BLOCK_SIZE],
+    counter: [u8; AEAD_BLOCK_SIZE],
+    keystream: [u8; AEAD_BLOCK_SIZE],
+    keystream_pos: usize,
+    aad_len: u64,
+    text_len: u64,
+}
+
+impl Aes256Gcm {
+    fn new(key: &sgx_aes_gcm_256bit_key_t, iv: &[u8], aad: &[u8]) -> SgxResult<Aes256Gcm> {
+        if iv.len() != SGX_AESGCM_IV_SIZE {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        let cipher = Aes256::new(key);
+        let mut h = [0_u8; AEAD_BLOCK_SIZE];
+        cipher.encrypt_block(&mut h);
+
+        let mut counter = [0_u8; AEAD_BLOCK_SIZE];
+        counter[..SGX_AESGCM_IV_SIZE].copy_from_slice(iv);
+        counter[AEAD_BLOCK_SIZE - 1] = 1;
+        let mut tag_mask = counter;
+        cipher.encrypt_block(&mut tag_mask);
+
+        let mut ghash = GHash::new(&h);
+        zeroize(&mut h);
+        ghash.update(aad);
+        ghash.pad();
+
+        Ok(Aes256Gcm {
+            cipher,
+            ghash,
+            tag_mask,
+            counter,
+            keystream: [0; AEAD_BLOCK_SIZE],
+            keystream_pos: AEAD_BLOCK_SIZE,
+            aad_len: aad.len() as u64,
+            text_len: 0,
+        })
+    }
+
+    fn apply_keystream(&mut self, src: &[u8], dst: &mut [u8]) -> SgxError {
+        // NIST SP 800-38D limits the plain text to 2^39 - 256 bits
+        let text_len = self.text_len + src.len() as u64;
+        if text_len > (1_u64 << 36) - 32 {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        for (s, d) in src.iter().zip(dst.iter_mut()) {
+            if self.keystream_pos == AEAD_BLOCK_SIZE {
+                let ctr = u32::from_be_bytes([
+                    self.counter[12],
+                    self.counter[13],
+                    self.counter[14],
+                    self.counter[15],
+                ])
+                .wrapping_add(1);
+                self.counter[12..].copy_from_slice(&ctr.to_be_bytes());
+                self.keystream = self.counter;
+                self.cipher.encrypt_block(&mut self.keystream);
+                self.keystream_pos = 0;
+            }
+            *d = *s ^ self.keystream[self.keystream_pos];
+            self.keystream_pos += 1;
+        }
+        self.text_len = text_len;
+        Ok(())
+    }
+
+    fn encrypt(&mut self, src: &[u8], dst: &mut [u8]) -> SgxError {
+        self.apply_keystream(src, dst)?;
+        self.ghash.update(&dst[..src.len()]);
+        Ok(())
+    }
+
+    fn decrypt(&mut self, src: &[u8], dst: &mut [u8]) -> SgxError {
+        self.ghash.update(src);
+        self.apply_keystream(src, dst)
+    }
+
+    fn tag(&mut self) -> sgx_aes_gcm_128bit_tag_t {
+        let mut tag = self.ghash.finalize(self.aad_len, self.text_len);
+        for (t, m) in tag.iter_mut().zip(self.tag_mask.iter()) {
+            *t ^= *m;
+        }
+        tag
+    }
+}
+
+impl Drop for Aes256Gcm {
+    fn drop(&mut self) {
+        zeroize(&mut self.tag_mask);
+        zeroize(&mut self.keystream);
+    }
+}
+
+//
+// ChaCha20 and Poly1305 as specified in RFC 8439
+//
+#[derive(Clone)]
+struct ChaCha20 {
+    state: [u32; 16],
+    keystream: [u8; CHACHA20_BLOCK_SIZE],
+    keystream_pos: usize,
+}
+
+macro_rules! chacha20_quarter_round {
+    ($x:expr, $a:expr, $b:expr, $c:expr, $d:expr) => {
+        $x[$a] = $x[$a].wrapping_add($x[$b]);
+        $x[$d] = ($x[$d] ^ $x[$a]).rotate_left(16);
+        $x[$c] = $x[$c].wrapping_add($x[$d]);
+        $x[$b] = ($x[$b] ^ $x[$c]).rotate_left(12);
+        $x[$a] = $x[$a].wrapping_add($x[$b]);
+        $x[$d] = ($x[$d] ^ $x[$a]).rotate_left(8);
+        $x[$c] = $x[$c].wrapping_add($x[$d]);
+        $x[$b] = ($x[$b] ^ $x[$c]).rotate_left(7);
+    };
+}
+
+impl ChaCha20 {
+    fn new(key: &sgx_chacha20_poly1305_key_t, nonce: &[u8], counter: u32) -> ChaCha20 {
+        let mut state = [0_u32; 16];
+        state[0] = 0x6170_7865;
+        state[1] = 0x3320_646e;
+        state[2] = 0x7962_2d32;
+        state[3] = 0x6b20_6574;
+        for i in 0..8 {
+            state[4 + i] = load_le32(&key[i * 4..]);
+        }
+        state[12] = counter;
+        for i in 0..3 {
+            state[13 + i] = load_le32(&nonce[i * 4..]);
+        }
+        ChaCha20 {
+            state,
+            keystream: [0; CHACHA20_BLOCK_SIZE],
+            keystream_pos: CHACHA20_BLOCK_SIZE,
+        }
+    }
+
+    fn block(&mut self) {
+        let mut x = self.state;
+        for _ in 0..10 {
+            chacha20_quarter_round!(x, 0, 4, 8, 12);
+            chacha20_quarter_round!(x, 1, 5, 9, 13);
+            chacha20_quarter_round!(x, 2, 6, 10, 14);
+            chacha20_quarter_round!(x, 3, 7, 11, 15);
+            chacha20_quarter_round!(x, 0, 5, 10, 15);
+            chacha20_quarter_round!(x, 1, 6, 11, 12);
+            chacha20_quarter_round!(x, 2, 7, 8, 13);
+            chacha20_quarter_round!(x, 3, 4, 9, 14);
+        }
+        for (i, word) in x.iter().enumerate() {
+            let word = word.wrapping_add(self.state[i]);
+            self.keystream[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+        }
+        for word in x.iter_mut() {
+            unsafe { ptr::write_volatile(word, 0) };
+        }
+        self.state[12] = self.state[12].wrapping_add(1);
+        self.keystream_pos = 0;
+    }
+
+    fn apply_keystream(&mut self, src: &[u8], dst: &mut [u8]) {
+        for (s, d) in src.iter().zip(dst.iter_mut()) {
+            if self.keystream_pos == CHACHA20_BLOCK_SIZE {
+                self.block();
+            }
+            *d = *s ^ self.keystream[self.keystream_pos];
+            self.keystream_pos += 1;
+        }
+    }
+}
+
+impl Drop for ChaCha20 {
+    fn drop(&mut self) {
+        for word in self.state.iter_mut() {
+            unsafe { ptr::write_volatile(word, 0) };
+        }
+        zeroize(&mut self.keystream);
+    }
+}
+
+#[derive(Clone)]
+struct Poly1305 {
+    r: [u32; 5],
+    h: [u32; 5],
+    pad: [u32; 4],
+    buf: [u8; AEAD_BLOCK_SIZE],
+    buf_len: usize,
+}
+
+impl Poly1305 {
+    fn new(key: &[u8; 32]) -> Poly1305 {
+        Poly1305 {
+            r: [
+                load_le32(&key[0..]) & 0x03ff_ffff,
+                (load_le32(&key[3..]) >> 2) & 0x03ff_ff03,
+                (load_le32(&key[6..]) >> 4) & 0x03ff_c0ff,
+                (load_le32(&key[9..]) >> 6) & 0x03f0_3fff,
+                (load_le32(&key[12..]) >> 8) & 0x000f_ffff,
+            ],
+            h: [0; 5],
+            pad: [
+                load_le32(&key[16..]),
+                load_le32(&key[20..]),
+                load_le32(&key[24..]),
+                load_le32(&key[28..]),
+            ],
+            buf: [0; AEAD_BLOCK_SIZE],
+            buf_len: 0,
+        }
+    }
+
+    fn block(&mut self, m: &[u8], hibit: u32) {
+        let [r0, r1, r2, r3, r4] = self.r;
+        let (s1, s2, s3, s4) = (r1 * 5, r2 * 5, r3 * 5, r4 * 5);
+        let [mut h0, mut h1, mut h2, mut h3, mut h4] = self.h;
+
+        h0 += load_le32(&m[0..]) & 0x03ff_ffff;
+        h1 += (load_le32(&m[3..]) >> 2) & 0x03ff_ffff;
+        h2 += (load_le32(&m[6..]) >> 4) & 0x03ff_ffff;
+        h3 += (load_le32(&m[9..]) >> 6) & 0x03ff_ffff;
+        h4 += (load_le32(&m[12..]) >> 8) | hibit;
+
+        let mul = |a: u32, b: u32| a as u64 * b as u64;
+        let d0 = mul(h0, r0) + mul(h1, s4) + mul(h2, s3) + mul(h3, s2) + mul(h4, s1);
+        let mut d1 = mul(h0, r1) + mul(h1, r0) + mul(h2, s4) + mul(h3, s3) + mul(h4, s2);
+        let mut d2 = mul(h0, r2) + mul(h1, r1) + mul(h2, r0) + mul(h3, s4) + mul(h4, s3);
+        let mut d3 = mul(h0, r3) + mul(h1, r2) + mul(h2, r1) + mul(h3, r0) + mul(h4, s4);
+        let mut d4 = mul(h0, r4) + mul(h1, r3) + mul(h2, r2) + mul(h3, r1) + mul(h4, r0);
+
+        let mut c = (d0 >> 26) as u32;
+        h0 = d0 as u32 & 0x03ff_ffff;
+        d1 += c as u64;
+        c = (d1 >> 26) as u32;
+        h1 = d1 as u32 & 0x03ff_ffff;
+        d2 += c as u64;
+        c = (d2 >> 26) as u32;
+        h2 = d2 as u32 & 0x03ff_ffff;
+        d3 += c as u64;
+        c = (d3 >> 26) as u32;
+        h3 = d3 as u32 & 0x03ff_ffff;
+        d4 += c as u64;
+        c = (d4 >> 26) as u32;
+        h4 = d4 as u32 & 0x03ff_ffff;
+        h0 += c * 5;
+        c = h0 >> 26;
+        h0 &= 0x03ff_ffff;
+        h1 += c;
+
+        self.h = [h0, h1, h2, h3, h4];
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        if self.buf_len > 0 {
+            let n = core::cmp::min(AEAD_BLOCK_SIZE - self.buf_len, data.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+            if self.buf_len < AEAD_BLOCK_SIZE {
+                return;
+            }
+            let block = self.buf;
+            self.block(&block, 1 << 24);
+            self.buf_len = 0;
+        }
+        let mut chunks = data.chunks_exact(AEAD_BLOCK_SIZE);
+        for chunk in &mut chunks {
+            self.block(chunk, 1 << 24);
+        }
+        let rem = chunks.remainder();
+        self.buf[..rem.len()].copy_from_slice(rem);
+        self.buf_len = rem.len();
+    }
+
+    /// Zero-pad the pending data to a whole block, as required by the AEAD construction.
+    fn pad(&mut self) {
+        if self.buf_len > 0 {
+            let mut block = [0_u8; AEAD_BLOCK_SIZE];
+            block[..self.buf_len].copy_from_slice(&self.buf[..self.buf_len]);
+            self.block(&block, 1 << 24);
+            self.buf_len = 0;
+        }
+    }
+
+    fn finalize(&mut self) -> [u8; AEAD_BLOCK_SIZE] {
+        if self.buf_len > 0 {
+            let mut block = [0_u8; AEAD_BLOCK_SIZE];
+            block[..self.buf_len].copy_from_slice(&self.buf[..self.buf_len]);
+            block[self.buf_len] = 1;
+            self.block(&block, 0);
+            self.buf_len = 0;
+        }
+
+        let [mut h0, mut h1, mut h2, mut h3, mut h4] = self.h;
+        let mut c = h1 >> 26;
+        h1 &= 0x03ff_ffff;
+        h2 += c;
+        c = h2 >> 26;
+        h2 &= 0x03ff_ffff;
+        h3 += c;
+        c = h3 >> 26;
+        h3 &= 0x03ff_ffff;
+        h4 += c;
+        c = h4 >> 26;
+        h4 &= 0x03ff_ffff;
+        h0 += c * 5;
+        c = h0 >> 26;
+        h0 &= 0x03ff_ffff;
+        h1 += c;
+
+        // compute h - p and select it if it does not underflow
+        let mut g0 = h0.wrapping_add(5);
+        c = g0 >> 26;
+        g0 &= 0x03ff_ffff;
+        let mut g1 = h1.wrapping_add(c);
+        c = g1 >> 26;
+        g1 &= 0x03ff_ffff;
+        let mut g2 = h2.wrapping_add(c);
+        c = g2 >> 26;
+        g2 &= 0x03ff_ffff;
+        let mut g3 = h3.wrapping_add(c);
+        c = g3 >> 26;
+        g3 &= 0x03ff_ffff;
+        let g4 = h4.wrapping_add(c).wrapping_sub(1 << 26);
+
+        let mask = (g4 >> 31).wrapping_sub(1);
+        h0 = (h0 & !mask) | (g0 & mask);
+        h1 = (h1 & !mask) | (g1 & mask);
+        h2 = (h2 & !mask) | (g2 & mask);
+        h3 = (h3 & !mask) | (g3 & mask);
+        h4 = (h4 & !mask) | (g4 & mask);
+
+        let h0 = h0 | (h1 << 26);
+        let h1 = (h1 >> 6) | (h2 << 20);
+        let h2 = (h2 >> 12) | (h3 << 14);
+        let h3 = (h3 >> 18) | (h4 << 8);
+
+        let mut f = h0 as u64 + self.pad[0] as u64;
+        let t0 = f as u32;
+        f = h1 as u64 + self.pad[1] as u64 + (f >> 32);
+        let t1 = f as u32;
+        f = h2 as u64 + self.pad[2] as u64 + (f >> 32);
+        let t2 = f as u32;
+        f = h3 as u64 + self.pad[3] as u64 + (f >> 32);
+        let t3 = f as u32;
+
+        let mut tag = [0_u8; AEAD_BLOCK_SIZE];
+        tag[0..4].copy_from_slice(&t0.to_le_bytes());
+        tag[4..8].copy_from_slice(&t1.to_le_bytes());
+        tag[8..12].copy_from_slice(&t2.to_le_bytes());
+        tag[12..16].copy_from_slice(&t3.to_le_bytes());
+        tag
+    }
+}
+
+impl Drop for Poly1305 {
+    fn drop(&mut self) {
+        for word in self
+            .r
+            .iter_mut()
+            .chain(self.h.iter_mut())
+            .chain(self.pad.iter_mut())
+        {
+            unsafe { ptr::write_volatile(word, 0) };
+        }
+        zeroize(&mut self.buf);
+    }
+}
+
+#[derive(Clone)]
+struct ChaCha20Poly1305 {
+    cipher: ChaCha20,
+    mac: Poly1305,
+    aad_len: u64,
+    text_len: u64,
+}
+
+impl ChaCha20Poly1305 {
+    fn new(
+        key: &sgx_chacha20_poly1305_key_t,
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> SgxResult<ChaCha20Poly1305> {
+        if nonce.len() != SGX_CHACHA20POLY1305_NONCE_SIZE {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        let mut cipher = ChaCha20::new(key, nonce, 0);
+        let mut mac_key = [0_u8; 32];
+        cipher.apply_keystream(&[0_u8; 32], &mut mac_key);
+        let mut mac = Poly1305::new(&mac_key);
+        zeroize(&mut mac_key);
+        // the first block of the key stream is only used for the Poly1305 key
+        cipher.keystream_pos = CHACHA20_BLOCK_SIZE;
+
+        mac.update(aad);
+        mac.pad();
+
+        Ok(ChaCha20Poly1305 {
+            cipher,
+            mac,
+            aad_len: aad.len() as u64,
+            text_len: 0,
+        })
+    }
+
+    fn check_len(&self, len: usize) -> SgxResult<u64> {
+        // RFC 8439 limits the plain text to 2^32 - 1 blocks of 64 bytes
+        let text_len = self.text_len + len as u64;
+        if text_len > ((1_u64 << 32) - 1) * CHACHA20_BLOCK_SIZE as u64 {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        Ok(text_len)
+    }
+
+    fn encrypt(&mut self, src: &[u8], dst: &mut [u8]) -> SgxError {
+        self.text_len = self.check_len(src.len())?;
+        self.cipher.apply_keystream(src, dst);
+        self.mac.update(&dst[..src.len()]);
+        Ok(())
+    }
+
+    fn decrypt(&mut self, src: &[u8], dst: &mut [u8]) -> SgxError {
+        self.text_len = self.check_len(src.len())?;
+        self.mac.update(src);
+        self.cipher.apply_keystream(src, dst);
+        Ok(())
+    }
+
+    fn tag(&mut self) -> sgx_chacha20_poly1305_tag_t {
+        self.mac.pad();
+        let mut lengths = [0_u8; AEAD_BLOCK_SIZE];
+        lengths[..8].copy_from_slice(&self.aad_len.to_le_bytes());
+        lengths[8..].copy_from_slice(&self.text_len.to_le_bytes());
+        self.mac.update(&lengths);
+        self.mac.finalize()
+    }
+}
+
+fn check_aead_buffers(src: &[u8], aad: &[u8], dst: &[u8]) -> SgxError {
+    if src.len() > u32::MAX as usize || aad.len() > u32::MAX as usize {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    if dst.len() < src.len() {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    if src.is_empty() && aad.is_empty() {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    Ok(())
+}
+
+///
+/// rsgx_aes256GCM_encrypt performs an AES-GCM encryption operation with a 256-bit key.
+///
+/// # Description
+///
+/// The function has the same shape as `rsgx_rijndael128GCM_encrypt`, but uses a 256-bit key.
+/// Only the NIST recommended IV size of 96 bits (12 bytes) is supported.
+///
+/// # Parameters
+///
+/// **key**
+///
+/// A pointer to key to be used in the AES-GCM encryption operation. The size must be 256 bits.
+///
+/// **src**
+///
+/// A pointer to the input data stream to be encrypted. Buffer content could be empty if there is AAD text.
+///
+/// **iv**
+///
+/// A pointer to the initialization vector to be used in the AES-GCM calculation. The size must be 96 bits.
+///
+/// **aad**
+///
+/// A pointer to an optional additional authentication data buffer which is used in the GCM MAC calculation.
+/// The data in this buffer will not be encrypted. The field is optional and content could be empty.
+///
+/// **dst**
+///
+/// A pointer to the output encrypted data buffer. This buffer should be allocated by the calling code.
+///
+/// **mac**
+///
+/// This is the output GCM MAC performed over the input data buffer (data to be encrypted) as well as
+/// the additional authentication data (this is optional data). The calling code should allocate this buffer.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// If both source buffer and AAD buffer content are empty.
+///
+/// If IV Length is not equal to 12 (bytes).
+///
+pub fn rsgx_aes256GCM_encrypt(
+    key: &sgx_aes_gcm_256bit_key_t,
+    src: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    dst: &mut [u8],
+    mac: &mut sgx_aes_gcm_128bit_tag_t,
+) -> SgxError {
+    check_aead_buffers(src, aad, dst)?;
+    let mut gcm = Aes256Gcm::new(key, iv, aad)?;
+    gcm.encrypt(src, dst)?;
+    *mac = gcm.tag();
+    Ok(())
+}
+
+///
+/// rsgx_aes256GCM_decrypt performs an AES-GCM decryption operation with a 256-bit key.
+///
+/// # Description
+///
+/// The function has the same shape as `rsgx_rijndael128GCM_decrypt`, but uses a 256-bit key.
+/// The output buffer is cleared if the MAC does not match.
+///
+/// # Parameters
+///
+/// **key**
+///
+/// A pointer to key to be used in the AES-GCM decryption operation. The size must be 256 bits.
+///
+/// **src**
+///
+/// A pointer to the input data stream to be decrypted. Buffer content could be empty if there is AAD text.
+///
+/// **iv**
+///
+/// A pointer to the initialization vector to be used in the AES-GCM calculation. The size must be 96 bits.
+///
+/// **aad**
+///
+/// A pointer to an optional additional authentication data buffer which is provided for the GCM MAC calculation
+/// when encrypting. The data in this buffer was not encrypted. The field is optional and content could be empty.
+///
+/// **mac**
+///
+/// This is the GCM MAC that was performed over the input data buffer as well as the additional
+/// authentication data during the encryption process (call to rsgx_aes256GCM_encrypt).
+///
+/// **dst**
+///
+/// A pointer to the output decrypted data buffer. This buffer should be allocated by the calling code.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// If both source buffer and AAD buffer content are empty.
+///
+/// If IV Length is not equal to 12 (bytes).
+///
+/// **SGX_ERROR_MAC_MISMATCH**
+///
+/// The input MAC does not match the MAC calculated.
+///
+pub fn rsgx_aes256GCM_decrypt(
+    key: &sgx_aes_gcm_256bit_key_t,
+    src: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    mac: &sgx_aes_gcm_128bit_tag_t,
+    dst: &mut [u8],
+) -> SgxError {
+    check_aead_buffers(src, aad, dst)?;
+    let mut gcm = Aes256Gcm::new(key, iv, aad)?;
+    gcm.decrypt(src, dst)?;
+    if !ct_eq(&gcm.tag(), mac) {
+        zeroize(&mut dst[..src.len()]);
+        return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
+    }
+    Ok(())
+}
+
+///
+/// rsgx_chacha20_poly1305_encrypt performs a ChaCha20-Poly1305 encryption operation as specified in RFC 8439.
+///
+/// # Parameters
+///
+/// **key**
+///
+/// A pointer to key to be used in the encryption operation. The size must be 256 bits.
+///
+/// **src**
+///
+/// A pointer to the input data stream to be encrypted. Buffer content could be empty if there is AAD text.
+///
+/// **nonce**
+///
+/// A pointer to the nonce. The size must be 96 bits (12 bytes). A nonce must never be reused with the same key.
+///
+/// **aad**
+///
+/// A pointer to an optional additional authentication data buffer which is used in the MAC calculation.
+/// The data in this buffer will not be encrypted. The field is optional and content could be empty.
+///
+/// **dst**
+///
+/// A pointer to the output encrypted data buffer. This buffer should be allocated by the calling code.
+///
+/// **mac**
+///
+/// This is the output Poly1305 MAC performed over the encrypted data as well as the additional
+/// authentication data. The calling code should allocate this buffer.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// If both source buffer and AAD buffer content are empty.
+///
+/// If nonce Length is not equal to 12 (bytes).
+///
+pub fn rsgx_chacha20_poly1305_encrypt(
+    key: &sgx_chacha20_poly1305_key_t,
+    src: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    dst: &mut [u8],
+    mac: &mut sgx_chacha20_poly1305_tag_t,
+) -> SgxError {
+    check_aead_buffers(src, aad, dst)?;
+    let mut aead = ChaCha20Poly1305::new(key, nonce, aad)?;
+    aead.encrypt(src, dst)?;
+    *mac = aead.tag();
+    Ok(())
+}
+
+///
+/// rsgx_chacha20_poly1305_decrypt performs a ChaCha20-Poly1305 decryption operation as specified in RFC 8439.
+///
+/// The output buffer is cleared if the MAC does not match.
+///
+/// # Parameters
+///
+/// **key**
+///
+/// A pointer to key to be used in the decryption operation. The size must be 256 bits.
+///
+/// **src**
+///
+/// A pointer to the input data stream to be decrypted. Buffer content could be empty if there is AAD text.
+///
+/// **nonce**
+///
+/// A pointer to the nonce. The size must be 96 bits (12 bytes).
+///
+/// **aad**
+///
+/// A pointer to an optional additional authentication data buffer which is provided for the MAC calculation
+/// when encrypting. The field is optional and content could be empty.
+///
+/// **mac**
+///
+/// This is the Poly1305 MAC that was computed during the encryption process
+/// (call to rsgx_chacha20_poly1305_encrypt).
+///
+/// **dst**
+///
+/// A pointer to the output decrypted data buffer. This buffer should be allocated by the calling code.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// If both source buffer and AAD buffer content are empty.
+///
+/// If nonce Length is not equal to 12 (bytes).
+///
+/// **SGX_ERROR_MAC_MISMATCH**
+///
+/// The input MAC does not match the MAC calculated.
+///
+pub fn rsgx_chacha20_poly1305_decrypt(
+    key: &sgx_chacha20_poly1305_key_t,
+    src: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    mac: &sgx_chacha20_poly1305_tag_t,
+    dst: &mut [u8],
+) -> SgxError {
+    check_aead_buffers(src, aad, dst)?;
+    let mut aead = ChaCha20Poly1305::new(key, nonce, aad)?;
+    aead.decrypt(src, dst)?;
+    if !ct_eq(&aead.tag(), mac) {
+        zeroize(&mut dst[..src.len()]);
+        return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
+    }
+    Ok(())
+}
+
+fn check_update_buffers(src: &[u8], dst: &[u8]) -> SgxError {
+    if src.is_empty() || src.len() > u32::MAX as usize {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    if dst.len() < src.len() {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    Ok(())
+}
+
+///
+/// SgxAes256Handle is the streaming AES-256-GCM encryption context, with the same
+/// init/update/get_mac/close life cycle as `SgxAesHandle`.
+///
+pub struct SgxAes256Handle {
+    state: RefCell<Option<Aes256Gcm>>,
+    initflag: Cell<bool>,
+}
+
+impl SgxAes256Handle {
+    pub fn new() -> SgxAes256Handle {
+        SgxAes256Handle {
+            state: RefCell::new(None),
+            initflag: Cell::new(false),
+        }
+    }
+
+    pub fn init(&self, key: &sgx_aes_gcm_256bit_key_t, iv: &[u8], aad: &[u8]) -> SgxError {
+        if self.initflag.get() {
+            return Ok(());
+        }
+        if aad.len() > u32::MAX as usize {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        *self.state.borrow_mut() = Some(Aes256Gcm::new(key, iv, aad)?);
+        self.initflag.set(true);
+        Ok(())
+    }
+
+    pub fn update(&self, src: &[u8], dst: &mut [u8]) -> SgxError {
+        if !self.initflag.get() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+        check_update_buffers(src, dst)?;
+        match self.state.borrow_mut().as_mut() {
+            Some(gcm) => gcm.encrypt(src, dst),
+            None => Err(sgx_status_t::SGX_ERROR_INVALID_STATE),
+        }
+    }
+
+    pub fn get_mac(&self) -> SgxResult<sgx_aes_gcm_128bit_tag_t> {
+        if !self.initflag.get() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+        // the MAC is computed on a copy of the GHASH state so that it can be queried at any time
+        match self.state.borrow().as_ref() {
+            Some(gcm) => Ok(gcm.clone().tag()),
+            None => Err(sgx_status_t::SGX_ERROR_INVALID_STATE),
+        }
+    }
+
+    pub fn close(&self) -> SgxError {
+        if !self.initflag.get() {
+            return Ok(());
+        }
+        *self.state.borrow_mut() = None;
+        self.initflag.set(false);
+        Ok(())
+    }
+}
+
+impl Default for SgxAes256Handle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SgxAes256Handle {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+///
+/// SgxChaCha20Poly1305Handle is the streaming ChaCha20-Poly1305 encryption context, with the
+/// same init/update/get_mac/close life cycle as `SgxAesHandle`.
+///
+pub struct SgxChaCha20Poly1305Handle {
+    state: RefCell<Option<ChaCha20Poly1305>>,
+    initflag: Cell<bool>,
+}
+
+impl SgxChaCha20Poly1305Handle {
+    pub fn new() -> SgxChaCha20Poly1305Handle {
+        SgxChaCha20Poly1305Handle {
+            state: RefCell::new(None),
+            initflag: Cell::new(false),
+        }
+    }
+
+    pub fn init(&self, key: &sgx_chacha20_poly1305_key_t, nonce: &[u8], aad: &[u8]) -> SgxError {
+        if self.initflag.get() {
+            return Ok(());
+        }
+        if aad.len() > u32::MAX as usize {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        *self.state.borrow_mut() = Some(ChaCha20Poly1305::new(key, nonce, aad)?);
+        self.initflag.set(true);
+        Ok(())
+    }
+
+    pub fn update(&self, src: &[u8], dst: &mut [u8]) -> SgxError {
+        if !self.initflag.get() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+        check_update_buffers(src, dst)?;
+        match self.state.borrow_mut().as_mut() {
+            Some(aead) => aead.encrypt(src, dst),
+            None => Err(sgx_status_t::SGX_ERROR_INVALID_STATE),
+        }
+    }
+
+    pub fn get_mac(&self) -> SgxResult<sgx_chacha20_poly1305_tag_t> {
+        if !self.initflag.get() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+        // the MAC is computed on a copy of the Poly1305 state so that it can be queried at any time
+        match self.state.borrow().as_ref() {
+            Some(aead) => Ok(aead.clone().tag()),
+            None => Err(sgx_status_t::SGX_ERROR_INVALID_STATE),
+        }
+    }
+
+    pub fn close(&self) -> SgxError {
+        if !self.initflag.get() {
+            return Ok(());
+        }
+        *self.state.borrow_mut() = None;
+        self.initflag.set(false);
+        Ok(())
+    }
+}
+
+impl Default for SgxChaCha20Poly1305Handle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SgxChaCha20Poly1305Handle {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    pub(crate) fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn aes256_fips197() {
+        let mut key = [0_u8; 32];
+        key.copy_from_slice(&hex(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        ));
+        let expected = hex("8ea2b7ca516745bfeafc49904b496089");
+
+        let mut block = [0_u8; AEAD_BLOCK_SIZE];
+        block.copy_from_slice(&hex("00112233445566778899aabbccddeeff"));
+        Aes256::expand_soft(&key).encrypt_block(&mut block);
+        assert_eq!(block.to_vec(), expected);
+
+        if crate::has_aes_ni() {
+            block.copy_from_slice(&hex("00112233445566778899aabbccddeeff"));
+            unsafe { Aes256::expand_aesni(&key) }.encrypt_block(&mut block);
+            assert_eq!(block.to_vec(), expected);
+        }
+    }
+
+    #[test]
+    fn aes256_software_matches_aesni() {
+        if !crate::has_aes_ni() {
+            return;
+        }
+        let mut key = [0_u8; 32];
+        let mut block = [0_u8; AEAD_BLOCK_SIZE];
+        for i in 0..64_u8 {
+            for (j, k) in key.iter_mut().enumerate() {
+                *k = i.wrapping_mul(31).wrapping_add(j as u8).rotate_left(3);
+            }
+            let soft = Aes256::expand_soft(&key);
+            let ni = unsafe { Aes256::expand_aesni(&key) };
+            let mut other = block;
+            soft.encrypt_block(&mut block);
+            ni.encrypt_block(&mut other);
+            assert_eq!(block, other);
+        }
+    }
+
+    #[test]
+    fn aes256_gcm_vectors() {
+        // Test case 16 of the GCM specification (McGrew and Viega).
+        let mut key = [0_u8; 32];
+        key.copy_from_slice(&hex(
+            "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+        ));
+        let iv = hex("cafebabefacedbaddecaf888");
+        let aad = hex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let plain = hex(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+             1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        );
+        let mut cipher = vec![0_u8; plain.len()];
+        let mut mac = [0_u8; 16];
+        rsgx_aes256GCM_encrypt(&key, &plain, &iv, &aad, &mut cipher, &mut mac).unwrap();
+        assert_eq!(
+            cipher,
+            hex(
+                "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa\
+                 8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662"
+            )
+        );
+        assert_eq!(mac.to_vec(), hex("76fc6ece0f4e1768cddf8853bb2d551b"));
+
+        let mut decrypted = vec![0_u8; plain.len()];
+        rsgx_aes256GCM_decrypt(&key, &cipher, &iv, &aad, &mac, &mut decrypted).unwrap();
+        assert_eq!(decrypted, plain);
+        mac[0] ^= 1;
+        assert!(rsgx_aes256GCM_decrypt(&key, &cipher, &iv, &aad, &mac, &mut decrypted).is_err());
+        mac[0] ^= 1;
+
+        let handle = SgxAes256Handle::new();
+        handle.init(&key, &iv, &aad).unwrap();
+        let mut streamed = vec![0_u8; plain.len()];
+        handle.update(&plain[..7], &mut streamed[..7]).unwrap();
+        handle.update(&plain[7..], &mut streamed[7..]).unwrap();
+        assert_eq!(streamed, cipher);
+        assert_eq!(handle.get_mac().unwrap(), mac);
+        handle.close().unwrap();
+    }
+
+    #[test]
+    fn chacha20_poly1305_vector() {
+        // RFC 8439, section 2.8.2.
+        let mut key = [0_u8; 32];
+        key.copy_from_slice(&hex(
+            "808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f",
+        ));
+        let nonce = hex("070000004041424344454647");
+        let aad = hex("50515253c0c1c2c3c4c5c6c7");
+        let plain: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you \
+            only one tip for the future, sunscreen would be it.";
+        let mut cipher = vec![0_u8; plain.len()];
+        let mut mac = [0_u8; 16];
+        rsgx_chacha20_poly1305_encrypt(&key, plain, &nonce, &aad, &mut cipher, &mut mac).unwrap();
+        assert_eq!(
+            cipher,
+            hex(
+                "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6\
+                 3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36\
+                 92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc\
+                 3ff4def08e4b7a9de576d26586cec64b6116"
+            )
+        );
+        assert_eq!(mac.to_vec(), hex("1ae10b594f09e26a7e902ecbd0600691"));
+
+        let mut decrypted = vec![0_u8; plain.len()];
+        rsgx_chacha20_poly1305_decrypt(&key, &cipher, &nonce, &aad, &mac, &mut decrypted).unwrap();
+        assert_eq!(decrypted, plain);
+        cipher[5] ^= 1;
+        assert!(
+            rsgx_chacha20_poly1305_decrypt(&key, &cipher, &nonce, &aad, &mac, &mut decrypted)
+                .is_err()
+        );
+    }
+}
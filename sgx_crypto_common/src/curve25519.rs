@@ -0,0 +1,887 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Curve25519
+//!
+//! Ed25519 signatures (RFC 8032) and X25519 key agreement (RFC 7748).
+//!
+//! The cryptography library only supports NIST P-256, so both are implemented here.
+//! Operations on secret data are constant time.
+//!
+use crate::aead::{ct_eq, zeroize};
+use crate::sha512::sha512;
+use core::mem;
+use core::slice;
+use sgx_types::marker::ContiguousMemory;
+use sgx_types::*;
+
+//
+// Field arithmetic modulo p = 2^255 - 19, with five 51-bit limbs.
+//
+
+const MASK51: u64 = (1 << 51) - 1;
+
+// p - 2, little endian
+#[rustfmt::skip]
+const P_MINUS_2: [u8; 32] = [
+    0xeb, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f,
+];
+
+// (p - 5) / 8, little endian
+#[rustfmt::skip]
+const P_MINUS_5_DIV_8: [u8; 32] = [
+    0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x0f,
+];
+
+// d = -121665 / 121666
+#[rustfmt::skip]
+const EDWARDS_D: [u8; 32] = [
+    0xa3, 0x78, 0x59, 0x13, 0xca, 0x4d, 0xeb, 0x75, 0xab, 0xd8, 0x41, 0x41, 0x4d, 0x0a, 0x70, 0x00,
+    0x98, 0xe8, 0x79, 0x77, 0x79, 0x40, 0xc7, 0x8c, 0x73, 0xfe, 0x6f, 0x2b, 0xee, 0x6c, 0x03, 0x52,
+];
+
+// sqrt(-1) = 2^((p - 1) / 4)
+#[rustfmt::skip]
+const SQRT_M1: [u8; 32] = [
+    0xb0, 0xa0, 0x0e, 0x4a, 0x27, 0x1b, 0xee, 0xc4, 0x78, 0xe4, 0x2f, 0xad, 0x06, 0x18, 0x43, 0x2f,
+    0xa7, 0xd7, 0xfb, 0x3d, 0x99, 0x00, 0x4d, 0x2b, 0x0b, 0xdf, 0xc1, 0x4f, 0x80, 0x24, 0x83, 0x2b,
+];
+
+// The compressed Ed25519 base point, y = 4/5.
+#[rustfmt::skip]
+const ED25519_BASEPOINT: [u8; 32] = [
+    0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+];
+
+// The X25519 base point, u = 9.
+#[rustfmt::skip]
+const X25519_BASEPOINT: [u8; 32] = [
+    9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+#[derive(Clone, Copy)]
+struct Fe([u64; 5]);
+
+impl Fe {
+    const ZERO: Fe = Fe([0, 0, 0, 0, 0]);
+    const ONE: Fe = Fe([1, 0, 0, 0, 0]);
+
+    fn from_bytes(b: &[u8; 32]) -> Fe {
+        let load = |i: usize| {
+            let mut w = [0_u8; 8];
+            w.copy_from_slice(&b[i..i + 8]);
+            u64::from_le_bytes(w)
+        };
+        Fe([
+            load(0) & MASK51,
+            (load(6) >> 3) & MASK51,
+            (load(12) >> 6) & MASK51,
+            (load(19) >> 1) & MASK51,
+            (load(24) >> 12) & MASK51,
+        ])
+    }
+
+    fn to_bytes(self) -> [u8; 32] {
+        let mut l = Fe::weak_reduce(self.0).0;
+
+        // Subtract p if the value is not smaller than p.
+        let mut q = (l[0] + 19) >> 51;
+        q = (l[1] + q) >> 51;
+        q = (l[2] + q) >> 51;
+        q = (l[3] + q) >> 51;
+        q = (l[4] + q) >> 51;
+        l[0] += 19 * q;
+        l[1] += l[0] >> 51;
+        l[0] &= MASK51;
+        l[2] += l[1] >> 51;
+        l[1] &= MASK51;
+        l[3] += l[2] >> 51;
+        l[2] &= MASK51;
+        l[4] += l[3] >> 51;
+        l[3] &= MASK51;
+        l[4] &= MASK51;
+
+        let words = [
+            l[0] | (l[1] << 51),
+            (l[1] >> 13) | (l[2] << 38),
+            (l[2] >> 26) | (l[3] << 25),
+            (l[3] >> 39) | (l[4] << 12),
+        ];
+        let mut s = [0_u8; 32];
+        for (chunk, w) in s.chunks_mut(8).zip(words.iter()) {
+            chunk.copy_from_slice(&w.to_le_bytes());
+        }
+        s
+    }
+
+    fn weak_reduce(mut l: [u64; 5]) -> Fe {
+        let c0 = l[0] >> 51;
+        let c1 = l[1] >> 51;
+        let c2 = l[2] >> 51;
+        let c3 = l[3] >> 51;
+        let c4 = l[4] >> 51;
+        l[0] &= MASK51;
+        l[1] &= MASK51;
+        l[2] &= MASK51;
+        l[3] &= MASK51;
+        l[4] &= MASK51;
+        l[0] += c4 * 19;
+        l[1] += c0;
+        l[2] += c1;
+        l[3] += c2;
+        l[4] += c3;
+        Fe(l)
+    }
+
+    fn add(&self, rhs: &Fe) -> Fe {
+        let mut l = [0_u64; 5];
+        for (i, x) in l.iter_mut().enumerate() {
+            *x = self.0[i] + rhs.0[i];
+        }
+        Fe::weak_reduce(l)
+    }
+
+    fn sub(&self, rhs: &Fe) -> Fe {
+        // Add 16p first so that the limbs never underflow.
+        Fe::weak_reduce([
+            (self.0[0] + 36028797018963664) - rhs.0[0],
+            (self.0[1] + 36028797018963952) - rhs.0[1],
+            (self.0[2] + 36028797018963952) - rhs.0[2],
+            (self.0[3] + 36028797018963952) - rhs.0[3],
+            (self.0[4] + 36028797018963952) - rhs.0[4],
+        ])
+    }
+
+    fn neg(&self) -> Fe {
+        Fe::ZERO.sub(self)
+    }
+
+    fn mul(&self, rhs: &Fe) -> Fe {
+        let m = |x: u64, y: u64| (x as u128) * (y as u128);
+        let a = &self.0;
+        let b = &rhs.0;
+        let b1_19 = b[1] * 19;
+        let b2_19 = b[2] * 19;
+        let b3_19 = b[3] * 19;
+        let b4_19 = b[4] * 19;
+
+        let c0 = m(a[0], b[0]) + m(a[4], b1_19) + m(a[3], b2_19) + m(a[2], b3_19) + m(a[1], b4_19);
+        let mut c1 =
+            m(a[1], b[0]) + m(a[0], b[1]) + m(a[4], b2_19) + m(a[3], b3_19) + m(a[2], b4_19);
+        let mut c2 =
+            m(a[2], b[0]) + m(a[1], b[1]) + m(a[0], b[2]) + m(a[4], b3_19) + m(a[3], b4_19);
+        let mut c3 = m(a[3], b[0]) + m(a[2], b[1]) + m(a[1], b[2]) + m(a[0], b[3]) + m(a[4], b4_19);
+        let mut c4 = m(a[4], b[0]) + m(a[3], b[1]) + m(a[2], b[2]) + m(a[1], b[3]) + m(a[0], b[4]);
+
+        c1 += c0 >> 51;
+        c2 += c1 >> 51;
+        c3 += c2 >> 51;
+        c4 += c3 >> 51;
+        let carry = (c4 >> 51) as u64;
+
+        let mut l = [
+            (c0 as u64) & MASK51,
+            (c1 as u64) & MASK51,
+            (c2 as u64) & MASK51,
+            (c3 as u64) & MASK51,
+            (c4 as u64) & MASK51,
+        ];
+        l[0] += carry * 19;
+        l[1] += l[0] >> 51;
+        l[0] &= MASK51;
+        Fe(l)
+    }
+
+    fn square(&self) -> Fe {
+        self.mul(self)
+    }
+
+    // The exponent is public, so this does not need to be constant time in it.
+    fn pow(&self, exp: &[u8; 32]) -> Fe {
+        let mut r = Fe::ONE;
+        for i in (0..256).rev() {
+            r = r.square();
+            if (exp[i >> 3] >> (i & 7)) & 1 == 1 {
+                r = r.mul(self);
+            }
+        }
+        r
+    }
+
+    fn invert(&self) -> Fe {
+        self.pow(&P_MINUS_2)
+    }
+
+    fn is_negative(&self) -> u8 {
+        self.to_bytes()[0] & 1
+    }
+
+    fn is_zero(&self) -> bool {
+        ct_eq(&self.to_bytes(), &[0_u8; 32])
+    }
+
+    fn ct_equal(&self, rhs: &Fe) -> bool {
+        ct_eq(&self.to_bytes(), &rhs.to_bytes())
+    }
+
+    fn cswap(a: &mut Fe, b: &mut Fe, swap: u64) {
+        let mask = 0_u64.wrapping_sub(swap);
+        for i in 0..5 {
+            let t = mask & (a.0[i] ^ b.0[i]);
+            a.0[i] ^= t;
+            b.0[i] ^= t;
+        }
+    }
+
+    fn cmov(&mut self, other: &Fe, choice: u64) {
+        let mask = 0_u64.wrapping_sub(choice);
+        for i in 0..5 {
+            self.0[i] ^= mask & (self.0[i] ^ other.0[i]);
+        }
+    }
+}
+
+//
+// X25519
+//
+
+fn x25519(scalar: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    let mut k = *scalar;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+
+    let a24 = Fe([121665, 0, 0, 0, 0]);
+    let x1 = Fe::from_bytes(u);
+    let mut x2 = Fe::ONE;
+    let mut z2 = Fe::ZERO;
+    let mut x3 = x1;
+    let mut z3 = Fe::ONE;
+    let mut swap = 0_u64;
+
+    for t in (0..255).rev() {
+        let kt = ((k[t >> 3] >> (t & 7)) & 1) as u64;
+        swap ^= kt;
+        Fe::cswap(&mut x2, &mut x3, swap);
+        Fe::cswap(&mut z2, &mut z3, swap);
+        swap = kt;
+
+        let a = x2.add(&z2);
+        let aa = a.square();
+        let b = x2.sub(&z2);
+        let bb = b.square();
+        let e = aa.sub(&bb);
+        let c = x3.add(&z3);
+        let d = x3.sub(&z3);
+        let da = d.mul(&a);
+        let cb = c.mul(&b);
+        x3 = da.add(&cb).square();
+        z3 = x1.mul(&da.sub(&cb).square());
+        x2 = aa.mul(&bb);
+        z2 = e.mul(&aa.add(&a24.mul(&e)));
+    }
+    Fe::cswap(&mut x2, &mut x3, swap);
+    Fe::cswap(&mut z2, &mut z3, swap);
+    zeroize(&mut k);
+
+    x2.mul(&z2.invert()).to_bytes()
+}
+
+//
+// Edwards points in extended coordinates (X:Y:Z:T), with x = X/Z, y = Y/Z and xy = T/Z.
+//
+
+#[derive(Clone, Copy)]
+struct EdwardsPoint {
+    x: Fe,
+    y: Fe,
+    z: Fe,
+    t: Fe,
+}
+
+impl EdwardsPoint {
+    const IDENTITY: EdwardsPoint = EdwardsPoint {
+        x: Fe::ZERO,
+        y: Fe::ONE,
+        z: Fe::ONE,
+        t: Fe::ZERO,
+    };
+
+    fn decompress(s: &[u8; 32]) -> Option<EdwardsPoint> {
+        let y = Fe::from_bytes(s);
+        let mut canonical = *s;
+        canonical[31] &= 0x7f;
+        if y.to_bytes() != canonical {
+            return None;
+        }
+        let sign = s[31] >> 7;
+
+        let d = Fe::from_bytes(&EDWARDS_D);
+        let yy = y.square();
+        let u = yy.sub(&Fe::ONE);
+        let v = d.mul(&yy).add(&Fe::ONE);
+
+        // x = u v^3 (u v^7)^((p - 5) / 8)
+        let v3 = v.square().mul(&v);
+        let v7 = v3.square().mul(&v);
+        let mut x = u.mul(&v3).mul(&u.mul(&v7).pow(&P_MINUS_5_DIV_8));
+
+        let vxx = v.mul(&x.square());
+        let correct = vxx.ct_equal(&u);
+        let flipped = vxx.ct_equal(&u.neg());
+        if !correct && !flipped {
+            return None;
+        }
+        if flipped {
+            x = x.mul(&Fe::from_bytes(&SQRT_M1));
+        }
+        if x.is_zero() && sign == 1 {
+            return None;
+        }
+        if x.is_negative() != sign {
+            x = x.neg();
+        }
+
+        Some(EdwardsPoint {
+            x,
+            y,
+            z: Fe::ONE,
+            t: x.mul(&y),
+        })
+    }
+
+    fn compress(&self) -> [u8; 32] {
+        let zinv = self.z.invert();
+        let x = self.x.mul(&zinv);
+        let y = self.y.mul(&zinv);
+        let mut s = y.to_bytes();
+        s[31] ^= x.is_negative() << 7;
+        s
+    }
+
+    fn basepoint() -> EdwardsPoint {
+        EdwardsPoint::decompress(&ED25519_BASEPOINT).unwrap()
+    }
+
+    fn add(&self, rhs: &EdwardsPoint) -> EdwardsPoint {
+        let d2 = Fe::from_bytes(&EDWARDS_D).add(&Fe::from_bytes(&EDWARDS_D));
+        let a = self.y.sub(&self.x).mul(&rhs.y.sub(&rhs.x));
+        let b = self.y.add(&self.x).mul(&rhs.y.add(&rhs.x));
+        let c = self.t.mul(&d2).mul(&rhs.t);
+        let zz = self.z.mul(&rhs.z);
+        let d = zz.add(&zz);
+        let e = b.sub(&a);
+        let f = d.sub(&c);
+        let g = d.add(&c);
+        let h = b.add(&a);
+        EdwardsPoint {
+            x: e.mul(&f),
+            y: g.mul(&h),
+            z: f.mul(&g),
+            t: e.mul(&h),
+        }
+    }
+
+    fn neg(&self) -> EdwardsPoint {
+        EdwardsPoint {
+            x: self.x.neg(),
+            y: self.y,
+            z: self.z,
+            t: self.t.neg(),
+        }
+    }
+
+    fn cmov(&mut self, other: &EdwardsPoint, choice: u64) {
+        self.x.cmov(&other.x, choice);
+        self.y.cmov(&other.y, choice);
+        self.z.cmov(&other.z, choice);
+        self.t.cmov(&other.t, choice);
+    }
+
+    // Double-and-add-always, constant time in the scalar.
+    fn mul(&self, scalar: &[u8; 32]) -> EdwardsPoint {
+        let mut r = EdwardsPoint::IDENTITY;
+        for i in (0..256).rev() {
+            r = r.add(&r);
+            let sum = r.add(self);
+            r.cmov(&sum, ((scalar[i >> 3] >> (i & 7)) & 1) as u64);
+        }
+        r
+    }
+}
+
+//
+// Scalars modulo the group order L = 2^252 + 27742317777372353535851937790883648493.
+//
+
+const GROUP_ORDER: [u64; 4] = [
+    0x5812631a5cf5d3ed,
+    0x14def9dea2f79cd6,
+    0x0000000000000000,
+    0x1000000000000000,
+];
+
+fn sc_sub_order(r: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut out = [0_u64; 4];
+    let mut borrow = 0_u64;
+    for i in 0..4 {
+        let (d1, b1) = r[i].overflowing_sub(GROUP_ORDER[i]);
+        let (d2, b2) = d1.overflowing_sub(borrow);
+        out[i] = d2;
+        borrow = (b1 | b2) as u64;
+    }
+    (out, borrow)
+}
+
+// Reduces a little endian integer modulo L, one bit at a time, in constant time.
+fn sc_reduce(x: &[u8]) -> [u8; 32] {
+    let mut r = [0_u64; 4];
+    for i in (0..x.len() * 8).rev() {
+        let bit = ((x[i >> 3] >> (i & 7)) & 1) as u64;
+        r[3] = (r[3] << 1) | (r[2] >> 63);
+        r[2] = (r[2] << 1) | (r[1] >> 63);
+        r[1] = (r[1] << 1) | (r[0] >> 63);
+        r[0] = (r[0] << 1) | bit;
+
+        let (d, borrow) = sc_sub_order(&r);
+        let mask = borrow.wrapping_sub(1);
+        for j in 0..4 {
+            r[j] = (d[j] & mask) | (r[j] & !mask);
+        }
+    }
+
+    let mut s = [0_u8; 32];
+    for (chunk, w) in s.chunks_mut(8).zip(r.iter()) {
+        chunk.copy_from_slice(&w.to_le_bytes());
+    }
+    s
+}
+
+// (a * b + c) mod L
+fn sc_muladd(a: &[u8; 32], b: &[u8; 32], c: &[u8; 32]) -> [u8; 32] {
+    let load = |s: &[u8; 32]| {
+        let mut l = [0_u64; 4];
+        for (x, chunk) in l.iter_mut().zip(s.chunks(8)) {
+            let mut w = [0_u8; 8];
+            w.copy_from_slice(chunk);
+            *x = u64::from_le_bytes(w);
+        }
+        l
+    };
+    let al = load(a);
+    let bl = load(b);
+    let cl = load(c);
+
+    let mut t = [0_u64; 8];
+    t[..4].copy_from_slice(&cl);
+    for i in 0..4 {
+        let mut carry = 0_u128;
+        for j in 0..4 {
+            let v = (al[i] as u128) * (bl[j] as u128) + (t[i + j] as u128) + carry;
+            t[i + j] = v as u64;
+            carry = v >> 64;
+        }
+        let mut k = i + 4;
+        while carry != 0 && k < 8 {
+            let v = (t[k] as u128) + carry;
+            t[k] = v as u64;
+            carry = v >> 64;
+            k += 1;
+        }
+    }
+
+    let mut wide = [0_u8; 64];
+    for (chunk, w) in wide.chunks_mut(8).zip(t.iter()) {
+        chunk.copy_from_slice(&w.to_le_bytes());
+    }
+    let s = sc_reduce(&wide);
+    zeroize(&mut wide);
+    s
+}
+
+fn sc_is_canonical(s: &[u8; 32]) -> bool {
+    for i in (0..4).rev() {
+        let mut w = [0_u8; 8];
+        w.copy_from_slice(&s[i * 8..i * 8 + 8]);
+        let w = u64::from_le_bytes(w);
+        if w != GROUP_ORDER[i] {
+            return w < GROUP_ORDER[i];
+        }
+    }
+    false
+}
+
+//
+// Ed25519
+//
+
+struct ExpandedKey {
+    scalar: [u8; 32],
+    prefix: [u8; 32],
+}
+
+impl ExpandedKey {
+    fn new(seed: &[u8; SGX_ED25519_KEY_SIZE]) -> ExpandedKey {
+        let mut h = sha512(&[seed]);
+        let mut key = ExpandedKey {
+            scalar: [0_u8; 32],
+            prefix: [0_u8; 32],
+        };
+        key.scalar.copy_from_slice(&h[..32]);
+        key.prefix.copy_from_slice(&h[32..]);
+        key.scalar[0] &= 248;
+        key.scalar[31] &= 127;
+        key.scalar[31] |= 64;
+        zeroize(&mut h);
+        key
+    }
+}
+
+impl Drop for ExpandedKey {
+    fn drop(&mut self) {
+        zeroize(&mut self.scalar);
+        zeroize(&mut self.prefix);
+    }
+}
+
+fn ed25519_sign(
+    data: &[u8],
+    private: &sgx_ed25519_private_t,
+) -> SgxResult<sgx_ed25519_signature_t> {
+    let key = ExpandedKey::new(&private.seed);
+    let base = EdwardsPoint::basepoint();
+    let public = base.mul(&key.scalar).compress();
+
+    let mut h = sha512(&[&key.prefix, data]);
+    let mut r = sc_reduce(&h);
+    zeroize(&mut h);
+    let big_r = base.mul(&r).compress();
+
+    let k = sc_reduce(&sha512(&[&big_r, &public, data]));
+    let s = sc_muladd(&k, &key.scalar, &r);
+    zeroize(&mut r);
+
+    Ok(sgx_ed25519_signature_t { r: big_r, s })
+}
+
+fn ed25519_verify(
+    data: &[u8],
+    public: &sgx_ed25519_public_t,
+    signature: &sgx_ed25519_signature_t,
+) -> SgxResult<bool> {
+    let a =
+        EdwardsPoint::decompress(&public.key).ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+    if !sc_is_canonical(&signature.s) {
+        return Ok(false);
+    }
+
+    let k = sc_reduce(&sha512(&[&signature.r, &public.key, data]));
+    let check = EdwardsPoint::basepoint()
+        .mul(&signature.s)
+        .add(&a.neg().mul(&k))
+        .compress();
+    Ok(check == signature.r)
+}
+
+fn as_bytes_msg<T: Copy + ContiguousMemory>(data: &T) -> &[u8] {
+    unsafe { slice::from_raw_parts(data as *const T as *const u8, mem::size_of::<T>()) }
+}
+
+fn as_bytes_slice<T: Copy + ContiguousMemory>(data: &[T]) -> &[u8] {
+    unsafe { slice::from_raw_parts(data.as_ptr() as *const u8, mem::size_of_val(data)) }
+}
+
+fn read_rand(buf: &mut [u8]) -> SgxError {
+    let ret = unsafe { sgx_read_rand(buf.as_mut_ptr(), buf.len()) };
+    match ret {
+        sgx_status_t::SGX_SUCCESS => Ok(()),
+        _ => Err(ret),
+    }
+}
+
+///
+/// rsgx_ed25519_create_key_pair generates an Ed25519 key pair from a random seed.
+///
+/// # Return value
+///
+/// The private key (the 32-byte seed of RFC 8032) and the public key.
+///
+/// # Errors
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// The random number generator failed.
+///
+pub fn rsgx_ed25519_create_key_pair() -> SgxResult<(sgx_ed25519_private_t, sgx_ed25519_public_t)> {
+    let mut private = sgx_ed25519_private_t::default();
+    read_rand(&mut private.seed)?;
+    let public = rsgx_ed25519_public_from_private(&private)?;
+    Ok((private, public))
+}
+
+///
+/// rsgx_ed25519_public_from_private computes the Ed25519 public key of a private key.
+///
+pub fn rsgx_ed25519_public_from_private(
+    private: &sgx_ed25519_private_t,
+) -> SgxResult<sgx_ed25519_public_t> {
+    let key = ExpandedKey::new(&private.seed);
+    Ok(sgx_ed25519_public_t {
+        key: EdwardsPoint::basepoint().mul(&key.scalar).compress(),
+    })
+}
+
+///
+/// rsgx_ed25519_sign_msg computes an Ed25519 signature for a given data set.
+///
+/// # Description
+///
+/// The signature is deterministic, as specified by RFC 8032. Unlike the P-256 ECDSA
+/// functions, the data set could be empty.
+///
+/// # Parameters
+///
+/// **data**
+///
+/// Data to be signed.
+///
+/// **private**
+///
+/// The Ed25519 private key.
+///
+/// # Return value
+///
+/// The Ed25519 signature, R and S.
+///
+pub fn rsgx_ed25519_sign_msg<T>(
+    data: &T,
+    private: &sgx_ed25519_private_t,
+) -> SgxResult<sgx_ed25519_signature_t>
+where
+    T: Copy + ContiguousMemory,
+{
+    ed25519_sign(as_bytes_msg(data), private)
+}
+
+///
+/// rsgx_ed25519_sign_slice computes an Ed25519 signature for a given data set.
+///
+pub fn rsgx_ed25519_sign_slice<T>(
+    data: &[T],
+    private: &sgx_ed25519_private_t,
+) -> SgxResult<sgx_ed25519_signature_t>
+where
+    T: Copy + ContiguousMemory,
+{
+    ed25519_sign(as_bytes_slice(data), private)
+}
+
+///
+/// rsgx_ed25519_verify_msg verifies an Ed25519 signature.
+///
+/// # Description
+///
+/// Signatures with a non-canonical S (S >= L) are rejected.
+///
+/// # Parameters
+///
+/// **data**
+///
+/// Signed data.
+///
+/// **public**
+///
+/// The Ed25519 public key.
+///
+/// **signature**
+///
+/// The signature to be verified.
+///
+/// # Return value
+///
+/// **true**
+///
+/// Digital signature is valid.
+///
+/// **false**
+///
+/// Digital signature is not valid.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The public key is not a valid encoding of a curve point.
+///
+pub fn rsgx_ed25519_verify_msg<T>(
+    data: &T,
+    public: &sgx_ed25519_public_t,
+    signature: &sgx_ed25519_signature_t,
+) -> SgxResult<bool>
+where
+    T: Copy + ContiguousMemory,
+{
+    ed25519_verify(as_bytes_msg(data), public, signature)
+}
+
+///
+/// rsgx_ed25519_verify_slice verifies an Ed25519 signature.
+///
+pub fn rsgx_ed25519_verify_slice<T>(
+    data: &[T],
+    public: &sgx_ed25519_public_t,
+    signature: &sgx_ed25519_signature_t,
+) -> SgxResult<bool>
+where
+    T: Copy + ContiguousMemory,
+{
+    ed25519_verify(as_bytes_slice(data), public, signature)
+}
+
+///
+/// rsgx_x25519_create_key_pair generates an X25519 key pair from a random scalar.
+///
+/// # Errors
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// The random number generator failed.
+///
+pub fn rsgx_x25519_create_key_pair() -> SgxResult<(sgx_x25519_private_t, sgx_x25519_public_t)> {
+    let mut private = sgx_x25519_private_t::default();
+    read_rand(&mut private.scalar)?;
+    let public = rsgx_x25519_public_from_private(&private)?;
+    Ok((private, public))
+}
+
+///
+/// rsgx_x25519_public_from_private computes the X25519 public key of a private key.
+///
+pub fn rsgx_x25519_public_from_private(
+    private: &sgx_x25519_private_t,
+) -> SgxResult<sgx_x25519_public_t> {
+    Ok(sgx_x25519_public_t {
+        u: x25519(&private.scalar, &X25519_BASEPOINT),
+    })
+}
+
+///
+/// rsgx_x25519_compute_shared_key computes the X25519 shared secret with a peer.
+///
+/// # Description
+///
+/// The shared secret should not be used as a key directly, but passed through a KDF
+/// such as rsgx_hkdf_sha256.
+///
+/// # Parameters
+///
+/// **private**
+///
+/// The local private key.
+///
+/// **peer_public**
+///
+/// The public key of the peer.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The peer public key is a low order point, so the shared secret is all zeros.
+///
+pub fn rsgx_x25519_compute_shared_key(
+    private: &sgx_x25519_private_t,
+    peer_public: &sgx_x25519_public_t,
+) -> SgxResult<sgx_x25519_shared_t> {
+    let mut shared = sgx_x25519_shared_t {
+        s: x25519(&private.scalar, &peer_public.u),
+    };
+    if ct_eq(&shared.s, &[0_u8; SGX_X25519_KEY_SIZE]) {
+        zeroize(&mut shared.s);
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    Ok(shared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aead::tests::hex;
+
+    #[test]
+    fn ed25519_rfc8032() {
+        // RFC 8032, section 7.1, test 2.
+        let mut private = sgx_ed25519_private_t::default();
+        private.seed.copy_from_slice(&hex(
+            "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+        ));
+        let public = rsgx_ed25519_public_from_private(&private).unwrap();
+        assert_eq!(
+            public.key.to_vec(),
+            hex("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c")
+        );
+        let signature = rsgx_ed25519_sign_slice(&[0x72_u8], &private).unwrap();
+        assert_eq!(
+            signature.r.to_vec(),
+            hex("92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da")
+        );
+        assert_eq!(
+            signature.s.to_vec(),
+            hex("085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00")
+        );
+        assert!(rsgx_ed25519_verify_slice(&[0x72_u8], &public, &signature).unwrap());
+        assert!(!rsgx_ed25519_verify_slice(&[0x73_u8], &public, &signature).unwrap());
+    }
+
+    #[test]
+    fn x25519_rfc7748() {
+        // RFC 7748, section 6.1.
+        let mut alice = sgx_x25519_private_t::default();
+        alice.scalar.copy_from_slice(&hex(
+            "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+        ));
+        let mut bob = sgx_x25519_private_t::default();
+        bob.scalar.copy_from_slice(&hex(
+            "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb",
+        ));
+        let alice_public = rsgx_x25519_public_from_private(&alice).unwrap();
+        let bob_public = rsgx_x25519_public_from_private(&bob).unwrap();
+        assert_eq!(
+            alice_public.u.to_vec(),
+            hex("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+        );
+        let shared = rsgx_x25519_compute_shared_key(&alice, &bob_public).unwrap();
+        assert_eq!(
+            shared.s.to_vec(),
+            hex("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742")
+        );
+        assert_eq!(
+            rsgx_x25519_compute_shared_key(&bob, &alice_public)
+                .unwrap()
+                .s,
+            shared.s
+        );
+        assert_eq!(
+            rsgx_x25519_compute_shared_key(&alice, &sgx_x25519_public_t::default()).err(),
+            Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+        );
+    }
+}
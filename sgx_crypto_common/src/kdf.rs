@@ -0,0 +1,359 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Key Derivation Functions
+//!
+//! HKDF (RFC 5869), PBKDF2 (RFC 8018) and the NIST SP800-108 counter-mode KDF
+//! with AES-CMAC as PRF.
+//!
+//! HMAC is computed here on top of the `Digest` trait, because the cryptography
+//! library only provides HMAC-SHA256 and does not accept empty messages.
+//!
+//! The functions are generic over the hash function and the MAC, and are
+//! instantiated by the rsgx_hkdf_*, rsgx_pbkdf2_* and rsgx_sp800_108_* functions
+//! of sgx_tcrypto and sgx_ucrypto with the handles of the cryptography library.
+//! The output is cleared when an error is returned.
+//!
+use crate::aead::zeroize;
+use crate::traits::*;
+use core::marker::PhantomData;
+use sgx_types::*;
+
+const HMAC_MAX_BLOCK_SIZE: usize = 128;
+const HMAC_MAX_OUTPUT_SIZE: usize = 64;
+const HKDF_MAX_BLOCKS: usize = 255;
+
+struct HmacKey<D: Digest> {
+    ipad: [u8; HMAC_MAX_BLOCK_SIZE],
+    opad: [u8; HMAC_MAX_BLOCK_SIZE],
+    phantom: PhantomData<D>,
+}
+
+impl<D: Digest> HmacKey<D> {
+    fn new(key: &[u8]) -> SgxResult<Self> {
+        let mut hmac_key = HmacKey {
+            ipad: [0_u8; HMAC_MAX_BLOCK_SIZE],
+            opad: [0_u8; HMAC_MAX_BLOCK_SIZE],
+            phantom: PhantomData,
+        };
+        if key.len() > D::BLOCK_SIZE {
+            let mut hash = D::digest(key)?;
+            hmac_key.ipad[..D::OUTPUT_SIZE].copy_from_slice(hash.as_ref());
+            zeroize(hash.as_mut());
+        } else {
+            hmac_key.ipad[..key.len()].copy_from_slice(key);
+        }
+        for i in 0..D::BLOCK_SIZE {
+            hmac_key.opad[i] = hmac_key.ipad[i] ^ 0x5c;
+            hmac_key.ipad[i] ^= 0x36;
+        }
+        Ok(hmac_key)
+    }
+
+    fn mac(&self, parts: &[&[u8]]) -> SgxResult<D::Output> {
+        let inner = D::default();
+        inner.init()?;
+        inner.update(&self.ipad[..D::BLOCK_SIZE])?;
+        for part in parts {
+            inner.update(part)?;
+        }
+        let mut inner_hash = inner.finalize()?;
+
+        let outer = D::default();
+        let result = outer
+            .init()
+            .and_then(|_| outer.update(&self.opad[..D::BLOCK_SIZE]))
+            .and_then(|_| outer.update(inner_hash.as_ref()))
+            .and_then(|_| outer.finalize());
+        zeroize(inner_hash.as_mut());
+        result
+    }
+}
+
+impl<D: Digest> Drop for HmacKey<D> {
+    fn drop(&mut self) {
+        zeroize(&mut self.ipad);
+        zeroize(&mut self.opad);
+    }
+}
+
+///
+/// The HKDF-Extract step of RFC 5869. See rsgx_hkdf_sha256_extract.
+///
+pub fn hkdf_extract<D: Digest>(salt: &[u8], ikm: &[u8]) -> SgxResult<D::Output> {
+    // An empty salt is the same HMAC key as HashLen zero bytes.
+    HmacKey::<D>::new(salt)?.mac(&[ikm])
+}
+
+fn expand<D: Digest>(prk: &[u8], info: &[u8], okm: &mut [u8]) -> SgxError {
+    if prk.len() < D::OUTPUT_SIZE {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    if okm.is_empty() || okm.len() > HKDF_MAX_BLOCKS * D::OUTPUT_SIZE {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+
+    let hmac_key = HmacKey::<D>::new(prk)?;
+    let mut prev: Option<D::Output> = None;
+    let mut result = Ok(());
+    for (i, chunk) in okm.chunks_mut(D::OUTPUT_SIZE).enumerate() {
+        let counter = [(i + 1) as u8];
+        let t = match prev {
+            Some(ref p) => hmac_key.mac(&[p.as_ref(), info, &counter]),
+            None => hmac_key.mac(&[info, &counter]),
+        };
+        match t {
+            Ok(t) => {
+                chunk.copy_from_slice(&t.as_ref()[..chunk.len()]);
+                prev = Some(t);
+            }
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+    // The last block may hold key material beyond the end of okm.
+    if let Some(ref mut p) = prev {
+        zeroize(p.as_mut());
+    }
+    result
+}
+
+fn extract_and_expand<D: Digest>(salt: &[u8], ikm: &[u8], info: &[u8], okm: &mut [u8]) -> SgxError {
+    let mut prk = hkdf_extract::<D>(salt, ikm)?;
+    let result = expand::<D>(prk.as_ref(), info, okm);
+    zeroize(prk.as_mut());
+    result
+}
+
+fn pbkdf2_block<D: Digest>(
+    hmac_key: &HmacKey<D>,
+    salt: &[u8],
+    index: u32,
+    iterations: u32,
+    block: &mut [u8],
+) -> SgxError {
+    let mut u = hmac_key.mac(&[salt, &index.to_be_bytes()])?;
+    block.copy_from_slice(u.as_ref());
+    let mut result = Ok(());
+    for _ in 1..iterations {
+        match hmac_key.mac(&[u.as_ref()]) {
+            Ok(next) => {
+                zeroize(u.as_mut());
+                u = next;
+            }
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+        for (b, x) in block.iter_mut().zip(u.as_ref().iter()) {
+            *b ^= x;
+        }
+    }
+    zeroize(u.as_mut());
+    result
+}
+
+fn pbkdf2<D: Digest>(password: &[u8], salt: &[u8], iterations: u32, dk: &mut [u8]) -> SgxError {
+    if iterations == 0 || dk.is_empty() {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    if (dk.len() as u64) > (u32::MAX as u64) * (D::OUTPUT_SIZE as u64) {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+
+    let hmac_key = HmacKey::<D>::new(password)?;
+    let mut block = [0_u8; HMAC_MAX_OUTPUT_SIZE];
+    let mut result = Ok(());
+    for (i, chunk) in dk.chunks_mut(D::OUTPUT_SIZE).enumerate() {
+        let block = &mut block[..D::OUTPUT_SIZE];
+        result = pbkdf2_block(&hmac_key, salt, i as u32 + 1, iterations, block);
+        if result.is_err() {
+            break;
+        }
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+    zeroize(&mut block);
+    result
+}
+
+fn sp800_108_cmac_block<M>(
+    key: &sgx_cmac_128bit_key_t,
+    counter: u32,
+    label: &[u8],
+    context: &[u8],
+    length: u32,
+) -> SgxResult<sgx_cmac_128bit_tag_t>
+where
+    M: Mac<Key = sgx_cmac_128bit_key_t, Output = sgx_cmac_128bit_tag_t>,
+{
+    let handle = M::default();
+    handle.init(key)?;
+    handle.update(&counter.to_be_bytes())?;
+    handle.update(label)?;
+    handle.update(&[0x00])?;
+    handle.update(context)?;
+    handle.update(&length.to_be_bytes())?;
+    handle.finalize()
+}
+
+fn clear_on_error(result: SgxError, out: &mut [u8]) -> SgxError {
+    if result.is_err() {
+        zeroize(out);
+    }
+    result
+}
+
+///
+/// The HKDF-Expand step of RFC 5869. See rsgx_hkdf_sha256_expand.
+///
+pub fn hkdf_expand<D: Digest>(prk: &[u8], info: &[u8], okm: &mut [u8]) -> SgxError {
+    clear_on_error(expand::<D>(prk, info, okm), okm)
+}
+
+///
+/// Both HKDF steps of RFC 5869. See rsgx_hkdf_sha256.
+///
+pub fn hkdf<D: Digest>(salt: &[u8], ikm: &[u8], info: &[u8], okm: &mut [u8]) -> SgxError {
+    clear_on_error(extract_and_expand::<D>(salt, ikm, info, okm), okm)
+}
+
+///
+/// PBKDF2 (RFC 8018) with HMAC. See rsgx_pbkdf2_hmac_sha256.
+///
+pub fn pbkdf2_hmac<D: Digest>(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    dk: &mut [u8],
+) -> SgxError {
+    clear_on_error(pbkdf2::<D>(password, salt, iterations, dk), dk)
+}
+
+///
+/// The SP800-108 KDF in counter mode with AES-CMAC. See rsgx_sp800_108_cmac_kdf.
+///
+pub fn sp800_108_cmac_kdf<M>(
+    key: &sgx_cmac_128bit_key_t,
+    label: &[u8],
+    context: &[u8],
+    okm: &mut [u8],
+) -> SgxError
+where
+    M: Mac<Key = sgx_cmac_128bit_key_t, Output = sgx_cmac_128bit_tag_t>,
+{
+    if okm.is_empty() || (okm.len() as u64) * 8 > u32::MAX as u64 {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+
+    let length = (okm.len() * 8) as u32;
+    for (i, chunk) in okm.chunks_mut(SGX_CMAC_MAC_SIZE).enumerate() {
+        match sp800_108_cmac_block::<M>(key, i as u32 + 1, label, context, length) {
+            Ok(mut block) => {
+                chunk.copy_from_slice(&block[..chunk.len()]);
+                zeroize(&mut block);
+            }
+            Err(e) => return clear_on_error(Err(e), okm),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aead::tests::hex;
+    use crate::sha512::tests::TestDigest;
+    use crate::sha512::SHA512_HASH_SIZE;
+    use alloc::vec;
+
+    // The generic code is tested with the SHA-512 of this crate, which unlike the
+    // handles of the cryptography library does not need the SGX SDK libraries.
+    // sgx_tcrypto tests its instantiations with the RFC vectors.
+    type TestSha512 = TestDigest<SHA512_HASH_SIZE>;
+
+    #[test]
+    fn hkdf_sha512() {
+        // The inputs of RFC 5869 test case 1, with SHA-512. The output was
+        // computed with the hmac and hashlib modules of Python.
+        let ikm = hex("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b");
+        let salt = hex("000102030405060708090a0b0c");
+        let info = hex("f0f1f2f3f4f5f6f7f8f9");
+        let prk = hkdf_extract::<TestSha512>(&salt, &ikm).unwrap();
+        assert_eq!(
+            prk.to_vec(),
+            hex(
+                "665799823737ded04a88e47e54a5890bb2c3d247c7a4254a8e61350723590a26\
+                 c36238127d8661b88cf80ef802d57e2f7cebcf1e00e083848be19929c61b4237"
+            )
+        );
+        let expected = hex(
+            "832390086cda71fb47625bb5ceb168e4c8e26a1a16ed34d9fc7fe92c1481579338da362cb8d9f925d7cb\
+             cce0dff7098769cf15959867d571c1715450cb530137be3fb62f3cf32b84feba8f1eb1b563e20d9749b8\
+             640b8264c4b69b14ad5199115e1d609c83c6940ce5b4214a0c79946983547a35cdcc17e0daf31b647dec\
+             0d0e6142b1deaa036b348422068ca66631c0ca5586485a27",
+        );
+        let mut okm = vec![0_u8; expected.len()];
+        hkdf::<TestSha512>(&salt, &ikm, &info, &mut okm).unwrap();
+        assert_eq!(okm, expected);
+        hkdf_expand::<TestSha512>(&prk, &info, &mut okm).unwrap();
+        assert_eq!(okm, expected);
+
+        let mut too_long = vec![1_u8; HKDF_MAX_BLOCKS * SHA512_HASH_SIZE + 1];
+        assert_eq!(
+            hkdf::<TestSha512>(&salt, &ikm, &info, &mut too_long),
+            Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+        );
+        assert!(too_long.iter().all(|&b| b == 0));
+        assert_eq!(
+            hkdf_expand::<TestSha512>(&prk[1..], &info, &mut okm),
+            Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+        );
+    }
+
+    #[test]
+    fn pbkdf2_hmac_sha512() {
+        // Computed with hashlib.pbkdf2_hmac of Python.
+        let mut dk = [0_u8; 80];
+        pbkdf2_hmac::<TestSha512>(b"passwd", b"salt", 1, &mut dk).unwrap();
+        assert_eq!(
+            dk.to_vec(),
+            hex(
+                "c74319d99499fc3e9013acff597c23c5baf0a0bec5634c46b8352b793e324723\
+                 d55caa76b2b25c43402dcfdc06cdcf66f95b7d0429420b39520006749c51a04e\
+                 f3eb99e576617395a178ba33214793e4"
+            )
+        );
+        pbkdf2_hmac::<TestSha512>(b"Password", b"NaCl", 3, &mut dk).unwrap();
+        assert_eq!(
+            dk.to_vec(),
+            hex(
+                "d2b837231629c12cba0771aee222b20cf79dc0842f2725586580aadbce243a11\
+                 c6023b87d40865dd514d0484e43966636cf5ec2ccd75d208fd47240e83f3e849\
+                 c84fe889827ca7391167be52e62b8d3f"
+            )
+        );
+        assert_eq!(
+            pbkdf2_hmac::<TestSha512>(b"passwd", b"salt", 0, &mut dk),
+            Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+        );
+        assert_eq!(dk, [0_u8; 80]);
+    }
+}
@@ -0,0 +1,95 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! # Cryptography Library Common Code
+//!
+//! The algorithms of sgx_tcrypto and sgx_ucrypto that are implemented in Rust
+//! rather than in the Intel(R) SGX cryptography library. Both crates re-export
+//! this crate, so it is not meant to be used directly.
+//!
+//! The key derivation functions and RSA signatures are generic over the `Digest`
+//! and `Mac` traits, and sgx_tcrypto and sgx_ucrypto instantiate them with the
+//! handles of the cryptography library.
+//!
+
+#![no_std]
+#![cfg_attr(target_env = "sgx", feature(rustc_private))]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(clippy::too_many_arguments)]
+
+extern crate alloc;
+#[cfg(feature = "detect_aes_ni")]
+extern crate std;
+extern crate sgx_types;
+
+pub mod aead;
+pub use self::aead::*;
+
+mod traits;
+pub use self::traits::*;
+
+pub mod kdf;
+
+mod sha512;
+
+pub mod curve25519;
+pub use self::curve25519::*;
+
+pub mod encoding;
+pub use self::encoding::*;
+
+pub mod rsa;
+
+// Every SGX capable processor has AES-NI, and CPUID cannot be executed inside
+// an enclave.
+#[cfg(not(feature = "detect_aes_ni"))]
+#[inline]
+pub(crate) fn has_aes_ni() -> bool {
+    true
+}
+
+// The untrusted host may be any x86_64 processor, so sgx_ucrypto enables
+// detect_aes_ni to only take the AES-NI code path after checking CPUID.
+#[cfg(feature = "detect_aes_ni")]
+#[inline]
+pub(crate) fn has_aes_ni() -> bool {
+    std::is_x86_feature_detected!("aes") && std::is_x86_feature_detected!("sse2")
+}
+
+// sgx_read_rand is provided by sgx_trts inside an enclave and by sgx_ucrypto
+// outside, so the tests of this crate provide their own.
+///
+/// # Safety
+///
+/// `rand` must be valid for writes of `len` bytes.
+#[cfg(test)]
+#[no_mangle]
+pub unsafe extern "C" fn sgx_read_rand(rand: *mut u8, len: usize) -> sgx_types::sgx_status_t {
+    use core::sync::atomic::{AtomicU64, Ordering};
+    static STATE: AtomicU64 = AtomicU64::new(0x2545_f491_4f6c_dd1d);
+
+    for i in 0..len {
+        let mut x = STATE.load(Ordering::Relaxed);
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        STATE.store(x, Ordering::Relaxed);
+        *rand.add(i) = x as u8;
+    }
+    sgx_types::sgx_status_t::SGX_SUCCESS
+}
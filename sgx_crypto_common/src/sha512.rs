@@ -0,0 +1,260 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! SHA-512
+//!
+//! The cryptography library does not provide SHA-512, which Ed25519 requires. This
+//! is the only SHA-512 implementation of sgx_tcrypto and sgx_ucrypto; use it rather
+//! than adding another.
+//!
+use crate::aead::zeroize;
+
+const SHA512_BLOCK_SIZE: usize = 128;
+pub(crate) const SHA512_HASH_SIZE: usize = 64;
+
+#[rustfmt::skip]
+const SHA512_K: [u64; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+    0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+    0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+    0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+    0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+    0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+    0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+    0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+    0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+#[rustfmt::skip]
+const SHA512_IV: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+pub(crate) struct Sha512 {
+    state: [u64; 8],
+    buf: [u8; SHA512_BLOCK_SIZE],
+    buf_len: usize,
+    total_len: u128,
+}
+
+impl Sha512 {
+    pub(crate) fn new() -> Sha512 {
+        Sha512 {
+            state: SHA512_IV,
+            buf: [0_u8; SHA512_BLOCK_SIZE],
+            buf_len: 0,
+            total_len: 0,
+        }
+    }
+
+    fn compress(state: &mut [u64; 8], block: &[u8]) {
+        let mut w = [0_u64; 80];
+        for (i, word) in block.chunks(8).enumerate() {
+            let mut b = [0_u8; 8];
+            b.copy_from_slice(word);
+            w[i] = u64::from_be_bytes(b);
+        }
+        for i in 16..80 {
+            let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+            let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let mut v = *state;
+        for i in 0..80 {
+            let s1 = v[4].rotate_right(14) ^ v[4].rotate_right(18) ^ v[4].rotate_right(41);
+            let ch = (v[4] & v[5]) ^ (!v[4] & v[6]);
+            let t1 = v[7]
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA512_K[i])
+                .wrapping_add(w[i]);
+            let s0 = v[0].rotate_right(28) ^ v[0].rotate_right(34) ^ v[0].rotate_right(39);
+            let maj = (v[0] & v[1]) ^ (v[0] & v[2]) ^ (v[1] & v[2]);
+            let t2 = s0.wrapping_add(maj);
+            v[7] = v[6];
+            v[6] = v[5];
+            v[5] = v[4];
+            v[4] = v[3].wrapping_add(t1);
+            v[3] = v[2];
+            v[2] = v[1];
+            v[1] = v[0];
+            v[0] = t1.wrapping_add(t2);
+        }
+        for (s, x) in state.iter_mut().zip(v.iter()) {
+            *s = s.wrapping_add(*x);
+        }
+    }
+
+    pub(crate) fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u128;
+        if self.buf_len > 0 {
+            let n = core::cmp::min(SHA512_BLOCK_SIZE - self.buf_len, data.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+            if self.buf_len < SHA512_BLOCK_SIZE {
+                return;
+            }
+            Self::compress(&mut self.state, &self.buf);
+            self.buf_len = 0;
+        }
+        while data.len() >= SHA512_BLOCK_SIZE {
+            Self::compress(&mut self.state, &data[..SHA512_BLOCK_SIZE]);
+            data = &data[SHA512_BLOCK_SIZE..];
+        }
+        self.buf[..data.len()].copy_from_slice(data);
+        self.buf_len = data.len();
+    }
+
+    pub(crate) fn finalize(mut self) -> [u8; SHA512_HASH_SIZE] {
+        let bit_len = self.total_len << 3;
+        self.buf[self.buf_len] = 0x80;
+        for b in self.buf[self.buf_len + 1..].iter_mut() {
+            *b = 0;
+        }
+        if self.buf_len + 1 > SHA512_BLOCK_SIZE - 16 {
+            Self::compress(&mut self.state, &self.buf);
+            self.buf = [0_u8; SHA512_BLOCK_SIZE];
+        }
+        self.buf[SHA512_BLOCK_SIZE - 16..].copy_from_slice(&bit_len.to_be_bytes());
+        Self::compress(&mut self.state, &self.buf);
+
+        let mut hash = [0_u8; SHA512_HASH_SIZE];
+        for (chunk, s) in hash.chunks_mut(8).zip(self.state.iter()) {
+            chunk.copy_from_slice(&s.to_be_bytes());
+        }
+        hash
+    }
+}
+
+impl Drop for Sha512 {
+    fn drop(&mut self) {
+        zeroize(&mut self.buf);
+        for s in self.state.iter_mut() {
+            unsafe { core::ptr::write_volatile(s, 0) };
+        }
+    }
+}
+
+/// Hash the concatenation of `parts`.
+pub(crate) fn sha512(parts: &[&[u8]]) -> [u8; SHA512_HASH_SIZE] {
+    let mut sha = Sha512::new();
+    for part in parts {
+        sha.update(part);
+    }
+    sha.finalize()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::aead::tests::hex;
+    use crate::traits::Digest;
+    use core::cell::RefCell;
+    use sgx_types::*;
+
+    /// SHA-512 truncated to N bytes, to test the code that is generic over `Digest`
+    /// without the handles of the cryptography library.
+    #[derive(Default)]
+    pub(crate) struct TestDigest<const N: usize>(RefCell<Option<Sha512>>);
+
+    impl<const N: usize> Digest for TestDigest<N> {
+        type Output = [u8; N];
+
+        const OUTPUT_SIZE: usize = N;
+        const BLOCK_SIZE: usize = SHA512_BLOCK_SIZE;
+
+        fn init(&self) -> SgxError {
+            *self.0.borrow_mut() = Some(Sha512::new());
+            Ok(())
+        }
+
+        fn update(&self, data: &[u8]) -> SgxError {
+            match self.0.borrow_mut().as_mut() {
+                Some(sha) => {
+                    sha.update(data);
+                    Ok(())
+                }
+                None => Err(sgx_status_t::SGX_ERROR_INVALID_STATE),
+            }
+        }
+
+        fn finalize(&self) -> SgxResult<Self::Output> {
+            let sha = self
+                .0
+                .borrow_mut()
+                .take()
+                .ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+            let mut hash = [0_u8; N];
+            hash.copy_from_slice(&sha.finalize()[..N]);
+            Ok(hash)
+        }
+
+        fn close(&self) -> SgxError {
+            self.0.borrow_mut().take();
+            Ok(())
+        }
+    }
+
+    const TWO_BLOCKS: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+        hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    #[test]
+    fn sha512_fips180() {
+        // FIPS 180-2, appendix C.
+        assert_eq!(
+            sha512(&[b"abc"]).to_vec(),
+            hex(
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+                 2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+            )
+        );
+        assert_eq!(
+            sha512(&[TWO_BLOCKS]).to_vec(),
+            hex(
+                "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018\
+                 501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+            )
+        );
+    }
+
+    #[test]
+    fn sha512_split_updates() {
+        let expected = sha512(&[TWO_BLOCKS]);
+        for split in 0..TWO_BLOCKS.len() {
+            let (a, b) = TWO_BLOCKS.split_at(split);
+            assert_eq!(sha512(&[a, b]), expected);
+        }
+    }
+}
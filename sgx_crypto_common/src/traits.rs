@@ -0,0 +1,331 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Algorithm Traits
+//!
+//! `Digest`, `Mac` and `Aead` are implemented by the streaming handles of
+//! sgx_tcrypto and sgx_ucrypto, so that generic code (e.g. a KDF or a Merkle tree)
+//! can be written once and used with any algorithm, inside the enclave with
+//! sgx_tcrypto and outside with sgx_ucrypto.
+//!
+//! The handles close their context when they are dropped, and the cryptography
+//! library clears the context when it is closed.
+//!
+//! The handles of the cryptography library are defined in both crates, so the
+//! implementations are generated by the impl_digest, impl_mac and impl_aead macros.
+//!
+use crate::aead::*;
+use sgx_types::*;
+
+/// A streaming hash function.
+pub trait Digest: Default {
+    /// The hash value.
+    type Output: AsRef<[u8]> + AsMut<[u8]> + Copy;
+
+    /// The size of the hash value in bytes.
+    const OUTPUT_SIZE: usize;
+    /// The size of the internal block in bytes, as used by HMAC.
+    const BLOCK_SIZE: usize;
+
+    fn init(&self) -> SgxError;
+
+    /// Hash more data. Empty input is accepted and ignored.
+    fn update(&self, data: &[u8]) -> SgxError;
+
+    fn finalize(&self) -> SgxResult<Self::Output>;
+
+    fn close(&self) -> SgxError;
+
+    /// Hash `data` in one call.
+    fn digest(data: &[u8]) -> SgxResult<Self::Output> {
+        let handle = Self::default();
+        handle.init()?;
+        handle.update(data)?;
+        let hash = handle.finalize()?;
+        handle.close()?;
+        Ok(hash)
+    }
+}
+
+/// A streaming message authentication code.
+pub trait Mac: Default {
+    /// The key.
+    type Key;
+    /// The authentication tag.
+    type Output: AsRef<[u8]> + Copy;
+
+    /// The size of the key in bytes.
+    const KEY_SIZE: usize;
+    /// The size of the tag in bytes.
+    const OUTPUT_SIZE: usize;
+
+    fn init(&self, key: &Self::Key) -> SgxError;
+
+    /// Authenticate more data. Empty input is accepted and ignored.
+    fn update(&self, data: &[u8]) -> SgxError;
+
+    fn finalize(&self) -> SgxResult<Self::Output>;
+
+    fn close(&self) -> SgxError;
+
+    /// Compute the tag of `data` in one call.
+    fn mac(key: &Self::Key, data: &[u8]) -> SgxResult<Self::Output> {
+        let handle = Self::default();
+        handle.init(key)?;
+        handle.update(data)?;
+        let tag = handle.finalize()?;
+        handle.close()?;
+        Ok(tag)
+    }
+
+    /// Verify the tag of `data` in constant time.
+    ///
+    /// Returns SGX_ERROR_MAC_MISMATCH if the tag does not match.
+    fn verify(key: &Self::Key, data: &[u8], tag: &[u8]) -> SgxError {
+        let expected = Self::mac(key, data)?;
+        if ct_eq(expected.as_ref(), tag) {
+            Ok(())
+        } else {
+            Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH)
+        }
+    }
+}
+
+/// An authenticated encryption algorithm with associated data.
+///
+/// The streaming interface mirrors `SgxAesHandle`, which only encrypts. The one-shot
+/// functions both encrypt and decrypt.
+pub trait Aead: Default {
+    /// The key.
+    type Key;
+    /// The authentication tag.
+    type Tag: AsRef<[u8]> + Copy;
+
+    /// The size of the key in bytes.
+    const KEY_SIZE: usize;
+    /// The size of the nonce (IV) in bytes.
+    const NONCE_SIZE: usize;
+    /// The size of the tag in bytes.
+    const TAG_SIZE: usize;
+
+    fn init(&self, key: &Self::Key, nonce: &[u8], aad: &[u8]) -> SgxError;
+
+    fn update(&self, src: &[u8], dst: &mut [u8]) -> SgxError;
+
+    fn get_mac(&self) -> SgxResult<Self::Tag>;
+
+    fn close(&self) -> SgxError;
+
+    fn encrypt(
+        key: &Self::Key,
+        nonce: &[u8],
+        aad: &[u8],
+        src: &[u8],
+        dst: &mut [u8],
+    ) -> SgxResult<Self::Tag>;
+
+    fn decrypt(
+        key: &Self::Key,
+        nonce: &[u8],
+        aad: &[u8],
+        src: &[u8],
+        tag: &Self::Tag,
+        dst: &mut [u8],
+    ) -> SgxError;
+}
+
+/// Implements `Digest` for a hash handle of the cryptography library.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_digest {
+    ($handle:ident, $output:ty, $size:expr, $block_size:expr) => {
+        impl $crate::Digest for $handle {
+            type Output = $output;
+
+            const OUTPUT_SIZE: usize = $size;
+            const BLOCK_SIZE: usize = $block_size;
+
+            fn init(&self) -> SgxError {
+                $handle::init(self)
+            }
+
+            fn update(&self, data: &[u8]) -> SgxError {
+                if data.is_empty() {
+                    return Ok(());
+                }
+                self.update_slice(data)
+            }
+
+            fn finalize(&self) -> SgxResult<Self::Output> {
+                self.get_hash()
+            }
+
+            fn close(&self) -> SgxError {
+                $handle::close(self)
+            }
+        }
+    };
+}
+
+/// Implements `Mac` for a MAC handle of the cryptography library.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_mac {
+    ($handle:ident, $key:ty, $key_size:expr, $output:ty, $size:expr) => {
+        impl $crate::Mac for $handle {
+            type Key = $key;
+            type Output = $output;
+
+            const KEY_SIZE: usize = $key_size;
+            const OUTPUT_SIZE: usize = $size;
+
+            fn init(&self, key: &Self::Key) -> SgxError {
+                $handle::init(self, key)
+            }
+
+            fn update(&self, data: &[u8]) -> SgxError {
+                if data.is_empty() {
+                    return Ok(());
+                }
+                self.update_slice(data)
+            }
+
+            fn finalize(&self) -> SgxResult<Self::Output> {
+                self.get_hash()
+            }
+
+            fn close(&self) -> SgxError {
+                $handle::close(self)
+            }
+        }
+    };
+}
+
+/// Implements `Aead` for an AEAD handle and its one-shot functions.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_aead {
+    ($handle:ident, $key:ty, $key_size:expr, $nonce_size:expr, $tag:ty, $tag_size:expr, $encrypt:ident, $decrypt:ident) => {
+        impl $crate::Aead for $handle {
+            type Key = $key;
+            type Tag = $tag;
+
+            const KEY_SIZE: usize = $key_size;
+            const NONCE_SIZE: usize = $nonce_size;
+            const TAG_SIZE: usize = $tag_size;
+
+            fn init(&self, key: &Self::Key, nonce: &[u8], aad: &[u8]) -> SgxError {
+                $handle::init(self, key, nonce, aad)
+            }
+
+            fn update(&self, src: &[u8], dst: &mut [u8]) -> SgxError {
+                $handle::update(self, src, dst)
+            }
+
+            fn get_mac(&self) -> SgxResult<Self::Tag> {
+                $handle::get_mac(self)
+            }
+
+            fn close(&self) -> SgxError {
+                $handle::close(self)
+            }
+
+            fn encrypt(
+                key: &Self::Key,
+                nonce: &[u8],
+                aad: &[u8],
+                src: &[u8],
+                dst: &mut [u8],
+            ) -> SgxResult<Self::Tag> {
+                let mut tag = <$tag>::default();
+                $encrypt(key, src, nonce, aad, dst, &mut tag)?;
+                Ok(tag)
+            }
+
+            fn decrypt(
+                key: &Self::Key,
+                nonce: &[u8],
+                aad: &[u8],
+                src: &[u8],
+                tag: &Self::Tag,
+                dst: &mut [u8],
+            ) -> SgxError {
+                $decrypt(key, src, nonce, aad, tag, dst)
+            }
+        }
+    };
+}
+
+crate::impl_aead!(
+    SgxAes256Handle,
+    sgx_aes_gcm_256bit_key_t,
+    SGX_AESGCM256_KEY_SIZE,
+    SGX_AESGCM_IV_SIZE,
+    sgx_aes_gcm_128bit_tag_t,
+    SGX_AESGCM_MAC_SIZE,
+    rsgx_aes256GCM_encrypt,
+    rsgx_aes256GCM_decrypt
+);
+crate::impl_aead!(
+    SgxChaCha20Poly1305Handle,
+    sgx_chacha20_poly1305_key_t,
+    SGX_CHACHA20POLY1305_KEY_SIZE,
+    SGX_CHACHA20POLY1305_NONCE_SIZE,
+    sgx_chacha20_poly1305_tag_t,
+    SGX_CHACHA20POLY1305_MAC_SIZE,
+    rsgx_chacha20_poly1305_encrypt,
+    rsgx_chacha20_poly1305_decrypt
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn aead_round_trip<A: Aead>(key: &A::Key) {
+        let nonce = vec![7_u8; A::NONCE_SIZE];
+        let aad = b"associated data";
+        let plain = b"the quick brown fox jumps over the lazy dog";
+
+        let mut cipher = vec![0_u8; plain.len()];
+        let tag = A::encrypt(key, &nonce, aad, plain, &mut cipher).unwrap();
+        assert_eq!(tag.as_ref().len(), A::TAG_SIZE);
+
+        let handle = A::default();
+        handle.init(key, &nonce, aad).unwrap();
+        let mut streamed = vec![0_u8; plain.len()];
+        handle.update(&plain[..10], &mut streamed[..10]).unwrap();
+        handle.update(&plain[10..], &mut streamed[10..]).unwrap();
+        assert_eq!(streamed, cipher);
+        assert_eq!(handle.get_mac().unwrap().as_ref(), tag.as_ref());
+        handle.close().unwrap();
+
+        let mut decrypted = vec![0_u8; plain.len()];
+        A::decrypt(key, &nonce, aad, &cipher, &tag, &mut decrypted).unwrap();
+        assert_eq!(&decrypted[..], &plain[..]);
+
+        cipher[0] ^= 1;
+        assert!(A::decrypt(key, &nonce, aad, &cipher, &tag, &mut decrypted).is_err());
+    }
+
+    #[test]
+    fn aead_round_trips() {
+        aead_round_trip::<SgxAes256Handle>(&[2_u8; SGX_AESGCM256_KEY_SIZE]);
+        aead_round_trip::<SgxChaCha20Poly1305Handle>(&[3_u8; SGX_CHACHA20POLY1305_KEY_SIZE]);
+    }
+}
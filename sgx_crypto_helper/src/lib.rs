@@ -49,6 +49,15 @@ use std::prelude::v1::*;
 use sgx_types::SgxResult;
 use crypto::SgxRsaPrivKey;
 use crypto::SgxRsaPubKey;
+use crypto::{SgxRsaPrivateKeyComponents, SgxRsaPublicKeyComponents};
+
+/// The padding scheme of an RSA signature. Both schemes hash the data with SHA-256,
+/// and PSS uses MGF1 with SHA-256 and a 32-byte salt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RsaSignatureScheme {
+    Pkcs1v15Sha256,
+    PssSha256,
+}
 
 /// A trait to express the ability to create a RSA keypair with default e
 /// (65537) or customized e, and to_privkey/to_pubkey, encryption/decryption API.
@@ -67,6 +76,38 @@ pub trait RsaKeyPair {
     fn decrypt_buffer(self, ciphertext: &[u8], plaintext: &mut Vec<u8>) -> SgxResult<usize>;
 }
 
+/// RSA signatures, implemented by the key pairs of this crate. This is a separate
+/// trait so that implementations of `RsaKeyPair` outside this crate are not affected.
+pub trait RsaSigningKeyPair {
+    /// Sign a u8 slice. Returns the big-endian signature, as long as the modulus.
+    fn sign(self, scheme: RsaSignatureScheme, data: &[u8]) -> SgxResult<Vec<u8>>;
+    /// Verify a big-endian signature of a u8 slice. Returns false if it is not valid.
+    fn verify(self, scheme: RsaSignatureScheme, data: &[u8], signature: &[u8]) -> SgxResult<bool>;
+}
+
+pub(crate) fn rsa_sign(
+    scheme: RsaSignatureScheme,
+    data: &[u8],
+    key: &SgxRsaPrivateKeyComponents,
+) -> SgxResult<Vec<u8>> {
+    match scheme {
+        RsaSignatureScheme::Pkcs1v15Sha256 => crypto::rsgx_rsa_sign_pkcs1v15_sha256(data, key),
+        RsaSignatureScheme::PssSha256 => crypto::rsgx_rsa_sign_pss_sha256(data, key),
+    }
+}
+
+pub(crate) fn rsa_verify(
+    scheme: RsaSignatureScheme,
+    data: &[u8],
+    key: &SgxRsaPublicKeyComponents,
+    signature: &[u8],
+) -> SgxResult<bool> {
+    match scheme {
+        RsaSignatureScheme::Pkcs1v15Sha256 => crypto::rsgx_rsa_verify_pkcs1v15_sha256(data, key, signature),
+        RsaSignatureScheme::PssSha256 => crypto::rsgx_rsa_verify_pss_sha256(data, key, signature),
+    }
+}
+
 #[cfg(any(feature = "mesalock_sgx", target_env = "sgx"))]
 extern crate serde_sgx;
 #[cfg(not(any(feature = "mesalock_sgx", target_env = "sgx")))]
@@ -84,5 +125,11 @@ extern crate serde_big_array_sgx as serde_big_array;
 #[macro_use]
 extern crate serde_big_array;
 
+big_array! { BigArray; }
+
+#[macro_use]
+mod macros;
+
 pub mod rsa2048;
 pub mod rsa3072;
+pub mod rsa4096;
@@ -0,0 +1,312 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+// Generates an RSA key pair type and its public key type for one modulus size.
+// The types only differ in the size of their arrays, so rsa2048, rsa3072 and
+// rsa4096 share this definition.
+macro_rules! rsa_key_pair {
+    (
+        $(#[$attr:meta])*
+        $keypair:ident, $pubkey:ident,
+        $key_size:expr, $pri_exp_size:expr, $pub_exp_size:expr, $default_e:expr
+    ) => {
+        $(#[$attr])*
+        #[cfg(any(feature = "mesalock_sgx", target_env = "sgx"))]
+        #[derive(serde_derive::Serialize, serde_derive::Deserialize, Clone, Copy)]
+        #[serde(crate = "serde_sgx")]
+        pub struct $keypair {
+            #[serde(with = "crate::BigArray")]
+            n: [u8; $key_size],
+            #[serde(with = "crate::BigArray")]
+            d: [u8; $pri_exp_size],
+            e: [u8; $pub_exp_size],
+            #[serde(with = "crate::BigArray")]
+            p: [u8; $key_size / 2],
+            #[serde(with = "crate::BigArray")]
+            q: [u8; $key_size / 2],
+            #[serde(with = "crate::BigArray")]
+            dmp1: [u8; $key_size / 2],
+            #[serde(with = "crate::BigArray")]
+            dmq1: [u8; $key_size / 2],
+            #[serde(with = "crate::BigArray")]
+            iqmp: [u8; $key_size / 2],
+        }
+        $(#[$attr])*
+        #[cfg(not(any(feature = "mesalock_sgx", target_env = "sgx")))]
+        #[derive(serde_derive::Serialize, serde_derive::Deserialize, Clone, Copy)]
+        pub struct $keypair {
+            #[serde(with = "crate::BigArray")]
+            n: [u8; $key_size],
+            #[serde(with = "crate::BigArray")]
+            d: [u8; $pri_exp_size],
+            e: [u8; $pub_exp_size],
+            #[serde(with = "crate::BigArray")]
+            p: [u8; $key_size / 2],
+            #[serde(with = "crate::BigArray")]
+            q: [u8; $key_size / 2],
+            #[serde(with = "crate::BigArray")]
+            dmp1: [u8; $key_size / 2],
+            #[serde(with = "crate::BigArray")]
+            dmq1: [u8; $key_size / 2],
+            #[serde(with = "crate::BigArray")]
+            iqmp: [u8; $key_size / 2],
+        }
+
+        impl Default for $keypair {
+            fn default() -> Self {
+                $keypair {
+                    n: [0; $key_size],
+                    d: [0; $pri_exp_size],
+                    e: $default_e,
+                    p: [0; $key_size / 2],
+                    q: [0; $key_size / 2],
+                    dmp1: [0; $key_size / 2],
+                    dmq1: [0; $key_size / 2],
+                    iqmp: [0; $key_size / 2],
+                }
+            }
+        }
+
+        impl std::fmt::Debug for $keypair {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                use itertools::Itertools;
+                write!(f, concat!(stringify!($keypair), r#": {{ n:{:02X}, d:{:02X}, e:{:02X}, p:{:02X}, q:{:02X}, dmp1:{:02X}, dmq:{:02X}, iqmp:{:02X} }}"#),
+                    self.n.iter().format(""),
+                    self.d.iter().format(""),
+                    self.e.iter().format(""),
+                    self.p.iter().format(""),
+                    self.q.iter().format(""),
+                    self.dmp1.iter().format(""),
+                    self.dmq1.iter().format(""),
+                    self.iqmp.iter().format(""))
+            }
+        }
+
+        impl $crate::RsaKeyPair for $keypair {
+            fn new() -> sgx_types::SgxResult<Self> {
+                Self::create(Self::default())
+            }
+
+            fn new_with_e(e: u32) -> sgx_types::SgxResult<Self> {
+                let mut newkey = Self::default();
+                newkey.e = e.to_le_bytes();
+                Self::create(newkey)
+            }
+
+            fn to_privkey(self) -> sgx_types::SgxResult<crypto::SgxRsaPrivKey> {
+                let result = crypto::SgxRsaPrivKey::new();
+                match result.create(
+                    $key_size as i32,
+                    $pri_exp_size as i32,
+                    &self.e,
+                    &self.p,
+                    &self.q,
+                    &self.dmp1,
+                    &self.dmq1,
+                    &self.iqmp,
+                ) {
+                    Ok(()) => Ok(result),
+                    Err(x) => Err(x),
+                }
+            }
+
+            fn to_pubkey(self) -> sgx_types::SgxResult<crypto::SgxRsaPubKey> {
+                self.export_pubkey()?.to_pubkey()
+            }
+
+            fn encrypt_buffer(self, plaintext: &[u8], ciphertext: &mut Vec<u8>) -> sgx_types::SgxResult<usize> {
+                self.export_pubkey()?.encrypt_buffer(plaintext, ciphertext)
+            }
+
+            fn decrypt_buffer(self, ciphertext: &[u8], plaintext: &mut Vec<u8>) -> sgx_types::SgxResult<usize> {
+                let privkey = self.to_privkey()?;
+                let bs = $key_size;
+
+                if ciphertext.len() % bs != 0 {
+                    return Err(sgx_types::sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+                }
+
+                // Additional one byte is required in 1.0.9
+                // let bs_plain = bs - 2 * 256 / 8 - 2;
+                // In 2.6, we need a longer buf to put the decrypted data.
+                // The output length is exactly bs_plain above, but it results in
+                // SGX_ERROR_INVALID_PARAMETER.
+                let bs_plain = bs;
+                let count = ciphertext.len() / bs;
+                plaintext.clear();
+
+                for i in 0..count {
+                    let cipher_slice = &ciphertext[i * bs..i * bs + bs];
+                    let plain_slice = &mut vec![0;bs_plain];
+                    let mut plain_len = bs_plain;
+
+                    privkey.decrypt_sha256(plain_slice, &mut plain_len, cipher_slice)?;
+                    let mut decoded_vec = plain_slice[..plain_len].to_vec();
+                    plaintext.append(&mut decoded_vec);
+                }
+
+                Ok(plaintext.len())
+            }
+        }
+
+        impl $crate::RsaSigningKeyPair for $keypair {
+            fn sign(self, scheme: $crate::RsaSignatureScheme, data: &[u8]) -> sgx_types::SgxResult<Vec<u8>> {
+                $crate::rsa_sign(scheme, data, &self.to_components())
+            }
+
+            fn verify(self, scheme: $crate::RsaSignatureScheme, data: &[u8], signature: &[u8]) -> sgx_types::SgxResult<bool> {
+                self.export_pubkey()?.verify(scheme, data, signature)
+            }
+        }
+
+        impl $keypair {
+            pub fn export_pubkey(self) -> sgx_types::SgxResult<$pubkey> {
+                Ok($pubkey {
+                    n: self.n,
+                    e: self.e,
+                })
+            }
+
+            fn create(mut newkey: Self) -> sgx_types::SgxResult<Self> {
+                match crypto::rsgx_create_rsa_key_pair(
+                    $key_size as i32,
+                    $pub_exp_size as i32,
+                    &mut newkey.n,
+                    &mut newkey.d,
+                    &mut newkey.e,
+                    &mut newkey.p,
+                    &mut newkey.q,
+                    &mut newkey.dmp1,
+                    &mut newkey.dmq1,
+                    &mut newkey.iqmp,
+                ) {
+                    Ok(()) => Ok(newkey),
+                    Err(x) => Err(x),
+                }
+            }
+
+            fn to_components(self) -> crypto::SgxRsaPrivateKeyComponents {
+                crypto::SgxRsaPrivateKeyComponents {
+                    n: self.n.to_vec(),
+                    e: self.e.to_vec(),
+                    d: self.d.to_vec(),
+                    p: self.p.to_vec(),
+                    q: self.q.to_vec(),
+                    dmp1: self.dmp1.to_vec(),
+                    dmq1: self.dmq1.to_vec(),
+                    iqmp: self.iqmp.to_vec(),
+                }
+            }
+
+            // Builds a key pair from components with the sizes of this type, as
+            // returned by rsgx_rsa_private_from_der.
+            #[cfg(test)]
+            fn from_components(components: &crypto::SgxRsaPrivateKeyComponents) -> Self {
+                let mut key = Self::default();
+                key.n.copy_from_slice(&components.n);
+                key.d.copy_from_slice(&components.d);
+                key.e.copy_from_slice(&components.e);
+                key.p.copy_from_slice(&components.p);
+                key.q.copy_from_slice(&components.q);
+                key.dmp1.copy_from_slice(&components.dmp1);
+                key.dmq1.copy_from_slice(&components.dmq1);
+                key.iqmp.copy_from_slice(&components.iqmp);
+                key
+            }
+        }
+
+        #[cfg(any(feature = "mesalock_sgx", target_env = "sgx"))]
+        #[derive(serde_derive::Serialize, serde_derive::Deserialize, Clone, Copy)]
+        #[serde(crate = "serde_sgx")]
+        pub struct $pubkey {
+            #[serde(with = "crate::BigArray")]
+            n: [u8; $key_size],
+            e: [u8; $pub_exp_size],
+        }
+        #[cfg(not(any(feature = "mesalock_sgx", target_env = "sgx")))]
+        #[derive(serde_derive::Serialize, serde_derive::Deserialize, Clone, Copy)]
+        pub struct $pubkey {
+            #[serde(with = "crate::BigArray")]
+            n: [u8; $key_size],
+            e: [u8; $pub_exp_size],
+        }
+
+        impl Default for $pubkey {
+            fn default() -> Self {
+                $pubkey {
+                    n: [0; $key_size],
+                    e: $default_e,
+                }
+            }
+        }
+
+        impl $pubkey {
+            pub fn to_pubkey(self) -> sgx_types::SgxResult<crypto::SgxRsaPubKey> {
+                let result = crypto::SgxRsaPubKey::new();
+                match result.create(
+                    $key_size as i32,
+                    $pub_exp_size as i32,
+                    &self.n,
+                    &self.e,
+                ) {
+                    Ok(()) => Ok(result),
+                    Err(x) => Err(x),
+                }
+            }
+
+            pub fn encrypt_buffer(self, plaintext: &[u8], ciphertext: &mut Vec<u8>) -> sgx_types::SgxResult<usize> {
+                let pubkey = self.to_pubkey()?;
+                let bs = $key_size;
+
+                // The plain text block size comes from RSA-OAEP with SHA-256:
+                // RSAOAEP_ENCRYPT_MAXLEN = RSA_KEY_BYTE - 2*SHA_SIZE_BIT/8 - 2
+                let bs_plain = bs - 2 * 256 / 8 - 2;
+                let count = (plaintext.len() + bs_plain - 1) / bs_plain;
+                ciphertext.resize(bs * count, 0);
+
+                for i in 0..count {
+                    let cipher_slice = &mut ciphertext[i * bs..i * bs + bs];
+                    let mut out_len = bs;
+                    let plain_slice =
+                        &plaintext[i * bs_plain..std::cmp::min(i * bs_plain + bs_plain, plaintext.len())];
+
+                    pubkey.encrypt_sha256(cipher_slice, &mut out_len, plain_slice)?;
+                }
+
+                Ok(ciphertext.len())
+            }
+
+            /// Verify a big-endian signature of a u8 slice. Returns false if it is not valid.
+            pub fn verify(self, scheme: $crate::RsaSignatureScheme, data: &[u8], signature: &[u8]) -> sgx_types::SgxResult<bool> {
+                let public = crypto::SgxRsaPublicKeyComponents {
+                    n: self.n.to_vec(),
+                    e: self.e.to_vec(),
+                };
+                $crate::rsa_verify(scheme, data, &public, signature)
+            }
+        }
+
+        impl std::fmt::Debug for $pubkey {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                use itertools::Itertools;
+                write!(f, concat!(stringify!($keypair), r#": {{ n:{:02X}, e:{:02X} }}"#),
+                    self.n.iter().format(""),
+                    self.e.iter().format(""))
+            }
+        }
+    };
+}
@@ -0,0 +1,305 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::der::{hex_decode, parse_iso8601};
+use crate::json::Json;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::slice;
+use sgx_types::*;
+
+///
+/// The collateral a quote is verified against, as returned by the PCCS or by
+/// `sgx_ql_get_quote_verification_collateral`.
+///
+/// The certificate chains are PEM. The CRLs are PEM, DER, or hex-encoded DER. The TCB info
+/// and the QE identity are the signed JSON documents.
+///
+#[derive(Clone, Debug, Default)]
+pub struct QuoteCollateral {
+    pub pck_crl_issuer_chain: Vec<u8>,
+    pub root_ca_crl: Vec<u8>,
+    pub pck_crl: Vec<u8>,
+    pub tcb_info_issuer_chain: Vec<u8>,
+    pub tcb_info: Vec<u8>,
+    pub qe_identity_issuer_chain: Vec<u8>,
+    pub qe_identity: Vec<u8>,
+}
+
+unsafe fn collateral_field(p: *const c_char, size: uint32_t) -> Vec<u8> {
+    if p.is_null() || size == 0 {
+        return Vec::new();
+    }
+    let mut field = slice::from_raw_parts(p as *const u8, size as usize).to_vec();
+    // The strings returned by the quote library include their terminating NUL.
+    while field.last() == Some(&0) {
+        field.pop();
+    }
+    field
+}
+
+impl QuoteCollateral {
+    ///
+    /// Copies the collateral out of a `sgx_ql_qve_collateral_t`, this is an unsafe function.
+    ///
+    /// # Parameters
+    ///
+    /// **collateral**
+    ///
+    /// The collateral returned by the quote library. Every pointer must be null or point to
+    /// as many bytes as its size says.
+    ///
+    pub unsafe fn from_raw(collateral: &sgx_ql_qve_collateral_t) -> QuoteCollateral {
+        QuoteCollateral {
+            pck_crl_issuer_chain: collateral_field(
+                collateral.pck_crl_issuer_chain,
+                collateral.pck_crl_issuer_chain_size,
+            ),
+            root_ca_crl: collateral_field(collateral.root_ca_crl, collateral.root_ca_crl_size),
+            pck_crl: collateral_field(collateral.pck_crl, collateral.pck_crl_size),
+            tcb_info_issuer_chain: collateral_field(
+                collateral.tcb_info_issuer_chain,
+                collateral.tcb_info_issuer_chain_size,
+            ),
+            tcb_info: collateral_field(collateral.tcb_info, collateral.tcb_info_size),
+            qe_identity_issuer_chain: collateral_field(
+                collateral.qe_identity_issuer_chain,
+                collateral.qe_identity_issuer_chain_size,
+            ),
+            qe_identity: collateral_field(collateral.qe_identity, collateral.qe_identity_size),
+        }
+    }
+}
+
+///
+/// The status of a TCB level in the TCB info or the QE identity, and the verdict of a quote
+/// verification.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TcbStatus {
+    UpToDate,
+    SWHardeningNeeded,
+    ConfigurationNeeded,
+    ConfigurationAndSWHardeningNeeded,
+    OutOfDate,
+    OutOfDateConfigurationNeeded,
+    Revoked,
+}
+
+impl TcbStatus {
+    fn from_str(status: &str) -> Option<TcbStatus> {
+        match status {
+            "UpToDate" => Some(TcbStatus::UpToDate),
+            "SWHardeningNeeded" => Some(TcbStatus::SWHardeningNeeded),
+            "ConfigurationNeeded" => Some(TcbStatus::ConfigurationNeeded),
+            "ConfigurationAndSWHardeningNeeded" => {
+                Some(TcbStatus::ConfigurationAndSWHardeningNeeded)
+            }
+            "OutOfDate" => Some(TcbStatus::OutOfDate),
+            "OutOfDateConfigurationNeeded" => Some(TcbStatus::OutOfDateConfigurationNeeded),
+            "Revoked" => Some(TcbStatus::Revoked),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            TcbStatus::UpToDate => "UpToDate",
+            TcbStatus::SWHardeningNeeded => "SWHardeningNeeded",
+            TcbStatus::ConfigurationNeeded => "ConfigurationNeeded",
+            TcbStatus::ConfigurationAndSWHardeningNeeded => "ConfigurationAndSWHardeningNeeded",
+            TcbStatus::OutOfDate => "OutOfDate",
+            TcbStatus::OutOfDateConfigurationNeeded => "OutOfDateConfigurationNeeded",
+            TcbStatus::Revoked => "Revoked",
+        }
+    }
+}
+
+impl From<TcbStatus> for sgx_ql_qv_result_t {
+    fn from(status: TcbStatus) -> sgx_ql_qv_result_t {
+        match status {
+            TcbStatus::UpToDate => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OK,
+            TcbStatus::SWHardeningNeeded => {
+                sgx_ql_qv_result_t::SGX_QL_QV_RESULT_SW_HARDENING_NEEDED
+            }
+            TcbStatus::ConfigurationNeeded => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_CONFIG_NEEDED,
+            TcbStatus::ConfigurationAndSWHardeningNeeded => {
+                sgx_ql_qv_result_t::SGX_QL_QV_RESULT_CONFIG_AND_SW_HARDENING_NEEDED
+            }
+            TcbStatus::OutOfDate => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OUT_OF_DATE,
+            TcbStatus::OutOfDateConfigurationNeeded => {
+                sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OUT_OF_DATE_CONFIG_NEEDED
+            }
+            TcbStatus::Revoked => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_REVOKED,
+        }
+    }
+}
+
+pub(crate) struct TcbLevel {
+    pub sgx_tcb_components: [u8; 16],
+    pub pce_svn: u16,
+    pub tcb_date: String,
+    pub status: TcbStatus,
+    pub advisory_ids: Vec<String>,
+}
+
+pub(crate) struct TcbInfo {
+    pub next_update: i64,
+    pub fmspc: [u8; 6],
+    pub pce_id: [u8; 2],
+    pub levels: Vec<TcbLevel>,
+}
+
+pub(crate) struct QeTcbLevel {
+    pub isv_svn: u16,
+    pub status: TcbStatus,
+    pub advisory_ids: Vec<String>,
+}
+
+pub(crate) struct QeIdentity {
+    pub next_update: i64,
+    pub misc_select: u32,
+    pub misc_select_mask: u32,
+    pub attributes: [u8; 16],
+    pub attributes_mask: [u8; 16],
+    pub mr_signer: [u8; 32],
+    pub isv_prod_id: u16,
+    pub levels: Vec<QeTcbLevel>,
+}
+
+/// A signed collateral document: the signed member, its exact text, and its signature.
+pub(crate) struct Signed<'a> {
+    pub body: &'a Json<'a>,
+    pub signature: [u8; 64],
+}
+
+pub(crate) fn parse_signed<'a>(document: &'a Json<'a>, member: &str) -> Option<Signed<'a>> {
+    let body = document.get(member)?;
+    let signature = hex_array(document.get("signature")?)?;
+    Some(Signed { body, signature })
+}
+
+fn hex_array<const N: usize>(json: &Json) -> Option<[u8; N]> {
+    hex_decode(json.as_str()?)?.try_into().ok()
+}
+
+fn u16_field(json: &Json, key: &str) -> Option<u16> {
+    u16::try_from(json.get(key)?.as_i64()?).ok()
+}
+
+fn u8_field(json: &Json, key: &str) -> Option<u8> {
+    u8::try_from(json.get(key)?.as_i64()?).ok()
+}
+
+fn time_field(json: &Json, key: &str) -> Option<i64> {
+    parse_iso8601(json.get(key)?.as_str()?)
+}
+
+fn status_fields(level: &Json) -> Option<(String, TcbStatus, Vec<String>)> {
+    let tcb_date = level.get("tcbDate")?.as_str()?.to_string();
+    let status = TcbStatus::from_str(level.get("tcbStatus")?.as_str()?)?;
+    let advisory_ids = match level.get("advisoryIDs") {
+        Some(ids) => ids
+            .as_array()?
+            .iter()
+            .map(|id| id.as_str().map(|id| id.to_string()))
+            .collect::<Option<Vec<String>>>()?,
+        None => Vec::new(),
+    };
+    Some((tcb_date, status, advisory_ids))
+}
+
+impl TcbInfo {
+    /// Parses the `tcbInfo` member of a TCB info document, in version 2 or 3.
+    pub fn parse(tcb_info: &Json) -> Option<TcbInfo> {
+        let version = tcb_info.get("version")?.as_i64()?;
+        match version {
+            2 => {}
+            3 => {
+                if tcb_info.get("id")?.as_str()? != "SGX" {
+                    return None;
+                }
+            }
+            _ => return None,
+        }
+
+        let mut levels = Vec::new();
+        for level in tcb_info.get("tcbLevels")?.as_array()? {
+            let tcb = level.get("tcb")?;
+            let mut sgx_tcb_components = [0_u8; 16];
+            if version == 2 {
+                for (i, svn) in sgx_tcb_components.iter_mut().enumerate() {
+                    let key = alloc::format!("sgxtcbcomp{:02}svn", i + 1);
+                    *svn = u8_field(tcb, &key)?;
+                }
+            } else {
+                let components = tcb.get("sgxtcbcomponents")?.as_array()?;
+                if components.len() != sgx_tcb_components.len() {
+                    return None;
+                }
+                for (svn, component) in sgx_tcb_components.iter_mut().zip(components) {
+                    *svn = u8_field(component, "svn")?;
+                }
+            }
+            let (tcb_date, status, advisory_ids) = status_fields(level)?;
+            levels.push(TcbLevel {
+                sgx_tcb_components,
+                pce_svn: u16_field(tcb, "pcesvn")?,
+                tcb_date,
+                status,
+                advisory_ids,
+            });
+        }
+
+        Some(TcbInfo {
+            next_update: time_field(tcb_info, "nextUpdate")?,
+            fmspc: hex_array(tcb_info.get("fmspc")?)?,
+            pce_id: hex_array(tcb_info.get("pceId")?)?,
+            levels,
+        })
+    }
+}
+
+impl QeIdentity {
+    /// Parses the `enclaveIdentity` member of a QE identity document, in version 2.
+    pub fn parse(identity: &Json) -> Option<QeIdentity> {
+        if identity.get("version")?.as_i64()? != 2 || identity.get("id")?.as_str()? != "QE" {
+            return None;
+        }
+
+        let mut levels = Vec::new();
+        for level in identity.get("tcbLevels")?.as_array()? {
+            let (_, status, advisory_ids) = status_fields(level)?;
+            levels.push(QeTcbLevel {
+                isv_svn: u16_field(level.get("tcb")?, "isvsvn")?,
+                status,
+                advisory_ids,
+            });
+        }
+
+        Some(QeIdentity {
+            next_update: time_field(identity, "nextUpdate")?,
+            misc_select: u32::from_be_bytes(hex_array(identity.get("miscselect")?)?),
+            misc_select_mask: u32::from_be_bytes(hex_array(identity.get("miscselectMask")?)?),
+            attributes: hex_array(identity.get("attributes")?)?,
+            attributes_mask: hex_array(identity.get("attributesMask")?)?,
+            mr_signer: hex_array(identity.get("mrsigner")?)?,
+            isv_prod_id: u16_field(identity, "isvprodid")?,
+            levels,
+        })
+    }
+}
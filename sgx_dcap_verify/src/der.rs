@@ -0,0 +1,301 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! A minimal reader for the DER encoding, PEM, and the time formats used in the collateral.
+
+use alloc::vec::Vec;
+
+pub const TAG_BOOLEAN: u8 = 0x01;
+pub const TAG_INTEGER: u8 = 0x02;
+pub const TAG_BIT_STRING: u8 = 0x03;
+pub const TAG_OCTET_STRING: u8 = 0x04;
+pub const TAG_OID: u8 = 0x06;
+pub const TAG_UTC_TIME: u8 = 0x17;
+pub const TAG_GENERALIZED_TIME: u8 = 0x18;
+pub const TAG_SEQUENCE: u8 = 0x30;
+
+#[derive(Clone, Copy)]
+pub struct Element<'a> {
+    pub tag: u8,
+    pub value: &'a [u8],
+    /// The whole element, with its tag and length.
+    pub raw: &'a [u8],
+}
+
+#[derive(Clone, Copy)]
+pub struct DerReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> DerReader<'a> {
+    pub fn new(data: &'a [u8]) -> DerReader<'a> {
+        DerReader { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    pub fn read_any(&mut self) -> Option<Element<'a>> {
+        let tag = *self.data.first()?;
+        let first = *self.data.get(1)? as usize;
+        let (len, header) = if first < 0x80 {
+            (first, 2)
+        } else {
+            let count = first & 0x7f;
+            if count == 0 || count > 4 {
+                return None;
+            }
+            let mut len = 0_usize;
+            for i in 0..count {
+                len = (len << 8) | *self.data.get(2 + i)? as usize;
+            }
+            if len < 0x80 {
+                return None;
+            }
+            (len, 2 + count)
+        };
+        let end = header.checked_add(len)?;
+        if end > self.data.len() {
+            return None;
+        }
+        let element = Element {
+            tag,
+            value: &self.data[header..end],
+            raw: &self.data[..end],
+        };
+        self.data = &self.data[end..];
+        Some(element)
+    }
+
+    pub fn read(&mut self, tag: u8) -> Option<Element<'a>> {
+        if self.peek_tag()? != tag {
+            return None;
+        }
+        self.read_any()
+    }
+
+    pub fn read_sequence(&mut self) -> Option<DerReader<'a>> {
+        self.read(TAG_SEQUENCE).map(|e| DerReader::new(e.value))
+    }
+
+    /// Reads an element with a context-specific tag, if it is the next one.
+    pub fn read_optional(&mut self, tag: u8) -> Option<Element<'a>> {
+        if self.peek_tag() == Some(tag) {
+            self.read_any()
+        } else {
+            None
+        }
+    }
+
+    /// Reads a non-negative INTEGER, without its leading zero byte.
+    pub fn read_unsigned(&mut self) -> Option<&'a [u8]> {
+        let value = self.read(TAG_INTEGER)?.value;
+        if value.is_empty() || value[0] & 0x80 != 0 {
+            return None;
+        }
+        let start = value
+            .iter()
+            .position(|&b| b != 0)
+            .unwrap_or(value.len() - 1);
+        Some(&value[start..])
+    }
+
+    pub fn read_small_unsigned(&mut self) -> Option<u32> {
+        let value = self.read_unsigned()?;
+        if value.len() > 4 {
+            return None;
+        }
+        Some(value.iter().fold(0_u32, |acc, &b| (acc << 8) | b as u32))
+    }
+
+    /// Reads a BIT STRING without unused bits.
+    pub fn read_bit_string(&mut self) -> Option<&'a [u8]> {
+        let value = self.read(TAG_BIT_STRING)?.value;
+        match value.split_first() {
+            Some((0, bits)) => Some(bits),
+            _ => None,
+        }
+    }
+
+    pub fn read_time(&mut self) -> Option<i64> {
+        let element = self.read_any()?;
+        match element.tag {
+            TAG_UTC_TIME => parse_asn1_time(element.value, false),
+            TAG_GENERALIZED_TIME => parse_asn1_time(element.value, true),
+            _ => None,
+        }
+    }
+}
+
+fn digits(s: &[u8]) -> Option<i64> {
+    if s.is_empty() {
+        return None;
+    }
+    s.iter().try_fold(0_i64, |acc, &c| {
+        if c.is_ascii_digit() {
+            Some(acc * 10 + (c - b'0') as i64)
+        } else {
+            None
+        }
+    })
+}
+
+// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn unix_time(year: i64, month: i64, day: i64, hour: i64, min: i64, sec: i64) -> Option<i64> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || min > 59 || sec > 60 {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * 86_400 + hour * 3600 + min * 60 + sec)
+}
+
+// YYMMDDHHMMSSZ for UTCTime, YYYYMMDDHHMMSSZ for GeneralizedTime.
+fn parse_asn1_time(value: &[u8], generalized: bool) -> Option<i64> {
+    let (year, rest) = if generalized {
+        if value.len() != 15 {
+            return None;
+        }
+        (digits(&value[..4])?, &value[4..])
+    } else {
+        if value.len() != 13 {
+            return None;
+        }
+        let yy = digits(&value[..2])?;
+        (if yy >= 50 { 1900 + yy } else { 2000 + yy }, &value[2..])
+    };
+    if rest[10] != b'Z' {
+        return None;
+    }
+    unix_time(
+        year,
+        digits(&rest[0..2])?,
+        digits(&rest[2..4])?,
+        digits(&rest[4..6])?,
+        digits(&rest[6..8])?,
+        digits(&rest[8..10])?,
+    )
+}
+
+/// Parses an ISO 8601 UTC time, `YYYY-MM-DDTHH:MM:SSZ`, with optional fractional seconds.
+pub fn parse_iso8601(value: &str) -> Option<i64> {
+    let s = value.as_bytes();
+    if s.len() < 20 || s[4] != b'-' || s[7] != b'-' || s[10] != b'T' || s[13] != b':' {
+        return None;
+    }
+    if s[16] != b':' || *s.last()? != b'Z' {
+        return None;
+    }
+    let fraction = &s[19..s.len() - 1];
+    if !fraction.is_empty() && (fraction[0] != b'.' || digits(&fraction[1..]).is_none()) {
+        return None;
+    }
+    unix_time(
+        digits(&s[0..4])?,
+        digits(&s[5..7])?,
+        digits(&s[8..10])?,
+        digits(&s[11..13])?,
+        digits(&s[14..16])?,
+        digits(&s[17..19])?,
+    )
+}
+
+fn base64_value(c: u8) -> Option<u32> {
+    match c {
+        b'A'..=b'Z' => Some((c - b'A') as u32),
+        b'a'..=b'z' => Some((c - b'a') as u32 + 26),
+        b'0'..=b'9' => Some((c - b'0') as u32 + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+fn base64_decode(text: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let mut acc = 0_u32;
+    let mut bits = 0;
+    let mut padding = 0;
+    for &c in text.iter().filter(|c| !c.is_ascii_whitespace()) {
+        if c == b'=' {
+            padding += 1;
+            continue;
+        }
+        if padding > 0 {
+            return None;
+        }
+        acc = (acc << 6) | base64_value(c)?;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    if padding > 2 {
+        return None;
+    }
+    Some(out)
+}
+
+/// Decodes all the PEM blocks with the given label, in order.
+pub fn pem_decode_all(pem: &[u8], label: &str) -> Option<Vec<Vec<u8>>> {
+    let mut blocks = Vec::new();
+    let mut rest = pem;
+    let begin = [b"-----BEGIN ", label.as_bytes(), b"-----"].concat();
+    let end = [b"-----END ", label.as_bytes(), b"-----"].concat();
+    while let Some(start) = find(rest, &begin) {
+        let body = &rest[start + begin.len()..];
+        let stop = find(body, &end)?;
+        blocks.push(base64_decode(&body[..stop])?);
+        rest = &body[stop + end.len()..];
+    }
+    Some(blocks)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Decodes a hex string, in upper or lower case.
+pub fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.as_bytes();
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    let value = |c: u8| match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    };
+    hex.chunks(2)
+        .map(|pair| Some((value(pair[0])? << 4) | value(pair[1])?))
+        .collect()
+}
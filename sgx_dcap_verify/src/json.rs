@@ -0,0 +1,227 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! A minimal JSON parser for the TCB info and QE identity collateral.
+//!
+//! The collateral is signed over the exact text of one of its members, so every parsed value
+//! keeps the text it was parsed from.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const MAX_DEPTH: usize = 32;
+
+pub enum JsonValue<'a> {
+    Null,
+    // The collateral has no boolean members, so the value is not kept.
+    Bool,
+    Number(i64),
+    String(String),
+    Array(Vec<Json<'a>>),
+    Object(Vec<(String, Json<'a>)>),
+}
+
+pub struct Json<'a> {
+    pub value: JsonValue<'a>,
+    pub raw: &'a str,
+}
+
+impl<'a> Json<'a> {
+    pub fn parse(text: &'a str) -> Option<Json<'a>> {
+        let mut parser = Parser { text, pos: 0 };
+        let json = parser.parse_value(0)?;
+        parser.skip_whitespace();
+        if parser.pos != text.len() {
+            return None;
+        }
+        Some(json)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json<'a>> {
+        match &self.value {
+            JsonValue::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.value {
+            JsonValue::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json<'a>]> {
+        match &self.value {
+            JsonValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Option<()> {
+        if self.peek()? != c {
+            return None;
+        }
+        self.pos += 1;
+        Some(())
+    }
+
+    fn expect_literal(&mut self, literal: &str) -> Option<()> {
+        if !self.text[self.pos..].starts_with(literal) {
+            return None;
+        }
+        self.pos += literal.len();
+        Some(())
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Option<Json<'a>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        let start = self.pos;
+        let value = match self.peek()? {
+            b'n' => self.expect_literal("null").map(|_| JsonValue::Null)?,
+            b't' => self.expect_literal("true").map(|_| JsonValue::Bool)?,
+            b'f' => self.expect_literal("false").map(|_| JsonValue::Bool)?,
+            b'"' => JsonValue::String(self.parse_string()?),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.peek()? == b']' {
+                    self.pos += 1;
+                } else {
+                    loop {
+                        items.push(self.parse_value(depth + 1)?);
+                        self.skip_whitespace();
+                        match self.peek()? {
+                            b',' => self.pos += 1,
+                            b']' => {
+                                self.pos += 1;
+                                break;
+                            }
+                            _ => return None,
+                        }
+                    }
+                }
+                JsonValue::Array(items)
+            }
+            b'{' => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                self.skip_whitespace();
+                if self.peek()? == b'}' {
+                    self.pos += 1;
+                } else {
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.parse_string()?;
+                        self.skip_whitespace();
+                        self.expect(b':')?;
+                        members.push((key, self.parse_value(depth + 1)?));
+                        self.skip_whitespace();
+                        match self.peek()? {
+                            b',' => self.pos += 1,
+                            b'}' => {
+                                self.pos += 1;
+                                break;
+                            }
+                            _ => return None,
+                        }
+                    }
+                }
+                JsonValue::Object(members)
+            }
+            _ => JsonValue::Number(self.parse_number()?),
+        };
+        Some(Json {
+            value,
+            raw: &self.text[start..self.pos],
+        })
+    }
+
+    fn parse_number(&mut self) -> Option<i64> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        self.text[start..self.pos].parse::<i64>().ok()
+    }
+
+    fn parse_string(&mut self) -> Option<String> {
+        self.expect(b'"')?;
+        let mut s = String::new();
+        loop {
+            let rest = &self.text[self.pos..];
+            let c = rest.chars().next()?;
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Some(s),
+                '\\' => {
+                    let escape = self.peek()?;
+                    self.pos += 1;
+                    s.push(match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let hex = self.text.get(self.pos..self.pos + 4)?;
+                            self.pos += 4;
+                            char::from_u32(u32::from_str_radix(hex, 16).ok()?)?
+                        }
+                        _ => return None,
+                    });
+                }
+                c if (c as u32) < 0x20 => return None,
+                c => s.push(c),
+            }
+        }
+    }
+}
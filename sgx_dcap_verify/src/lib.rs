@@ -0,0 +1,50 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! # DCAP Quote Verification
+//!
+//! Pure Rust verification of SGX ECDSA quotes against their collateral, usable inside and
+//! outside an enclave without the Intel quote verification library.
+//!
+
+#![no_std]
+#![cfg_attr(
+    all(target_env = "sgx", target_vendor = "mesalock"),
+    feature(rustc_private)
+)]
+#![allow(clippy::missing_safety_doc)]
+
+extern crate alloc;
+
+extern crate sgx_types;
+
+mod der;
+mod json;
+mod p256;
+mod sha256;
+mod x509;
+
+pub use self::x509::PckExtension;
+
+mod quote;
+pub use self::quote::*;
+
+mod collateral;
+pub use self::collateral::*;
+
+mod verify;
+pub use self::verify::*;
@@ -0,0 +1,363 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! ECDSA signature verification on the NIST P-256 curve with SHA-256.
+//!
+//! Only public values are handled here, so the arithmetic is not constant time.
+
+use crate::sha256::sha256;
+use core::cmp::Ordering;
+
+type U256 = [u64; 4];
+
+const ZERO: U256 = [0; 4];
+const ONE: U256 = [1, 0, 0, 0];
+
+#[rustfmt::skip]
+const P: U256 = [0xffff_ffff_ffff_ffff, 0x0000_0000_ffff_ffff, 0x0000_0000_0000_0000, 0xffff_ffff_0000_0001];
+#[rustfmt::skip]
+const N: U256 = [0xf3b9_cac2_fc63_2551, 0xbce6_faad_a717_9e84, 0xffff_ffff_ffff_ffff, 0xffff_ffff_0000_0000];
+#[rustfmt::skip]
+const B: U256 = [0x3bce_3c3e_27d2_604b, 0x651d_06b0_cc53_b0f6, 0xb3eb_bd55_7698_86bc, 0x5ac6_35d8_aa3a_93e7];
+#[rustfmt::skip]
+const GX: U256 = [0xf4a1_3945_d898_c296, 0x7703_7d81_2deb_33a0, 0xf8bc_e6e5_63a4_40f2, 0x6b17_d1f2_e12c_4247];
+#[rustfmt::skip]
+const GY: U256 = [0xcbb6_4068_37bf_51f5, 0x2bce_3357_6b31_5ece, 0x8ee7_eb4a_7c0f_9e16, 0x4fe3_42e2_fe1a_7f9b];
+
+fn from_be(bytes: &[u8]) -> U256 {
+    let mut value = ZERO;
+    for (i, chunk) in bytes.rchunks(8).enumerate() {
+        let mut word = [0_u8; 8];
+        word.copy_from_slice(chunk);
+        value[i] = u64::from_be_bytes(word);
+    }
+    value
+}
+
+fn compare(a: &U256, b: &U256) -> Ordering {
+    for i in (0..4).rev() {
+        match a[i].cmp(&b[i]) {
+            Ordering::Equal => continue,
+            ordering => return ordering,
+        }
+    }
+    Ordering::Equal
+}
+
+fn is_zero(a: &U256) -> bool {
+    a.iter().all(|&w| w == 0)
+}
+
+fn add(a: &U256, b: &U256) -> (U256, bool) {
+    let mut r = ZERO;
+    let mut carry = false;
+    for i in 0..4 {
+        let (s1, c1) = a[i].overflowing_add(b[i]);
+        let (s2, c2) = s1.overflowing_add(carry as u64);
+        r[i] = s2;
+        carry = c1 || c2;
+    }
+    (r, carry)
+}
+
+fn sub(a: &U256, b: &U256) -> (U256, bool) {
+    let mut r = ZERO;
+    let mut borrow = false;
+    for i in 0..4 {
+        let (d1, b1) = a[i].overflowing_sub(b[i]);
+        let (d2, b2) = d1.overflowing_sub(borrow as u64);
+        r[i] = d2;
+        borrow = b1 || b2;
+    }
+    (r, borrow)
+}
+
+// Arithmetic modulo an odd 256-bit modulus, with values kept in the Montgomery domain.
+struct Modulus {
+    m: U256,
+    m0_inv: u64,
+    r2: U256,
+}
+
+impl Modulus {
+    fn new(m: U256) -> Modulus {
+        // -m^-1 mod 2^64, by Newton's iteration.
+        let mut inv = 1_u64;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2_u64.wrapping_sub(m[0].wrapping_mul(inv)));
+        }
+
+        let mut modulus = Modulus {
+            m,
+            m0_inv: inv.wrapping_neg(),
+            r2: ZERO,
+        };
+        let mut r2 = ONE;
+        for _ in 0..512 {
+            r2 = modulus.add(&r2, &r2);
+        }
+        modulus.r2 = r2;
+        modulus
+    }
+
+    fn reduce(&self, a: &U256) -> U256 {
+        let mut r = *a;
+        while compare(&r, &self.m) != Ordering::Less {
+            r = sub(&r, &self.m).0;
+        }
+        r
+    }
+
+    fn add(&self, a: &U256, b: &U256) -> U256 {
+        let (r, carry) = add(a, b);
+        if carry || compare(&r, &self.m) != Ordering::Less {
+            sub(&r, &self.m).0
+        } else {
+            r
+        }
+    }
+
+    fn sub(&self, a: &U256, b: &U256) -> U256 {
+        let (r, borrow) = sub(a, b);
+        if borrow {
+            add(&r, &self.m).0
+        } else {
+            r
+        }
+    }
+
+    fn mul(&self, a: &U256, b: &U256) -> U256 {
+        let mut t = [0_u64; 6];
+        for &bi in b.iter() {
+            let mut carry = 0_u128;
+            for j in 0..4 {
+                let v = t[j] as u128 + (a[j] as u128) * (bi as u128) + carry;
+                t[j] = v as u64;
+                carry = v >> 64;
+            }
+            let v = t[4] as u128 + carry;
+            t[4] = v as u64;
+            t[5] = (v >> 64) as u64;
+
+            let k = t[0].wrapping_mul(self.m0_inv);
+            let mut carry = (t[0] as u128 + (k as u128) * (self.m[0] as u128)) >> 64;
+            for j in 1..4 {
+                let v = t[j] as u128 + (k as u128) * (self.m[j] as u128) + carry;
+                t[j - 1] = v as u64;
+                carry = v >> 64;
+            }
+            let v = t[4] as u128 + carry;
+            t[3] = v as u64;
+            t[4] = t[5] + (v >> 64) as u64;
+            t[5] = 0;
+        }
+
+        let r = [t[0], t[1], t[2], t[3]];
+        if t[4] != 0 || compare(&r, &self.m) != Ordering::Less {
+            sub(&r, &self.m).0
+        } else {
+            r
+        }
+    }
+
+    fn to_mont(&self, a: &U256) -> U256 {
+        self.mul(a, &self.r2)
+    }
+
+    fn to_canonical(&self, a: &U256) -> U256 {
+        self.mul(a, &ONE)
+    }
+
+    // a^(m-2), the inverse of a for a prime modulus.
+    fn inv(&self, a: &U256) -> U256 {
+        let e = sub(&self.m, &[2, 0, 0, 0]).0;
+        let mut r = self.to_mont(&ONE);
+        for i in (0..256).rev() {
+            r = self.mul(&r, &r);
+            if (e[i / 64] >> (i % 64)) & 1 == 1 {
+                r = self.mul(&r, a);
+            }
+        }
+        r
+    }
+}
+
+// A point in Jacobian coordinates, with the coordinates in the Montgomery domain. The point
+// at infinity has z = 0.
+#[derive(Clone, Copy)]
+struct Point {
+    x: U256,
+    y: U256,
+    z: U256,
+}
+
+struct Curve {
+    p: Modulus,
+}
+
+impl Curve {
+    fn infinity(&self) -> Point {
+        Point {
+            x: ZERO,
+            y: ZERO,
+            z: ZERO,
+        }
+    }
+
+    fn double(&self, pt: &Point) -> Point {
+        let p = &self.p;
+        if is_zero(&pt.z) {
+            return *pt;
+        }
+        let delta = p.mul(&pt.z, &pt.z);
+        let gamma = p.mul(&pt.y, &pt.y);
+        let beta = p.mul(&pt.x, &gamma);
+        let t = p.mul(&p.sub(&pt.x, &delta), &p.add(&pt.x, &delta));
+        let alpha = p.add(&p.add(&t, &t), &t);
+        let beta4 = p.add(&p.add(&beta, &beta), &p.add(&beta, &beta));
+        let x3 = p.sub(&p.mul(&alpha, &alpha), &p.add(&beta4, &beta4));
+        let yz = p.add(&pt.y, &pt.z);
+        let z3 = p.sub(&p.sub(&p.mul(&yz, &yz), &gamma), &delta);
+        let gamma2 = p.mul(&gamma, &gamma);
+        let gamma8 = p.add(
+            &p.add(&p.add(&gamma2, &gamma2), &p.add(&gamma2, &gamma2)),
+            &p.add(&p.add(&gamma2, &gamma2), &p.add(&gamma2, &gamma2)),
+        );
+        let y3 = p.sub(&p.mul(&alpha, &p.sub(&beta4, &x3)), &gamma8);
+        Point {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    fn add(&self, a: &Point, b: &Point) -> Point {
+        let p = &self.p;
+        if is_zero(&a.z) {
+            return *b;
+        }
+        if is_zero(&b.z) {
+            return *a;
+        }
+        let z1z1 = p.mul(&a.z, &a.z);
+        let z2z2 = p.mul(&b.z, &b.z);
+        let u1 = p.mul(&a.x, &z2z2);
+        let u2 = p.mul(&b.x, &z1z1);
+        let s1 = p.mul(&p.mul(&a.y, &b.z), &z2z2);
+        let s2 = p.mul(&p.mul(&b.y, &a.z), &z1z1);
+        let h = p.sub(&u2, &u1);
+        let s = p.sub(&s2, &s1);
+        if is_zero(&h) {
+            return if is_zero(&s) {
+                self.double(a)
+            } else {
+                self.infinity()
+            };
+        }
+        let h2 = p.add(&h, &h);
+        let i = p.mul(&h2, &h2);
+        let j = p.mul(&h, &i);
+        let r = p.add(&s, &s);
+        let v = p.mul(&u1, &i);
+        let x3 = p.sub(&p.sub(&p.mul(&r, &r), &j), &p.add(&v, &v));
+        let s1j = p.mul(&s1, &j);
+        let y3 = p.sub(&p.mul(&r, &p.sub(&v, &x3)), &p.add(&s1j, &s1j));
+        let z12 = p.add(&a.z, &b.z);
+        let z3 = p.mul(&p.sub(&p.sub(&p.mul(&z12, &z12), &z1z1), &z2z2), &h);
+        Point {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    // y^2 = x^3 - 3x + b, with x and y in the Montgomery domain.
+    fn is_on_curve(&self, x: &U256, y: &U256) -> bool {
+        let p = &self.p;
+        let x3 = p.mul(&p.mul(x, x), x);
+        let three_x = p.add(&p.add(x, x), x);
+        let rhs = p.add(&p.sub(&x3, &three_x), &p.to_mont(&B));
+        compare(&p.mul(y, y), &rhs) == Ordering::Equal
+    }
+}
+
+///
+/// Verifies an ECDSA P-256 signature of `message` hashed with SHA-256.
+///
+/// The public key is the big-endian x and y coordinates, and the signature the big-endian
+/// r and s values, as they are laid out in an SGX ECDSA quote.
+///
+pub fn ecdsa_p256_verify(message: &[u8], public_key: &[u8; 64], signature: &[u8; 64]) -> bool {
+    let n = Modulus::new(N);
+    let curve = Curve { p: Modulus::new(P) };
+
+    let r = from_be(&signature[..32]);
+    let s = from_be(&signature[32..]);
+    if is_zero(&r)
+        || is_zero(&s)
+        || compare(&r, &N) != Ordering::Less
+        || compare(&s, &N) != Ordering::Less
+    {
+        return false;
+    }
+
+    let qx = from_be(&public_key[..32]);
+    let qy = from_be(&public_key[32..]);
+    if compare(&qx, &P) != Ordering::Less || compare(&qy, &P) != Ordering::Less {
+        return false;
+    }
+    let q = Point {
+        x: curve.p.to_mont(&qx),
+        y: curve.p.to_mont(&qy),
+        z: curve.p.to_mont(&ONE),
+    };
+    if !curve.is_on_curve(&q.x, &q.y) {
+        return false;
+    }
+    let g = Point {
+        x: curve.p.to_mont(&GX),
+        y: curve.p.to_mont(&GY),
+        z: curve.p.to_mont(&ONE),
+    };
+
+    let e = n.reduce(&from_be(&sha256(message)));
+    let w = n.inv(&n.to_mont(&s));
+    let u1 = n.to_canonical(&n.mul(&n.to_mont(&e), &w));
+    let u2 = n.to_canonical(&n.mul(&n.to_mont(&r), &w));
+
+    let gq = curve.add(&g, &q);
+    let mut acc = curve.infinity();
+    for i in (0..256).rev() {
+        acc = curve.double(&acc);
+        let b1 = (u1[i / 64] >> (i % 64)) & 1 == 1;
+        let b2 = (u2[i / 64] >> (i % 64)) & 1 == 1;
+        acc = match (b1, b2) {
+            (true, true) => curve.add(&acc, &gq),
+            (true, false) => curve.add(&acc, &g),
+            (false, true) => curve.add(&acc, &q),
+            (false, false) => acc,
+        };
+    }
+    if is_zero(&acc.z) {
+        return false;
+    }
+
+    let p = &curve.p;
+    let z_inv = p.inv(&acc.z);
+    let x = p.to_canonical(&p.mul(&acc.x, &p.mul(&z_inv, &z_inv)));
+    compare(&n.reduce(&x), &r) == Ordering::Equal
+}
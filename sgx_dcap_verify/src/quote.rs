@@ -0,0 +1,253 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::der::pem_decode_all;
+use crate::x509::{Certificate, PckExtension};
+use alloc::vec::Vec;
+use core::mem;
+use core::ptr;
+use sgx_types::*;
+
+pub const SGX_QUOTE_HEADER_SIZE: usize = 48;
+pub const SGX_QUOTE_REPORT_BODY_SIZE: usize = 384;
+
+/// The vendor ID of the Intel Quoting Enclave.
+#[rustfmt::skip]
+pub const INTEL_QE_VENDOR_ID: [u8; 16] = [
+    0x93, 0x9a, 0x72, 0x33, 0xf7, 0x9c, 0x4c, 0xa9, 0x94, 0x0a, 0x0d, 0xb3, 0x95, 0x7f, 0x06, 0x07,
+];
+
+const ATT_KEY_TYPE_ECDSA_P256: u16 = 2;
+const TEE_TYPE_SGX: u32 = 0;
+const CERT_TYPE_PCK_CERT_CHAIN: u16 = 5;
+const CERT_TYPE_QE_REPORT: u16 = 6;
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> SgxQuote3Result<&'a [u8]> {
+        if len > self.data.len() {
+            return Err(sgx_quote3_error_t::SGX_QL_ERROR_INVALID_PARAMETER);
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> SgxQuote3Result<[u8; N]> {
+        let mut array = [0_u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u16(&mut self) -> SgxQuote3Result<u16> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> SgxQuote3Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn report_body(
+        &mut self,
+    ) -> SgxQuote3Result<([u8; SGX_QUOTE_REPORT_BODY_SIZE], sgx_report_body_t)> {
+        let raw: [u8; SGX_QUOTE_REPORT_BODY_SIZE] = self.array()?;
+        let body = unsafe { ptr::read_unaligned(raw.as_ptr() as *const sgx_report_body_t) };
+        Ok((raw, body))
+    }
+
+    fn certification_data(&mut self) -> SgxQuote3Result<(u16, &'a [u8])> {
+        let cert_type = self.u16()?;
+        let size = self.u32()? as usize;
+        Ok((cert_type, self.take(size)?))
+    }
+}
+
+///
+/// An SGX ECDSA quote, version 3 or 4, signed with a P-256 attestation key and certified by
+/// a PCK certificate chain.
+///
+#[derive(Clone)]
+pub struct Quote {
+    pub version: u16,
+    pub att_key_type: u16,
+    pub tee_type: u32,
+    /// The QE and PCE SVNs, in the version 3 header. They are reserved in version 4.
+    pub qe_svn: u16,
+    pub pce_svn: u16,
+    pub qe_vendor_id: [u8; 16],
+    pub user_data: [u8; 20],
+    /// The report of the attested enclave.
+    pub report_body: sgx_report_body_t,
+    /// The big-endian r and s values of the signature of the header and the report body.
+    pub signature: [u8; 64],
+    /// The big-endian x and y coordinates of the attestation key.
+    pub attestation_key: [u8; 64],
+    /// The report of the Quoting Enclave, whose report data binds the attestation key.
+    pub qe_report_body: sgx_report_body_t,
+    /// The signature of the QE report by the PCK.
+    pub qe_report_signature: [u8; 64],
+    pub qe_auth_data: Vec<u8>,
+    /// The PEM certificate chain of the PCK, from the PCK certificate to the root CA.
+    pub pck_cert_chain: Vec<u8>,
+    signed_data: Vec<u8>,
+    qe_report: [u8; SGX_QUOTE_REPORT_BODY_SIZE],
+}
+
+impl Quote {
+    ///
+    /// Parses an SGX ECDSA quote.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_QL_ERROR_INVALID_PARAMETER**
+    ///
+    /// The quote is truncated or its lengths are inconsistent.
+    ///
+    /// **SGX_QL_QUOTE_FORMAT_UNSUPPORTED**
+    ///
+    /// The quote is not an SGX quote of version 3 or 4 with an ECDSA P-256 attestation key,
+    /// or it was not generated by the Intel Quoting Enclave.
+    ///
+    /// **SGX_QL_QUOTE_CERTIFICATION_DATA_UNSUPPORTED**
+    ///
+    /// The attestation key is not certified by a PCK certificate chain.
+    ///
+    pub fn parse(quote: &[u8]) -> SgxQuote3Result<Quote> {
+        debug_assert_eq!(
+            mem::size_of::<sgx_report_body_t>(),
+            SGX_QUOTE_REPORT_BODY_SIZE
+        );
+
+        let mut reader = Reader { data: quote };
+        let version = reader.u16()?;
+        let att_key_type = reader.u16()?;
+        let tee_type = reader.u32()?;
+        let qe_svn = reader.u16()?;
+        let pce_svn = reader.u16()?;
+        let qe_vendor_id = reader.array()?;
+        let user_data = reader.array()?;
+        if (version != 3 && version != 4)
+            || att_key_type != ATT_KEY_TYPE_ECDSA_P256
+            || (version == 4 && tee_type != TEE_TYPE_SGX)
+            || qe_vendor_id != INTEL_QE_VENDOR_ID
+        {
+            return Err(sgx_quote3_error_t::SGX_QL_QUOTE_FORMAT_UNSUPPORTED);
+        }
+        let (_, report_body) = reader.report_body()?;
+        let signed_data = quote[..SGX_QUOTE_HEADER_SIZE + SGX_QUOTE_REPORT_BODY_SIZE].to_vec();
+
+        let signature_data_len = reader.u32()? as usize;
+        let mut signature_data = Reader {
+            data: reader.take(signature_data_len)?,
+        };
+        if !reader.data.is_empty() {
+            return Err(sgx_quote3_error_t::SGX_QL_ERROR_INVALID_PARAMETER);
+        }
+        let signature = signature_data.array()?;
+        let attestation_key = signature_data.array()?;
+
+        // Version 4 wraps the QE report and the PCK certificate chain in certification data.
+        let mut qe_data = if version == 4 {
+            let (cert_type, data) = signature_data.certification_data()?;
+            if cert_type != CERT_TYPE_QE_REPORT {
+                return Err(sgx_quote3_error_t::SGX_QL_QUOTE_CERTIFICATION_DATA_UNSUPPORTED);
+            }
+            if !signature_data.data.is_empty() {
+                return Err(sgx_quote3_error_t::SGX_QL_ERROR_INVALID_PARAMETER);
+            }
+            Reader { data }
+        } else {
+            signature_data
+        };
+
+        let (qe_report, qe_report_body) = qe_data.report_body()?;
+        let qe_report_signature = qe_data.array()?;
+        let qe_auth_data_len = qe_data.u16()? as usize;
+        let qe_auth_data = qe_data.take(qe_auth_data_len)?.to_vec();
+        let (cert_type, pck_cert_chain) = qe_data.certification_data()?;
+        if cert_type != CERT_TYPE_PCK_CERT_CHAIN {
+            return Err(sgx_quote3_error_t::SGX_QL_QUOTE_CERTIFICATION_DATA_UNSUPPORTED);
+        }
+        if !qe_data.data.is_empty() {
+            return Err(sgx_quote3_error_t::SGX_QL_ERROR_INVALID_PARAMETER);
+        }
+
+        Ok(Quote {
+            version,
+            att_key_type,
+            tee_type,
+            qe_svn,
+            pce_svn,
+            qe_vendor_id,
+            user_data,
+            report_body,
+            signature,
+            attestation_key,
+            qe_report_body,
+            qe_report_signature,
+            qe_auth_data,
+            pck_cert_chain: pck_cert_chain.to_vec(),
+            signed_data,
+            qe_report,
+        })
+    }
+
+    ///
+    /// Gets the platform data of the PCK certificate, without verifying the certificate.
+    ///
+    /// The FMSPC and the PCE ID select the TCB info of the platform when the collateral is
+    /// fetched.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_QL_PCK_CERT_CHAIN_ERROR**
+    ///
+    /// The PCK certificate chain is malformed.
+    ///
+    /// **SGX_QL_PCK_CERT_UNSUPPORTED_FORMAT**
+    ///
+    /// The PCK certificate has no valid SGX extension.
+    ///
+    pub fn pck_extension(&self) -> SgxQuote3Result<PckExtension> {
+        let pck_cert = pem_decode_all(&self.pck_cert_chain, "CERTIFICATE")
+            .and_then(|mut ders| {
+                if ders.is_empty() {
+                    None
+                } else {
+                    Some(ders.remove(0))
+                }
+            })
+            .ok_or(sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR)?;
+        Certificate::parse(&pck_cert)
+            .ok_or(sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR)?
+            .pck_extension()
+            .ok_or(sgx_quote3_error_t::SGX_QL_PCK_CERT_UNSUPPORTED_FORMAT)
+    }
+
+    /// The header and the report body, which the attestation key signs.
+    pub(crate) fn signed_data(&self) -> &[u8] {
+        &self.signed_data
+    }
+
+    /// The QE report body, which the PCK signs.
+    pub(crate) fn qe_report(&self) -> &[u8] {
+        &self.qe_report
+    }
+}
@@ -0,0 +1,639 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::collateral::*;
+use crate::der::{hex_decode, pem_decode_all};
+use crate::json::Json;
+use crate::p256::ecdsa_p256_verify;
+use crate::quote::Quote;
+use crate::sha256::sha256;
+use crate::x509::{Certificate, Crl, PckExtension};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str;
+use sgx_types::*;
+
+const PEM_CERTIFICATE_LABEL: &str = "CERTIFICATE";
+const PEM_CRL_LABEL: &str = "X509 CRL";
+
+///
+/// The result of a successful quote verification.
+///
+#[derive(Clone)]
+pub struct QuoteVerdict {
+    /// The TCB status of the platform, combined with the TCB status of the Quoting Enclave.
+    pub status: TcbStatus,
+    /// The Intel security advisories that apply to the platform or the Quoting Enclave.
+    pub advisory_ids: Vec<String>,
+    /// The date of the TCB level the platform matched.
+    pub tcb_date: String,
+    /// The earliest time at which a certificate, CRL, TCB info or QE identity used in the
+    /// verification expires.
+    pub collateral_expiration: i64,
+    /// The platform data of the PCK certificate.
+    pub pck: PckExtension,
+    /// The verified quote, which holds the report of the attested enclave.
+    pub quote: Quote,
+}
+
+///
+/// Verifies SGX ECDSA quotes against their collateral, without the Intel quote verification
+/// library.
+///
+/// The verifier checks the PCK certificate chain up to the trusted root CA and against the
+/// CRLs, the signature of the QE report by the PCK, the binding of the attestation key to
+/// the QE report, and the signature of the quote by the attestation key. It then checks the
+/// signed TCB info and QE identity, and evaluates the TCB level of the platform and of the
+/// Quoting Enclave.
+///
+pub struct QuoteVerifier {
+    root_ca: Vec<u8>,
+}
+
+struct VerifiedChain<'a> {
+    certs: Vec<Certificate<'a>>,
+    expiration: i64,
+}
+
+fn decode_certs(pem: &[u8], error: sgx_quote3_error_t) -> SgxQuote3Result<Vec<Vec<u8>>> {
+    match pem_decode_all(pem, PEM_CERTIFICATE_LABEL) {
+        Some(ders) if !ders.is_empty() => Ok(ders),
+        _ => Err(error),
+    }
+}
+
+fn decode_crl(crl: &[u8]) -> SgxQuote3Result<Vec<u8>> {
+    let error = sgx_quote3_error_t::SGX_QL_CRL_UNSUPPORTED_FORMAT;
+    if crl.starts_with(b"-----BEGIN") {
+        let mut ders = pem_decode_all(crl, PEM_CRL_LABEL).ok_or(error)?;
+        return if ders.len() == 1 {
+            Ok(ders.remove(0))
+        } else {
+            Err(error)
+        };
+    }
+    if crl.first() == Some(&0x30) {
+        return Ok(crl.to_vec());
+    }
+    str::from_utf8(crl)
+        .ok()
+        .and_then(|hex| hex_decode(hex.trim()))
+        .ok_or(error)
+}
+
+impl QuoteVerifier {
+    ///
+    /// Creates a verifier which trusts the given root CA certificate, in DER or PEM.
+    ///
+    /// For production quotes, this is the Intel SGX Provisioning Certification Root CA,
+    /// published at https://certificates.trustedservices.intel.com/.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_QL_PCK_CERT_CHAIN_ERROR**
+    ///
+    /// The certificate is malformed, or it is not a self-signed P-256 CA certificate.
+    ///
+    pub fn new(root_ca: &[u8]) -> SgxQuote3Result<QuoteVerifier> {
+        let error = sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR;
+        let der = if root_ca.starts_with(b"-----BEGIN") {
+            let mut ders = decode_certs(root_ca, error)?;
+            if ders.len() != 1 {
+                return Err(error);
+            }
+            ders.remove(0)
+        } else {
+            root_ca.to_vec()
+        };
+
+        let cert = Certificate::parse(&der).ok_or(error)?;
+        if !cert.is_issued_by(&cert) {
+            return Err(error);
+        }
+        Ok(QuoteVerifier { root_ca: der })
+    }
+
+    ///
+    /// Verifies a quote against its collateral.
+    ///
+    /// # Parameters
+    ///
+    /// **quote**
+    ///
+    /// The quote, as returned by sgx_qe_get_quote.
+    ///
+    /// **collateral**
+    ///
+    /// The collateral for the platform that generated the quote.
+    ///
+    /// **current_time**
+    ///
+    /// The current time, in seconds since the Unix epoch. The enclave has no trusted time,
+    /// so the caller decides which time the certificates and the collateral are checked at.
+    ///
+    /// # Return value
+    ///
+    /// The verdict. A quote whose PCK certificate is revoked is verified successfully with
+    /// the Revoked status. The caller decides which statuses it accepts, and must still
+    /// check the identity of the attested enclave in the report.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_QL_ERROR_INVALID_PARAMETER**, **SGX_QL_QUOTE_FORMAT_UNSUPPORTED**,
+    /// **SGX_QL_QUOTE_CERTIFICATION_DATA_UNSUPPORTED**
+    ///
+    /// The quote is malformed or unsupported, see Quote::parse.
+    ///
+    /// **SGX_QL_PCK_CERT_CHAIN_ERROR**, **SGX_QL_PCK_CERT_UNSUPPORTED_FORMAT**
+    ///
+    /// The PCK certificate chain or the PCK CRL issuer chain is malformed, does not end at
+    /// the trusted root CA, or its signatures are invalid.
+    ///
+    /// **SGX_QL_CRL_UNSUPPORTED_FORMAT**
+    ///
+    /// A CRL is malformed or not signed by its issuer.
+    ///
+    /// **SGX_QL_QE_REPORT_INVALID_SIGNATURE**
+    ///
+    /// The QE report is not signed by the PCK, or it does not bind the attestation key.
+    ///
+    /// **SGX_QL_INVALID_REPORT**
+    ///
+    /// The quote is not signed by the attestation key.
+    ///
+    /// **SGX_QL_TCBINFO_UNSUPPORTED_FORMAT**, **SGX_QL_TCBINFO_CHAIN_ERROR**,
+    /// **SGX_QL_TCBINFO_MISMATCH**
+    ///
+    /// The TCB info is malformed, is not signed by a TCB signing certificate issued by the
+    /// trusted root CA, is for another platform, or has no TCB level the platform matches.
+    ///
+    /// **SGX_QL_QEIDENTITY_UNSUPPORTED_FORMAT**, **SGX_QL_QEIDENTITY_CHAIN_ERROR**,
+    /// **SGX_QL_QEIDENTITY_MISMATCH**, **SGX_QL_SGX_ENCLAVE_REPORT_ISVSVN_OUT_OF_DATE**
+    ///
+    /// The QE identity is malformed or not signed, the Quoting Enclave does not match it, or
+    /// the ISVSVN of the Quoting Enclave is below all its TCB levels.
+    ///
+    /// **SGX_QL_SGX_PCK_CERT_CHAIN_EXPIRED**, **SGX_QL_SGX_SIGNING_CERT_CHAIN_EXPIRED**,
+    /// **SGX_QL_SGX_CRL_EXPIRED**, **SGX_QL_SGX_TCB_INFO_EXPIRED**,
+    /// **SGX_QL_SGX_ENCLAVE_IDENTITY_EXPIRED**
+    ///
+    /// A certificate or a piece of collateral is not valid at current_time.
+    ///
+    pub fn verify(
+        &self,
+        quote: &[u8],
+        collateral: &QuoteCollateral,
+        current_time: i64,
+    ) -> SgxQuote3Result<QuoteVerdict> {
+        let root = Certificate::parse(&self.root_ca)
+            .ok_or(sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR)?;
+
+        let root_crl_der = decode_crl(&collateral.root_ca_crl)?;
+        let root_crl = self.verify_crl(&root_crl_der, &root, current_time)?;
+
+        let quote = Quote::parse(quote)?;
+
+        // The PCK certificate chain, and the CRL of the CA that issued the PCK certificate.
+        let pck_chain_der = decode_certs(
+            &quote.pck_cert_chain,
+            sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR,
+        )?;
+        let pck_chain = self.verify_chain(
+            &pck_chain_der,
+            &root,
+            current_time,
+            sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR,
+            sgx_quote3_error_t::SGX_QL_SGX_PCK_CERT_CHAIN_EXPIRED,
+        )?;
+        let pck_cert = &pck_chain.certs[0];
+        let pck = pck_cert
+            .pck_extension()
+            .ok_or(sgx_quote3_error_t::SGX_QL_PCK_CERT_UNSUPPORTED_FORMAT)?;
+
+        let pck_crl_chain_der = decode_certs(
+            &collateral.pck_crl_issuer_chain,
+            sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR,
+        )?;
+        let pck_crl_chain = self.verify_chain(
+            &pck_crl_chain_der,
+            &root,
+            current_time,
+            sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR,
+            sgx_quote3_error_t::SGX_QL_SGX_PCK_CERT_CHAIN_EXPIRED,
+        )?;
+        let pck_crl_der = decode_crl(&collateral.pck_crl)?;
+        let pck_crl = self.verify_crl(&pck_crl_der, &pck_crl_chain.certs[0], current_time)?;
+        if pck_crl.issuer != pck_cert.issuer {
+            return Err(sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR);
+        }
+
+        let pck_revoked = pck_crl.is_revoked(pck_cert)
+            || pck_chain.certs[1..]
+                .iter()
+                .any(|cert| root_crl.is_revoked(cert));
+
+        // The QE report is signed by the PCK and binds the attestation key.
+        if !ecdsa_p256_verify(
+            quote.qe_report(),
+            &pck_cert.public_key,
+            &quote.qe_report_signature,
+        ) {
+            return Err(sgx_quote3_error_t::SGX_QL_QE_REPORT_INVALID_SIGNATURE);
+        }
+        let mut binding = quote.attestation_key.to_vec();
+        binding.extend_from_slice(&quote.qe_auth_data);
+        let report_data = &quote.qe_report_body.report_data.d;
+        if report_data[..32] != sha256(&binding) || report_data[32..].iter().any(|&b| b != 0) {
+            return Err(sgx_quote3_error_t::SGX_QL_QE_REPORT_INVALID_SIGNATURE);
+        }
+        if !ecdsa_p256_verify(
+            quote.signed_data(),
+            &quote.attestation_key,
+            &quote.signature,
+        ) {
+            return Err(sgx_quote3_error_t::SGX_QL_INVALID_REPORT);
+        }
+
+        // The TCB info of the platform.
+        let tcb_info_text = str::from_utf8(&collateral.tcb_info)
+            .map_err(|_| sgx_quote3_error_t::SGX_QL_TCBINFO_UNSUPPORTED_FORMAT)?;
+        let tcb_info_json = Json::parse(tcb_info_text.trim_end_matches('\0'))
+            .ok_or(sgx_quote3_error_t::SGX_QL_TCBINFO_UNSUPPORTED_FORMAT)?;
+        let signed = parse_signed(&tcb_info_json, "tcbInfo")
+            .ok_or(sgx_quote3_error_t::SGX_QL_TCBINFO_UNSUPPORTED_FORMAT)?;
+        let tcb_signing_expiration = self.verify_signed(
+            &signed,
+            &collateral.tcb_info_issuer_chain,
+            &root,
+            &root_crl,
+            current_time,
+            sgx_quote3_error_t::SGX_QL_TCBINFO_CHAIN_ERROR,
+        )?;
+        let tcb_info = TcbInfo::parse(signed.body)
+            .ok_or(sgx_quote3_error_t::SGX_QL_TCBINFO_UNSUPPORTED_FORMAT)?;
+        if current_time > tcb_info.next_update {
+            return Err(sgx_quote3_error_t::SGX_QL_SGX_TCB_INFO_EXPIRED);
+        }
+        if tcb_info.fmspc != pck.fmspc || tcb_info.pce_id != pck.pce_id {
+            return Err(sgx_quote3_error_t::SGX_QL_TCBINFO_MISMATCH);
+        }
+        let tcb_level = tcb_info
+            .levels
+            .iter()
+            .find(|level| {
+                level.pce_svn <= pck.pce_svn
+                    && level
+                        .sgx_tcb_components
+                        .iter()
+                        .zip(pck.sgx_tcb_components.iter())
+                        .all(|(required, platform)| required <= platform)
+            })
+            .ok_or(sgx_quote3_error_t::SGX_QL_TCBINFO_MISMATCH)?;
+
+        // The identity and the TCB level of the Quoting Enclave.
+        let qe_identity_text = str::from_utf8(&collateral.qe_identity)
+            .map_err(|_| sgx_quote3_error_t::SGX_QL_QEIDENTITY_UNSUPPORTED_FORMAT)?;
+        let qe_identity_json = Json::parse(qe_identity_text.trim_end_matches('\0'))
+            .ok_or(sgx_quote3_error_t::SGX_QL_QEIDENTITY_UNSUPPORTED_FORMAT)?;
+        let signed = parse_signed(&qe_identity_json, "enclaveIdentity")
+            .ok_or(sgx_quote3_error_t::SGX_QL_QEIDENTITY_UNSUPPORTED_FORMAT)?;
+        let qe_signing_expiration = self.verify_signed(
+            &signed,
+            &collateral.qe_identity_issuer_chain,
+            &root,
+            &root_crl,
+            current_time,
+            sgx_quote3_error_t::SGX_QL_QEIDENTITY_CHAIN_ERROR,
+        )?;
+        let qe_identity = QeIdentity::parse(signed.body)
+            .ok_or(sgx_quote3_error_t::SGX_QL_QEIDENTITY_UNSUPPORTED_FORMAT)?;
+        if current_time > qe_identity.next_update {
+            return Err(sgx_quote3_error_t::SGX_QL_SGX_ENCLAVE_IDENTITY_EXPIRED);
+        }
+        let qe_level = verify_qe_identity(&quote.qe_report_body, &qe_identity)?;
+
+        let mut advisory_ids = tcb_level.advisory_ids.clone();
+        for id in qe_level.advisory_ids.iter() {
+            if !advisory_ids.contains(id) {
+                advisory_ids.push(id.clone());
+            }
+        }
+        let status = if pck_revoked {
+            TcbStatus::Revoked
+        } else {
+            converge_tcb_status(tcb_level.status, qe_level.status)
+        };
+        let collateral_expiration = [
+            pck_chain.expiration,
+            pck_crl_chain.expiration,
+            root_crl.next_update.unwrap_or(i64::MAX),
+            pck_crl.next_update.unwrap_or(i64::MAX),
+            tcb_signing_expiration,
+            tcb_info.next_update,
+            qe_signing_expiration,
+            qe_identity.next_update,
+        ]
+        .iter()
+        .copied()
+        .min()
+        .unwrap_or(i64::MAX);
+
+        Ok(QuoteVerdict {
+            status,
+            advisory_ids,
+            tcb_date: tcb_level.tcb_date.clone(),
+            collateral_expiration,
+            pck,
+            quote,
+        })
+    }
+
+    // Checks that the chain is ordered from the leaf to the trusted root CA, and that every
+    // certificate is issued by the next one and valid at current_time.
+    fn verify_chain<'a>(
+        &self,
+        ders: &'a [Vec<u8>],
+        root: &Certificate,
+        current_time: i64,
+        error: sgx_quote3_error_t,
+        expired: sgx_quote3_error_t,
+    ) -> SgxQuote3Result<VerifiedChain<'a>> {
+        let certs = ders
+            .iter()
+            .map(|der| Certificate::parse(der))
+            .collect::<Option<Vec<Certificate>>>()
+            .ok_or(error)?;
+        if certs.len() < 2 || certs[certs.len() - 1].raw != root.raw {
+            return Err(error);
+        }
+        for pair in certs.windows(2) {
+            if !pair[0].is_issued_by(&pair[1]) {
+                return Err(error);
+            }
+        }
+        if certs.iter().any(|cert| !cert.is_valid_at(current_time)) {
+            return Err(expired);
+        }
+        let expiration = certs
+            .iter()
+            .map(|cert| cert.not_after)
+            .min()
+            .unwrap_or(i64::MAX);
+        Ok(VerifiedChain { certs, expiration })
+    }
+
+    fn verify_crl<'a>(
+        &self,
+        der: &'a [u8],
+        issuer: &Certificate,
+        current_time: i64,
+    ) -> SgxQuote3Result<Crl<'a>> {
+        let crl = Crl::parse(der).ok_or(sgx_quote3_error_t::SGX_QL_CRL_UNSUPPORTED_FORMAT)?;
+        if !crl.is_issued_by(issuer) {
+            return Err(sgx_quote3_error_t::SGX_QL_CRL_UNSUPPORTED_FORMAT);
+        }
+        if !crl.is_valid_at(current_time) {
+            return Err(sgx_quote3_error_t::SGX_QL_SGX_CRL_EXPIRED);
+        }
+        Ok(crl)
+    }
+
+    // Checks the signature of a TCB info or QE identity, and the chain of its signing
+    // certificate. Returns when the chain expires.
+    fn verify_signed(
+        &self,
+        signed: &Signed,
+        issuer_chain: &[u8],
+        root: &Certificate,
+        root_crl: &Crl,
+        current_time: i64,
+        error: sgx_quote3_error_t,
+    ) -> SgxQuote3Result<i64> {
+        let ders = decode_certs(issuer_chain, error)?;
+        let chain = self.verify_chain(
+            &ders,
+            root,
+            current_time,
+            error,
+            sgx_quote3_error_t::SGX_QL_SGX_SIGNING_CERT_CHAIN_EXPIRED,
+        )?;
+        if chain.certs[..chain.certs.len() - 1]
+            .iter()
+            .any(|cert| root_crl.is_revoked(cert))
+        {
+            return Err(error);
+        }
+        if !ecdsa_p256_verify(
+            signed.body.raw.as_bytes(),
+            &chain.certs[0].public_key,
+            &signed.signature,
+        ) {
+            return Err(error);
+        }
+        Ok(chain.expiration)
+    }
+}
+
+fn verify_qe_identity<'a>(
+    report: &sgx_report_body_t,
+    identity: &'a QeIdentity,
+) -> SgxQuote3Result<&'a QeTcbLevel> {
+    let mut attributes = [0_u8; 16];
+    attributes[..8].copy_from_slice(&report.attributes.flags.to_le_bytes());
+    attributes[8..].copy_from_slice(&report.attributes.xfrm.to_le_bytes());
+    let attributes_match = attributes
+        .iter()
+        .zip(identity.attributes.iter())
+        .zip(identity.attributes_mask.iter())
+        .all(|((report, expected), mask)| report & mask == expected & mask);
+
+    if report.misc_select & identity.misc_select_mask
+        != identity.misc_select & identity.misc_select_mask
+        || !attributes_match
+        || report.mr_signer.m != identity.mr_signer
+        || report.isv_prod_id != identity.isv_prod_id
+    {
+        return Err(sgx_quote3_error_t::SGX_QL_QEIDENTITY_MISMATCH);
+    }
+
+    identity
+        .levels
+        .iter()
+        .find(|level| level.isv_svn <= report.isv_svn)
+        .ok_or(sgx_quote3_error_t::SGX_QL_SGX_ENCLAVE_REPORT_ISVSVN_OUT_OF_DATE)
+}
+
+// Combines the status of the platform TCB with the status of the Quoting Enclave, the way
+// the Intel quote verification library does.
+fn converge_tcb_status(platform: TcbStatus, qe: TcbStatus) -> TcbStatus {
+    match qe {
+        TcbStatus::Revoked => TcbStatus::Revoked,
+        TcbStatus::OutOfDate | TcbStatus::OutOfDateConfigurationNeeded => match platform {
+            TcbStatus::UpToDate | TcbStatus::SWHardeningNeeded => TcbStatus::OutOfDate,
+            TcbStatus::ConfigurationNeeded | TcbStatus::ConfigurationAndSWHardeningNeeded => {
+                TcbStatus::OutOfDateConfigurationNeeded
+            }
+            status => status,
+        },
+        _ => platform,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quote::{SGX_QUOTE_HEADER_SIZE, SGX_QUOTE_REPORT_BODY_SIZE};
+
+    // 2026-01-01T00:00:00Z, when the test PKI and collateral are valid.
+    const NOW: i64 = 1_767_225_600;
+    // 2031-01-01T00:00:00Z, after the TCB info expires.
+    const LATER: i64 = 1_924_992_000;
+
+    const ROOT_CA: &[u8] = include_bytes!("../testdata/root_ca.pem");
+    const QUOTE_V3: &[u8] = include_bytes!("../testdata/quote_v3.bin");
+    const QUOTE_V4: &[u8] = include_bytes!("../testdata/quote_v4.bin");
+    const QUOTE_V3_SW_HARDENING: &[u8] = include_bytes!("../testdata/quote_v3_sw_hardening.bin");
+
+    fn collateral() -> QuoteCollateral {
+        let signing_chain = include_bytes!("../testdata/tcb_signing_chain.pem").to_vec();
+        QuoteCollateral {
+            pck_crl_issuer_chain: include_bytes!("../testdata/pck_crl_issuer_chain.pem").to_vec(),
+            root_ca_crl: include_bytes!("../testdata/root_ca_crl.der").to_vec(),
+            pck_crl: include_bytes!("../testdata/pck_crl.pem").to_vec(),
+            tcb_info_issuer_chain: signing_chain.clone(),
+            tcb_info: include_bytes!("../testdata/tcb_info.json").to_vec(),
+            qe_identity_issuer_chain: signing_chain,
+            qe_identity: include_bytes!("../testdata/qe_identity.json").to_vec(),
+        }
+    }
+
+    fn verifier() -> QuoteVerifier {
+        QuoteVerifier::new(ROOT_CA).unwrap()
+    }
+
+    #[test]
+    fn verify_up_to_date_quote() {
+        for quote in [QUOTE_V3, QUOTE_V4] {
+            let verdict = verifier().verify(quote, &collateral(), NOW).unwrap();
+            assert_eq!(verdict.status, TcbStatus::UpToDate);
+            assert!(verdict.advisory_ids.is_empty());
+            assert_eq!(verdict.tcb_date, "2023-08-09T00:00:00Z");
+            assert_eq!(verdict.pck.sgx_tcb_components, [3; 16]);
+            assert_eq!(verdict.pck.pce_svn, 11);
+            assert_eq!(verdict.quote.report_body.mr_enclave.m, [0xee; 32]);
+            assert_eq!(verdict.quote.report_body.isv_prod_id, 7);
+            assert_eq!(
+                &verdict.quote.report_body.report_data.d[..16],
+                b"user report data"
+            );
+            // The TCB info expires first, on 2030-01-01T00:00:00Z.
+            assert_eq!(verdict.collateral_expiration, 1_893_456_000);
+            assert_eq!(verdict.quote.pck_extension().unwrap(), verdict.pck);
+        }
+    }
+
+    #[test]
+    fn verify_tcb_status() {
+        let verdict = verifier()
+            .verify(QUOTE_V3_SW_HARDENING, &collateral(), NOW)
+            .unwrap();
+        assert_eq!(verdict.status, TcbStatus::SWHardeningNeeded);
+        assert_eq!(verdict.advisory_ids, ["INTEL-SA-00657"]);
+
+        let mut collateral = collateral();
+        collateral.qe_identity =
+            include_bytes!("../testdata/qe_identity_out_of_date.json").to_vec();
+        let verdict = verifier()
+            .verify(QUOTE_V3_SW_HARDENING, &collateral, NOW)
+            .unwrap();
+        assert_eq!(verdict.status, TcbStatus::OutOfDate);
+        assert_eq!(verdict.advisory_ids, ["INTEL-SA-00657", "INTEL-SA-00615"]);
+    }
+
+    #[test]
+    fn verify_revoked_pck() {
+        let mut collateral = collateral();
+        collateral.pck_crl = include_bytes!("../testdata/pck_crl_revoked.pem").to_vec();
+        let verdict = verifier().verify(QUOTE_V3, &collateral, NOW).unwrap();
+        assert_eq!(verdict.status, TcbStatus::Revoked);
+        assert_eq!(
+            sgx_ql_qv_result_t::from(verdict.status),
+            sgx_ql_qv_result_t::SGX_QL_QV_RESULT_REVOKED
+        );
+
+        // The other PCK certificate of the platform is not revoked.
+        let verdict = verifier()
+            .verify(QUOTE_V3_SW_HARDENING, &collateral, NOW)
+            .unwrap();
+        assert_eq!(verdict.status, TcbStatus::SWHardeningNeeded);
+    }
+
+    #[test]
+    fn verify_expired_collateral() {
+        assert_eq!(
+            verifier().verify(QUOTE_V3, &collateral(), LATER).err(),
+            Some(sgx_quote3_error_t::SGX_QL_SGX_TCB_INFO_EXPIRED)
+        );
+    }
+
+    #[test]
+    fn verify_tampered_quote() {
+        // A byte of the enclave report.
+        let mut quote = QUOTE_V3.to_vec();
+        quote[SGX_QUOTE_HEADER_SIZE + 112] ^= 1;
+        assert_eq!(
+            verifier().verify(&quote, &collateral(), NOW).err(),
+            Some(sgx_quote3_error_t::SGX_QL_INVALID_REPORT)
+        );
+
+        // A byte of the QE report.
+        let mut quote = QUOTE_V3.to_vec();
+        quote[SGX_QUOTE_HEADER_SIZE + SGX_QUOTE_REPORT_BODY_SIZE + 4 + 128 + 64] ^= 1;
+        assert_eq!(
+            verifier().verify(&quote, &collateral(), NOW).err(),
+            Some(sgx_quote3_error_t::SGX_QL_QE_REPORT_INVALID_SIGNATURE)
+        );
+
+        let quote = &QUOTE_V3[..QUOTE_V3.len() - 1];
+        assert_eq!(
+            verifier().verify(quote, &collateral(), NOW).err(),
+            Some(sgx_quote3_error_t::SGX_QL_ERROR_INVALID_PARAMETER)
+        );
+    }
+
+    #[test]
+    fn verify_tampered_collateral() {
+        let mut collateral = collateral();
+        let tcb_info = core::str::from_utf8(&collateral.tcb_info).unwrap();
+        collateral.tcb_info = tcb_info
+            .replacen("SWHardeningNeeded", "UpToDate", 1)
+            .into_bytes();
+        assert_eq!(
+            verifier().verify(QUOTE_V3, &collateral, NOW).err(),
+            Some(sgx_quote3_error_t::SGX_QL_TCBINFO_CHAIN_ERROR)
+        );
+    }
+
+    #[test]
+    fn verify_untrusted_root() {
+        let verifier = QuoteVerifier::new(include_bytes!("../testdata/pck_crl_issuer_chain.pem"));
+        assert_eq!(
+            verifier.err(),
+            Some(sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR)
+        );
+    }
+}
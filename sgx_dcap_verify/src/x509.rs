@@ -0,0 +1,341 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! X.509 certificates and CRLs, restricted to the ECDSA P-256 profile of the Intel SGX PKI.
+
+use crate::der::*;
+use crate::p256::ecdsa_p256_verify;
+use alloc::vec::Vec;
+
+// 1.2.840.10045.4.3.2
+const OID_ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+// 1.2.840.10045.2.1
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+// 1.2.840.10045.3.1.7
+const OID_PRIME256V1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+// 2.5.29.19
+const OID_BASIC_CONSTRAINTS: &[u8] = &[0x55, 0x1d, 0x13];
+// 1.2.840.113741.1.13.1
+const OID_SGX_EXTENSION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x01, 0x0d, 0x01];
+
+const SGX_EXTENSION_PPID: u8 = 1;
+const SGX_EXTENSION_TCB: u8 = 2;
+const SGX_EXTENSION_PCE_ID: u8 = 3;
+const SGX_EXTENSION_FMSPC: u8 = 4;
+const SGX_TCB_PCE_SVN: u8 = 17;
+const SGX_TCB_CPU_SVN: u8 = 18;
+
+///
+/// The SGX extension of a PCK certificate: the platform it was issued to, and the TCB of
+/// that platform.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PckExtension {
+    pub ppid: [u8; 16],
+    pub fmspc: [u8; 6],
+    pub pce_id: [u8; 2],
+    pub cpu_svn: [u8; 16],
+    pub sgx_tcb_components: [u8; 16],
+    pub pce_svn: u16,
+}
+
+pub struct Certificate<'a> {
+    pub raw: &'a [u8],
+    pub tbs: &'a [u8],
+    pub serial: &'a [u8],
+    pub issuer: &'a [u8],
+    pub subject: &'a [u8],
+    pub not_before: i64,
+    pub not_after: i64,
+    pub public_key: [u8; 64],
+    pub signature: [u8; 64],
+    pub is_ca: bool,
+    pub sgx_extension: Option<&'a [u8]>,
+}
+
+pub struct Crl<'a> {
+    pub tbs: &'a [u8],
+    pub issuer: &'a [u8],
+    pub this_update: i64,
+    pub next_update: Option<i64>,
+    pub revoked: Vec<&'a [u8]>,
+    pub signature: [u8; 64],
+}
+
+fn read_signature_algorithm(reader: &mut DerReader) -> Option<()> {
+    let mut algorithm = reader.read_sequence()?;
+    if algorithm.read(TAG_OID)?.value != OID_ECDSA_WITH_SHA256 {
+        return None;
+    }
+    Some(())
+}
+
+// Converts an ECDSA-Sig-Value to the big-endian r and s values.
+fn read_signature_value(reader: &mut DerReader) -> Option<[u8; 64]> {
+    let bits = reader.read_bit_string()?;
+    let mut outer = DerReader::new(bits);
+    let mut values = outer.read_sequence()?;
+    if !outer.is_empty() {
+        return None;
+    }
+    let mut signature = [0_u8; 64];
+    for half in signature.chunks_mut(32) {
+        let value = values.read_unsigned()?;
+        if value.len() > 32 {
+            return None;
+        }
+        half[32 - value.len()..].copy_from_slice(value);
+    }
+    if !values.is_empty() {
+        return None;
+    }
+    Some(signature)
+}
+
+fn read_public_key(reader: &mut DerReader) -> Option<[u8; 64]> {
+    let mut spki = reader.read_sequence()?;
+    let mut algorithm = spki.read_sequence()?;
+    if algorithm.read(TAG_OID)?.value != OID_EC_PUBLIC_KEY
+        || algorithm.read(TAG_OID)?.value != OID_PRIME256V1
+    {
+        return None;
+    }
+    let point = spki.read_bit_string()?;
+    if point.len() != 65 || point[0] != 0x04 {
+        return None;
+    }
+    let mut public_key = [0_u8; 64];
+    public_key.copy_from_slice(&point[1..]);
+    Some(public_key)
+}
+
+impl<'a> Certificate<'a> {
+    pub fn parse(der: &'a [u8]) -> Option<Certificate<'a>> {
+        let mut outer = DerReader::new(der);
+        let raw = outer.read(TAG_SEQUENCE)?;
+        if !outer.is_empty() {
+            return None;
+        }
+        let mut cert = DerReader::new(raw.value);
+        let tbs = cert.read(TAG_SEQUENCE)?;
+        read_signature_algorithm(&mut cert)?;
+        let signature = read_signature_value(&mut cert)?;
+
+        let mut reader = DerReader::new(tbs.value);
+        let version = reader.read(0xa0)?;
+        if version.value != [TAG_INTEGER, 0x01, 0x02] {
+            return None;
+        }
+        let serial = reader.read(TAG_INTEGER)?.value;
+        read_signature_algorithm(&mut reader)?;
+        let issuer = reader.read(TAG_SEQUENCE)?.raw;
+        let mut validity = reader.read_sequence()?;
+        let not_before = validity.read_time()?;
+        let not_after = validity.read_time()?;
+        let subject = reader.read(TAG_SEQUENCE)?.raw;
+        let public_key = read_public_key(&mut reader)?;
+        reader.read_optional(0x81);
+        reader.read_optional(0x82);
+
+        let mut is_ca = false;
+        let mut sgx_extension = None;
+        if let Some(extensions) = reader.read_optional(0xa3) {
+            let mut outer = DerReader::new(extensions.value);
+            let mut extensions = outer.read_sequence()?;
+            while !extensions.is_empty() {
+                let mut extension = extensions.read_sequence()?;
+                let oid = extension.read(TAG_OID)?.value;
+                extension.read_optional(TAG_BOOLEAN);
+                let value = extension.read(TAG_OCTET_STRING)?.value;
+                if oid == OID_BASIC_CONSTRAINTS {
+                    let mut constraints = DerReader::new(value).read_sequence()?;
+                    if let Some(ca) = constraints.read_optional(TAG_BOOLEAN) {
+                        is_ca = ca.value == [0xff];
+                    }
+                } else if oid == OID_SGX_EXTENSION {
+                    sgx_extension = Some(value);
+                }
+            }
+        }
+        if !reader.is_empty() {
+            return None;
+        }
+
+        Some(Certificate {
+            raw: raw.raw,
+            tbs: tbs.raw,
+            serial,
+            issuer,
+            subject,
+            not_before,
+            not_after,
+            public_key,
+            signature,
+            is_ca,
+            sgx_extension,
+        })
+    }
+
+    pub fn is_valid_at(&self, time: i64) -> bool {
+        self.not_before <= time && time <= self.not_after
+    }
+
+    /// Checks that this certificate names `issuer` as its issuer and is signed by it.
+    pub fn is_issued_by(&self, issuer: &Certificate) -> bool {
+        self.issuer == issuer.subject
+            && issuer.is_ca
+            && ecdsa_p256_verify(self.tbs, &issuer.public_key, &self.signature)
+    }
+
+    pub fn pck_extension(&self) -> Option<PckExtension> {
+        let mut outer = DerReader::new(self.sgx_extension?);
+        let mut entries = outer.read_sequence()?;
+        let mut extension = PckExtension::default();
+        let mut found = 0_u8;
+        while !entries.is_empty() {
+            let mut entry = entries.read_sequence()?;
+            let oid = entry.read(TAG_OID)?.value;
+            if oid.len() != OID_SGX_EXTENSION.len() + 1 || !oid.starts_with(OID_SGX_EXTENSION) {
+                continue;
+            }
+            match oid[OID_SGX_EXTENSION.len()] {
+                SGX_EXTENSION_PPID => {
+                    copy_exact(&mut extension.ppid, entry.read(TAG_OCTET_STRING)?.value)?
+                }
+                SGX_EXTENSION_TCB => read_tcb(&mut extension, entry.read_sequence()?)?,
+                SGX_EXTENSION_PCE_ID => {
+                    copy_exact(&mut extension.pce_id, entry.read(TAG_OCTET_STRING)?.value)?
+                }
+                SGX_EXTENSION_FMSPC => {
+                    copy_exact(&mut extension.fmspc, entry.read(TAG_OCTET_STRING)?.value)?
+                }
+                _ => continue,
+            }
+            found |= 1 << oid[OID_SGX_EXTENSION.len()];
+        }
+        let required = (1 << SGX_EXTENSION_PPID)
+            | (1 << SGX_EXTENSION_TCB)
+            | (1 << SGX_EXTENSION_PCE_ID)
+            | (1 << SGX_EXTENSION_FMSPC);
+        if found & required != required {
+            return None;
+        }
+        Some(extension)
+    }
+}
+
+fn copy_exact(dst: &mut [u8], src: &[u8]) -> Option<()> {
+    if dst.len() != src.len() {
+        return None;
+    }
+    dst.copy_from_slice(src);
+    Some(())
+}
+
+fn read_tcb(extension: &mut PckExtension, mut tcb: DerReader) -> Option<()> {
+    let mut found = 0_u32;
+    while !tcb.is_empty() {
+        let mut entry = tcb.read_sequence()?;
+        let oid = entry.read(TAG_OID)?.value;
+        let prefix_len = OID_SGX_EXTENSION.len() + 1;
+        if oid.len() != prefix_len + 1
+            || !oid.starts_with(OID_SGX_EXTENSION)
+            || oid[prefix_len - 1] != SGX_EXTENSION_TCB
+        {
+            continue;
+        }
+        let index = oid[prefix_len];
+        match index {
+            1..=16 => {
+                let svn = entry.read_small_unsigned()?;
+                extension.sgx_tcb_components[index as usize - 1] = u8::try_from(svn).ok()?;
+            }
+            SGX_TCB_PCE_SVN => {
+                extension.pce_svn = u16::try_from(entry.read_small_unsigned()?).ok()?;
+            }
+            SGX_TCB_CPU_SVN => {
+                copy_exact(&mut extension.cpu_svn, entry.read(TAG_OCTET_STRING)?.value)?
+            }
+            _ => continue,
+        }
+        found |= 1 << index;
+    }
+    if found != 0x7_fffe {
+        return None;
+    }
+    Some(())
+}
+
+impl<'a> Crl<'a> {
+    pub fn parse(der: &'a [u8]) -> Option<Crl<'a>> {
+        let mut outer = DerReader::new(der);
+        let mut crl = outer.read_sequence()?;
+        if !outer.is_empty() {
+            return None;
+        }
+        let tbs = crl.read(TAG_SEQUENCE)?;
+        read_signature_algorithm(&mut crl)?;
+        let signature = read_signature_value(&mut crl)?;
+
+        let mut reader = DerReader::new(tbs.value);
+        if reader.peek_tag() == Some(TAG_INTEGER) {
+            reader.read(TAG_INTEGER)?;
+        }
+        read_signature_algorithm(&mut reader)?;
+        let issuer = reader.read(TAG_SEQUENCE)?.raw;
+        let this_update = reader.read_time()?;
+        let next_update = match reader.peek_tag() {
+            Some(TAG_UTC_TIME) | Some(TAG_GENERALIZED_TIME) => Some(reader.read_time()?),
+            _ => None,
+        };
+        let mut revoked = Vec::new();
+        if reader.peek_tag() == Some(TAG_SEQUENCE) {
+            let mut entries = reader.read_sequence()?;
+            while !entries.is_empty() {
+                let mut entry = entries.read_sequence()?;
+                revoked.push(entry.read(TAG_INTEGER)?.value);
+            }
+        }
+        reader.read_optional(0xa0);
+        if !reader.is_empty() {
+            return None;
+        }
+
+        Some(Crl {
+            tbs: tbs.raw,
+            issuer,
+            this_update,
+            next_update,
+            revoked,
+            signature,
+        })
+    }
+
+    /// Checks that this CRL names `issuer` as its issuer and is signed by it.
+    pub fn is_issued_by(&self, issuer: &Certificate) -> bool {
+        self.issuer == issuer.subject
+            && ecdsa_p256_verify(self.tbs, &issuer.public_key, &self.signature)
+    }
+
+    pub fn is_valid_at(&self, time: i64) -> bool {
+        self.this_update <= time && self.next_update.iter().all(|&next| time <= next)
+    }
+
+    pub fn is_revoked(&self, cert: &Certificate) -> bool {
+        self.revoked.contains(&cert.serial)
+    }
+}
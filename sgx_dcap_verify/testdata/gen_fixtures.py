@@ -0,0 +1,279 @@
+#!/usr/bin/env python3
+# Licensed to the Apache Software Foundation (ASF) under one
+# or more contributor license agreements.  See the NOTICE file
+# distributed with this work for additional information
+# regarding copyright ownership.  The ASF licenses this file
+# to you under the Apache License, Version 2.0 (the
+# "License"); you may not use this file except in compliance
+# with the License.  You may obtain a copy of the License at
+#
+#   http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing,
+# software distributed under the License is distributed on an
+# "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+# KIND, either express or implied.  See the License for the
+# specific language governing permissions and limitations
+# under the License..
+
+# Generates a test PKI, collateral and quotes for the sgx_dcap_verify unit tests.
+# The fixtures mimic the Intel SGX PKI, but every key is generated here.
+
+import datetime
+import hashlib
+import json
+import os
+import struct
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec
+from cryptography.hazmat.primitives.asymmetric.utils import decode_dss_signature
+from cryptography.x509.oid import NameOID, ObjectIdentifier
+
+OUT = os.path.dirname(os.path.abspath(__file__))
+NOT_BEFORE = datetime.datetime(2024, 1, 1, tzinfo=datetime.timezone.utc)
+NOT_AFTER = datetime.datetime(2034, 1, 1, tzinfo=datetime.timezone.utc)
+CRL_NEXT_UPDATE = datetime.datetime(2032, 1, 1, tzinfo=datetime.timezone.utc)
+TCB_INFO_NEXT_UPDATE = "2030-01-01T00:00:00Z"
+QE_IDENTITY_NEXT_UPDATE = "2030-06-01T00:00:00Z"
+
+INTEL_QE_VENDOR_ID = bytes.fromhex("939a7233f79c4ca9940a0db3957f0607")
+FMSPC = bytes.fromhex("00906ed50000")
+PCE_ID = bytes.fromhex("0000")
+QE_MRSIGNER = bytes.fromhex("8c4f5775d796503e96137f77c68a829a0056ac8ded70140b081b094490c57bff")
+SGX_OID = "1.2.840.113741.1.13.1"
+
+
+def name(cn):
+    return x509.Name([
+        x509.NameAttribute(NameOID.COMMON_NAME, cn),
+        x509.NameAttribute(NameOID.ORGANIZATION_NAME, "Test SGX PKI"),
+    ])
+
+
+def cert(subject, key, issuer, issuer_key, ca, serial, extensions=()):
+    builder = (
+        x509.CertificateBuilder()
+        .subject_name(name(subject))
+        .issuer_name(name(issuer))
+        .public_key(key.public_key())
+        .serial_number(serial)
+        .not_valid_before(NOT_BEFORE)
+        .not_valid_after(NOT_AFTER)
+        .add_extension(x509.BasicConstraints(ca=ca, path_length=None), critical=True)
+    )
+    for extension in extensions:
+        builder = builder.add_extension(extension, critical=False)
+    return builder.sign(issuer_key, hashes.SHA256())
+
+
+def crl(issuer, issuer_key, revoked=()):
+    builder = (
+        x509.CertificateRevocationListBuilder()
+        .issuer_name(name(issuer))
+        .last_update(NOT_BEFORE)
+        .next_update(CRL_NEXT_UPDATE)
+    )
+    for serial in revoked:
+        builder = builder.add_revoked_certificate(
+            x509.RevokedCertificateBuilder()
+            .serial_number(serial)
+            .revocation_date(NOT_BEFORE)
+            .build()
+        )
+    return builder.sign(issuer_key, hashes.SHA256())
+
+
+def der_len(n):
+    if n < 0x80:
+        return bytes([n])
+    b = n.to_bytes((n.bit_length() + 7) // 8, "big")
+    return bytes([0x80 | len(b)]) + b
+
+
+def der(tag, value):
+    return bytes([tag]) + der_len(len(value)) + value
+
+
+def der_oid(oid):
+    parts = [int(p) for p in oid.split(".")]
+    body = bytes([parts[0] * 40 + parts[1]])
+    for p in parts[2:]:
+        enc = [p & 0x7F]
+        p >>= 7
+        while p:
+            enc.append(0x80 | (p & 0x7F))
+            p >>= 7
+        body += bytes(reversed(enc))
+    return der(0x06, body)
+
+
+def der_int(n):
+    b = n.to_bytes((n.bit_length() + 8) // 8, "big")
+    return der(0x02, b)
+
+
+def sgx_extension(components, pce_svn):
+    tcb = b""
+    for i, svn in enumerate(components):
+        tcb += der(0x30, der_oid("%s.2.%d" % (SGX_OID, i + 1)) + der_int(svn))
+    tcb += der(0x30, der_oid(SGX_OID + ".2.17") + der_int(pce_svn))
+    tcb += der(0x30, der_oid(SGX_OID + ".2.18") + der(0x04, bytes(components)))
+    value = der(0x30, der_oid(SGX_OID + ".1") + der(0x04, bytes(range(16))))
+    value += der(0x30, der_oid(SGX_OID + ".2") + der(0x30, tcb))
+    value += der(0x30, der_oid(SGX_OID + ".3") + der(0x04, PCE_ID))
+    value += der(0x30, der_oid(SGX_OID + ".4") + der(0x04, FMSPC))
+    value += der(0x30, der_oid(SGX_OID + ".5") + der(0x0A, b"\x00"))
+    return x509.UnrecognizedExtension(ObjectIdentifier(SGX_OID), der(0x30, value))
+
+
+def raw_signature(key, data):
+    r, s = decode_dss_signature(key.sign(data, ec.ECDSA(hashes.SHA256())))
+    return r.to_bytes(32, "big") + s.to_bytes(32, "big")
+
+
+def raw_public_key(key):
+    return key.public_key().public_bytes(
+        serialization.Encoding.X962, serialization.PublicFormat.UncompressedPoint
+    )[1:]
+
+
+def pem(*certs):
+    return b"".join(c.public_bytes(serialization.Encoding.PEM) for c in certs)
+
+
+def report_body(misc_select, flags, xfrm, mr_enclave, mr_signer, isv_prod_id, isv_svn,
+                report_data):
+    body = bytes(16)  # cpu_svn
+    body += struct.pack("<I", misc_select) + bytes(12) + bytes(16)
+    body += struct.pack("<QQ", flags, xfrm)
+    body += mr_enclave + bytes(32) + mr_signer + bytes(32)
+    body += bytes(64)  # config_id
+    body += struct.pack("<HHH", isv_prod_id, isv_svn, 0) + bytes(42) + bytes(16)
+    body += report_data
+    assert len(body) == 384
+    return body
+
+
+def quote(version, pck_key, pck_chain, report_data=b"user report data".ljust(64, b"\0")):
+    att_key = ec.generate_private_key(ec.SECP256R1())
+    att_pub = raw_public_key(att_key)
+    auth_data = bytes(range(32))
+    qe_report = report_body(
+        0, 0x11, 0xE7, bytes(32), QE_MRSIGNER, 1, 8,
+        hashlib.sha256(att_pub + auth_data).digest() + bytes(32),
+    )
+
+    header = struct.pack("<HHIHH", version, 2, 0, 8, 11) + INTEL_QE_VENDOR_ID + bytes(20)
+    enclave = report_body(
+        0, 0x07, 0x03, bytes([0xEE] * 32), bytes([0x55] * 32), 7, 3, report_data
+    )
+    signature = raw_signature(att_key, header + enclave)
+
+    qe_data = qe_report + raw_signature(pck_key, qe_report)
+    qe_data += struct.pack("<H", len(auth_data)) + auth_data
+    qe_data += struct.pack("<HI", 5, len(pck_chain)) + pck_chain
+    if version == 4:
+        qe_data = struct.pack("<HI", 6, len(qe_data)) + qe_data
+    signature_data = signature + att_pub + qe_data
+    return header + enclave + struct.pack("<I", len(signature_data)) + signature_data
+
+
+def signed_document(member, body, key):
+    text = json.dumps(body, separators=(",", ":"))
+    signature = raw_signature(key, text.encode()).hex()
+    return ('{"%s":%s,"signature":"%s"}' % (member, text, signature)).encode()
+
+
+def tcb_level(components, pce_svn, date, status, advisories):
+    level = {
+        "tcb": {
+            "sgxtcbcomponents": [{"svn": svn} for svn in components],
+            "pcesvn": pce_svn,
+        },
+        "tcbDate": date,
+        "tcbStatus": status,
+    }
+    if advisories:
+        level["advisoryIDs"] = advisories
+    return level
+
+
+def qe_identity(up_to_date_isvsvn, key):
+    return signed_document("enclaveIdentity", {
+        "id": "QE",
+        "version": 2,
+        "issueDate": "2024-01-01T00:00:00Z",
+        "nextUpdate": QE_IDENTITY_NEXT_UPDATE,
+        "tcbEvaluationDataNumber": 16,
+        "miscselect": "00000000",
+        "miscselectMask": "FFFFFFFF",
+        "attributes": "11000000000000000000000000000000",
+        "attributesMask": "FBFFFFFFFFFFFFFF0000000000000000",
+        "mrsigner": QE_MRSIGNER.hex().upper(),
+        "isvprodid": 1,
+        "tcbLevels": [
+            {"tcb": {"isvsvn": up_to_date_isvsvn}, "tcbDate": "2023-08-09T00:00:00Z",
+             "tcbStatus": "UpToDate"},
+            {"tcb": {"isvsvn": 6}, "tcbDate": "2021-11-10T00:00:00Z",
+             "tcbStatus": "OutOfDate", "advisoryIDs": ["INTEL-SA-00615"]},
+        ],
+    }, key)
+
+
+def write(file_name, data):
+    with open(os.path.join(OUT, file_name), "wb") as f:
+        f.write(data)
+
+
+def main():
+    root_key = ec.generate_private_key(ec.SECP256R1())
+    root = cert("Test SGX Root CA", root_key, "Test SGX Root CA", root_key, True, 1)
+    platform_key = ec.generate_private_key(ec.SECP256R1())
+    platform = cert("Test SGX PCK Platform CA", platform_key, "Test SGX Root CA", root_key, True, 2)
+    signing_key = ec.generate_private_key(ec.SECP256R1())
+    signing = cert("Test SGX TCB Signing", signing_key, "Test SGX Root CA", root_key, False, 3)
+
+    pck_key = ec.generate_private_key(ec.SECP256R1())
+    pck = cert("Test SGX PCK Certificate", pck_key, "Test SGX PCK Platform CA", platform_key,
+               False, 0x1001, [sgx_extension([3] * 16, 11)])
+    old_pck_key = ec.generate_private_key(ec.SECP256R1())
+    old_pck = cert("Test SGX PCK Certificate", old_pck_key, "Test SGX PCK Platform CA",
+                   platform_key, False, 0x1002, [sgx_extension([2] * 16, 10)])
+
+    write("root_ca.pem", pem(root))
+    write("pck_crl_issuer_chain.pem", pem(platform, root))
+    write("tcb_signing_chain.pem", pem(signing, root))
+    write("root_ca_crl.der", crl("Test SGX Root CA", root_key).public_bytes(serialization.Encoding.DER))
+    write("pck_crl.pem", crl("Test SGX PCK Platform CA", platform_key).public_bytes(serialization.Encoding.PEM))
+    write("pck_crl_revoked.pem",
+          crl("Test SGX PCK Platform CA", platform_key, [0x1001]).public_bytes(serialization.Encoding.PEM))
+
+    write("tcb_info.json", signed_document("tcbInfo", {
+        "id": "SGX",
+        "version": 3,
+        "issueDate": "2024-01-01T00:00:00Z",
+        "nextUpdate": TCB_INFO_NEXT_UPDATE,
+        "fmspc": FMSPC.hex(),
+        "pceId": PCE_ID.hex(),
+        "tcbType": 0,
+        "tcbEvaluationDataNumber": 16,
+        "tcbLevels": [
+            tcb_level([3] * 16, 11, "2023-08-09T00:00:00Z", "UpToDate", []),
+            tcb_level([2] * 16, 10, "2023-02-15T00:00:00Z", "SWHardeningNeeded",
+                      ["INTEL-SA-00657"]),
+            tcb_level([0] * 16, 0, "2018-01-04T00:00:00Z", "OutOfDate",
+                      ["INTEL-SA-00657", "INTEL-SA-00334"]),
+        ],
+    }, signing_key))
+    write("qe_identity.json", qe_identity(8, signing_key))
+    write("qe_identity_out_of_date.json", qe_identity(9, signing_key))
+
+    write("quote_v3.bin", quote(3, pck_key, pem(pck, platform, root)))
+    write("quote_v4.bin", quote(4, pck_key, pem(pck, platform, root)))
+    write("quote_v3_sw_hardening.bin", quote(3, old_pck_key, pem(old_pck, platform, root)))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,34 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! # Remote Attestation Service Provider Library
+//!
+//! The library implements the service provider side of the remote attestation and key
+//! exchange of the SGX SDK: it checks message 1 of an enclave, answers with message 2,
+//! checks message 3 and its quote, and returns the attestation result in message 4.
+//! The enclave side is RaSession in sgx_tkey_exchange.
+//!
+//! The library does not verify the quote itself: the quote of message 3 is verified by
+//! the Intel Attestation Service, or trusted as is by a stand-in service provider in
+//! simulation mode.
+//!
+
+extern crate sgx_types;
+extern crate sgx_ucrypto;
+
+mod sp;
+pub use self::sp::*;
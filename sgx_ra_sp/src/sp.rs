@@ -0,0 +1,414 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use sgx_types::marker::ContiguousMemory;
+use sgx_types::*;
+use sgx_ucrypto::*;
+use std::mem;
+use std::ptr;
+use std::slice;
+
+/// The extended EPID group ID of the Intel Attestation Service, the only one accepted in
+/// message 0.
+pub const SGX_RA_EXTENDED_EPID_GROUP_ID: u32 = 0;
+/// The key derivation function of the SDK, sent in message 2.
+pub const SGX_RA_KDF_ID: u16 = 1;
+
+/// Size of the part of message 2 the MAC covers: g_b, spid, quote_type, kdf_id and sign_gb_ga.
+const MSG2_MAC_SIZE: usize = 148;
+/// Offset of the report body in a quote.
+const QUOTE_REPORT_BODY_OFFSET: usize = 48;
+/// Size of a quote without its signature.
+const QUOTE_MIN_SIZE: usize = 436;
+
+///
+/// The service provider of a remote attestation: its signing key, and the SPID and the
+/// quote type it registered with the Intel Attestation Service.
+///
+/// The enclave is built with the public key of the service provider, and rejects a
+/// message 2 which is not signed by it.
+///
+pub struct RaServiceProvider {
+    private_key: EcPrivateKey,
+    public_key: sgx_ec256_public_t,
+    spid: sgx_spid_t,
+    quote_type: sgx_quote_sign_type_t,
+}
+
+impl RaServiceProvider {
+    ///
+    /// Creates a service provider.
+    ///
+    /// # Parameters
+    ///
+    /// **private_key**
+    ///
+    /// The EC P-256 signing key of the service provider.
+    ///
+    /// **spid**
+    ///
+    /// The service provider ID registered with the Intel Attestation Service.
+    ///
+    /// **quote_type**
+    ///
+    /// Whether the enclave creates linkable or unlinkable quotes.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The private key is invalid.
+    ///
+    pub fn new(
+        private_key: EcPrivateKey,
+        spid: sgx_spid_t,
+        quote_type: sgx_quote_sign_type_t,
+    ) -> SgxResult<RaServiceProvider> {
+        let public_key = rsgx_ecc256_pub_from_priv(private_key.as_raw())?;
+        Ok(RaServiceProvider {
+            private_key,
+            public_key,
+            spid,
+            quote_type,
+        })
+    }
+
+    /// The public key the enclave passes to RaSession::new.
+    pub fn public_key(&self) -> &sgx_ec256_public_t {
+        &self.public_key
+    }
+
+    ///
+    /// Processes message 0, and starts a session with the enclave.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The extended EPID group ID of the platform is not supported.
+    ///
+    pub fn proc_msg0(&self, extended_epid_group_id: u32) -> SgxResult<RaSpSession<'_>> {
+        if extended_epid_group_id != SGX_RA_EXTENDED_EPID_GROUP_ID {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        Ok(RaSpSession {
+            sp: self,
+            state: RaSpSessionState::ExpectingMsg1,
+            g_a: sgx_ec256_public_t::default(),
+            g_b: sgx_ec256_public_t::default(),
+            smk: SecretKey128::default(),
+            sk: SecretKey128::default(),
+            mk: SecretKey128::default(),
+            vk: SecretKey128::default(),
+            quote: Vec::new(),
+        })
+    }
+}
+
+/// The progress of a remote attestation session on the side of the service provider.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RaSpSessionState {
+    ExpectingMsg1,
+    ExpectingMsg3,
+    /// Message 3 is valid, and the quote is waiting to be verified.
+    QuoteReceived,
+    /// Message 4 accepted the attestation, and the session keys can be used.
+    Trusted,
+    /// Message 4 rejected the attestation.
+    Rejected,
+}
+
+///
+/// A remote attestation and key exchange with one enclave, on the side of the service
+/// provider.
+///
+/// The messages must be processed in order. The session keys are derived as the SDK does,
+/// and the SK and the MK are only released once the attestation is accepted.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut session = sp.proc_msg0(msg0)?;
+/// let msg2 = session.proc_msg1(&msg1, &sig_rl)?;
+/// // Send message 2, and receive message 3.
+/// let report = session.proc_msg3(&msg3)?;
+/// let trusted = ias_verify(session.quote())? && policy(&report);
+/// let msg4 = session.gen_msg4(trusted)?;
+/// ```
+///
+pub struct RaSpSession<'a> {
+    sp: &'a RaServiceProvider,
+    state: RaSpSessionState,
+    g_a: sgx_ec256_public_t,
+    g_b: sgx_ec256_public_t,
+    smk: SecretKey128,
+    sk: SecretKey128,
+    mk: SecretKey128,
+    vk: SecretKey128,
+    quote: Vec<u8>,
+}
+
+impl<'a> RaSpSession<'a> {
+    pub fn state(&self) -> RaSpSessionState {
+        self.state
+    }
+
+    ///
+    /// Processes message 1, and creates message 2.
+    ///
+    /// # Parameters
+    ///
+    /// **msg1**
+    ///
+    /// Message 1, with the public key of the enclave for the key exchange.
+    ///
+    /// **sig_rl**
+    ///
+    /// The signature revocation list of the EPID group of the platform, from the Intel
+    /// Attestation Service, or empty.
+    ///
+    /// # Return value
+    ///
+    /// Message 2: a sgx_ra_msg2_t followed by the signature revocation list.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// Message 1 has already been processed.
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The public key of the enclave is not a valid point, or the signature revocation list
+    /// is too large.
+    ///
+    /// **SGX_ERROR_OUT_OF_MEMORY**
+    ///
+    /// Not enough memory is available to complete this operation.
+    ///
+    pub fn proc_msg1(&mut self, msg1: &sgx_ra_msg1_t, sig_rl: &[u8]) -> SgxResult<Vec<u8>> {
+        if self.state != RaSpSessionState::ExpectingMsg1 {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+        if sig_rl.len() > u32::MAX as usize {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let ecc = SgxEccHandle::new();
+        ecc.open()?;
+        if !ecc.check_point(&msg1.g_a)? {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        let (private_b, g_b) = ecc.create_secret_key_pair()?;
+        let shared = ecc.compute_shared_secret(&private_b, &msg1.g_a)?;
+
+        let kdk = SecretKey128::new(rsgx_rijndael128_cmac_slice(
+            &[0_u8; SGX_CMAC_KEY_SIZE],
+            &shared.as_raw().s,
+        )?);
+        self.smk = derive_key(&kdk, b"SMK")?;
+        self.sk = derive_key(&kdk, b"SK")?;
+        self.mk = derive_key(&kdk, b"MK")?;
+        self.vk = derive_key(&kdk, b"VK")?;
+        self.g_a = msg1.g_a;
+        self.g_b = g_b;
+
+        let mut msg2 = sgx_ra_msg2_t {
+            g_b,
+            spid: self.sp.spid,
+            quote_type: self.sp.quote_type as u16,
+            kdf_id: SGX_RA_KDF_ID,
+            sign_gb_ga: ecc.ecdsa_sign_slice(&[g_b, msg1.g_a], self.sp.private_key.as_raw())?,
+            mac: sgx_mac_t::default(),
+            sig_rl_size: sig_rl.len() as u32,
+            sig_rl: [],
+        };
+        msg2.mac =
+            rsgx_rijndael128_cmac_slice(self.smk.as_raw(), &as_bytes(&msg2)[..MSG2_MAC_SIZE])?;
+
+        let mut bytes = as_bytes(&msg2).to_vec();
+        bytes.extend_from_slice(sig_rl);
+        self.state = RaSpSessionState::ExpectingMsg3;
+        Ok(bytes)
+    }
+
+    ///
+    /// Processes message 3: checks its MAC, and that its quote binds the keys of the
+    /// session.
+    ///
+    /// # Parameters
+    ///
+    /// **msg3**
+    ///
+    /// Message 3: a sgx_ra_msg3_t followed by the quote.
+    ///
+    /// # Return value
+    ///
+    /// The report of the enclave in the quote. The quote itself, returned by `quote`, must
+    /// be verified before the attestation is accepted with `gen_msg4`.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// Message 1 has not been processed, or message 3 has already been processed.
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The message is too short, or its public key is not the one of message 1.
+    ///
+    /// **SGX_ERROR_MAC_MISMATCH**
+    ///
+    /// The MAC of the message is invalid, or the report data of the quote does not bind
+    /// the keys of the session.
+    ///
+    pub fn proc_msg3(&mut self, msg3: &[u8]) -> SgxResult<sgx_report_body_t> {
+        if self.state != RaSpSessionState::ExpectingMsg3 {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+        let header_size = mem::size_of::<sgx_ra_msg3_t>();
+        if msg3.len() < header_size + QUOTE_MIN_SIZE {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let mac_size = mem::size_of::<sgx_mac_t>();
+        let g_a = &msg3[mac_size..mac_size + mem::size_of::<sgx_ec256_public_t>()];
+        if g_a != as_bytes(&self.g_a) {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        let mac = rsgx_rijndael128_cmac_slice(self.smk.as_raw(), &msg3[mac_size..])?;
+        if !ct_eq(&mac, &msg3[..mac_size]) {
+            return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
+        }
+
+        let quote = &msg3[header_size..];
+        let report: sgx_report_body_t = unsafe {
+            ptr::read_unaligned(
+                quote[QUOTE_REPORT_BODY_OFFSET..].as_ptr() as *const sgx_report_body_t
+            )
+        };
+        let sha = SgxShaHandle::new();
+        sha.init()?;
+        sha.update_msg(&self.g_a)?;
+        sha.update_msg(&self.g_b)?;
+        sha.update_slice(self.vk.as_raw())?;
+        let hash = sha.get_hash()?;
+        if !ct_eq(&hash, &report.report_data.d[..SGX_SHA256_HASH_SIZE]) {
+            return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
+        }
+
+        self.quote = quote.to_vec();
+        self.state = RaSpSessionState::QuoteReceived;
+        Ok(report)
+    }
+
+    /// The quote of message 3, to be verified by the Intel Attestation Service.
+    pub fn quote(&self) -> &[u8] {
+        &self.quote
+    }
+
+    ///
+    /// Creates message 4, which tells the enclave whether the attestation is accepted.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// Message 3 has not been processed, or message 4 has already been created.
+    ///
+    pub fn gen_msg4(&mut self, trusted: bool) -> SgxResult<sgx_ra_msg4_t> {
+        if self.state != RaSpSessionState::QuoteReceived {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+        let status = if trusted {
+            SGX_RA_MSG4_TRUSTED
+        } else {
+            SGX_RA_MSG4_UNTRUSTED
+        };
+        let msg4 = sgx_ra_msg4_t {
+            status,
+            mac: rsgx_rijndael128_cmac_msg(self.mk.as_raw(), &status)?,
+        };
+        self.state = if trusted {
+            RaSpSessionState::Trusted
+        } else {
+            RaSpSessionState::Rejected
+        };
+        Ok(msg4)
+    }
+
+    ///
+    /// The SK, which protects the secrets exchanged with the enclave.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The attestation has not been accepted.
+    ///
+    pub fn secret_key(&self) -> SgxResult<&SecretKey128> {
+        self.trusted_key(&self.sk)
+    }
+
+    ///
+    /// The MK, which authenticates the messages exchanged with the enclave.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The attestation has not been accepted.
+    ///
+    pub fn mac_key(&self) -> SgxResult<&SecretKey128> {
+        self.trusted_key(&self.mk)
+    }
+
+    fn trusted_key<'k>(&self, key: &'k SecretKey128) -> SgxResult<&'k SecretKey128> {
+        match self.state {
+            RaSpSessionState::Trusted => Ok(key),
+            _ => Err(sgx_status_t::SGX_ERROR_INVALID_STATE),
+        }
+    }
+}
+
+// Derives a session key from the KDK as the SDK does: AES-CMAC of
+// 0x01 || label || 0x00 || 0x0080 (the key size in bits, little endian).
+fn derive_key(kdk: &SecretKey128, label: &[u8]) -> SgxResult<SecretKey128> {
+    let mut derivation = Vec::with_capacity(label.len() + 4);
+    derivation.push(0x01);
+    derivation.extend_from_slice(label);
+    derivation.extend_from_slice(&[0x00, 0x80, 0x00]);
+    Ok(SecretKey128::new(rsgx_rijndael128_cmac_slice(
+        kdk.as_raw(),
+        &derivation,
+    )?))
+}
+
+fn as_bytes<T: ContiguousMemory>(value: &T) -> &[u8] {
+    unsafe { slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>()) }
+}
+
+fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a
+        .iter()
+        .zip(b.iter())
+        .fold(0_u8, |acc, (x, y)| acc | (x ^ y));
+    unsafe { ptr::read_volatile(&diff) == 0 }
+}
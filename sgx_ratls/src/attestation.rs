@@ -0,0 +1,924 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+//!
+//! Attestation evidence, and the attesters which produce it and the verifiers which
+//! appraise it, in the roles of the IETF RATS architecture.
+//!
+//! The evidence is an EPID quote as verified by the Intel Attestation Service, a DCAP quote,
+//! or a simulated quote signed by a local test key. An application which selects its
+//! attester and its verifier at runtime runs the same attestation flow with any of them,
+//! and with the simulated one on a machine without SGX hardware.
+//!
+
+use crate::cert::ec_private_key_pkcs8;
+use crate::verify::{CollateralProvider, RaTlsError};
+use ring::rand::SystemRandom;
+use ring::signature::{
+    EcdsaKeyPair, UnparsedPublicKey, ECDSA_P256_SHA256_FIXED, ECDSA_P256_SHA256_FIXED_SIGNING,
+};
+use sgx_dcap_verify::{QuoteVerifier, TcbStatus};
+use sgx_types::*;
+use std::prelude::v1::*;
+use std::ptr;
+use std::sync::Arc;
+
+/// The offset of the report body in an EPID, DCAP or simulated quote.
+const QUOTE_REPORT_BODY_OFFSET: usize = 48;
+/// The size of the signed part of an EPID or simulated quote: the header and the report body.
+const QUOTE_BODY_SIZE: usize = 432;
+/// The version in the header of a simulated quote, which no quoting enclave produces.
+const SIMULATED_QUOTE_VERSION: u16 = 0;
+/// The size of a simulated quote: the body, the signature length and the signature.
+const SIMULATED_QUOTE_SIZE: usize = QUOTE_BODY_SIZE + 4 + 64;
+
+static IAS_SIGNATURE_ALGS: &[&webpki::SignatureAlgorithm] = &[&webpki::RSA_PKCS1_2048_8192_SHA256];
+
+/// The big-endian private scalar of the well-known simulation key, which signs simulated
+/// quotes unless another key is given. It is public: never trust it in production.
+pub const SIMULATED_ATTESTATION_PRIVATE_KEY: [u8; 32] = [
+    0x2e, 0xb7, 0x44, 0xf2, 0x6a, 0x0d, 0xc6, 0x94, 0x46, 0x47, 0xc6, 0x5b, 0x49, 0x61, 0xa2, 0x05,
+    0xab, 0xf0, 0x70, 0xf9, 0xf1, 0x0e, 0x8b, 0x40, 0x93, 0xb0, 0xe6, 0x36, 0x65, 0xd3, 0x09, 0xa1,
+];
+
+/// The big-endian x and y coordinates of the public key of the simulation key.
+pub const SIMULATED_ATTESTATION_PUBLIC_KEY: [u8; 64] = [
+    0xaf, 0x1f, 0xee, 0xd5, 0xed, 0x96, 0xcb, 0xde, 0xd8, 0x8b, 0x91, 0xc7, 0xfa, 0x51, 0x17, 0x24,
+    0x7e, 0xb3, 0xf8, 0x46, 0xdc, 0x22, 0x0e, 0xd3, 0xad, 0x48, 0x3b, 0xf8, 0x7c, 0x3d, 0xa1, 0xbb,
+    0x97, 0xc0, 0x87, 0x12, 0x37, 0xfc, 0x4d, 0xdf, 0x95, 0x4c, 0x64, 0x0f, 0x4c, 0x2a, 0x2d, 0xd4,
+    0x3b, 0x98, 0xc8, 0xfb, 0x9f, 0x13, 0x03, 0xc4, 0xe6, 0x55, 0x7b, 0x74, 0xd7, 0x18, 0xcd, 0xf7,
+];
+
+/// The kind of attestation evidence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvidenceKind {
+    Epid,
+    Dcap,
+    Simulated,
+}
+
+///
+/// Attestation evidence about an enclave.
+///
+pub trait Evidence {
+    fn kind(&self) -> EvidenceKind;
+
+    /// The report body of the attested enclave the evidence claims. It is not verified.
+    fn report_body(&self) -> Option<sgx_report_body_t>;
+}
+
+fn read_report_body(quote: &[u8]) -> Option<sgx_report_body_t> {
+    if quote.len() < QUOTE_BODY_SIZE {
+        return None;
+    }
+    Some(unsafe {
+        ptr::read_unaligned(quote[QUOTE_REPORT_BODY_OFFSET..].as_ptr() as *const sgx_report_body_t)
+    })
+}
+
+///
+/// An EPID quote, as verified by the Intel Attestation Service: an EPID quote can only be
+/// verified by IAS, so that the evidence is its signed attestation verification report.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpidEvidence {
+    /// The body of the attestation verification report, as returned by IAS.
+    pub report: Vec<u8>,
+    /// The signature of the report, base64-decoded from X-IASReport-Signature.
+    pub signature: Vec<u8>,
+    /// The DER certificate that signed the report, from X-IASReport-Signing-Certificate.
+    pub signing_cert: Vec<u8>,
+}
+
+impl Evidence for EpidEvidence {
+    fn kind(&self) -> EvidenceKind {
+        EvidenceKind::Epid
+    }
+
+    fn report_body(&self) -> Option<sgx_report_body_t> {
+        ias_quote_body(&self.report).and_then(|quote| read_report_body(&quote))
+    }
+}
+
+/// A DCAP ECDSA quote, version 3 or 4.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DcapEvidence {
+    pub quote: Vec<u8>,
+}
+
+impl Evidence for DcapEvidence {
+    fn kind(&self) -> EvidenceKind {
+        EvidenceKind::Dcap
+    }
+
+    fn report_body(&self) -> Option<sgx_report_body_t> {
+        read_report_body(&self.quote)
+    }
+}
+
+///
+/// A simulated quote, laid out as an EPID quote of version 0: the header, the report body,
+/// and the ECDSA P-256 signature of both by the simulation key, as big-endian r and s
+/// values, after its 4-byte length.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulatedEvidence {
+    pub quote: Vec<u8>,
+}
+
+impl Evidence for SimulatedEvidence {
+    fn kind(&self) -> EvidenceKind {
+        EvidenceKind::Simulated
+    }
+
+    fn report_body(&self) -> Option<sgx_report_body_t> {
+        read_report_body(&self.quote)
+    }
+}
+
+///
+/// Evidence of any kind, as produced by an attester and sent to the verifier.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttestationEvidence {
+    Epid(EpidEvidence),
+    Dcap(DcapEvidence),
+    Simulated(SimulatedEvidence),
+}
+
+impl Evidence for AttestationEvidence {
+    fn kind(&self) -> EvidenceKind {
+        match self {
+            AttestationEvidence::Epid(evidence) => evidence.kind(),
+            AttestationEvidence::Dcap(evidence) => evidence.kind(),
+            AttestationEvidence::Simulated(evidence) => evidence.kind(),
+        }
+    }
+
+    fn report_body(&self) -> Option<sgx_report_body_t> {
+        match self {
+            AttestationEvidence::Epid(evidence) => evidence.report_body(),
+            AttestationEvidence::Dcap(evidence) => evidence.report_body(),
+            AttestationEvidence::Simulated(evidence) => evidence.report_body(),
+        }
+    }
+}
+
+impl AttestationEvidence {
+    ///
+    /// Encodes the evidence to be sent to the verifier: a byte with the kind, 1 for EPID,
+    /// 2 for DCAP and 3 for simulated, then each part after its 4-byte little-endian length.
+    ///
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (kind, parts): (u8, Vec<&[u8]>) = match self {
+            AttestationEvidence::Epid(evidence) => (
+                1,
+                vec![
+                    &evidence.report,
+                    &evidence.signature,
+                    &evidence.signing_cert,
+                ],
+            ),
+            AttestationEvidence::Dcap(evidence) => (2, vec![&evidence.quote]),
+            AttestationEvidence::Simulated(evidence) => (3, vec![&evidence.quote]),
+        };
+        let mut bytes = vec![kind];
+        for part in parts {
+            bytes.extend_from_slice(&(part.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(part);
+        }
+        bytes
+    }
+
+    /// Decodes the evidence encoded by to_bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Option<AttestationEvidence> {
+        let (&kind, mut rest) = bytes.split_first()?;
+        let mut parts = Vec::new();
+        while !rest.is_empty() {
+            if rest.len() < 4 {
+                return None;
+            }
+            let (len, tail) = rest.split_at(4);
+            let len = u32::from_le_bytes(len.try_into().ok()?) as usize;
+            if tail.len() < len {
+                return None;
+            }
+            let (part, tail) = tail.split_at(len);
+            parts.push(part.to_vec());
+            rest = tail;
+        }
+        let mut parts = parts.into_iter();
+        let evidence = match kind {
+            1 => AttestationEvidence::Epid(EpidEvidence {
+                report: parts.next()?,
+                signature: parts.next()?,
+                signing_cert: parts.next()?,
+            }),
+            2 => AttestationEvidence::Dcap(DcapEvidence {
+                quote: parts.next()?,
+            }),
+            3 => AttestationEvidence::Simulated(SimulatedEvidence {
+                quote: parts.next()?,
+            }),
+            _ => return None,
+        };
+        match parts.next() {
+            Some(_) => None,
+            None => Some(evidence),
+        }
+    }
+}
+
+///
+/// Produces evidence for the reports of an enclave.
+///
+/// The enclave creates a report targeted at the enclave the attester quotes with, with
+/// rsgx_create_report and the target info of the attester, and the attester turns the
+/// report into evidence. The attester runs where its quoting enclave is reached: in the
+/// application, or in the enclave through OCALLs.
+///
+pub trait Attester {
+    fn kind(&self) -> EvidenceKind;
+
+    /// The target info of the enclave which quotes the reports.
+    fn target_info(&self) -> SgxResult<sgx_target_info_t>;
+
+    /// Turns a report targeted at target_info into evidence.
+    fn attest(&self, report: &sgx_report_t) -> SgxResult<AttestationEvidence>;
+}
+
+///
+/// Attests with EPID: it gets an EPID quote of the report from the quoting enclave, and the
+/// attestation verification report of the quote from the Intel Attestation Service, with
+/// the quote function.
+///
+pub struct EpidAttester<Q> {
+    qe_target_info: sgx_target_info_t,
+    quote: Q,
+}
+
+impl<Q> EpidAttester<Q>
+where
+    Q: Fn(&sgx_report_t) -> SgxResult<EpidEvidence>,
+{
+    ///
+    /// Creates an EPID attester.
+    ///
+    /// # Parameters
+    ///
+    /// **qe_target_info**
+    ///
+    /// The target info of the quoting enclave, from sgx_init_quote.
+    ///
+    /// **quote**
+    ///
+    /// Quotes a report with sgx_get_quote and has IAS verify the quote.
+    ///
+    pub fn new(qe_target_info: sgx_target_info_t, quote: Q) -> EpidAttester<Q> {
+        EpidAttester {
+            qe_target_info,
+            quote,
+        }
+    }
+}
+
+impl<Q> Attester for EpidAttester<Q>
+where
+    Q: Fn(&sgx_report_t) -> SgxResult<EpidEvidence>,
+{
+    fn kind(&self) -> EvidenceKind {
+        EvidenceKind::Epid
+    }
+
+    fn target_info(&self) -> SgxResult<sgx_target_info_t> {
+        Ok(self.qe_target_info)
+    }
+
+    fn attest(&self, report: &sgx_report_t) -> SgxResult<AttestationEvidence> {
+        (self.quote)(report).map(AttestationEvidence::Epid)
+    }
+}
+
+///
+/// Attests with DCAP: it gets an ECDSA quote of the report from the quoting enclave with
+/// the quote function.
+///
+/// # Examples
+///
+/// ```ignore
+/// let generator = QuoteGenerator::new().build()?;
+/// let qe_target_info = generator.target_info()?;
+/// let attester = DcapAttester::new(qe_target_info, |report: &sgx_report_t| {
+///     generator
+///         .quote_for(report)
+///         .map_err(|_| sgx_status_t::SGX_ERROR_UNEXPECTED)
+/// });
+/// ```
+///
+pub struct DcapAttester<Q> {
+    qe_target_info: sgx_target_info_t,
+    quote: Q,
+}
+
+impl<Q> DcapAttester<Q>
+where
+    Q: Fn(&sgx_report_t) -> SgxResult<Vec<u8>>,
+{
+    ///
+    /// Creates a DCAP attester.
+    ///
+    /// # Parameters
+    ///
+    /// **qe_target_info**
+    ///
+    /// The target info of the quoting enclave, from sgx_qe_get_target_info.
+    ///
+    /// **quote**
+    ///
+    /// Quotes a report with sgx_qe_get_quote.
+    ///
+    pub fn new(qe_target_info: sgx_target_info_t, quote: Q) -> DcapAttester<Q> {
+        DcapAttester {
+            qe_target_info,
+            quote,
+        }
+    }
+}
+
+impl<Q> Attester for DcapAttester<Q>
+where
+    Q: Fn(&sgx_report_t) -> SgxResult<Vec<u8>>,
+{
+    fn kind(&self) -> EvidenceKind {
+        EvidenceKind::Dcap
+    }
+
+    fn target_info(&self) -> SgxResult<sgx_target_info_t> {
+        Ok(self.qe_target_info)
+    }
+
+    fn attest(&self, report: &sgx_report_t) -> SgxResult<AttestationEvidence> {
+        (self.quote)(report).map(|quote| AttestationEvidence::Dcap(DcapEvidence { quote }))
+    }
+}
+
+///
+/// Attests without SGX hardware, for tests and for enclaves in simulation mode: it signs
+/// the report body with a local key, the simulation key unless another one is given.
+///
+/// Unlike a quoting enclave, it cannot check the MAC of the report, so that its evidence
+/// proves nothing about the enclave. Only the SimulatedVerifier accepts it.
+///
+/// The signatures are not deterministic, as ring has no RFC 6979 signing: two quotes of
+/// the same report differ. ring derives each nonce from the private key and the message
+/// as well as from the random generator, so a weak generator does not leak the key.
+///
+pub struct SimulatedAttester {
+    key: EcdsaKeyPair,
+}
+
+impl SimulatedAttester {
+    /// Creates an attester which signs with the simulation key.
+    pub fn new() -> SimulatedAttester {
+        SimulatedAttester::with_key(
+            &SIMULATED_ATTESTATION_PRIVATE_KEY,
+            &SIMULATED_ATTESTATION_PUBLIC_KEY,
+        )
+        .expect("the simulation key is valid")
+    }
+
+    ///
+    /// Creates an attester which signs with an EC P-256 key.
+    ///
+    /// # Parameters
+    ///
+    /// **private_key**
+    ///
+    /// The big-endian private scalar.
+    ///
+    /// **public_key**
+    ///
+    /// The big-endian x and y coordinates of the public key.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The keys are not a P-256 key pair.
+    ///
+    pub fn with_key(private_key: &[u8; 32], public_key: &[u8; 64]) -> SgxResult<SimulatedAttester> {
+        let pkcs8 = ec_private_key_pkcs8(private_key, public_key);
+        let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &pkcs8)
+            .map_err(|_| sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+        Ok(SimulatedAttester { key })
+    }
+}
+
+impl Default for SimulatedAttester {
+    fn default() -> SimulatedAttester {
+        SimulatedAttester::new()
+    }
+}
+
+impl Attester for SimulatedAttester {
+    fn kind(&self) -> EvidenceKind {
+        EvidenceKind::Simulated
+    }
+
+    /// The reports are not checked, so that they may target any enclave.
+    fn target_info(&self) -> SgxResult<sgx_target_info_t> {
+        Ok(sgx_target_info_t::default())
+    }
+
+    fn attest(&self, report: &sgx_report_t) -> SgxResult<AttestationEvidence> {
+        let mut quote = Vec::with_capacity(SIMULATED_QUOTE_SIZE);
+        quote.extend_from_slice(&SIMULATED_QUOTE_VERSION.to_le_bytes());
+        quote.resize(QUOTE_REPORT_BODY_OFFSET, 0);
+        quote.extend_from_slice(unsafe {
+            std::slice::from_raw_parts(
+                &report.body as *const sgx_report_body_t as *const u8,
+                std::mem::size_of::<sgx_report_body_t>(),
+            )
+        });
+        let signature = self
+            .key
+            .sign(&SystemRandom::new(), &quote)
+            .map_err(|_| sgx_status_t::SGX_ERROR_UNEXPECTED)?;
+        quote.extend_from_slice(&(signature.as_ref().len() as u32).to_le_bytes());
+        quote.extend_from_slice(signature.as_ref());
+        Ok(AttestationEvidence::Simulated(SimulatedEvidence { quote }))
+    }
+}
+
+///
+/// Appraises evidence. It returns the verified report body of the attested enclave, from
+/// which the caller decides whether the enclave is trusted, and checks that the report data
+/// binds what it expects.
+///
+pub trait Verifier: Send + Sync {
+    ///
+    /// Verifies evidence.
+    ///
+    /// # Parameters
+    ///
+    /// **evidence**
+    ///
+    /// The evidence, which is rejected with RaTlsError::Evidence if the verifier does not
+    /// accept its kind.
+    ///
+    /// **now**
+    ///
+    /// The current time, in seconds since the Unix epoch.
+    ///
+    fn verify(
+        &self,
+        evidence: &AttestationEvidence,
+        now: i64,
+    ) -> Result<sgx_report_body_t, RaTlsError>;
+}
+
+///
+/// Verifies EPID evidence: the attestation verification report must be signed by a
+/// certificate of the IAS root CA, and its quote status must be accepted.
+///
+#[derive(Clone, Debug)]
+pub struct EpidVerifier {
+    root_ca: Vec<u8>,
+    quote_statuses: Vec<String>,
+}
+
+impl EpidVerifier {
+    /// Creates a verifier of the reports signed by a certificate of the IAS root CA, given
+    /// in DER, which accepts the quote status OK.
+    pub fn new(root_ca: &[u8]) -> EpidVerifier {
+        EpidVerifier {
+            root_ca: root_ca.to_vec(),
+            quote_statuses: vec!["OK".to_owned()],
+        }
+    }
+
+    /// Accepts the quote status, such as SW_HARDENING_NEEDED, in addition to OK.
+    pub fn accept_quote_status(&mut self, status: &str) -> &mut EpidVerifier {
+        if !self.quote_statuses.iter().any(|s| s == status) {
+            self.quote_statuses.push(status.to_owned());
+        }
+        self
+    }
+}
+
+impl Verifier for EpidVerifier {
+    fn verify(
+        &self,
+        evidence: &AttestationEvidence,
+        now: i64,
+    ) -> Result<sgx_report_body_t, RaTlsError> {
+        match evidence {
+            AttestationEvidence::Epid(evidence) => {
+                verify_ias_report(&self.root_ca, &self.quote_statuses, evidence, now)
+            }
+            _ => Err(RaTlsError::Evidence),
+        }
+    }
+}
+
+///
+/// Verifies DCAP evidence with sgx_dcap_verify, against the collateral of a provider.
+/// The TCB status of the quote must be accepted.
+///
+#[derive(Clone)]
+pub struct DcapVerifier {
+    verifier: Arc<QuoteVerifier>,
+    collateral: Arc<dyn CollateralProvider>,
+    tcb_statuses: Vec<TcbStatus>,
+}
+
+impl DcapVerifier {
+    /// Creates a verifier which accepts the TCB status UpToDate.
+    pub fn new<C: CollateralProvider + 'static>(
+        verifier: QuoteVerifier,
+        collateral: C,
+    ) -> DcapVerifier {
+        DcapVerifier {
+            verifier: Arc::new(verifier),
+            collateral: Arc::new(collateral),
+            tcb_statuses: vec![TcbStatus::UpToDate],
+        }
+    }
+
+    /// Accepts the TCB status, in addition to UpToDate.
+    pub fn accept_tcb_status(&mut self, status: TcbStatus) -> &mut DcapVerifier {
+        if !self.tcb_statuses.contains(&status) {
+            self.tcb_statuses.push(status);
+        }
+        self
+    }
+
+    pub(crate) fn verify_quote(
+        &self,
+        quote: &[u8],
+        tcb_statuses: &[TcbStatus],
+        now: i64,
+    ) -> Result<sgx_report_body_t, RaTlsError> {
+        let parsed = sgx_dcap_verify::Quote::parse(quote).map_err(RaTlsError::Quote)?;
+        let collateral = self
+            .collateral
+            .collateral(&parsed)
+            .map_err(RaTlsError::Quote)?;
+        let verdict = self
+            .verifier
+            .verify(quote, &collateral, now)
+            .map_err(RaTlsError::Quote)?;
+        if !tcb_statuses.contains(&verdict.status) {
+            return Err(RaTlsError::TcbStatus(verdict.status));
+        }
+        Ok(verdict.quote.report_body)
+    }
+}
+
+impl Verifier for DcapVerifier {
+    fn verify(
+        &self,
+        evidence: &AttestationEvidence,
+        now: i64,
+    ) -> Result<sgx_report_body_t, RaTlsError> {
+        match evidence {
+            AttestationEvidence::Dcap(evidence) => {
+                self.verify_quote(&evidence.quote, &self.tcb_statuses, now)
+            }
+            _ => Err(RaTlsError::Evidence),
+        }
+    }
+}
+
+///
+/// Verifies simulated evidence: the quote must be signed by the simulation key, or by
+/// the key the attester was given.
+///
+/// It must only be configured in tests and in simulation mode, as the simulation key is
+/// public.
+///
+#[derive(Clone, Debug)]
+pub struct SimulatedVerifier {
+    public_key: [u8; 65],
+}
+
+impl SimulatedVerifier {
+    /// Creates a verifier of the quotes signed by the simulation key.
+    pub fn new() -> SimulatedVerifier {
+        SimulatedVerifier::with_key(&SIMULATED_ATTESTATION_PUBLIC_KEY)
+    }
+
+    /// Creates a verifier of the quotes signed by the key, given as the big-endian x and y
+    /// coordinates of its public key.
+    pub fn with_key(public_key: &[u8; 64]) -> SimulatedVerifier {
+        let mut point = [0x04_u8; 65];
+        point[1..].copy_from_slice(public_key);
+        SimulatedVerifier { public_key: point }
+    }
+}
+
+impl Default for SimulatedVerifier {
+    fn default() -> SimulatedVerifier {
+        SimulatedVerifier::new()
+    }
+}
+
+impl Verifier for SimulatedVerifier {
+    fn verify(
+        &self,
+        evidence: &AttestationEvidence,
+        _now: i64,
+    ) -> Result<sgx_report_body_t, RaTlsError> {
+        let quote = match evidence {
+            AttestationEvidence::Simulated(evidence) => &evidence.quote,
+            _ => return Err(RaTlsError::Evidence),
+        };
+        if quote.len() != SIMULATED_QUOTE_SIZE
+            || quote[..2] != SIMULATED_QUOTE_VERSION.to_le_bytes()
+            || quote[QUOTE_BODY_SIZE..QUOTE_BODY_SIZE + 4] != 64_u32.to_le_bytes()
+        {
+            return Err(RaTlsError::SimulatedQuote);
+        }
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, &self.public_key)
+            .verify(&quote[..QUOTE_BODY_SIZE], &quote[QUOTE_BODY_SIZE + 4..])
+            .map_err(|_| RaTlsError::SimulatedQuote)?;
+        read_report_body(quote).ok_or(RaTlsError::SimulatedQuote)
+    }
+}
+
+///
+/// Verifies evidence of the kinds it is configured for, with the verifier of the kind.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut verifier = EvidenceVerifier::new();
+/// verifier.dcap(DcapVerifier::new(QuoteVerifier::new(INTEL_SGX_ROOT_CA)?, collateral));
+/// if simulation_mode {
+///     verifier.simulated(SimulatedVerifier::new());
+/// }
+///
+/// let report = verifier.verify(&AttestationEvidence::from_bytes(&received)?, now)?;
+/// ```
+///
+#[derive(Clone, Default)]
+pub struct EvidenceVerifier {
+    epid: Option<EpidVerifier>,
+    dcap: Option<DcapVerifier>,
+    simulated: Option<SimulatedVerifier>,
+}
+
+impl EvidenceVerifier {
+    /// Creates a verifier which accepts no evidence until a kind is configured.
+    pub fn new() -> EvidenceVerifier {
+        EvidenceVerifier::default()
+    }
+
+    pub fn epid(&mut self, verifier: EpidVerifier) -> &mut EvidenceVerifier {
+        self.epid = Some(verifier);
+        self
+    }
+
+    pub fn dcap(&mut self, verifier: DcapVerifier) -> &mut EvidenceVerifier {
+        self.dcap = Some(verifier);
+        self
+    }
+
+    pub fn simulated(&mut self, verifier: SimulatedVerifier) -> &mut EvidenceVerifier {
+        self.simulated = Some(verifier);
+        self
+    }
+
+    /// Whether evidence of the kind is accepted.
+    pub fn accepts(&self, kind: EvidenceKind) -> bool {
+        match kind {
+            EvidenceKind::Epid => self.epid.is_some(),
+            EvidenceKind::Dcap => self.dcap.is_some(),
+            EvidenceKind::Simulated => self.simulated.is_some(),
+        }
+    }
+}
+
+impl Verifier for EvidenceVerifier {
+    fn verify(
+        &self,
+        evidence: &AttestationEvidence,
+        now: i64,
+    ) -> Result<sgx_report_body_t, RaTlsError> {
+        let verifier: Option<&dyn Verifier> = match evidence.kind() {
+            EvidenceKind::Epid => self.epid.as_ref().map(|v| v as &dyn Verifier),
+            EvidenceKind::Dcap => self.dcap.as_ref().map(|v| v as &dyn Verifier),
+            EvidenceKind::Simulated => self.simulated.as_ref().map(|v| v as &dyn Verifier),
+        };
+        verifier.ok_or(RaTlsError::Evidence)?.verify(evidence, now)
+    }
+}
+
+fn ias_quote_body(report: &[u8]) -> Option<Vec<u8>> {
+    let report: serde_json::Value = serde_json::from_slice(report).ok()?;
+    base64::decode(report["isvEnclaveQuoteBody"].as_str()?).ok()
+}
+
+pub(crate) fn verify_ias_report(
+    root_ca: &[u8],
+    quote_statuses: &[String],
+    evidence: &EpidEvidence,
+    now: i64,
+) -> Result<sgx_report_body_t, RaTlsError> {
+    let anchor = webpki::trust_anchor_util::cert_der_as_trust_anchor(root_ca)
+        .map_err(|_| RaTlsError::IasReport)?;
+    let signer =
+        webpki::EndEntityCert::from(&evidence.signing_cert).map_err(|_| RaTlsError::IasReport)?;
+    signer
+        .verify_is_valid_tls_server_cert(
+            IAS_SIGNATURE_ALGS,
+            &webpki::TLSServerTrustAnchors(&[anchor]),
+            &[],
+            webpki::Time::from_seconds_since_unix_epoch(now.max(0) as u64),
+        )
+        .and_then(|_| {
+            signer.verify_signature(IAS_SIGNATURE_ALGS[0], &evidence.report, &evidence.signature)
+        })
+        .map_err(|_| RaTlsError::IasReport)?;
+
+    let report: serde_json::Value =
+        serde_json::from_slice(&evidence.report).map_err(|_| RaTlsError::IasReport)?;
+    let status = report["isvEnclaveQuoteStatus"]
+        .as_str()
+        .ok_or(RaTlsError::IasReport)?;
+    if !quote_statuses.iter().any(|s| s == status) {
+        return Err(RaTlsError::IasQuoteStatus(status.to_owned()));
+    }
+    ias_quote_body(&evidence.report)
+        .and_then(|quote| read_report_body(&quote))
+        .ok_or(RaTlsError::IasReport)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::tests::collateral;
+
+    // 2026-01-01T00:00:00Z, when the test PKI and collateral are valid.
+    const NOW: i64 = 1_767_225_600;
+
+    const ENCLAVE_KEY: &[u8] = include_bytes!("../testdata/enclave_key.bin");
+    const ROOT_CA: &[u8] = include_bytes!("../testdata/root_ca.pem");
+    const QUOTE: &[u8] = include_bytes!("../testdata/quote.bin");
+    const IAS_ROOT_CA: &[u8] = include_bytes!("../testdata/ias_root_ca.der");
+
+    fn report() -> sgx_report_t {
+        let mut report = sgx_report_t::default();
+        report.body.mr_enclave.m = [0xee; 32];
+        report.body.mr_signer.m = [0x55; 32];
+        report.body.isv_svn = 3;
+        report.body.report_data.d = [0xab; 64];
+        report
+    }
+
+    fn epid_evidence(report: &[u8], signature: &[u8]) -> AttestationEvidence {
+        AttestationEvidence::Epid(EpidEvidence {
+            report: report.to_vec(),
+            signature: signature.to_vec(),
+            signing_cert: include_bytes!("../testdata/ias_signing_cert.der").to_vec(),
+        })
+    }
+
+    fn verifier() -> EvidenceVerifier {
+        let mut verifier = EvidenceVerifier::new();
+        verifier
+            .epid(EpidVerifier::new(IAS_ROOT_CA))
+            .dcap(DcapVerifier::new(
+                QuoteVerifier::new(ROOT_CA).unwrap(),
+                collateral(),
+            ));
+        verifier
+    }
+
+    #[test]
+    fn simulated_attestation() {
+        let attester = SimulatedAttester::new();
+        let evidence = attester.attest(&report()).unwrap();
+        assert_eq!(evidence.kind(), EvidenceKind::Simulated);
+
+        let received = AttestationEvidence::from_bytes(&evidence.to_bytes()).unwrap();
+        assert_eq!(received, evidence);
+        let mut verifier = verifier();
+        verifier.simulated(SimulatedVerifier::new());
+        let report = verifier.verify(&received, NOW).unwrap();
+        assert_eq!(report.mr_enclave.m, [0xee; 32]);
+        assert_eq!(report.isv_svn, 3);
+        assert_eq!(report.report_data.d, [0xab; 64]);
+    }
+
+    #[test]
+    fn reject_unconfigured_simulation() {
+        let evidence = SimulatedAttester::new().attest(&report()).unwrap();
+        assert_eq!(
+            verifier().verify(&evidence, NOW).err(),
+            Some(RaTlsError::Evidence)
+        );
+        let dcap = AttestationEvidence::Dcap(DcapEvidence {
+            quote: QUOTE.to_vec(),
+        });
+        assert_eq!(
+            SimulatedVerifier::new().verify(&dcap, NOW).err(),
+            Some(RaTlsError::Evidence)
+        );
+    }
+
+    #[test]
+    fn reject_forged_simulated_quote() {
+        let mut evidence = SimulatedAttester::new().attest(&report()).unwrap();
+        if let AttestationEvidence::Simulated(evidence) = &mut evidence {
+            // The ISV SVN of the report body.
+            evidence.quote[QUOTE_REPORT_BODY_OFFSET + 258] = 4;
+        }
+        assert_eq!(
+            SimulatedVerifier::new().verify(&evidence, NOW).err(),
+            Some(RaTlsError::SimulatedQuote)
+        );
+    }
+
+    #[test]
+    fn simulation_key() {
+        let private_key = ENCLAVE_KEY[..32].try_into().unwrap();
+        let public_key = ENCLAVE_KEY[32..].try_into().unwrap();
+        let attester = SimulatedAttester::with_key(private_key, public_key).unwrap();
+        let evidence = attester.attest(&report()).unwrap();
+        assert!(SimulatedVerifier::with_key(public_key)
+            .verify(&evidence, NOW)
+            .is_ok());
+        assert_eq!(
+            SimulatedVerifier::new().verify(&evidence, NOW).err(),
+            Some(RaTlsError::SimulatedQuote)
+        );
+        assert!(
+            SimulatedAttester::with_key(private_key, &SIMULATED_ATTESTATION_PUBLIC_KEY).is_err()
+        );
+    }
+
+    #[test]
+    fn dcap_attestation() {
+        let attester = DcapAttester::new(sgx_target_info_t::default(), |_: &sgx_report_t| {
+            Ok(QUOTE.to_vec())
+        });
+        let evidence = attester.attest(&report()).unwrap();
+        let claimed = evidence.report_body().unwrap();
+        let report = verifier().verify(&evidence, NOW).unwrap();
+        assert_eq!(report.mr_enclave.m, claimed.mr_enclave.m);
+        assert_eq!(report.isv_prod_id, 7);
+    }
+
+    #[test]
+    fn epid_attestation() {
+        let evidence = epid_evidence(
+            include_bytes!("../testdata/ias_report.json"),
+            include_bytes!("../testdata/ias_report.sig"),
+        );
+        assert_eq!(evidence.kind(), EvidenceKind::Epid);
+        let report = verifier().verify(&evidence, NOW).unwrap();
+        assert_eq!(report.mr_enclave.m, [0xee; 32]);
+
+        let out_of_date = epid_evidence(
+            include_bytes!("../testdata/ias_report_out_of_date.json"),
+            include_bytes!("../testdata/ias_report_out_of_date.sig"),
+        );
+        let mut epid = EpidVerifier::new(IAS_ROOT_CA);
+        assert_eq!(
+            epid.verify(&out_of_date, NOW).err(),
+            Some(RaTlsError::IasQuoteStatus("GROUP_OUT_OF_DATE".to_owned()))
+        );
+        epid.accept_quote_status("GROUP_OUT_OF_DATE");
+        assert!(epid.verify(&out_of_date, NOW).is_ok());
+    }
+
+    #[test]
+    fn evidence_encoding() {
+        let evidence = epid_evidence(b"report", b"signature");
+        assert_eq!(
+            AttestationEvidence::from_bytes(&evidence.to_bytes()),
+            Some(evidence.clone())
+        );
+        let mut bytes = evidence.to_bytes();
+        bytes.push(0);
+        assert_eq!(AttestationEvidence::from_bytes(&bytes), None);
+        bytes.pop();
+        bytes[0] = 4;
+        assert_eq!(AttestationEvidence::from_bytes(&bytes), None);
+        assert_eq!(AttestationEvidence::from_bytes(&[2]), None);
+    }
+}
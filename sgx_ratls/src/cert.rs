@@ -0,0 +1,452 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::evidence::RaTlsEvidence;
+use ring::digest;
+use sgx_types::*;
+use std::prelude::v1::*;
+use yasna::models::ObjectIdentifier;
+use yasna::Tag;
+
+pub(crate) const OID_EC_PUBLIC_KEY: &[u64] = &[1, 2, 840, 10045, 2, 1];
+pub(crate) const OID_PRIME256V1: &[u64] = &[1, 2, 840, 10045, 3, 1, 7];
+pub(crate) const OID_ECDSA_WITH_SHA256: &[u64] = &[1, 2, 840, 10045, 4, 3, 2];
+const OID_COMMON_NAME: &[u64] = &[2, 5, 4, 3];
+
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+
+/// The subject and issuer common name of an RA-TLS certificate, unless set otherwise.
+pub const RATLS_DEFAULT_SUBJECT: &str = "Teaclave SGX RA-TLS";
+/// 9999-12-31T23:59:59Z, the RFC 5280 value for a certificate without expiration.
+pub const RATLS_NO_EXPIRATION: i64 = 253_402_300_799;
+
+///
+/// An EC P-256 key that signs an RA-TLS certificate.
+///
+/// Inside the enclave, RaTlsKeyPair implements it with sgx_tcrypto.
+///
+pub trait CertificateKey {
+    /// The big-endian x and y coordinates of the public key.
+    fn public_key(&self) -> [u8; 64];
+    /// Signs the data with ECDSA and SHA-256. Returns the big-endian r and s values.
+    fn sign(&self, data: &[u8]) -> SgxResult<[u8; 64]>;
+}
+
+///
+/// Encodes a P-256 public key as a DER SubjectPublicKeyInfo.
+///
+pub fn public_key_info(public_key: &[u8; 64]) -> Vec<u8> {
+    let mut point = Vec::with_capacity(65);
+    point.push(0x04);
+    point.extend_from_slice(public_key);
+    yasna::construct_der(|writer| {
+        writer.write_sequence(|writer| {
+            writer.next().write_sequence(|writer| {
+                writer
+                    .next()
+                    .write_oid(&ObjectIdentifier::from_slice(OID_EC_PUBLIC_KEY));
+                writer
+                    .next()
+                    .write_oid(&ObjectIdentifier::from_slice(OID_PRIME256V1));
+            });
+            writer.next().write_bitvec_bytes(&point, point.len() * 8);
+        });
+    })
+}
+
+///
+/// Computes the report data that binds a quote to the public key of an RA-TLS certificate.
+///
+/// The first 32 bytes are the SHA-256 hash of the DER SubjectPublicKeyInfo of the key, and
+/// the last 32 bytes are zero. The enclave creates the report for its quote with this
+/// report data, and the verifier recomputes it from the certificate.
+///
+pub fn public_key_report_data(public_key: &[u8; 64]) -> sgx_report_data_t {
+    report_data_for_key_info(&public_key_info(public_key))
+}
+
+pub(crate) fn report_data_for_key_info(public_key_info: &[u8]) -> sgx_report_data_t {
+    let hash = digest::digest(&digest::SHA256, public_key_info);
+    let mut report_data = sgx_report_data_t::default();
+    report_data.d[..hash.as_ref().len()].copy_from_slice(hash.as_ref());
+    report_data
+}
+
+///
+/// Encodes a P-256 key pair as a DER PKCS#8 private key, which rustls takes as the key of
+/// its certificate.
+///
+/// # Parameters
+///
+/// **private_key**
+///
+/// The big-endian private scalar.
+///
+/// **public_key**
+///
+/// The big-endian x and y coordinates of the public key.
+///
+pub fn ec_private_key_pkcs8(private_key: &[u8; 32], public_key: &[u8; 64]) -> Vec<u8> {
+    let mut point = Vec::with_capacity(65);
+    point.push(0x04);
+    point.extend_from_slice(public_key);
+    let ec_private_key = yasna::construct_der(|writer| {
+        writer.write_sequence(|writer| {
+            writer.next().write_u8(1);
+            writer.next().write_bytes(private_key);
+            writer.next().write_tagged(Tag::context(1), |writer| {
+                writer.write_bitvec_bytes(&point, point.len() * 8);
+            });
+        });
+    });
+    yasna::construct_der(|writer| {
+        writer.write_sequence(|writer| {
+            writer.next().write_u8(0);
+            writer.next().write_sequence(|writer| {
+                writer
+                    .next()
+                    .write_oid(&ObjectIdentifier::from_slice(OID_EC_PUBLIC_KEY));
+                writer
+                    .next()
+                    .write_oid(&ObjectIdentifier::from_slice(OID_PRIME256V1));
+            });
+            writer.next().write_bytes(&ec_private_key);
+        });
+    })
+}
+
+///
+/// Builds a self-signed X.509 certificate which carries attestation evidence for its key.
+///
+/// # Examples
+///
+/// ```ignore
+/// let key = RaTlsKeyPair::new()?;
+/// let report_data = public_key_report_data(&key.public_key());
+/// // Create a report with report_data and get a quote for it, then:
+/// let cert = RaTlsCertificateBuilder::new(RaTlsEvidence::Dcap { quote }).build(&key)?;
+/// let private_key = key.private_key_pkcs8();
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct RaTlsCertificateBuilder {
+    evidence: RaTlsEvidence,
+    subject: String,
+    not_before: i64,
+    not_after: i64,
+}
+
+impl RaTlsCertificateBuilder {
+    pub fn new(evidence: RaTlsEvidence) -> RaTlsCertificateBuilder {
+        RaTlsCertificateBuilder {
+            evidence,
+            subject: RATLS_DEFAULT_SUBJECT.to_owned(),
+            not_before: 0,
+            not_after: RATLS_NO_EXPIRATION,
+        }
+    }
+
+    /// Sets the common name of the subject and the issuer.
+    pub fn subject(&mut self, subject: &str) -> &mut RaTlsCertificateBuilder {
+        self.subject = subject.to_owned();
+        self
+    }
+
+    /// Sets the validity period, in seconds since the Unix epoch. The certificate never
+    /// expires by default, because the enclave has no trusted time.
+    pub fn validity(&mut self, not_before: i64, not_after: i64) -> &mut RaTlsCertificateBuilder {
+        self.not_before = not_before;
+        self.not_after = not_after;
+        self
+    }
+
+    ///
+    /// Builds and signs the DER certificate.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The validity period is empty or outside of the years 0 to 9999.
+    ///
+    /// The errors of CertificateKey::sign.
+    ///
+    pub fn build<K: CertificateKey>(&self, key: &K) -> SgxResult<Vec<u8>> {
+        let not_before = asn1_time(self.not_before);
+        let not_after = asn1_time(self.not_after);
+        if self.not_before > self.not_after || not_before.is_none() || not_after.is_none() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        let public_key_info = public_key_info(&key.public_key());
+
+        let tbs = yasna::construct_der(|writer| {
+            writer.write_sequence(|writer| {
+                writer.next().write_tagged(Tag::context(0), |writer| {
+                    writer.write_u8(2);
+                });
+                writer.next().write_u8(1);
+                writer.next().write_sequence(|writer| {
+                    writer
+                        .next()
+                        .write_oid(&ObjectIdentifier::from_slice(OID_ECDSA_WITH_SHA256));
+                });
+                write_name(writer.next(), &self.subject);
+                writer.next().write_sequence(|writer| {
+                    writer.next().write_der(not_before.as_ref().unwrap());
+                    writer.next().write_der(not_after.as_ref().unwrap());
+                });
+                write_name(writer.next(), &self.subject);
+                writer.next().write_der(&public_key_info);
+                writer.next().write_tagged(Tag::context(3), |writer| {
+                    writer.write_sequence(|writer| {
+                        for (oid, value) in self.evidence.extensions() {
+                            writer.next().write_sequence(|writer| {
+                                writer.next().write_oid(&ObjectIdentifier::from_slice(oid));
+                                writer.next().write_bytes(value);
+                            });
+                        }
+                    });
+                });
+            });
+        });
+
+        let signature = key.sign(&tbs)?;
+        let signature = yasna::construct_der(|writer| {
+            writer.write_sequence(|writer| {
+                writer.next().write_der(&der_unsigned(&signature[..32]));
+                writer.next().write_der(&der_unsigned(&signature[32..]));
+            });
+        });
+        Ok(yasna::construct_der(|writer| {
+            writer.write_sequence(|writer| {
+                writer.next().write_der(&tbs);
+                writer.next().write_sequence(|writer| {
+                    writer
+                        .next()
+                        .write_oid(&ObjectIdentifier::from_slice(OID_ECDSA_WITH_SHA256));
+                });
+                writer
+                    .next()
+                    .write_bitvec_bytes(&signature, signature.len() * 8);
+            });
+        }))
+    }
+}
+
+// Encodes a big-endian unsigned integer as a DER INTEGER.
+fn der_unsigned(bytes: &[u8]) -> Vec<u8> {
+    let start = bytes
+        .iter()
+        .position(|&b| b != 0)
+        .unwrap_or(bytes.len() - 1);
+    let bytes = &bytes[start..];
+    let mut der = vec![0x02];
+    if bytes[0] & 0x80 != 0 {
+        der.push(bytes.len() as u8 + 1);
+        der.push(0);
+    } else {
+        der.push(bytes.len() as u8);
+    }
+    der.extend_from_slice(bytes);
+    der
+}
+
+fn write_name(writer: yasna::DERWriter, common_name: &str) {
+    writer.write_sequence(|writer| {
+        writer.next().write_set(|writer| {
+            writer.next().write_sequence(|writer| {
+                writer
+                    .next()
+                    .write_oid(&ObjectIdentifier::from_slice(OID_COMMON_NAME));
+                writer.next().write_utf8_string(common_name);
+            });
+        });
+    });
+}
+
+// The days between 1970-01-01 and the given date of the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// Encodes a time as a DER UTCTime before 2050 and as a GeneralizedTime from 2050, as
+// RFC 5280 requires.
+pub(crate) fn asn1_time(time: i64) -> Option<Vec<u8>> {
+    let (year, month, day) = civil_from_days(time.div_euclid(86_400));
+    let seconds = time.rem_euclid(86_400);
+    let (hour, minute, second) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    let (tag, text) = if (1950..2050).contains(&year) {
+        (
+            TAG_UTC_TIME,
+            format!(
+                "{:02}{:02}{:02}{:02}{:02}{:02}Z",
+                year % 100,
+                month,
+                day,
+                hour,
+                minute,
+                second
+            ),
+        )
+    } else if (0..=9999).contains(&year) {
+        (
+            TAG_GENERALIZED_TIME,
+            format!(
+                "{:04}{:02}{:02}{:02}{:02}{:02}Z",
+                year, month, day, hour, minute, second
+            ),
+        )
+    } else {
+        return None;
+    };
+    let mut der = vec![tag, text.len() as u8];
+    der.extend_from_slice(text.as_bytes());
+    Some(der)
+}
+
+fn digits(text: &[u8]) -> Option<i64> {
+    text.iter().try_fold(0_i64, |value, &c| {
+        if c.is_ascii_digit() {
+            Some(value * 10 + (c - b'0') as i64)
+        } else {
+            None
+        }
+    })
+}
+
+// Parses the value of a DER UTCTime or GeneralizedTime, in seconds since the Unix epoch.
+pub(crate) fn parse_asn1_time(tag: u8, value: &[u8]) -> Option<i64> {
+    let (year, rest) = match (tag, value.len()) {
+        (TAG_UTC_TIME, 13) => {
+            let year = digits(&value[..2])?;
+            (
+                if year < 50 { 2000 + year } else { 1900 + year },
+                &value[2..],
+            )
+        }
+        (TAG_GENERALIZED_TIME, 15) => (digits(&value[..4])?, &value[4..]),
+        _ => return None,
+    };
+    if rest[10] != b'Z' {
+        return None;
+    }
+    let month = digits(&rest[0..2])?;
+    let day = digits(&rest[2..4])?;
+    let hour = digits(&rest[4..6])?;
+    let minute = digits(&rest[6..8])?;
+    let second = digits(&rest[8..10])?;
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+///
+/// An EC P-256 key pair generated in the enclave, which signs the RA-TLS certificate and is
+/// the key of the TLS connection.
+///
+#[cfg(any(feature = "mesalock_sgx", target_env = "sgx"))]
+pub struct RaTlsKeyPair {
+    private_key: EcPrivateKey,
+    public_key: sgx_ec256_public_t,
+}
+
+#[cfg(any(feature = "mesalock_sgx", target_env = "sgx"))]
+impl RaTlsKeyPair {
+    ///
+    /// Generates a key pair.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_OUT_OF_MEMORY**
+    ///
+    /// Not enough memory is available to complete this operation.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// The key pair could not be generated.
+    ///
+    pub fn new() -> SgxResult<RaTlsKeyPair> {
+        let handle = sgx_tcrypto::SgxEccHandle::new();
+        handle.open()?;
+        let (private_key, public_key) = handle.create_secret_key_pair()?;
+        Ok(RaTlsKeyPair {
+            private_key,
+            public_key,
+        })
+    }
+
+    /// Encodes the key pair as a DER PKCS#8 private key, for rustls::PrivateKey.
+    pub fn private_key_pkcs8(&self) -> Vec<u8> {
+        let mut private_key = [0_u8; 32];
+        private_key.copy_from_slice(&self.private_key.as_raw().r);
+        private_key.reverse();
+        let pkcs8 = ec_private_key_pkcs8(&private_key, &self.public_key());
+        unsafe { core::ptr::write_volatile(&mut private_key, [0_u8; 32]) };
+        pkcs8
+    }
+}
+
+#[cfg(any(feature = "mesalock_sgx", target_env = "sgx"))]
+impl CertificateKey for RaTlsKeyPair {
+    fn public_key(&self) -> [u8; 64] {
+        // sgx_tcrypto keeps the coordinates little-endian.
+        let mut public_key = [0_u8; 64];
+        public_key[..32].copy_from_slice(&self.public_key.gx);
+        public_key[32..].copy_from_slice(&self.public_key.gy);
+        public_key[..32].reverse();
+        public_key[32..].reverse();
+        public_key
+    }
+
+    fn sign(&self, data: &[u8]) -> SgxResult<[u8; 64]> {
+        let handle = sgx_tcrypto::SgxEccHandle::new();
+        handle.open()?;
+        let signature = handle.ecdsa_sign_slice(data, self.private_key.as_raw())?;
+        let mut raw = [0_u8; 64];
+        for (i, word) in signature.x.iter().chain(signature.y.iter()).enumerate() {
+            raw[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+        }
+        raw[..32].reverse();
+        raw[32..].reverse();
+        Ok(raw)
+    }
+}
@@ -0,0 +1,92 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use std::prelude::v1::*;
+
+/// The OID of the extension with the IAS attestation verification report, in the Intel
+/// RA-TLS arc 1.2.840.113741.1337.
+pub const RATLS_IAS_REPORT_OID: &[u64] = &[1, 2, 840, 113741, 1337, 2];
+/// The OID of the extension with the signature of the IAS report.
+pub const RATLS_IAS_SIGNATURE_OID: &[u64] = &[1, 2, 840, 113741, 1337, 3];
+/// The OID of the extension with the certificate that signed the IAS report.
+pub const RATLS_IAS_SIGNING_CERT_OID: &[u64] = &[1, 2, 840, 113741, 1337, 4];
+/// The OID of the extension with a DCAP quote.
+pub const RATLS_DCAP_QUOTE_OID: &[u64] = &[1, 2, 840, 113741, 1337, 6];
+
+///
+/// The attestation evidence an RA-TLS certificate carries for its key. The report data of
+/// the quote must be public_key_report_data of the certificate key.
+///
+/// Each part is stored unencoded in a non-critical extension of the certificate.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RaTlsEvidence {
+    /// An EPID quote, as verified by the Intel Attestation Service.
+    Ias {
+        /// The body of the attestation verification report, as returned by IAS.
+        report: Vec<u8>,
+        /// The signature of the report, base64-decoded from X-IASReport-Signature.
+        signature: Vec<u8>,
+        /// The DER certificate that signed the report, from X-IASReport-Signing-Certificate.
+        signing_cert: Vec<u8>,
+    },
+    /// A DCAP ECDSA quote, version 3 or 4.
+    Dcap { quote: Vec<u8> },
+}
+
+impl RaTlsEvidence {
+    pub(crate) fn extensions(&self) -> Vec<(&'static [u64], &[u8])> {
+        match self {
+            RaTlsEvidence::Ias {
+                report,
+                signature,
+                signing_cert,
+            } => vec![
+                (RATLS_IAS_REPORT_OID, report.as_slice()),
+                (RATLS_IAS_SIGNATURE_OID, signature.as_slice()),
+                (RATLS_IAS_SIGNING_CERT_OID, signing_cert.as_slice()),
+            ],
+            RaTlsEvidence::Dcap { quote } => vec![(RATLS_DCAP_QUOTE_OID, quote.as_slice())],
+        }
+    }
+
+    // Rebuilds the evidence from the extensions of a certificate. A certificate must carry
+    // exactly one kind of evidence.
+    pub(crate) fn from_extensions(extensions: &[(Vec<u64>, Vec<u8>)]) -> Option<RaTlsEvidence> {
+        let find = |oid: &[u64]| {
+            let mut values = extensions.iter().filter(|(o, _)| o.as_slice() == oid);
+            match (values.next(), values.next()) {
+                (Some((_, value)), None) => Some(Some(value.clone())),
+                (None, _) => Some(None),
+                _ => None,
+            }
+        };
+        let quote = find(RATLS_DCAP_QUOTE_OID)?;
+        let report = find(RATLS_IAS_REPORT_OID)?;
+        let signature = find(RATLS_IAS_SIGNATURE_OID)?;
+        let signing_cert = find(RATLS_IAS_SIGNING_CERT_OID)?;
+        match (quote, report, signature, signing_cert) {
+            (Some(quote), None, None, None) => Some(RaTlsEvidence::Dcap { quote }),
+            (None, Some(report), Some(signature), Some(signing_cert)) => Some(RaTlsEvidence::Ias {
+                report,
+                signature,
+                signing_cert,
+            }),
+            _ => None,
+        }
+    }
+}
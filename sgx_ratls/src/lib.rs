@@ -0,0 +1,77 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! # Remote Attestation TLS
+//!
+//! RA-TLS binds remote attestation to a TLS connection. The enclave generates a key pair,
+//! gets a quote whose report data is the hash of the public key, and presents a
+//! self-signed certificate for the key which carries the quote. The peer verifies the
+//! quote, its binding to the certificate key, and the identity of the enclave before it
+//! accepts the connection.
+//!
+//! The evidence is either a DCAP quote, verified with sgx_dcap_verify, or an EPID quote
+//! verified by the Intel Attestation Service, whose signed report is verified.
+//!
+//! The attestation module abstracts the evidence over EPID, DCAP and a simulated backend,
+//! whose quotes are signed by a local test key, with the attesters which produce it and the
+//! verifiers which appraise it.
+//!
+//! The crate also verifies the attestation tokens of Microsoft Azure Attestation, the JWTs
+//! the service issues for the quotes it verifies, offline against its JSON web key set.
+//!
+
+#![cfg_attr(all(feature = "mesalock_sgx", not(target_env = "sgx")), no_std)]
+#![cfg_attr(target_env = "sgx", feature(rustc_private))]
+
+#[cfg(all(feature = "mesalock_sgx", not(target_env = "sgx")))]
+#[macro_use]
+extern crate sgx_tstd as std;
+
+extern crate sgx_dcap_verify;
+#[cfg(any(feature = "mesalock_sgx", target_env = "sgx"))]
+extern crate sgx_tcrypto;
+extern crate sgx_types;
+
+#[cfg(feature = "mesalock_sgx")]
+extern crate base64_sgx as base64;
+#[cfg(feature = "mesalock_sgx")]
+extern crate ring_sgx as ring;
+#[cfg(feature = "mesalock_sgx")]
+extern crate rustls_sgx as rustls;
+#[cfg(feature = "mesalock_sgx")]
+extern crate serde_json_sgx as serde_json;
+#[cfg(feature = "mesalock_sgx")]
+extern crate webpki_sgx as webpki;
+#[cfg(feature = "mesalock_sgx")]
+extern crate yasna_sgx as yasna;
+
+mod attestation;
+pub use self::attestation::*;
+
+mod cert;
+pub use self::cert::*;
+
+mod evidence;
+pub use self::evidence::*;
+
+mod verify;
+pub use self::verify::*;
+
+mod maa;
+pub use self::maa::*;
+
+mod tls;
@@ -0,0 +1,597 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use ring::signature::{RsaPublicKeyComponents, RSA_PKCS1_2048_8192_SHA256};
+use serde_json::Value;
+use std::fmt;
+use std::prelude::v1::*;
+
+/// The JWS algorithm of the tokens of Microsoft Azure Attestation.
+const MAA_TOKEN_ALGORITHM: &str = "RS256";
+
+///
+/// The reason an attestation token is rejected.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MaaError {
+    /// The token is not a JWS in compact serialization, or its header or claims are not
+    /// JSON objects.
+    Token,
+    /// The token is not signed with RS256.
+    Algorithm(String),
+    /// The key set is malformed.
+    KeySet,
+    /// The key set has no RSA signing key with the key ID of the token.
+    UnknownKey,
+    /// The signature of the token does not verify.
+    Signature,
+    /// The token has expired.
+    Expired,
+    /// The token is not valid yet.
+    NotYetValid,
+    /// The token is issued by another attestation provider.
+    Issuer(String),
+    /// The claim is missing or malformed.
+    Claim(&'static str),
+}
+
+impl fmt::Display for MaaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaaError::Token => write!(f, "malformed attestation token"),
+            MaaError::Algorithm(alg) => write!(f, "token algorithm {} is not supported", alg),
+            MaaError::KeySet => write!(f, "malformed JSON web key set"),
+            MaaError::UnknownKey => write!(f, "token signing key is not in the key set"),
+            MaaError::Signature => write!(f, "invalid token signature"),
+            MaaError::Expired => write!(f, "attestation token has expired"),
+            MaaError::NotYetValid => write!(f, "attestation token is not valid yet"),
+            MaaError::Issuer(issuer) => write!(f, "token issuer {} is not accepted", issuer),
+            MaaError::Claim(claim) => write!(f, "missing or invalid claim {}", claim),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum JwkKey {
+    /// The modulus and the public exponent, big-endian.
+    Rsa { n: Vec<u8>, e: Vec<u8> },
+    /// The DER certificate of the key, the first of its x5c chain.
+    Certificate(Vec<u8>),
+}
+
+impl JwkKey {
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        match self {
+            JwkKey::Rsa { n, e } => RsaPublicKeyComponents { n, e }
+                .verify(&RSA_PKCS1_2048_8192_SHA256, message, signature)
+                .is_ok(),
+            JwkKey::Certificate(cert) => webpki::EndEntityCert::from(cert)
+                .and_then(|cert| {
+                    cert.verify_signature(&webpki::RSA_PKCS1_2048_8192_SHA256, message, signature)
+                })
+                .is_ok(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Jwk {
+    kid: Option<String>,
+    key: JwkKey,
+}
+
+///
+/// The signing keys of an attestation provider, from the JSON web key set it publishes at
+/// its `/certs` endpoint.
+///
+/// Only the RSA signing keys are kept. A key is given by its modulus and exponent, or by an
+/// x5c certificate chain, of which only the key of the first certificate is used: the key
+/// set is trusted as a whole, so that the chain is not verified.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+impl Jwks {
+    ///
+    /// Parses a JSON web key set.
+    ///
+    /// # Parameters
+    ///
+    /// **jwks**
+    ///
+    /// The JSON document, as fetched by the caller.
+    ///
+    pub fn from_json(jwks: &[u8]) -> Result<Jwks, MaaError> {
+        let jwks: Value = serde_json::from_slice(jwks).map_err(|_| MaaError::KeySet)?;
+        let mut keys = Vec::new();
+        for key in jwks["keys"].as_array().ok_or(MaaError::KeySet)? {
+            if key["kty"] != "RSA" || !(key["use"].is_null() || key["use"] == "sig") {
+                continue;
+            }
+            let kid = match &key["kid"] {
+                Value::Null => None,
+                Value::String(kid) => Some(kid.clone()),
+                _ => return Err(MaaError::KeySet),
+            };
+            let rsa_key = if key["n"].is_string() || key["e"].is_string() {
+                let n = base64url_decode(&key["n"]).ok_or(MaaError::KeySet)?;
+                let e = base64url_decode(&key["e"]).ok_or(MaaError::KeySet)?;
+                JwkKey::Rsa {
+                    n: strip_leading_zeros(n),
+                    e: strip_leading_zeros(e),
+                }
+            } else {
+                let cert = key["x5c"][0]
+                    .as_str()
+                    .and_then(|cert| base64::decode(cert).ok())
+                    .ok_or(MaaError::KeySet)?;
+                webpki::EndEntityCert::from(&cert).map_err(|_| MaaError::KeySet)?;
+                JwkKey::Certificate(cert)
+            };
+            keys.push(Jwk { kid, key: rsa_key });
+        }
+        Ok(Jwks { keys })
+    }
+
+    /// The number of RSA signing keys in the key set.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Finds the key with the key ID, or the only key of the set for a token without one.
+    fn find(&self, kid: Option<&str>) -> Option<&JwkKey> {
+        match kid {
+            Some(kid) => self
+                .keys
+                .iter()
+                .find(|key| key.kid.as_deref() == Some(kid))
+                .map(|key| &key.key),
+            None if self.keys.len() == 1 => Some(&self.keys[0].key),
+            None => None,
+        }
+    }
+}
+
+///
+/// The claims of a verified attestation token about the attested SGX enclave.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MaaClaims {
+    /// The attestation provider which issued the token, `iss`.
+    pub issuer: String,
+    /// When the token was issued, `iat`, in seconds since the Unix epoch.
+    pub issued_at: Option<i64>,
+    /// When the token becomes valid, `nbf`.
+    pub not_before: Option<i64>,
+    /// When the token expires, `exp`.
+    pub expires_at: i64,
+    /// The attestation type, `x-ms-attestation-type`, such as sgx.
+    pub attestation_type: String,
+    /// `x-ms-sgx-mrenclave`.
+    pub mr_enclave: [u8; 32],
+    /// `x-ms-sgx-mrsigner`.
+    pub mr_signer: [u8; 32],
+    /// `x-ms-sgx-is-debuggable`.
+    pub is_debuggable: bool,
+    /// `x-ms-sgx-product-id`.
+    pub product_id: u16,
+    /// `x-ms-sgx-svn`.
+    pub svn: u16,
+    /// The report data of the quote, `x-ms-sgx-report-data`.
+    pub report_data: [u8; 64],
+    /// The runtime data submitted with the quote, `x-ms-sgx-ehd`, whose hash MAA checks
+    /// against the report data. It is empty if no runtime data was submitted.
+    pub enclave_held_data: Vec<u8>,
+}
+
+impl MaaClaims {
+    fn from_json(claims: &Value) -> Result<MaaClaims, MaaError> {
+        let enclave_held_data = match &claims["x-ms-sgx-ehd"] {
+            Value::Null => Vec::new(),
+            ehd => base64url_decode(ehd).ok_or(MaaError::Claim("x-ms-sgx-ehd"))?,
+        };
+        Ok(MaaClaims {
+            issuer: string_claim(claims, "iss")?,
+            issued_at: optional_time_claim(claims, "iat")?,
+            not_before: optional_time_claim(claims, "nbf")?,
+            expires_at: claims["exp"].as_i64().ok_or(MaaError::Claim("exp"))?,
+            attestation_type: string_claim(claims, "x-ms-attestation-type")?,
+            mr_enclave: hex_claim(claims, "x-ms-sgx-mrenclave")?,
+            mr_signer: hex_claim(claims, "x-ms-sgx-mrsigner")?,
+            is_debuggable: claims["x-ms-sgx-is-debuggable"]
+                .as_bool()
+                .ok_or(MaaError::Claim("x-ms-sgx-is-debuggable"))?,
+            product_id: u16_claim(claims, "x-ms-sgx-product-id")?,
+            svn: u16_claim(claims, "x-ms-sgx-svn")?,
+            report_data: hex_claim(claims, "x-ms-sgx-report-data")?,
+            enclave_held_data,
+        })
+    }
+}
+
+///
+/// Verifies the attestation tokens of Microsoft Azure Attestation, or of a service which
+/// issues tokens in its format, offline against the key set of the attestation provider.
+///
+/// The caller fetches the key set and the current time, so that the verifier works the same
+/// in the enclave and in the application.
+///
+/// # Examples
+///
+/// ```ignore
+/// let jwks = Jwks::from_json(&fetch("https://sharedeus.eus.attest.azure.net/certs")?)?;
+/// let mut verifier = MaaVerifier::new(jwks);
+/// verifier.issuer("https://sharedeus.eus.attest.azure.net");
+///
+/// let claims = verifier.verify(&token, now)?;
+/// if claims.is_debuggable || claims.mr_enclave != expected_mr_enclave {
+///     return Err(..);
+/// }
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct MaaVerifier {
+    jwks: Jwks,
+    issuer: Option<String>,
+    leeway: i64,
+}
+
+impl MaaVerifier {
+    /// Creates a verifier of the tokens signed by a key of the key set, from any issuer and
+    /// without leeway for the validity period.
+    pub fn new(jwks: Jwks) -> MaaVerifier {
+        MaaVerifier {
+            jwks,
+            issuer: None,
+            leeway: 0,
+        }
+    }
+
+    /// Accepts only the tokens whose `iss` is the attestation provider URL.
+    pub fn issuer(&mut self, issuer: &str) -> &mut MaaVerifier {
+        self.issuer = Some(issuer.to_owned());
+        self
+    }
+
+    /// Allows for the clock skew between the attestation provider and the verifier, in
+    /// seconds, when `exp` and `nbf` are checked.
+    pub fn leeway(&mut self, leeway: i64) -> &mut MaaVerifier {
+        self.leeway = leeway.max(0);
+        self
+    }
+
+    ///
+    /// Verifies an attestation token.
+    ///
+    /// # Parameters
+    ///
+    /// **token**
+    ///
+    /// The JWT, in compact serialization.
+    ///
+    /// **now**
+    ///
+    /// The current time, in seconds since the Unix epoch, at which the token must be valid.
+    ///
+    /// # Return value
+    ///
+    /// The SGX claims of the token. The caller decides whether the enclave is trusted from
+    /// them, and checks that the runtime data or the report data binds what it expects.
+    ///
+    pub fn verify(&self, token: &str, now: i64) -> Result<MaaClaims, MaaError> {
+        let parts: Vec<&str> = token.split('.').collect();
+        if parts.len() != 3 {
+            return Err(MaaError::Token);
+        }
+        let header = json_part(parts[0])?;
+        let alg = header["alg"].as_str().ok_or(MaaError::Token)?;
+        if alg != MAA_TOKEN_ALGORITHM {
+            return Err(MaaError::Algorithm(alg.to_owned()));
+        }
+        // Header parameters which must be understood are not supported.
+        if !header["crit"].is_null() {
+            return Err(MaaError::Token);
+        }
+        let key = match &header["kid"] {
+            Value::Null => self.jwks.find(None),
+            Value::String(kid) => self.jwks.find(Some(kid)),
+            _ => return Err(MaaError::Token),
+        }
+        .ok_or(MaaError::UnknownKey)?;
+
+        let signature = base64::decode_config(parts[2], base64::URL_SAFE_NO_PAD)
+            .map_err(|_| MaaError::Token)?;
+        let signed = &token[..parts[0].len() + 1 + parts[1].len()];
+        if !key.verify(signed.as_bytes(), &signature) {
+            return Err(MaaError::Signature);
+        }
+
+        let claims = MaaClaims::from_json(&json_part(parts[1])?)?;
+        if now >= claims.expires_at.saturating_add(self.leeway) {
+            return Err(MaaError::Expired);
+        }
+        if let Some(not_before) = claims.not_before {
+            if now < not_before.saturating_sub(self.leeway) {
+                return Err(MaaError::NotYetValid);
+            }
+        }
+        if let Some(issuer) = &self.issuer {
+            if claims.issuer != *issuer {
+                return Err(MaaError::Issuer(claims.issuer));
+            }
+        }
+        Ok(claims)
+    }
+}
+
+fn base64url_decode(value: &Value) -> Option<Vec<u8>> {
+    base64::decode_config(value.as_str()?, base64::URL_SAFE_NO_PAD).ok()
+}
+
+fn strip_leading_zeros(mut bytes: Vec<u8>) -> Vec<u8> {
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    bytes.drain(..zeros);
+    bytes
+}
+
+fn json_part(part: &str) -> Result<Value, MaaError> {
+    let json = base64::decode_config(part, base64::URL_SAFE_NO_PAD).map_err(|_| MaaError::Token)?;
+    match serde_json::from_slice(&json) {
+        Ok(Value::Object(object)) => Ok(Value::Object(object)),
+        _ => Err(MaaError::Token),
+    }
+}
+
+fn string_claim(claims: &Value, name: &'static str) -> Result<String, MaaError> {
+    claims[name]
+        .as_str()
+        .map(|claim| claim.to_owned())
+        .ok_or(MaaError::Claim(name))
+}
+
+fn optional_time_claim(claims: &Value, name: &'static str) -> Result<Option<i64>, MaaError> {
+    match &claims[name] {
+        Value::Null => Ok(None),
+        claim => claim.as_i64().map(Some).ok_or(MaaError::Claim(name)),
+    }
+}
+
+fn u16_claim(claims: &Value, name: &'static str) -> Result<u16, MaaError> {
+    claims[name]
+        .as_u64()
+        .and_then(|claim| u16::try_from(claim).ok())
+        .ok_or(MaaError::Claim(name))
+}
+
+fn hex_claim<const N: usize>(claims: &Value, name: &'static str) -> Result<[u8; N], MaaError> {
+    let hex = claims[name].as_str().ok_or(MaaError::Claim(name))?;
+    if hex.len() != N * 2 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return Err(MaaError::Claim(name));
+    }
+    let mut bytes = [0_u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte =
+            u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| MaaError::Claim(name))?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+    use serde_json::json;
+
+    // 2026-01-01T00:00:00Z.
+    const NOW: i64 = 1_767_225_600;
+
+    const AZURE_JWKS: &[u8] = include_bytes!("../testdata/maa_azure_jwks.json");
+    const AZURE_TOKEN: &str = include_str!("../testdata/maa_azure_token.jwt");
+    const AZURE_ISSUER: &str = "https://sharedeus.eus.attest.azure.net";
+    // The iat of the Azure token.
+    const AZURE_ISSUED_AT: i64 = 1_695_737_747;
+
+    const JWKS: &[u8] = include_bytes!("../testdata/maa_jwks.json");
+    const SIGNING_KEY: &[u8] = include_bytes!("../testdata/maa_signing_key.pk8");
+    const ISSUER: &str = "https://test.attest.example";
+
+    fn encode(data: &[u8]) -> String {
+        base64::encode_config(data, base64::URL_SAFE_NO_PAD)
+    }
+
+    fn sign_token(header: &Value, claims: &Value) -> String {
+        let signed = format!(
+            "{}.{}",
+            encode(header.to_string().as_bytes()),
+            encode(claims.to_string().as_bytes())
+        );
+        let key = RsaKeyPair::from_pkcs8(SIGNING_KEY).unwrap();
+        let mut signature = vec![0; key.public_modulus_len()];
+        key.sign(
+            &RSA_PKCS1_SHA256,
+            &SystemRandom::new(),
+            signed.as_bytes(),
+            &mut signature,
+        )
+        .unwrap();
+        format!("{}.{}", signed, encode(&signature))
+    }
+
+    fn claims() -> Value {
+        json!({
+            "iss": ISSUER,
+            "iat": NOW - 60,
+            "nbf": NOW - 60,
+            "exp": NOW + 3600,
+            "x-ms-attestation-type": "sgx",
+            "x-ms-sgx-mrenclave": "ee".repeat(32),
+            "x-ms-sgx-mrsigner": "55".repeat(32),
+            "x-ms-sgx-is-debuggable": false,
+            "x-ms-sgx-product-id": 7,
+            "x-ms-sgx-svn": 3,
+            "x-ms-sgx-report-data": "ab".repeat(32) + &"00".repeat(32),
+            "x-ms-sgx-ehd": encode(b"runtime data"),
+        })
+    }
+
+    fn token(kid: &str, claims: &Value) -> String {
+        sign_token(&json!({"alg": "RS256", "kid": kid, "typ": "JWT"}), claims)
+    }
+
+    fn verifier() -> MaaVerifier {
+        let mut verifier = MaaVerifier::new(Jwks::from_json(JWKS).unwrap());
+        verifier.issuer(ISSUER);
+        verifier
+    }
+
+    #[test]
+    fn parse_jwks() {
+        // The EC key is not kept.
+        assert_eq!(Jwks::from_json(JWKS).unwrap().len(), 2);
+        assert_eq!(Jwks::from_json(AZURE_JWKS).unwrap().len(), 3);
+        assert_eq!(
+            Jwks::from_json(br#"{"keys":[{"kty":"RSA","kid":"k","n":"AQAB"}]}"#).err(),
+            Some(MaaError::KeySet)
+        );
+    }
+
+    #[test]
+    fn verify_azure_token() {
+        let mut verifier = MaaVerifier::new(Jwks::from_json(AZURE_JWKS).unwrap());
+        verifier.issuer(AZURE_ISSUER);
+        let claims = verifier
+            .verify(AZURE_TOKEN.trim(), AZURE_ISSUED_AT + 60)
+            .unwrap();
+        assert_eq!(claims.attestation_type, "sgx");
+        assert_eq!(claims.mr_enclave[..4], [0xf5, 0x67, 0x35, 0xaa]);
+        assert_eq!(claims.mr_signer[..4], [0xa5, 0x95, 0xc6, 0xc5]);
+        assert!(!claims.is_debuggable);
+        assert_eq!(claims.product_id, 1);
+        assert_eq!(claims.svn, 1);
+        assert_eq!(claims.report_data[..4], [0x94, 0xba, 0x44, 0xf3]);
+        assert_eq!(claims.report_data[32..], [0; 32]);
+        assert_eq!(claims.enclave_held_data, b"This is some runtime data");
+    }
+
+    #[test]
+    fn verify_token() {
+        for kid in &["test-maa-jwk", "test-maa-x5c"] {
+            let claims = verifier().verify(&token(kid, &claims()), NOW).unwrap();
+            assert_eq!(claims.issuer, ISSUER);
+            assert_eq!(claims.expires_at, NOW + 3600);
+            assert_eq!(claims.mr_enclave, [0xee; 32]);
+            assert_eq!(claims.mr_signer, [0x55; 32]);
+            assert_eq!(claims.product_id, 7);
+            assert_eq!(claims.svn, 3);
+            assert_eq!(claims.report_data[..32], [0xab; 32]);
+            assert_eq!(claims.enclave_held_data, b"runtime data");
+        }
+    }
+
+    #[test]
+    fn reject_tampered_token() {
+        let token = token("test-maa-jwk", &claims());
+        let mut parts: Vec<String> = token.split('.').map(|part| part.to_owned()).collect();
+        let mut forged = claims();
+        forged["x-ms-sgx-is-debuggable"] = json!(true);
+        parts[1] = encode(forged.to_string().as_bytes());
+        assert_eq!(
+            verifier().verify(&parts.join("."), NOW).err(),
+            Some(MaaError::Signature)
+        );
+    }
+
+    #[test]
+    fn reject_unknown_key() {
+        for kid in &["test-maa-ec", "other-key"] {
+            assert_eq!(
+                verifier().verify(&token(kid, &claims()), NOW).err(),
+                Some(MaaError::UnknownKey)
+            );
+        }
+    }
+
+    #[test]
+    fn reject_other_algorithm() {
+        let token = sign_token(&json!({"alg": "none", "kid": "test-maa-jwk"}), &claims());
+        assert_eq!(
+            verifier().verify(&token, NOW).err(),
+            Some(MaaError::Algorithm("none".to_owned()))
+        );
+    }
+
+    #[test]
+    fn check_validity_period() {
+        let token = token("test-maa-jwk", &claims());
+        assert_eq!(
+            verifier().verify(&token, NOW + 3600).err(),
+            Some(MaaError::Expired)
+        );
+        assert_eq!(
+            verifier().verify(&token, NOW - 61).err(),
+            Some(MaaError::NotYetValid)
+        );
+        let mut verifier = verifier();
+        verifier.leeway(300);
+        assert!(verifier.verify(&token, NOW + 3600).is_ok());
+        assert!(verifier.verify(&token, NOW - 61).is_ok());
+    }
+
+    #[test]
+    fn reject_other_issuer() {
+        let mut other = claims();
+        other["iss"] = json!("https://other.attest.example");
+        assert_eq!(
+            verifier().verify(&token("test-maa-jwk", &other), NOW).err(),
+            Some(MaaError::Issuer("https://other.attest.example".to_owned()))
+        );
+    }
+
+    #[test]
+    fn reject_invalid_claims() {
+        let mut missing = claims();
+        missing
+            .as_object_mut()
+            .unwrap()
+            .remove("x-ms-sgx-mrenclave");
+        assert_eq!(
+            verifier()
+                .verify(&token("test-maa-jwk", &missing), NOW)
+                .err(),
+            Some(MaaError::Claim("x-ms-sgx-mrenclave"))
+        );
+        let mut short = claims();
+        short["x-ms-sgx-report-data"] = json!("ab".repeat(32));
+        assert_eq!(
+            verifier().verify(&token("test-maa-jwk", &short), NOW).err(),
+            Some(MaaError::Claim("x-ms-sgx-report-data"))
+        );
+    }
+
+    #[test]
+    fn reject_malformed_token() {
+        assert_eq!(verifier().verify("a.b", NOW).err(), Some(MaaError::Token));
+        let token = token("test-maa-jwk", &claims()) + ".";
+        assert_eq!(verifier().verify(&token, NOW).err(), Some(MaaError::Token));
+    }
+}
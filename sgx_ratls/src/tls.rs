@@ -0,0 +1,73 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::verify::RaTlsVerifier;
+use rustls::{
+    Certificate, ClientCertVerified, ClientCertVerifier, DistinguishedNames, RootCertStore,
+    ServerCertVerified, ServerCertVerifier, TLSError,
+};
+use std::prelude::v1::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "mesalock_sgx")]
+use std::untrusted::time::SystemTimeEx;
+
+impl RaTlsVerifier {
+    // The time is untrusted inside the enclave. It only limits how long a certificate, the
+    // IAS signing certificate and the DCAP collateral are accepted.
+    fn verify_presented_certs(&self, certs: &[Certificate]) -> Result<(), TLSError> {
+        let cert = certs.first().ok_or(TLSError::NoCertificatesPresented)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| TLSError::FailedToGetCurrentTime)?
+            .as_secs() as i64;
+        self.verify_certificate(&cert.0, now)
+            .map(|_| ())
+            .map_err(|e| TLSError::General(e.to_string()))
+    }
+}
+
+impl ServerCertVerifier for RaTlsVerifier {
+    // The server is identified by its attested enclave, not by its name.
+    fn verify_server_cert(
+        &self,
+        _roots: &RootCertStore,
+        presented_certs: &[Certificate],
+        _dns_name: webpki::DNSNameRef,
+        _ocsp_response: &[u8],
+    ) -> Result<ServerCertVerified, TLSError> {
+        self.verify_presented_certs(presented_certs)?;
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+impl ClientCertVerifier for RaTlsVerifier {
+    fn client_auth_root_subjects(
+        &self,
+        _sni: Option<&webpki::DNSName>,
+    ) -> Option<DistinguishedNames> {
+        Some(DistinguishedNames::new())
+    }
+
+    fn verify_client_cert(
+        &self,
+        presented_certs: &[Certificate],
+        _sni: Option<&webpki::DNSName>,
+    ) -> Result<ClientCertVerified, TLSError> {
+        self.verify_presented_certs(presented_certs)?;
+        Ok(ClientCertVerified::assertion())
+    }
+}
@@ -0,0 +1,555 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::attestation::{verify_ias_report, DcapVerifier, EpidEvidence};
+use crate::cert::{parse_asn1_time, report_data_for_key_info};
+use crate::evidence::RaTlsEvidence;
+use sgx_dcap_verify::{Quote, QuoteCollateral, QuoteVerifier, TcbStatus};
+use sgx_types::*;
+use std::fmt;
+use std::prelude::v1::*;
+use std::sync::Arc;
+use yasna::{Tag, TagClass};
+
+///
+/// Decides whether the attested enclave is trusted, from its verified report.
+///
+/// It is implemented for closures, so that any identity check can be plugged in. Inside the
+/// enclave, an sgx_tdh::EnclaveIdentityPolicy is used as
+/// `move |report: &sgx_report_body_t| policy.verify(report).is_ok()`.
+///
+pub trait IdentityPolicy: Send + Sync {
+    fn verify(&self, report: &sgx_report_body_t) -> bool;
+}
+
+impl<F> IdentityPolicy for F
+where
+    F: Fn(&sgx_report_body_t) -> bool + Send + Sync,
+{
+    fn verify(&self, report: &sgx_report_body_t) -> bool {
+        self(report)
+    }
+}
+
+///
+/// Supplies the collateral a DCAP quote is verified against, for example from a PCCS
+/// cache keyed by quote.pck_extension().fmspc.
+///
+/// A QuoteCollateral is a provider which always returns itself.
+///
+pub trait CollateralProvider: Send + Sync {
+    fn collateral(&self, quote: &Quote) -> SgxQuote3Result<QuoteCollateral>;
+}
+
+impl CollateralProvider for QuoteCollateral {
+    fn collateral(&self, _quote: &Quote) -> SgxQuote3Result<QuoteCollateral> {
+        Ok(self.clone())
+    }
+}
+
+///
+/// The reason an RA-TLS certificate, or attestation evidence, is rejected.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RaTlsError {
+    /// The certificate is malformed, or it is not signed by its own key.
+    Certificate,
+    /// The certificate is not valid at the current time.
+    Expired,
+    /// The certificate carries no evidence, or evidence the verifier is not configured for.
+    Evidence,
+    /// The report data of the quote does not bind the certificate key.
+    ReportData,
+    /// The DCAP quote or its collateral failed verification.
+    Quote(sgx_quote3_error_t),
+    /// The TCB status of the DCAP quote is not accepted.
+    TcbStatus(TcbStatus),
+    /// The IAS report is malformed, or it is not signed by a certificate of the IAS root CA.
+    IasReport,
+    /// The quote status of the IAS report is not accepted.
+    IasQuoteStatus(String),
+    /// The identity policy rejects the enclave.
+    IdentityPolicy,
+    /// The simulated quote is malformed, or it is not signed by the simulation key.
+    SimulatedQuote,
+}
+
+impl fmt::Display for RaTlsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaTlsError::Certificate => write!(f, "invalid RA-TLS certificate"),
+            RaTlsError::Expired => write!(f, "RA-TLS certificate is not valid now"),
+            RaTlsError::Evidence => write!(f, "unsupported RA-TLS evidence"),
+            RaTlsError::ReportData => write!(f, "quote is not bound to the certificate key"),
+            RaTlsError::Quote(e) => write!(f, "quote verification failed: {}", e),
+            RaTlsError::TcbStatus(status) => {
+                write!(f, "TCB status {} is not accepted", status.as_str())
+            }
+            RaTlsError::IasReport => write!(f, "invalid IAS attestation report"),
+            RaTlsError::IasQuoteStatus(status) => {
+                write!(f, "IAS quote status {} is not accepted", status)
+            }
+            RaTlsError::IdentityPolicy => write!(f, "enclave identity is not trusted"),
+            RaTlsError::SimulatedQuote => write!(f, "invalid simulated quote"),
+        }
+    }
+}
+
+///
+/// Verifies RA-TLS certificates: their evidence, the binding of the evidence to the
+/// certificate key, and the identity of the attested enclave.
+///
+/// It implements rustls::ServerCertVerifier and rustls::ClientCertVerifier, so that either
+/// side of a TLS connection, or both, can require an RA-TLS certificate.
+///
+/// # Examples
+///
+/// ```ignore
+/// let policy = move |report: &sgx_report_body_t| report.mr_enclave.m == expected_mr_enclave;
+/// let mut verifier = RaTlsVerifier::new(policy);
+/// verifier
+///     .dcap(QuoteVerifier::new(INTEL_SGX_ROOT_CA)?, collateral)
+///     .accept_tcb_status(TcbStatus::SWHardeningNeeded);
+///
+/// let mut config = rustls::ClientConfig::new();
+/// config.dangerous().set_certificate_verifier(Arc::new(verifier));
+/// ```
+///
+#[derive(Clone)]
+pub struct RaTlsVerifier {
+    policy: Arc<dyn IdentityPolicy>,
+    dcap: Option<DcapVerifier>,
+    tcb_statuses: Vec<TcbStatus>,
+    ias_root_ca: Option<Vec<u8>>,
+    ias_quote_statuses: Vec<String>,
+}
+
+impl RaTlsVerifier {
+    ///
+    /// Creates a verifier which accepts no evidence until DCAP or IAS verification is
+    /// configured.
+    ///
+    pub fn new<P: IdentityPolicy + 'static>(policy: P) -> RaTlsVerifier {
+        RaTlsVerifier {
+            policy: Arc::new(policy),
+            dcap: None,
+            tcb_statuses: vec![TcbStatus::UpToDate],
+            ias_root_ca: None,
+            ias_quote_statuses: vec!["OK".to_owned()],
+        }
+    }
+
+    /// Accepts DCAP quotes, verified with the quote verifier against the collateral of
+    /// the provider.
+    pub fn dcap<C: CollateralProvider + 'static>(
+        &mut self,
+        verifier: QuoteVerifier,
+        collateral: C,
+    ) -> &mut RaTlsVerifier {
+        self.dcap = Some(DcapVerifier::new(verifier, collateral));
+        self
+    }
+
+    /// Accepts DCAP quotes with the TCB status, in addition to UpToDate.
+    pub fn accept_tcb_status(&mut self, status: TcbStatus) -> &mut RaTlsVerifier {
+        if !self.tcb_statuses.contains(&status) {
+            self.tcb_statuses.push(status);
+        }
+        self
+    }
+
+    /// Accepts IAS reports signed by a certificate of the IAS root CA, given in DER.
+    pub fn ias(&mut self, root_ca: &[u8]) -> &mut RaTlsVerifier {
+        self.ias_root_ca = Some(root_ca.to_vec());
+        self
+    }
+
+    /// Accepts IAS reports with the quote status, such as SW_HARDENING_NEEDED, in addition
+    /// to OK.
+    pub fn accept_ias_quote_status(&mut self, status: &str) -> &mut RaTlsVerifier {
+        if !self.ias_quote_statuses.iter().any(|s| s == status) {
+            self.ias_quote_statuses.push(status.to_owned());
+        }
+        self
+    }
+
+    ///
+    /// Verifies an RA-TLS certificate.
+    ///
+    /// # Parameters
+    ///
+    /// **cert**
+    ///
+    /// The DER certificate.
+    ///
+    /// **now**
+    ///
+    /// The current time, in seconds since the Unix epoch, at which the certificate, the
+    /// IAS signing certificate and the DCAP collateral must be valid.
+    ///
+    /// # Return value
+    ///
+    /// The verified report of the attested enclave.
+    ///
+    pub fn verify_certificate(
+        &self,
+        cert: &[u8],
+        now: i64,
+    ) -> Result<sgx_report_body_t, RaTlsError> {
+        let parsed = parse_certificate(cert).ok_or(RaTlsError::Certificate)?;
+        webpki::EndEntityCert::from(cert)
+            .and_then(|cert| {
+                cert.verify_signature(&webpki::ECDSA_P256_SHA256, &parsed.tbs, &parsed.signature)
+            })
+            .map_err(|_| RaTlsError::Certificate)?;
+        if now < parsed.not_before || now > parsed.not_after {
+            return Err(RaTlsError::Expired);
+        }
+
+        let evidence =
+            RaTlsEvidence::from_extensions(&parsed.extensions).ok_or(RaTlsError::Evidence)?;
+        let report = match evidence {
+            RaTlsEvidence::Dcap { quote } => self.verify_dcap(&quote, now)?,
+            RaTlsEvidence::Ias {
+                report,
+                signature,
+                signing_cert,
+            } => self.verify_ias(
+                EpidEvidence {
+                    report,
+                    signature,
+                    signing_cert,
+                },
+                now,
+            )?,
+        };
+
+        if report.report_data.d != report_data_for_key_info(&parsed.public_key_info).d {
+            return Err(RaTlsError::ReportData);
+        }
+        if !self.policy.verify(&report) {
+            return Err(RaTlsError::IdentityPolicy);
+        }
+        Ok(report)
+    }
+
+    fn verify_dcap(&self, quote: &[u8], now: i64) -> Result<sgx_report_body_t, RaTlsError> {
+        let dcap = self.dcap.as_ref().ok_or(RaTlsError::Evidence)?;
+        dcap.verify_quote(quote, &self.tcb_statuses, now)
+    }
+
+    fn verify_ias(
+        &self,
+        evidence: EpidEvidence,
+        now: i64,
+    ) -> Result<sgx_report_body_t, RaTlsError> {
+        let root_ca = self.ias_root_ca.as_ref().ok_or(RaTlsError::Evidence)?;
+        verify_ias_report(root_ca, &self.ias_quote_statuses, &evidence, now)
+    }
+}
+
+struct ParsedCertificate {
+    tbs: Vec<u8>,
+    signature: Vec<u8>,
+    public_key_info: Vec<u8>,
+    not_before: i64,
+    not_after: i64,
+    extensions: Vec<(Vec<u64>, Vec<u8>)>,
+}
+
+fn read_time(reader: yasna::BERReader) -> yasna::ASN1Result<Option<i64>> {
+    let time = reader.read_tagged_der()?;
+    let tag = time.tag();
+    if tag.tag_class != TagClass::Universal || tag.tag_number > 0xff {
+        return Ok(None);
+    }
+    Ok(parse_asn1_time(tag.tag_number as u8, time.value()))
+}
+
+fn parse_certificate(cert: &[u8]) -> Option<ParsedCertificate> {
+    let (tbs, signature) = yasna::parse_der(cert, |reader| {
+        reader.read_sequence(|reader| {
+            let tbs = reader.next().read_der()?;
+            reader.next().read_der()?;
+            let (signature, _) = reader.next().read_bitvec_bytes()?;
+            Ok((tbs, signature))
+        })
+    })
+    .ok()?;
+
+    let (validity, public_key_info, extensions) = yasna::parse_der(&tbs, |reader| {
+        reader.read_sequence(|reader| {
+            reader
+                .next()
+                .read_tagged(Tag::context(0), |reader| reader.read_u8())?;
+            // The serial number, the signature algorithm and the issuer.
+            reader.next().read_der()?;
+            reader.next().read_der()?;
+            reader.next().read_der()?;
+            let validity = reader.next().read_sequence(|reader| {
+                let not_before = read_time(reader.next())?;
+                let not_after = read_time(reader.next())?;
+                Ok(not_before.zip(not_after))
+            })?;
+            // The subject.
+            reader.next().read_der()?;
+            let public_key_info = reader.next().read_der()?;
+            let extensions = reader.next().read_tagged(Tag::context(3), |reader| {
+                reader.collect_sequence_of(|reader| {
+                    reader.read_sequence(|reader| {
+                        let oid = reader.next().read_oid()?;
+                        reader.read_default(false, |reader| reader.read_bool())?;
+                        let value = reader.next().read_bytes()?;
+                        Ok((oid.components().clone(), value))
+                    })
+                })
+            })?;
+            Ok((validity, public_key_info, extensions))
+        })
+    })
+    .ok()?;
+
+    let (not_before, not_after) = validity?;
+    Some(ParsedCertificate {
+        tbs,
+        signature,
+        public_key_info,
+        not_before,
+        not_after,
+        extensions,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cert::{
+        ec_private_key_pkcs8, public_key_report_data, CertificateKey, RaTlsCertificateBuilder,
+    };
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+
+    // 2026-01-01T00:00:00Z, when the test PKI and collateral are valid.
+    const NOW: i64 = 1_767_225_600;
+
+    const ENCLAVE_KEY: &[u8] = include_bytes!("../testdata/enclave_key.bin");
+    const ROOT_CA: &[u8] = include_bytes!("../testdata/root_ca.pem");
+    const QUOTE: &[u8] = include_bytes!("../testdata/quote.bin");
+    const QUOTE_OTHER_KEY: &[u8] = include_bytes!("../testdata/quote_other_key.bin");
+    const QUOTE_SW_HARDENING: &[u8] = include_bytes!("../testdata/quote_sw_hardening.bin");
+    const IAS_ROOT_CA: &[u8] = include_bytes!("../testdata/ias_root_ca.der");
+
+    struct TestKey(EcdsaKeyPair);
+
+    impl TestKey {
+        fn new() -> TestKey {
+            let private_key = ENCLAVE_KEY[..32].try_into().unwrap();
+            let public_key = ENCLAVE_KEY[32..].try_into().unwrap();
+            let pkcs8 = ec_private_key_pkcs8(private_key, public_key);
+            TestKey(EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &pkcs8).unwrap())
+        }
+    }
+
+    impl CertificateKey for TestKey {
+        fn public_key(&self) -> [u8; 64] {
+            self.0.public_key().as_ref()[1..].try_into().unwrap()
+        }
+
+        fn sign(&self, data: &[u8]) -> SgxResult<[u8; 64]> {
+            let signature = self
+                .0
+                .sign(&SystemRandom::new(), data)
+                .map_err(|_| sgx_status_t::SGX_ERROR_UNEXPECTED)?;
+            Ok(signature.as_ref().try_into().unwrap())
+        }
+    }
+
+    pub(crate) fn collateral() -> QuoteCollateral {
+        let signing_chain = include_bytes!("../testdata/tcb_signing_chain.pem").to_vec();
+        QuoteCollateral {
+            pck_crl_issuer_chain: include_bytes!("../testdata/pck_crl_issuer_chain.pem").to_vec(),
+            root_ca_crl: include_bytes!("../testdata/root_ca_crl.der").to_vec(),
+            pck_crl: include_bytes!("../testdata/pck_crl.pem").to_vec(),
+            tcb_info_issuer_chain: signing_chain.clone(),
+            tcb_info: include_bytes!("../testdata/tcb_info.json").to_vec(),
+            qe_identity_issuer_chain: signing_chain,
+            qe_identity: include_bytes!("../testdata/qe_identity.json").to_vec(),
+        }
+    }
+
+    fn dcap_certificate(quote: &[u8]) -> Vec<u8> {
+        let evidence = RaTlsEvidence::Dcap {
+            quote: quote.to_vec(),
+        };
+        RaTlsCertificateBuilder::new(evidence)
+            .build(&TestKey::new())
+            .unwrap()
+    }
+
+    fn ias_certificate(report: &[u8], signature: &[u8]) -> Vec<u8> {
+        let evidence = RaTlsEvidence::Ias {
+            report: report.to_vec(),
+            signature: signature.to_vec(),
+            signing_cert: include_bytes!("../testdata/ias_signing_cert.der").to_vec(),
+        };
+        RaTlsCertificateBuilder::new(evidence)
+            .build(&TestKey::new())
+            .unwrap()
+    }
+
+    fn trusted_enclave(report: &sgx_report_body_t) -> bool {
+        report.mr_enclave.m == [0xee; 32] && report.mr_signer.m == [0x55; 32]
+    }
+
+    fn verifier() -> RaTlsVerifier {
+        let mut verifier = RaTlsVerifier::new(trusted_enclave);
+        verifier
+            .dcap(QuoteVerifier::new(ROOT_CA).unwrap(), collateral())
+            .ias(IAS_ROOT_CA);
+        verifier
+    }
+
+    #[test]
+    fn report_data_binds_key() {
+        let key = TestKey::new();
+        let report = Quote::parse(QUOTE).unwrap().report_body;
+        assert_eq!(
+            public_key_report_data(&key.public_key()).d,
+            report.report_data.d
+        );
+    }
+
+    #[test]
+    fn verify_dcap_certificate() {
+        let report = verifier()
+            .verify_certificate(&dcap_certificate(QUOTE), NOW)
+            .unwrap();
+        assert_eq!(report.isv_prod_id, 7);
+        assert_eq!(report.isv_svn, 3);
+    }
+
+    #[test]
+    fn verify_ias_certificate() {
+        let cert = ias_certificate(
+            include_bytes!("../testdata/ias_report.json"),
+            include_bytes!("../testdata/ias_report.sig"),
+        );
+        let report = verifier().verify_certificate(&cert, NOW).unwrap();
+        assert_eq!(report.mr_enclave.m, [0xee; 32]);
+    }
+
+    #[test]
+    fn reject_quote_for_other_key() {
+        assert_eq!(
+            verifier()
+                .verify_certificate(&dcap_certificate(QUOTE_OTHER_KEY), NOW)
+                .err(),
+            Some(RaTlsError::ReportData)
+        );
+    }
+
+    #[test]
+    fn reject_untrusted_enclave() {
+        let verifier = {
+            let mut verifier = RaTlsVerifier::new(|report: &sgx_report_body_t| report.isv_svn >= 4);
+            verifier.dcap(QuoteVerifier::new(ROOT_CA).unwrap(), collateral());
+            verifier
+        };
+        assert_eq!(
+            verifier
+                .verify_certificate(&dcap_certificate(QUOTE), NOW)
+                .err(),
+            Some(RaTlsError::IdentityPolicy)
+        );
+    }
+
+    #[test]
+    fn accept_tcb_status() {
+        let cert = dcap_certificate(QUOTE_SW_HARDENING);
+        let mut verifier = verifier();
+        assert_eq!(
+            verifier.verify_certificate(&cert, NOW).err(),
+            Some(RaTlsError::TcbStatus(TcbStatus::SWHardeningNeeded))
+        );
+        verifier.accept_tcb_status(TcbStatus::SWHardeningNeeded);
+        assert!(verifier.verify_certificate(&cert, NOW).is_ok());
+    }
+
+    #[test]
+    fn accept_ias_quote_status() {
+        let cert = ias_certificate(
+            include_bytes!("../testdata/ias_report_out_of_date.json"),
+            include_bytes!("../testdata/ias_report_out_of_date.sig"),
+        );
+        let mut verifier = verifier();
+        assert_eq!(
+            verifier.verify_certificate(&cert, NOW).err(),
+            Some(RaTlsError::IasQuoteStatus("GROUP_OUT_OF_DATE".to_owned()))
+        );
+        verifier.accept_ias_quote_status("GROUP_OUT_OF_DATE");
+        assert!(verifier.verify_certificate(&cert, NOW).is_ok());
+    }
+
+    #[test]
+    fn reject_forged_ias_report() {
+        let cert = ias_certificate(
+            include_bytes!("../testdata/ias_report_out_of_date.json"),
+            include_bytes!("../testdata/ias_report.sig"),
+        );
+        assert_eq!(
+            verifier().verify_certificate(&cert, NOW).err(),
+            Some(RaTlsError::IasReport)
+        );
+    }
+
+    #[test]
+    fn reject_unconfigured_evidence() {
+        let verifier = RaTlsVerifier::new(trusted_enclave);
+        assert_eq!(
+            verifier
+                .verify_certificate(&dcap_certificate(QUOTE), NOW)
+                .err(),
+            Some(RaTlsError::Evidence)
+        );
+    }
+
+    #[test]
+    fn reject_tampered_certificate() {
+        let mut cert = dcap_certificate(QUOTE);
+        // A byte of the enclave report in the quote extension.
+        let offset = cert.len() - 2000;
+        cert[offset] ^= 1;
+        assert_eq!(
+            verifier().verify_certificate(&cert, NOW).err(),
+            Some(RaTlsError::Certificate)
+        );
+    }
+
+    #[test]
+    fn reject_expired_certificate() {
+        let cert = RaTlsCertificateBuilder::new(RaTlsEvidence::Dcap {
+            quote: QUOTE.to_vec(),
+        })
+        .validity(NOW - 86_400, NOW - 1)
+        .build(&TestKey::new())
+        .unwrap();
+        assert_eq!(
+            verifier().verify_certificate(&cert, NOW).err(),
+            Some(RaTlsError::Expired)
+        );
+    }
+}
@@ -0,0 +1,188 @@
+#!/usr/bin/env python3
+# Licensed to the Apache Software Foundation (ASF) under one
+# or more contributor license agreements.  See the NOTICE file
+# distributed with this work for additional information
+# regarding copyright ownership.  The ASF licenses this file
+# to you under the Apache License, Version 2.0 (the
+# "License"); you may not use this file except in compliance
+# with the License.  You may obtain a copy of the License at
+#
+#   http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing,
+# software distributed under the License is distributed on an
+# "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+# KIND, either express or implied.  See the License for the
+# specific language governing permissions and limitations
+# under the License..
+
+# Generates the recorded evidence for the sgx_ratls unit tests: DCAP quotes and IAS
+# reports whose report data binds the fixed enclave key in enclave_key.bin.
+# The DCAP fixtures reuse the test PKI of sgx_dcap_verify/testdata/gen_fixtures.py.
+#
+# It also generates the RSA key the MAA token tests sign with, and the JWKS which
+# publishes it. maa_azure_jwks.json and maa_azure_token.jwt are recorded from the
+# shared Azure attestation provider and are not generated.
+
+import base64
+import hashlib
+import json
+import os
+import struct
+import sys
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec, padding, rsa
+
+sys.path.insert(0, os.path.join(os.path.dirname(os.path.abspath(__file__)),
+                                "..", "..", "sgx_dcap_verify", "testdata"))
+import gen_fixtures as dcap  # noqa: E402
+
+OUT = os.path.dirname(os.path.abspath(__file__))
+ENCLAVE_KEY = int.from_bytes(hashlib.sha256(b"sgx_ratls test enclave key").digest(), "big")
+
+
+def report_data(key):
+    spki = key.public_key().public_bytes(
+        serialization.Encoding.DER, serialization.PublicFormat.SubjectPublicKeyInfo
+    )
+    return hashlib.sha256(spki).digest() + bytes(32)
+
+
+def ias_cert(subject, key, issuer, issuer_key, ca, serial):
+    return (
+        x509.CertificateBuilder()
+        .subject_name(dcap.name(subject))
+        .issuer_name(dcap.name(issuer))
+        .public_key(key.public_key())
+        .serial_number(serial)
+        .not_valid_before(dcap.NOT_BEFORE)
+        .not_valid_after(dcap.NOT_AFTER)
+        .add_extension(x509.BasicConstraints(ca=ca, path_length=None), critical=True)
+        .sign(issuer_key, hashes.SHA256())
+    )
+
+
+def ias_report(status, data, key):
+    header = struct.pack("<HHIHHI", 2, 1, 0xB7A, 12, 11, 0) + bytes(32)
+    enclave = dcap.report_body(
+        0, 0x07, 0x03, bytes([0xEE] * 32), bytes([0x55] * 32), 7, 3, data
+    )
+    report = json.dumps({
+        "id": "165171271757108173876306223827987629752",
+        "timestamp": "2024-06-01T00:00:00.000000",
+        "version": 4,
+        "epidPseudonym": base64.b64encode(bytes(range(128))).decode(),
+        "isvEnclaveQuoteStatus": status,
+        "isvEnclaveQuoteBody": base64.b64encode(header + enclave).decode(),
+    }, separators=(",", ":")).encode()
+    return report, key.sign(report, padding.PKCS1v15(), hashes.SHA256())
+
+
+def b64url(n):
+    return base64.urlsafe_b64encode(n.to_bytes((n.bit_length() + 7) // 8, "big")).rstrip(
+        b"=").decode()
+
+
+def maa_jwks(key, cert):
+    numbers = key.public_key().public_numbers()
+    other_key = ec.generate_private_key(ec.SECP256R1())
+    other_numbers = other_key.public_key().public_numbers()
+    return json.dumps({"keys": [
+        {"kty": "RSA", "kid": "test-maa-jwk", "use": "sig", "alg": "RS256",
+         "n": b64url(numbers.n), "e": b64url(numbers.e)},
+        {"kty": "RSA", "kid": "test-maa-x5c",
+         "x5c": [base64.b64encode(cert.public_bytes(serialization.Encoding.DER)).decode()]},
+        {"kty": "EC", "kid": "test-maa-ec", "crv": "P-256",
+         "x": b64url(other_numbers.x), "y": b64url(other_numbers.y)},
+    ]}, indent=2).encode() + b"\n"
+
+
+def write_maa_fixtures():
+    maa_key = rsa.generate_private_key(public_exponent=65537, key_size=2048)
+    maa_cert = ias_cert("https://test.attest.example", maa_key, "https://test.attest.example",
+                        maa_key, False, 1)
+    write("maa_signing_key.pk8", maa_key.private_bytes(
+        serialization.Encoding.DER, serialization.PrivateFormat.PKCS8,
+        serialization.NoEncryption()))
+    write("maa_jwks.json", maa_jwks(maa_key, maa_cert))
+
+
+def write(file_name, data):
+    with open(os.path.join(OUT, file_name), "wb") as f:
+        f.write(data)
+
+
+def main():
+    enclave_key = ec.derive_private_key(ENCLAVE_KEY, ec.SECP256R1())
+    other_key = ec.generate_private_key(ec.SECP256R1())
+    write("enclave_key.bin",
+          ENCLAVE_KEY.to_bytes(32, "big") + dcap.raw_public_key(enclave_key))
+
+    root_key = ec.generate_private_key(ec.SECP256R1())
+    root = dcap.cert("Test SGX Root CA", root_key, "Test SGX Root CA", root_key, True, 1)
+    platform_key = ec.generate_private_key(ec.SECP256R1())
+    platform = dcap.cert("Test SGX PCK Platform CA", platform_key, "Test SGX Root CA", root_key,
+                         True, 2)
+    signing_key = ec.generate_private_key(ec.SECP256R1())
+    signing = dcap.cert("Test SGX TCB Signing", signing_key, "Test SGX Root CA", root_key,
+                        False, 3)
+    pck_key = ec.generate_private_key(ec.SECP256R1())
+    pck = dcap.cert("Test SGX PCK Certificate", pck_key, "Test SGX PCK Platform CA",
+                    platform_key, False, 0x1001, [dcap.sgx_extension([3] * 16, 11)])
+    old_pck_key = ec.generate_private_key(ec.SECP256R1())
+    old_pck = dcap.cert("Test SGX PCK Certificate", old_pck_key, "Test SGX PCK Platform CA",
+                        platform_key, False, 0x1002, [dcap.sgx_extension([2] * 16, 10)])
+
+    write("root_ca.pem", dcap.pem(root))
+    write("pck_crl_issuer_chain.pem", dcap.pem(platform, root))
+    write("tcb_signing_chain.pem", dcap.pem(signing, root))
+    write("root_ca_crl.der",
+          dcap.crl("Test SGX Root CA", root_key).public_bytes(serialization.Encoding.DER))
+    write("pck_crl.pem", dcap.crl("Test SGX PCK Platform CA", platform_key).public_bytes(
+        serialization.Encoding.PEM))
+    write("tcb_info.json", dcap.signed_document("tcbInfo", {
+        "id": "SGX",
+        "version": 3,
+        "issueDate": "2024-01-01T00:00:00Z",
+        "nextUpdate": dcap.TCB_INFO_NEXT_UPDATE,
+        "fmspc": dcap.FMSPC.hex(),
+        "pceId": dcap.PCE_ID.hex(),
+        "tcbType": 0,
+        "tcbEvaluationDataNumber": 16,
+        "tcbLevels": [
+            dcap.tcb_level([3] * 16, 11, "2023-08-09T00:00:00Z", "UpToDate", []),
+            dcap.tcb_level([2] * 16, 10, "2023-02-15T00:00:00Z", "SWHardeningNeeded",
+                           ["INTEL-SA-00657"]),
+        ],
+    }, signing_key))
+    write("qe_identity.json", dcap.qe_identity(8, signing_key))
+
+    data = report_data(enclave_key)
+    write("quote.bin", dcap.quote(3, pck_key, dcap.pem(pck, platform, root), data))
+    write("quote_other_key.bin",
+          dcap.quote(3, pck_key, dcap.pem(pck, platform, root), report_data(other_key)))
+    write("quote_sw_hardening.bin",
+          dcap.quote(3, old_pck_key, dcap.pem(old_pck, platform, root), data))
+
+    ias_root_key = rsa.generate_private_key(public_exponent=65537, key_size=3072)
+    ias_root = ias_cert("Test Attestation Report Signing CA", ias_root_key,
+                        "Test Attestation Report Signing CA", ias_root_key, True, 1)
+    ias_signing_key = rsa.generate_private_key(public_exponent=65537, key_size=2048)
+    ias_signing = ias_cert("Test Attestation Report Signing", ias_signing_key,
+                           "Test Attestation Report Signing CA", ias_root_key, False, 2)
+    write("ias_root_ca.der", ias_root.public_bytes(serialization.Encoding.DER))
+    write("ias_signing_cert.der", ias_signing.public_bytes(serialization.Encoding.DER))
+    report, signature = ias_report("OK", data, ias_signing_key)
+    write("ias_report.json", report)
+    write("ias_report.sig", signature)
+    report, signature = ias_report("GROUP_OUT_OF_DATE", data, ias_signing_key)
+    write("ias_report_out_of_date.json", report)
+    write("ias_report_out_of_date.sig", signature)
+
+    write_maa_fixtures()
+
+
+if __name__ == "__main__":
+    main()
@@ -2538,6 +2538,25 @@ impl SgxEccHandle {
         }
     }
 
+    ///
+    /// create_secret_key_pair generates a private/public key pair like create_key_pair, with the
+    /// private key wrapped in EcPrivateKey, which is cleared when it is dropped.
+    ///
+    pub fn create_secret_key_pair(&self) -> SgxResult<(EcPrivateKey, sgx_ec256_public_t)> {
+        if !self.initflag.get() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+
+        let mut private = EcPrivateKey::default();
+        let mut public = sgx_ec256_public_t::default();
+        let ret = rsgx_ecc256_create_key_pair(private.as_mut_raw(), &mut public, *self.handle.borrow());
+
+        match ret {
+            sgx_status_t::SGX_SUCCESS => Ok((private, public)),
+            _ => Err(ret),
+        }
+    }
+
     ///
     /// check_point checks whether the input point is a valid point on the ECC curve for the given cryptographic system.
     ///
@@ -2738,6 +2757,32 @@ impl SgxEccHandle {
         }
     }
 
+    ///
+    /// compute_shared_secret computes the DH shared key like compute_shared_dhkey, wrapped in
+    /// EcSharedKey, which is cleared when it is dropped.
+    ///
+    pub fn compute_shared_secret(
+        &self,
+        private_b: &EcPrivateKey,
+        public_ga: &sgx_ec256_public_t,
+    ) -> SgxResult<EcSharedKey> {
+        if !self.initflag.get() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+
+        let mut shared_key = EcSharedKey::default();
+        let ret = rsgx_ecc256_compute_shared_dhkey(
+            private_b.as_raw(),
+            public_ga,
+            shared_key.as_mut_raw(),
+            *self.handle.borrow(),
+        );
+        match ret {
+            sgx_status_t::SGX_SUCCESS => Ok(shared_key),
+            _ => Err(ret),
+        }
+    }
+
     /* delete (intel sgx sdk 2.0)
     pub fn compute_shared_dhkey512(&self, private_b: &sgx_ec256_private_t, public_ga: &sgx_ec256_public_t) -> SgxResult<sgx_ec256_dh_shared512_t> {
         if self.initflag.get() == false {
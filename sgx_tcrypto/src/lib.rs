@@ -27,7 +27,23 @@
 #![allow(non_snake_case)]
 #![allow(clippy::too_many_arguments)]
 
+extern crate alloc;
+extern crate sgx_crypto_common;
 extern crate sgx_types;
 
 mod crypto;
 pub use self::crypto::*;
+
+// The algorithms implemented in Rust are shared with sgx_ucrypto.
+pub use sgx_crypto_common::aead::*;
+pub use sgx_crypto_common::curve25519::*;
+pub use sgx_crypto_common::encoding::*;
+pub use sgx_crypto_common::{Aead, Digest, Mac};
+
+mod traits;
+
+mod kdf;
+pub use self::kdf::*;
+
+mod rsa;
+pub use self::rsa::*;
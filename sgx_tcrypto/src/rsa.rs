@@ -0,0 +1,256 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! RSA Signatures
+//!
+//! RSASSA-PKCS1-v1_5 and RSASSA-PSS with SHA-256 (RFC 8017), for any modulus size
+//! from 1024 to 8192 bits, instantiated with SgxShaHandle. See the rsa module of
+//! sgx_crypto_common.
+//!
+use crate::crypto::SgxShaHandle;
+use alloc::vec::Vec;
+use sgx_crypto_common::rsa;
+use sgx_crypto_common::{SgxRsaPrivateKeyComponents, SgxRsaPublicKeyComponents};
+use sgx_types::*;
+
+///
+/// rsgx_rsa_sign_pkcs1v15_sha256 computes an RSASSA-PKCS1-v1_5 signature with SHA-256.
+///
+/// # Description
+///
+/// Unlike rsgx_rsa3072_sign_msg, the key can be of any size from 1024 to 8192 bits, and
+/// the signature is big-endian. The data set could be empty.
+///
+/// # Parameters
+///
+/// **data**
+///
+/// Data to be signed.
+///
+/// **private**
+///
+/// The RSA private key. Only n, e and the CRT components are used.
+///
+/// # Return value
+///
+/// The signature, as long as the modulus.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The key size is not supported, or the key components are inconsistent.
+///
+pub fn rsgx_rsa_sign_pkcs1v15_sha256(
+    data: &[u8],
+    private: &SgxRsaPrivateKeyComponents,
+) -> SgxResult<Vec<u8>> {
+    rsa::sign_pkcs1v15_sha256::<SgxShaHandle>(data, private)
+}
+
+///
+/// rsgx_rsa_verify_pkcs1v15_sha256 verifies an RSASSA-PKCS1-v1_5 signature with SHA-256.
+///
+/// # Parameters
+///
+/// **data**
+///
+/// Signed data.
+///
+/// **public**
+///
+/// The RSA public key.
+///
+/// **signature**
+///
+/// The big-endian signature.
+///
+/// # Return value
+///
+/// **true**
+///
+/// Digital signature is valid.
+///
+/// **false**
+///
+/// Digital signature is not valid.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The key size is not supported.
+///
+pub fn rsgx_rsa_verify_pkcs1v15_sha256(
+    data: &[u8],
+    public: &SgxRsaPublicKeyComponents,
+    signature: &[u8],
+) -> SgxResult<bool> {
+    rsa::verify_pkcs1v15_sha256::<SgxShaHandle>(data, public, signature)
+}
+
+///
+/// rsgx_rsa_sign_pss_sha256 computes an RSASSA-PSS signature with SHA-256.
+///
+/// # Description
+///
+/// MGF1 with SHA-256 is used as the mask generation function, and the salt is 32 random
+/// bytes. The data set could be empty.
+///
+/// # Parameters
+///
+/// **data**
+///
+/// Data to be signed.
+///
+/// **private**
+///
+/// The RSA private key. Only n, e and the CRT components are used.
+///
+/// # Return value
+///
+/// The signature, as long as the modulus.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The key size is not supported, or the key components are inconsistent.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// The random number generator failed.
+///
+pub fn rsgx_rsa_sign_pss_sha256(
+    data: &[u8],
+    private: &SgxRsaPrivateKeyComponents,
+) -> SgxResult<Vec<u8>> {
+    rsa::sign_pss_sha256::<SgxShaHandle>(data, private)
+}
+
+///
+/// rsgx_rsa_verify_pss_sha256 verifies an RSASSA-PSS signature with SHA-256.
+///
+/// # Description
+///
+/// MGF1 with SHA-256 is expected as the mask generation function. The salt length is
+/// recovered from the signature, so signatures with any salt length are accepted.
+///
+/// # Parameters
+///
+/// **data**
+///
+/// Signed data.
+///
+/// **public**
+///
+/// The RSA public key.
+///
+/// **signature**
+///
+/// The big-endian signature.
+///
+/// # Return value
+///
+/// **true**
+///
+/// Digital signature is valid.
+///
+/// **false**
+///
+/// Digital signature is not valid.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The key size is not supported.
+///
+pub fn rsgx_rsa_verify_pss_sha256(
+    data: &[u8],
+    public: &SgxRsaPublicKeyComponents,
+    signature: &[u8],
+) -> SgxResult<bool> {
+    rsa::verify_pss_sha256::<SgxShaHandle>(data, public, signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::tests::hex;
+    use sgx_crypto_common::rsgx_rsa_private_from_der;
+
+    #[test]
+    fn pkcs1v15_and_pss_vectors() {
+        // 1024-bit key and signatures of "abc" generated with pyca/cryptography
+        let key = rsgx_rsa_private_from_der(&hex(
+            "30820277020100300d06092a864886f70d0101010500048202613082025d02010002818100ac44ed9073d7\
+             7e0d19f091941c1381fab37f4a108fa3ece29c7b89f343e6ed4f9a2c2912213d62daed0aad919843caf791\
+             03b6658c45a25f072e134ef2cbe997ab7ebadf37376ecc0ac8326152d8e1f9bd704025991b93c354b58986\
+             ec9951b26d0bd334fd14d438849fab03ed49d1ea43cc21f64647a35a0f2d53a3b519d2a302030100010281\
+             810084a35db6c73ab857260dd0491584ffa323e97201665774e0d6535857f184305c05fcd2729f1d40ac07\
+             af1d0ce90be98ecc1ceba72c65c12b21c01c25c153b7ba486d65ca3a165943a0bbd08eedeabd6e50d4372f\
+             6b80534fd3e4ccdf2e0f7f29fa1acd2ba5dfe69291042db4dd31cbbbcb6a7f48be5e3644298b25f87795a2\
+             81024100d4460e462b46c401c54fce421bcd741961c57ff83ab3beea2cca3cc1f432acdda54d9159ae44ce\
+             74047db315a49ded4cc157114fe2007e98d0dde4166e7747b1024100cfc14c3a1319c8e00401c52bbe647a\
+             c1ddb6c3a633a61e1b36f40adb5bf7735e718db8277935244be646765a4f89928553052ca366745fc6a9a5\
+             23071be82893024100c32a51ef63d4a15bbb6cbf877a86704af1e0a2e2b81e10d6bf2d85a5117f11fe8b00\
+             0136420a93028a2a83dfcf45402b44cb676859b2f8b249bee8f952c12a51024015fa57eb08dc4070da5ba9\
+             952016171a6d2f09718aa3372a1fdd1f4ce764bd12afa69365b19fda9020f331500a7bfe41167044b1a084\
+             0d21c20e932f0c57b9fb02404d4508c471e42d36189b08905a573f56ded62f61150d8f486fa00610539b53\
+             c9342be986bf328522b3614060f68ede1c62108500be6496ad7df8d1db9ace863a",
+        ))
+        .unwrap();
+        let public = key.public_key();
+        let data = "abc".as_bytes();
+
+        let signature = hex(
+            "9652715d0082adf1307347c2ee89768c729cb7112a819561013dcf7b7e258391f88ccaee0d326e960ffed6\
+             1da2a5c4ac2c4865d4a8c787cf8f9662a0e4c7df92f6de32c3680180bc8b85e60b2b45d70a1f62de6f1b15\
+             772ea997598f9cbd1e6ff824aef9fd70bc75c7c7d5380823c8e0649aa757620baa3c6e3bb8e0d517545a",
+        );
+        assert_eq!(
+            rsgx_rsa_sign_pkcs1v15_sha256(data, &key).unwrap(),
+            signature
+        );
+        assert!(rsgx_rsa_verify_pkcs1v15_sha256(data, &public, &signature).unwrap());
+        assert!(!rsgx_rsa_verify_pkcs1v15_sha256(&data[..2], &public, &signature).unwrap());
+        assert!(!rsgx_rsa_verify_pss_sha256(data, &public, &signature).unwrap());
+
+        let signature = hex(
+            "a06971e3efa26e7c2bfd0453087d1b57b831121dba4432c789a95c1067f5ca2e736e8725ac352f0e096019\
+             cb7dd71ac8fd3617ec01efacfffad39f93e278167fc673628ea665796530c96ff3d99c67d8136b04694ee0\
+             cc8425aad789d3fb40bed31fb566db1ee65ff9568631c7495e9769d117ae04173c28073238d419abfee6",
+        );
+        assert!(rsgx_rsa_verify_pss_sha256(data, &public, &signature).unwrap());
+        assert!(!rsgx_rsa_verify_pss_sha256(&data[..2], &public, &signature).unwrap());
+        assert!(!rsgx_rsa_verify_pss_sha256(data, &public, &signature[1..]).unwrap());
+
+        let signature = rsgx_rsa_sign_pss_sha256(&[], &key).unwrap();
+        assert_eq!(signature.len(), 128);
+        assert!(rsgx_rsa_verify_pss_sha256(&[], &public, &signature).unwrap());
+        assert_ne!(rsgx_rsa_sign_pss_sha256(&[], &key).unwrap(), signature);
+
+        let mut broken = key;
+        broken.dmq1[0] ^= 1;
+        assert_eq!(
+            rsgx_rsa_sign_pkcs1v15_sha256(data, &broken).err(),
+            Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+        );
+    }
+}
@@ -0,0 +1,137 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Algorithm Traits
+//!
+//! The `Digest`, `Mac` and `Aead` implementations of the handles of the cryptography
+//! library. The traits are defined in sgx_crypto_common, together with their
+//! implementations for the AEAD handles implemented in Rust.
+//!
+use crate::crypto::*;
+use sgx_types::*;
+
+sgx_crypto_common::impl_digest!(SgxShaHandle, sgx_sha256_hash_t, SGX_SHA256_HASH_SIZE, 64);
+sgx_crypto_common::impl_digest!(
+    SgxSha384Handle,
+    sgx_sha384_hash_t,
+    SGX_SHA384_HASH_SIZE,
+    128
+);
+sgx_crypto_common::impl_digest!(SgxSha1Handle, sgx_sha1_hash_t, SGX_SHA1_HASH_SIZE, 64);
+
+sgx_crypto_common::impl_mac!(
+    SgxCmacHandle,
+    sgx_cmac_128bit_key_t,
+    SGX_CMAC_KEY_SIZE,
+    sgx_cmac_128bit_tag_t,
+    SGX_CMAC_MAC_SIZE
+);
+sgx_crypto_common::impl_mac!(
+    SgxHmacHandle,
+    sgx_hmac_256bit_key_t,
+    SGX_HMAC256_KEY_SIZE,
+    sgx_hmac_256bit_tag_t,
+    SGX_HMAC256_MAC_SIZE
+);
+
+sgx_crypto_common::impl_aead!(
+    SgxAesHandle,
+    sgx_aes_gcm_128bit_key_t,
+    SGX_AESGCM_KEY_SIZE,
+    SGX_AESGCM_IV_SIZE,
+    sgx_aes_gcm_128bit_tag_t,
+    SGX_AESGCM_MAC_SIZE,
+    rsgx_rijndael128GCM_encrypt,
+    rsgx_rijndael128GCM_decrypt
+);
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use sgx_crypto_common::{Aead, Digest, Mac};
+
+    pub(crate) fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn digest_sha256() {
+        let expected = hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(SgxShaHandle::digest(b"abc").unwrap().to_vec(), expected);
+
+        let handle = SgxShaHandle::default();
+        Digest::init(&handle).unwrap();
+        Digest::update(&handle, b"a").unwrap();
+        Digest::update(&handle, &[]).unwrap();
+        Digest::update(&handle, b"bc").unwrap();
+        assert_eq!(handle.finalize().unwrap().to_vec(), expected);
+        Digest::close(&handle).unwrap();
+    }
+
+    #[test]
+    fn mac_cmac() {
+        // RFC 4493, example 2.
+        let mut key = sgx_cmac_128bit_key_t::default();
+        key.copy_from_slice(&hex("2b7e151628aed2a6abf7158809cf4f3c"));
+        let data = hex("6bc1bee22e409f96e93d7e117393172a");
+        let tag = hex("070a16b46b4d4144f79bdd9dd04a287c");
+
+        assert_eq!(SgxCmacHandle::mac(&key, &data).unwrap().to_vec(), tag);
+        assert!(SgxCmacHandle::verify(&key, &data, &tag).is_ok());
+        assert_eq!(
+            SgxCmacHandle::verify(&key, &data[1..], &tag),
+            Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH)
+        );
+    }
+
+    fn aead_round_trip<A: Aead>(key: &A::Key) {
+        let nonce = vec![7_u8; A::NONCE_SIZE];
+        let aad = b"associated data";
+        let plain = b"the quick brown fox jumps over the lazy dog";
+
+        let mut cipher = vec![0_u8; plain.len()];
+        let tag = A::encrypt(key, &nonce, aad, plain, &mut cipher).unwrap();
+        assert_eq!(tag.as_ref().len(), A::TAG_SIZE);
+
+        let handle = A::default();
+        handle.init(key, &nonce, aad).unwrap();
+        let mut streamed = vec![0_u8; plain.len()];
+        handle.update(&plain[..10], &mut streamed[..10]).unwrap();
+        handle.update(&plain[10..], &mut streamed[10..]).unwrap();
+        assert_eq!(streamed, cipher);
+        assert_eq!(handle.get_mac().unwrap().as_ref(), tag.as_ref());
+        handle.close().unwrap();
+
+        let mut decrypted = vec![0_u8; plain.len()];
+        A::decrypt(key, &nonce, aad, &cipher, &tag, &mut decrypted).unwrap();
+        assert_eq!(&decrypted[..], &plain[..]);
+
+        cipher[0] ^= 1;
+        assert!(A::decrypt(key, &nonce, aad, &cipher, &tag, &mut decrypted).is_err());
+    }
+
+    #[test]
+    fn aead_round_trip_aes128() {
+        aead_round_trip::<SgxAesHandle>(&[1_u8; SGX_AESGCM_KEY_SIZE]);
+    }
+}
@@ -20,6 +20,8 @@
 //! These functions allow an ISV to establish secure session between two enclaves using the EC DH Key exchange protocol.
 //!
 use crate::ecp::*;
+use crate::policy::*;
+use crate::session::*;
 use alloc::boxed::Box;
 use alloc::slice;
 use alloc::vec::Vec;
@@ -348,7 +350,7 @@ impl SgxDhResponder {
     /// Identity information of initiator includes isv svn, isv product id, the
     /// enclave attributes, MRSIGNER, and MRENCLAVE. The buffer must be in
     /// enclave address space. The caller should check the identity of the peer and
-    /// decide whether to trust the peer and use the aek.
+    /// decide whether to trust the peer and use the aek, or use proc_msg2_with_policy.
     ///
     /// # Errors
     ///
@@ -379,6 +381,57 @@ impl SgxDhResponder {
         aek: &mut sgx_key_128bit_t,
         initiator_identity: &mut sgx_dh_session_enclave_identity_t,
     ) -> SgxError {
+        self.process_msg2(msg2, msg3, aek, initiator_identity, |_| Ok(()))
+    }
+
+    ///
+    /// proc_msg2_with_policy processes message 2 like proc_msg2, and checks the identity of
+    /// the initiator against policy before message 3 is generated. The AEK is only returned
+    /// if the initiator satisfies the policy.
+    ///
+    /// # Errors
+    ///
+    /// **SgxDhError::Status**
+    ///
+    /// The handshake failed, see proc_msg2.
+    ///
+    /// **SgxDhError::IdentityPolicy**
+    ///
+    /// The initiator does not satisfy the policy. The error names the field that failed.
+    ///
+    pub fn proc_msg2_with_policy(
+        &mut self,
+        msg2: &SgxDhMsg2,
+        msg3: &mut SgxDhMsg3,
+        policy: &EnclaveIdentityPolicy,
+        initiator_identity: &mut sgx_dh_session_enclave_identity_t,
+    ) -> Result<SecretKey128, SgxDhError> {
+        let mut violation = None;
+        let mut aek = SecretKey128::default();
+        self.process_msg2(msg2, msg3, aek.as_mut_raw(), initiator_identity, |peer| {
+            policy.verify(peer).map_err(|error| {
+                violation = Some(error);
+                sgx_status_t::SGX_ERROR_UNEXPECTED
+            })
+        })
+        .map_err(|ret| match violation {
+            Some(error) => SgxDhError::IdentityPolicy(error),
+            None => SgxDhError::Status(ret),
+        })?;
+        Ok(aek)
+    }
+
+    fn process_msg2<F>(
+        &mut self,
+        msg2: &SgxDhMsg2,
+        msg3: &mut SgxDhMsg3,
+        aek: &mut sgx_key_128bit_t,
+        initiator_identity: &mut sgx_dh_session_enclave_identity_t,
+        verify_peer: F,
+    ) -> SgxError
+    where
+        F: FnOnce(&sgx_report_body_t) -> SgxError,
+    {
         if !rsgx_data_is_within_enclave(self) {
             return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
         }
@@ -426,6 +479,14 @@ impl SgxDhResponder {
         self.dh_verify_message2(msg2)
             .map_err(|ret| self.set_error(ret))?;
 
+        // msg2 is packed, so the report body is copied out before it is borrowed.
+        let initiator_report_body = msg2.report.body;
+        if let Err(ret) = verify_peer(&initiator_report_body) {
+            *self = Self::default();
+            self.state = SgxDhSessionState::SGX_DH_SESSION_STATE_ERROR;
+            return Err(ret);
+        }
+
         initiator_identity.isv_svn = msg2.report.body.isv_svn;
         initiator_identity.isv_prod_id = msg2.report.body.isv_prod_id;
         initiator_identity.attributes = msg2.report.body.attributes;
@@ -448,6 +509,35 @@ impl SgxDhResponder {
         Ok(())
     }
 
+    ///
+    /// proc_msg2_secret processes message 2 like proc_msg2, and returns the AEK wrapped in
+    /// SecretKey128, which is cleared when it is dropped.
+    ///
+    pub fn proc_msg2_secret(
+        &mut self,
+        msg2: &SgxDhMsg2,
+        msg3: &mut SgxDhMsg3,
+        initiator_identity: &mut sgx_dh_session_enclave_identity_t,
+    ) -> SgxResult<SecretKey128> {
+        let mut aek = SecretKey128::default();
+        self.proc_msg2(msg2, msg3, aek.as_mut_raw(), initiator_identity)?;
+        Ok(aek)
+    }
+
+    ///
+    /// proc_msg2_session processes message 2 like proc_msg2, and returns a SgxDhSession
+    /// keyed by the AEK.
+    ///
+    pub fn proc_msg2_session(
+        &mut self,
+        msg2: &SgxDhMsg2,
+        msg3: &mut SgxDhMsg3,
+        initiator_identity: &mut sgx_dh_session_enclave_identity_t,
+    ) -> SgxResult<SgxDhSession> {
+        let aek = self.proc_msg2_secret(msg2, msg3, initiator_identity)?;
+        SgxDhSession::new(SgxDhSessionRole::Responder, &aek)
+    }
+
     fn dh_generate_message1(&mut self, msg1: &mut SgxDhMsg1) -> SgxError {
         msg1.target = Default::default();
         msg1.g_a = Default::default();
@@ -759,7 +849,8 @@ impl SgxDhInitiator {
     /// Identity information of responder including isv svn, isv product id, the enclave
     /// attributes, MRSIGNER, and MRENCLAVE. The buffer must be in enclave address space.
     /// The caller should check the identity of the peer and decide whether to trust the
-    /// peer and use the aek or the msg3_body.additional_prop field of msg3.
+    /// peer and use the aek or the msg3_body.additional_prop field of msg3, or use
+    /// proc_msg3_with_policy.
     ///
     /// # Errors
     ///
@@ -785,6 +876,55 @@ impl SgxDhInitiator {
         aek: &mut sgx_key_128bit_t,
         responder_identity: &mut sgx_dh_session_enclave_identity_t,
     ) -> SgxError {
+        self.process_msg3(msg3, aek, responder_identity, |_| Ok(()))
+    }
+
+    ///
+    /// proc_msg3_with_policy processes message 3 like proc_msg3, and checks the identity of
+    /// the responder against policy. The AEK is only returned if the responder satisfies the
+    /// policy.
+    ///
+    /// # Errors
+    ///
+    /// **SgxDhError::Status**
+    ///
+    /// The handshake failed, see proc_msg3.
+    ///
+    /// **SgxDhError::IdentityPolicy**
+    ///
+    /// The responder does not satisfy the policy. The error names the field that failed.
+    ///
+    pub fn proc_msg3_with_policy(
+        &mut self,
+        msg3: &SgxDhMsg3,
+        policy: &EnclaveIdentityPolicy,
+        responder_identity: &mut sgx_dh_session_enclave_identity_t,
+    ) -> Result<SecretKey128, SgxDhError> {
+        let mut violation = None;
+        let mut aek = SecretKey128::default();
+        self.process_msg3(msg3, aek.as_mut_raw(), responder_identity, |peer| {
+            policy.verify(peer).map_err(|error| {
+                violation = Some(error);
+                sgx_status_t::SGX_ERROR_UNEXPECTED
+            })
+        })
+        .map_err(|ret| match violation {
+            Some(error) => SgxDhError::IdentityPolicy(error),
+            None => SgxDhError::Status(ret),
+        })?;
+        Ok(aek)
+    }
+
+    fn process_msg3<F>(
+        &mut self,
+        msg3: &SgxDhMsg3,
+        aek: &mut sgx_key_128bit_t,
+        responder_identity: &mut sgx_dh_session_enclave_identity_t,
+        verify_peer: F,
+    ) -> SgxError
+    where
+        F: FnOnce(&sgx_report_body_t) -> SgxError,
+    {
         if !rsgx_data_is_within_enclave(self) {
             return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
         }
@@ -819,6 +959,12 @@ impl SgxDhInitiator {
         self.dh_verify_message3(msg3)
             .map_err(|ret| self.set_error(ret))?;
 
+        if let Err(ret) = verify_peer(&msg3.msg3_body.report.body) {
+            *self = Self::default();
+            self.state = SgxDhSessionState::SGX_DH_SESSION_STATE_ERROR;
+            return Err(ret);
+        }
+
         let align_aek =
             derive_key(&self.shared_key.key, &EC_AEK_LABEL).map_err(|ret| self.set_error(ret))?;
         *aek = align_aek.key;
@@ -837,6 +983,33 @@ impl SgxDhInitiator {
         Ok(())
     }
 
+    ///
+    /// proc_msg3_secret processes message 3 like proc_msg3, and returns the AEK wrapped in
+    /// SecretKey128, which is cleared when it is dropped.
+    ///
+    pub fn proc_msg3_secret(
+        &mut self,
+        msg3: &SgxDhMsg3,
+        responder_identity: &mut sgx_dh_session_enclave_identity_t,
+    ) -> SgxResult<SecretKey128> {
+        let mut aek = SecretKey128::default();
+        self.proc_msg3(msg3, aek.as_mut_raw(), responder_identity)?;
+        Ok(aek)
+    }
+
+    ///
+    /// proc_msg3_session processes message 3 like proc_msg3, and returns a SgxDhSession
+    /// keyed by the AEK.
+    ///
+    pub fn proc_msg3_session(
+        &mut self,
+        msg3: &SgxDhMsg3,
+        responder_identity: &mut sgx_dh_session_enclave_identity_t,
+    ) -> SgxResult<SgxDhSession> {
+        let aek = self.proc_msg3_secret(msg3, responder_identity)?;
+        SgxDhSession::new(SgxDhSessionRole::Initiator, &aek)
+    }
+
     fn dh_generate_message2(&self, msg1: &SgxDhMsg1, msg2: &mut SgxDhMsg2) -> SgxError {
         msg2.report = Default::default();
         msg2.cmac = Default::default();
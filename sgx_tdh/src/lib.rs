@@ -41,4 +41,10 @@ extern crate sgx_types;
 mod dh;
 pub use self::dh::*;
 
+mod policy;
+pub use self::policy::*;
+
+mod session;
+pub use self::session::*;
+
 mod ecp;
@@ -0,0 +1,345 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use alloc::vec::Vec;
+use core::fmt;
+use sgx_types::*;
+
+///
+/// The identity field of a peer enclave that did not satisfy an EnclaveIdentityPolicy.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdentityPolicyError {
+    /// The policy names neither a MRSIGNER nor a MRENCLAVE, so it would trust any enclave.
+    Unconstrained,
+    MrSigner,
+    MrEnclave,
+    IsvProdId,
+    IsvSvn,
+    /// The peer is a debug enclave, and the policy does not allow debug enclaves.
+    Debug,
+    ConfigId,
+    ConfigSvn,
+    IsvFamilyId,
+    IsvExtProdId,
+}
+
+impl IdentityPolicyError {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            IdentityPolicyError::Unconstrained => "The policy names no MRSIGNER or MRENCLAVE.",
+            IdentityPolicyError::MrSigner => "The MRSIGNER of the peer is not trusted.",
+            IdentityPolicyError::MrEnclave => "The MRENCLAVE of the peer is not allowed.",
+            IdentityPolicyError::IsvProdId => "The ISVPRODID of the peer is not the expected one.",
+            IdentityPolicyError::IsvSvn => "The ISVSVN of the peer is too low.",
+            IdentityPolicyError::Debug => "The peer is a debug enclave.",
+            IdentityPolicyError::ConfigId => "The CONFIGID of the peer is not the expected one.",
+            IdentityPolicyError::ConfigSvn => "The CONFIGSVN of the peer is too low.",
+            IdentityPolicyError::IsvFamilyId => {
+                "The ISVFAMILYID of the peer is not the expected one."
+            }
+            IdentityPolicyError::IsvExtProdId => {
+                "The ISVEXTPRODID of the peer is not the expected one."
+            }
+        }
+    }
+}
+
+impl fmt::Display for IdentityPolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+///
+/// The error of a handshake that checks the identity of the peer.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SgxDhError {
+    /// The handshake itself failed.
+    Status(sgx_status_t),
+    /// The handshake succeeded, but the peer does not satisfy the identity policy.
+    IdentityPolicy(IdentityPolicyError),
+}
+
+impl From<sgx_status_t> for SgxDhError {
+    fn from(status: sgx_status_t) -> SgxDhError {
+        SgxDhError::Status(status)
+    }
+}
+
+impl From<IdentityPolicyError> for SgxDhError {
+    fn from(error: IdentityPolicyError) -> SgxDhError {
+        SgxDhError::IdentityPolicy(error)
+    }
+}
+
+impl fmt::Display for SgxDhError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SgxDhError::Status(status) => write!(f, "{}", status),
+            SgxDhError::IdentityPolicy(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+///
+/// The identity a peer enclave must have for the local attestation to succeed.
+///
+/// A policy must name the trusted MRSIGNER, the allowed MRENCLAVE values, or both. The
+/// other fields are only checked when they are set. Debug enclaves are rejected unless
+/// they are explicitly allowed.
+///
+/// A policy can be built with the setters, or parsed from a configuration with
+/// `from_config`.
+///
+#[derive(Clone, Debug, Default)]
+pub struct EnclaveIdentityPolicy {
+    mr_signer: Option<[u8; SGX_HASH_SIZE]>,
+    mr_enclaves: Vec<[u8; SGX_HASH_SIZE]>,
+    isv_prod_id: Option<sgx_prod_id_t>,
+    min_isv_svn: sgx_isv_svn_t,
+    allow_debug: bool,
+    config_id: Option<sgx_config_id_t>,
+    min_config_svn: sgx_config_svn_t,
+    isv_family_id: Option<sgx_isvfamily_id_t>,
+    isv_ext_prod_id: Option<sgx_isvext_prod_id_t>,
+}
+
+impl EnclaveIdentityPolicy {
+    pub fn new() -> EnclaveIdentityPolicy {
+        EnclaveIdentityPolicy::default()
+    }
+
+    /// Trusts the enclaves signed by `mr_signer`.
+    pub fn mr_signer(&mut self, mr_signer: &sgx_measurement_t) -> &mut EnclaveIdentityPolicy {
+        self.mr_signer = Some(mr_signer.m);
+        self
+    }
+
+    /// Adds `mr_enclave` to the allowed enclave measurements. If no measurement is added,
+    /// any measurement is allowed.
+    pub fn allow_mr_enclave(
+        &mut self,
+        mr_enclave: &sgx_measurement_t,
+    ) -> &mut EnclaveIdentityPolicy {
+        self.mr_enclaves.push(mr_enclave.m);
+        self
+    }
+
+    pub fn isv_prod_id(&mut self, isv_prod_id: sgx_prod_id_t) -> &mut EnclaveIdentityPolicy {
+        self.isv_prod_id = Some(isv_prod_id);
+        self
+    }
+
+    pub fn min_isv_svn(&mut self, isv_svn: sgx_isv_svn_t) -> &mut EnclaveIdentityPolicy {
+        self.min_isv_svn = isv_svn;
+        self
+    }
+
+    pub fn allow_debug(&mut self, allow_debug: bool) -> &mut EnclaveIdentityPolicy {
+        self.allow_debug = allow_debug;
+        self
+    }
+
+    pub fn config_id(&mut self, config_id: &sgx_config_id_t) -> &mut EnclaveIdentityPolicy {
+        self.config_id = Some(*config_id);
+        self
+    }
+
+    pub fn min_config_svn(&mut self, config_svn: sgx_config_svn_t) -> &mut EnclaveIdentityPolicy {
+        self.min_config_svn = config_svn;
+        self
+    }
+
+    pub fn isv_family_id(
+        &mut self,
+        isv_family_id: &sgx_isvfamily_id_t,
+    ) -> &mut EnclaveIdentityPolicy {
+        self.isv_family_id = Some(*isv_family_id);
+        self
+    }
+
+    pub fn isv_ext_prod_id(
+        &mut self,
+        isv_ext_prod_id: &sgx_isvext_prod_id_t,
+    ) -> &mut EnclaveIdentityPolicy {
+        self.isv_ext_prod_id = Some(*isv_ext_prod_id);
+        self
+    }
+
+    ///
+    /// Parses a policy from a configuration.
+    ///
+    /// The configuration has one `key = value` pair per line. Empty lines and lines
+    /// starting with `#` are ignored. The keys are
+    ///
+    /// * `mr_signer`, `mr_enclave`, `config_id`, `isv_family_id` and `isv_ext_prod_id`,
+    ///   whose values are hex strings. `mr_enclave` can be given several times.
+    ///
+    /// * `isv_prod_id`, `min_isv_svn` and `min_config_svn`, whose values are decimal.
+    ///
+    /// * `allow_debug`, whose value is `true` or `false`.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The configuration has an unknown key, a malformed value, or names neither a
+    /// MRSIGNER nor a MRENCLAVE.
+    ///
+    pub fn from_config(config: &str) -> SgxResult<EnclaveIdentityPolicy> {
+        let mut policy = EnclaveIdentityPolicy::new();
+        for line in config.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+            let value = value.trim();
+            match key.trim() {
+                "mr_signer" => {
+                    policy.mr_signer(&sgx_measurement_t {
+                        m: parse_hex(value)?,
+                    });
+                }
+                "mr_enclave" => {
+                    policy.allow_mr_enclave(&sgx_measurement_t {
+                        m: parse_hex(value)?,
+                    });
+                }
+                "isv_prod_id" => {
+                    policy.isv_prod_id(parse_u16(value)?);
+                }
+                "min_isv_svn" => {
+                    policy.min_isv_svn(parse_u16(value)?);
+                }
+                "allow_debug" => {
+                    policy.allow_debug(match value {
+                        "true" => true,
+                        "false" => false,
+                        _ => return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
+                    });
+                }
+                "config_id" => {
+                    policy.config_id(&parse_hex(value)?);
+                }
+                "min_config_svn" => {
+                    policy.min_config_svn(parse_u16(value)?);
+                }
+                "isv_family_id" => {
+                    policy.isv_family_id(&parse_hex(value)?);
+                }
+                "isv_ext_prod_id" => {
+                    policy.isv_ext_prod_id(&parse_hex(value)?);
+                }
+                _ => return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
+            }
+        }
+
+        if !policy.is_constrained() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        Ok(policy)
+    }
+
+    ///
+    /// Checks the identity of a peer enclave, taken from its verified report.
+    ///
+    /// # Errors
+    ///
+    /// The first field of the identity that does not satisfy the policy.
+    ///
+    pub fn verify(&self, peer: &sgx_report_body_t) -> Result<(), IdentityPolicyError> {
+        if !self.is_constrained() {
+            return Err(IdentityPolicyError::Unconstrained);
+        }
+        if let Some(mr_signer) = self.mr_signer {
+            if peer.mr_signer.m != mr_signer {
+                return Err(IdentityPolicyError::MrSigner);
+            }
+        }
+        if !self.mr_enclaves.is_empty() && !self.mr_enclaves.contains(&peer.mr_enclave.m) {
+            return Err(IdentityPolicyError::MrEnclave);
+        }
+        if let Some(isv_prod_id) = self.isv_prod_id {
+            if peer.isv_prod_id != isv_prod_id {
+                return Err(IdentityPolicyError::IsvProdId);
+            }
+        }
+        if peer.isv_svn < self.min_isv_svn {
+            return Err(IdentityPolicyError::IsvSvn);
+        }
+        if !self.allow_debug && (peer.attributes.flags & SGX_FLAGS_DEBUG) != 0 {
+            return Err(IdentityPolicyError::Debug);
+        }
+        if let Some(config_id) = self.config_id {
+            if peer.config_id[..] != config_id[..] {
+                return Err(IdentityPolicyError::ConfigId);
+            }
+        }
+        if peer.config_svn < self.min_config_svn {
+            return Err(IdentityPolicyError::ConfigSvn);
+        }
+        if let Some(isv_family_id) = self.isv_family_id {
+            if peer.isv_family_id != isv_family_id {
+                return Err(IdentityPolicyError::IsvFamilyId);
+            }
+        }
+        if let Some(isv_ext_prod_id) = self.isv_ext_prod_id {
+            if peer.isv_ext_prod_id != isv_ext_prod_id {
+                return Err(IdentityPolicyError::IsvExtProdId);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_constrained(&self) -> bool {
+        self.mr_signer.is_some() || !self.mr_enclaves.is_empty()
+    }
+}
+
+fn parse_hex<const N: usize>(value: &str) -> SgxResult<[u8; N]> {
+    let value = value.as_bytes();
+    if value.len() != N * 2 {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+
+    let mut bytes = [0_u8; N];
+    for (byte, digits) in bytes.iter_mut().zip(value.chunks(2)) {
+        *byte = (hex_digit(digits[0])? << 4) | hex_digit(digits[1])?;
+    }
+    Ok(bytes)
+}
+
+fn hex_digit(digit: u8) -> SgxResult<u8> {
+    match digit {
+        b'0'..=b'9' => Ok(digit - b'0'),
+        b'a'..=b'f' => Ok(digit - b'a' + 10),
+        b'A'..=b'F' => Ok(digit - b'A' + 10),
+        _ => Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
+    }
+}
+
+fn parse_u16(value: &str) -> SgxResult<u16> {
+    value
+        .parse::<u16>()
+        .map_err(|_| sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+}
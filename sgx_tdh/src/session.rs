@@ -0,0 +1,371 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use sgx_tcrypto::*;
+use sgx_trts::trts::rsgx_read_rand;
+use sgx_types::*;
+
+/// Size of the sequence number and the IV at the start of a session message.
+pub const SGX_DH_SESSION_HEADER_SIZE: usize = 8 + SGX_AESGCM_IV_SIZE;
+/// Number of bytes a session message adds to the plaintext.
+pub const SGX_DH_SESSION_MESSAGE_OVERHEAD: usize = SGX_DH_SESSION_HEADER_SIZE + SGX_AESGCM_MAC_SIZE;
+/// Number of messages sent in each direction before the key of that direction is replaced.
+pub const SGX_DH_SESSION_DEFAULT_REKEY_INTERVAL: u64 = 1 << 16;
+/// The largest rekey interval. The IVs are random, so a key must not protect more than
+/// 2^32 messages.
+pub const SGX_DH_SESSION_MAX_REKEY_INTERVAL: u64 = 1 << 32;
+/// Size of the state returned by `SgxDhSession::to_bytes`.
+pub const SGX_DH_SESSION_STATE_SIZE: usize = 64;
+
+const SGX_DH_SESSION_STATE_VERSION: u32 = 1;
+const INITIATOR_TO_RESPONDER_LABEL: &[u8] = b"SGX DH SESSION I2R";
+const RESPONDER_TO_INITIATOR_LABEL: &[u8] = b"SGX DH SESSION R2I";
+const REKEY_LABEL: &[u8] = b"SGX DH SESSION REKEY";
+
+/// The side of the local attestation an enclave took.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SgxDhSessionRole {
+    Initiator,
+    Responder,
+}
+
+///
+/// A secure channel between two enclaves, keyed by the AEK of a DH local attestation.
+///
+/// Each direction has its own AES-GCM key derived from the AEK, so the two enclaves never
+/// encrypt with the same key. Every message carries a sequence number, which is
+/// authenticated with the message, and messages must be opened in the order they were
+/// sealed: a replayed, dropped or reordered message is rejected. After every
+/// `rekey_interval` messages the key of a direction is replaced by a key derived from it,
+/// and the old key is cleared.
+///
+/// A message is laid out as
+///
+/// `sequence number (8 bytes, little endian) || IV (12 bytes) || ciphertext || MAC (16 bytes)`
+///
+pub struct SgxDhSession {
+    role: SgxDhSessionRole,
+    rekey_interval: u64,
+    send_seq: u64,
+    recv_seq: u64,
+    send_key: SecretKey128,
+    recv_key: SecretKey128,
+}
+
+impl SgxDhSession {
+    ///
+    /// Creates a session from the AEK returned by proc_msg2 or proc_msg3, with the default
+    /// rekey interval.
+    ///
+    /// # Parameters
+    ///
+    /// **role**
+    ///
+    /// The side of the local attestation this enclave took.
+    ///
+    /// **aek**
+    ///
+    /// The AEK established by the local attestation.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// The session keys could not be derived.
+    ///
+    pub fn new(role: SgxDhSessionRole, aek: &SecretKey128) -> SgxResult<SgxDhSession> {
+        SgxDhSession::with_rekey_interval(role, aek, SGX_DH_SESSION_DEFAULT_REKEY_INTERVAL)
+    }
+
+    ///
+    /// Creates a session which replaces its keys after `rekey_interval` messages. Both
+    /// enclaves must use the same interval.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The interval is zero or greater than SGX_DH_SESSION_MAX_REKEY_INTERVAL.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// The session keys could not be derived.
+    ///
+    pub fn with_rekey_interval(
+        role: SgxDhSessionRole,
+        aek: &SecretKey128,
+        rekey_interval: u64,
+    ) -> SgxResult<SgxDhSession> {
+        if rekey_interval == 0 || rekey_interval > SGX_DH_SESSION_MAX_REKEY_INTERVAL {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let i2r = derive_session_key(aek, INITIATOR_TO_RESPONDER_LABEL, &[])?;
+        let r2i = derive_session_key(aek, RESPONDER_TO_INITIATOR_LABEL, &[])?;
+        let (send_key, recv_key) = match role {
+            SgxDhSessionRole::Initiator => (i2r, r2i),
+            SgxDhSessionRole::Responder => (r2i, i2r),
+        };
+        Ok(SgxDhSession {
+            role,
+            rekey_interval,
+            send_seq: 0,
+            recv_seq: 0,
+            send_key,
+            recv_key,
+        })
+    }
+
+    pub fn role(&self) -> SgxDhSessionRole {
+        self.role
+    }
+
+    /// The sequence number of the next message this enclave seals.
+    pub fn send_sequence(&self) -> u64 {
+        self.send_seq
+    }
+
+    /// The sequence number of the next message this enclave expects to open.
+    pub fn recv_sequence(&self) -> u64 {
+        self.recv_seq
+    }
+
+    ///
+    /// Encrypts a message for the peer.
+    ///
+    /// # Parameters
+    ///
+    /// **plaintext**
+    ///
+    /// The message to encrypt.
+    ///
+    /// **aad**
+    ///
+    /// Additional data authenticated with the message. The peer must pass the same data
+    /// to open_message.
+    ///
+    /// # Return value
+    ///
+    /// The message to send, SGX_DH_SESSION_MESSAGE_OVERHEAD bytes longer than the plaintext.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The plaintext or the additional data is too large.
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The sequence numbers are exhausted.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// An unexpected error occurred.
+    ///
+    pub fn seal_message(&mut self, plaintext: &[u8], aad: &[u8]) -> SgxResult<Vec<u8>> {
+        if self.send_seq == u64::MAX {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+        if plaintext.len() > u32::MAX as usize - SGX_DH_SESSION_MESSAGE_OVERHEAD {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let mut message = vec![0_u8; plaintext.len() + SGX_DH_SESSION_MESSAGE_OVERHEAD];
+        let (header, body) = message.split_at_mut(SGX_DH_SESSION_HEADER_SIZE);
+        header[..8].copy_from_slice(&self.send_seq.to_le_bytes());
+        rsgx_read_rand(&mut header[8..])?;
+
+        let header_aad = message_aad(header, aad);
+        let (ciphertext, mac) = body.split_at_mut(plaintext.len());
+        let mut tag = sgx_aes_gcm_128bit_tag_t::default();
+        rsgx_rijndael128GCM_encrypt(
+            self.send_key.as_raw(),
+            plaintext,
+            &header[8..],
+            &header_aad,
+            ciphertext,
+            &mut tag,
+        )?;
+        mac.copy_from_slice(&tag);
+
+        let next_seq = self.send_seq + 1;
+        if next_seq % self.rekey_interval == 0 {
+            self.send_key = rekey(&self.send_key, next_seq)?;
+        }
+        self.send_seq = next_seq;
+        Ok(message)
+    }
+
+    ///
+    /// Decrypts a message from the peer.
+    ///
+    /// # Parameters
+    ///
+    /// **message**
+    ///
+    /// The message returned by seal_message in the peer enclave.
+    ///
+    /// **aad**
+    ///
+    /// The additional data the message was sealed with.
+    ///
+    /// # Return value
+    ///
+    /// The plaintext of the message.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The message is too short or too large.
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The message is not the next one expected: it was replayed, reordered, or a message
+    /// before it was lost.
+    ///
+    /// **SGX_ERROR_MAC_MISMATCH**
+    ///
+    /// The message or the additional data was modified, or it was not sealed by the peer.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// An unexpected error occurred.
+    ///
+    pub fn open_message(&mut self, message: &[u8], aad: &[u8]) -> SgxResult<Vec<u8>> {
+        if message.len() < SGX_DH_SESSION_MESSAGE_OVERHEAD || message.len() > u32::MAX as usize {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let (header, body) = message.split_at(SGX_DH_SESSION_HEADER_SIZE);
+        let seq = u64::from_le_bytes(header[..8].try_into().unwrap());
+        if seq != self.recv_seq {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+
+        let header_aad = message_aad(header, aad);
+        let (ciphertext, mac) = body.split_at(body.len() - SGX_AESGCM_MAC_SIZE);
+        let mut tag = sgx_aes_gcm_128bit_tag_t::default();
+        tag.copy_from_slice(mac);
+        let mut plaintext = vec![0_u8; ciphertext.len()];
+        rsgx_rijndael128GCM_decrypt(
+            self.recv_key.as_raw(),
+            ciphertext,
+            &header[8..],
+            &header_aad,
+            &tag,
+            &mut plaintext,
+        )?;
+
+        let next_seq = self.recv_seq + 1;
+        if next_seq % self.rekey_interval == 0 {
+            self.recv_key = rekey(&self.recv_key, next_seq)?;
+        }
+        self.recv_seq = next_seq;
+        Ok(plaintext)
+    }
+
+    ///
+    /// Serializes the session, so that it can be kept outside the enclave between ECALLs.
+    ///
+    /// The state contains the session keys. It must be sealed, e.g. with sgx_tseal, before
+    /// it leaves the enclave, and cleared once it is no longer needed. Restoring an older
+    /// state lets the peer's old messages be opened again, so the enclave must make sure the
+    /// state it restores is the latest one.
+    ///
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut state = vec![0_u8; SGX_DH_SESSION_STATE_SIZE];
+        state[0..4].copy_from_slice(&SGX_DH_SESSION_STATE_VERSION.to_le_bytes());
+        state[4] = match self.role {
+            SgxDhSessionRole::Initiator => 0,
+            SgxDhSessionRole::Responder => 1,
+        };
+        state[8..16].copy_from_slice(&self.rekey_interval.to_le_bytes());
+        state[16..24].copy_from_slice(&self.send_seq.to_le_bytes());
+        state[24..32].copy_from_slice(&self.recv_seq.to_le_bytes());
+        state[32..48].copy_from_slice(self.send_key.as_bytes());
+        state[48..64].copy_from_slice(self.recv_key.as_bytes());
+        state
+    }
+
+    ///
+    /// Restores a session serialized by to_bytes.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The state is malformed or has an unknown version.
+    ///
+    pub fn from_bytes(state: &[u8]) -> SgxResult<SgxDhSession> {
+        if state.len() != SGX_DH_SESSION_STATE_SIZE
+            || state[0..4] != SGX_DH_SESSION_STATE_VERSION.to_le_bytes()
+            || state[5..8] != [0_u8; 3]
+        {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let role = match state[4] {
+            0 => SgxDhSessionRole::Initiator,
+            1 => SgxDhSessionRole::Responder,
+            _ => return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
+        };
+        let rekey_interval = u64::from_le_bytes(state[8..16].try_into().unwrap());
+        if rekey_interval == 0 || rekey_interval > SGX_DH_SESSION_MAX_REKEY_INTERVAL {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let mut session = SgxDhSession {
+            role,
+            rekey_interval,
+            send_seq: u64::from_le_bytes(state[16..24].try_into().unwrap()),
+            recv_seq: u64::from_le_bytes(state[24..32].try_into().unwrap()),
+            send_key: SecretKey128::default(),
+            recv_key: SecretKey128::default(),
+        };
+        session
+            .send_key
+            .as_mut_raw()
+            .copy_from_slice(&state[32..48]);
+        session
+            .recv_key
+            .as_mut_raw()
+            .copy_from_slice(&state[48..64]);
+        Ok(session)
+    }
+}
+
+// The sequence number and the IV are authenticated along with the caller's data.
+fn message_aad(header: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut header_aad = Vec::with_capacity(header.len() + aad.len());
+    header_aad.extend_from_slice(header);
+    header_aad.extend_from_slice(aad);
+    header_aad
+}
+
+fn rekey(key: &SecretKey128, seq: u64) -> SgxResult<SecretKey128> {
+    derive_session_key(key, REKEY_LABEL, &seq.to_le_bytes())
+}
+
+fn derive_session_key(key: &SecretKey128, label: &[u8], context: &[u8]) -> SgxResult<SecretKey128> {
+    let mut derived = SecretKey128::default();
+    rsgx_sp800_108_cmac_kdf(key.as_raw(), label, context, derived.as_mut_raw())
+        .map_err(|_| sgx_status_t::SGX_ERROR_UNEXPECTED)?;
+    Ok(derived)
+}
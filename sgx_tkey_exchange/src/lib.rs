@@ -24,9 +24,13 @@
 #![no_std]
 #![cfg_attr(target_env = "sgx", feature(rustc_private))]
 
+extern crate sgx_tcrypto;
 extern crate sgx_types;
 use sgx_types::*;
 
+mod session;
+pub use self::session::*;
+
 ///
 /// The rsgx_ra_init function creates a context for the remote attestation and key exchange process.
 ///
@@ -217,6 +221,22 @@ pub fn rsgx_ra_get_keys(
     }
 }
 
+///
+/// rsgx_ra_get_secret_key gets a secret key derived from the remote attestation session like
+/// rsgx_ra_get_keys, wrapped in SecretKey128, which is cleared when it is dropped.
+///
+pub fn rsgx_ra_get_secret_key(
+    context: sgx_ra_context_t,
+    keytype: sgx_ra_key_type_t,
+) -> SgxResult<SecretKey128> {
+    let mut key = SecretKey128::default();
+    let ret = unsafe { sgx_ra_get_keys(context, keytype, key.as_mut_raw() as *mut sgx_ra_key_128_t) };
+    match ret {
+        sgx_status_t::SGX_SUCCESS => Ok(key),
+        _ => Err(ret),
+    }
+}
+
 ///
 /// rsgx_ra_close release context created by rsgx_ra_init or rsgx_ra_init_ex.
 ///
@@ -0,0 +1,208 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::{rsgx_ra_close, rsgx_ra_get_secret_key, rsgx_ra_init, rsgx_ra_init_ex};
+use core::ptr;
+use sgx_tcrypto::rsgx_rijndael128_cmac_msg;
+use sgx_types::*;
+
+/// The progress of a remote attestation session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RaSessionState {
+    /// Messages 1 to 3 are exchanged through the untrusted side, and the attestation
+    /// result is not known yet.
+    Pending,
+    /// The service provider accepted the attestation in message 4.
+    Trusted,
+    /// The service provider rejected the attestation in message 4.
+    Rejected,
+}
+
+///
+/// The enclave side of a remote attestation and key exchange with a service provider.
+///
+/// The session owns the context of rsgx_ra_init, and closes it when it is dropped. The
+/// messages are exchanged as follows:
+///
+/// * The untrusted side sends the extended EPID group ID as message 0, and message 1 from
+///   rsgx_ra_get_msg1 with the context of the session.
+/// * It passes message 2 of the service provider to rsgx_ra_proc_msg2, and sends back
+///   message 3, whose quote binds the session keys.
+/// * The enclave passes message 4 to `proc_msg4`, which checks it with the MK.
+///
+/// The SK and the MK are only released once the service provider has accepted the
+/// attestation.
+///
+/// # Examples
+///
+/// ```ignore
+/// static RA_SESSION: SgxMutex<Option<RaSession>> = SgxMutex::new(None);
+///
+/// #[no_mangle]
+/// pub extern "C" fn enclave_init_ra(context: &mut sgx_ra_context_t) -> sgx_status_t {
+///     match RaSession::new(&SERVICE_PROVIDER_PUBLIC_KEY) {
+///         Ok(session) => {
+///             *context = session.context();
+///             *RA_SESSION.lock().unwrap() = Some(session);
+///             sgx_status_t::SGX_SUCCESS
+///         }
+///         Err(e) => e,
+///     }
+/// }
+/// ```
+///
+pub struct RaSession {
+    context: sgx_ra_context_t,
+    state: RaSessionState,
+}
+
+impl RaSession {
+    ///
+    /// Creates a session with the service provider, using the key derivation of the SDK.
+    ///
+    /// # Parameters
+    ///
+    /// **sp_pub_key**
+    ///
+    /// The EC public key of the service provider, which signs message 2.
+    ///
+    /// # Errors
+    ///
+    /// The errors of rsgx_ra_init.
+    ///
+    pub fn new(sp_pub_key: &sgx_ec256_public_t) -> SgxResult<RaSession> {
+        let context = rsgx_ra_init(sp_pub_key, 0)?;
+        Ok(RaSession {
+            context,
+            state: RaSessionState::Pending,
+        })
+    }
+
+    ///
+    /// Creates a session with the service provider, which derives the session keys from the
+    /// shared secret with `derive_key_cb`.
+    ///
+    /// # Errors
+    ///
+    /// The errors of rsgx_ra_init_ex.
+    ///
+    pub fn with_key_derivation(
+        sp_pub_key: &sgx_ec256_public_t,
+        derive_key_cb: sgx_ra_derive_secret_keys_t,
+    ) -> SgxResult<RaSession> {
+        let context = rsgx_ra_init_ex(sp_pub_key, 0, derive_key_cb)?;
+        Ok(RaSession {
+            context,
+            state: RaSessionState::Pending,
+        })
+    }
+
+    /// The context the untrusted side passes to rsgx_ra_get_msg1 and rsgx_ra_proc_msg2.
+    pub fn context(&self) -> sgx_ra_context_t {
+        self.context
+    }
+
+    pub fn state(&self) -> RaSessionState {
+        self.state
+    }
+
+    ///
+    /// Processes message 4, the attestation result of the service provider.
+    ///
+    /// # Parameters
+    ///
+    /// **msg4**
+    ///
+    /// The attestation result, with its MAC computed with the MK.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// Message 2 has not been processed yet, or message 4 has already been processed.
+    ///
+    /// **SGX_ERROR_MAC_MISMATCH**
+    ///
+    /// The MAC of the message is invalid. The session stays pending.
+    ///
+    /// **SGX_ERROR_AE_SESSION_INVALID**
+    ///
+    /// The service provider rejected the attestation.
+    ///
+    pub fn proc_msg4(&mut self, msg4: &sgx_ra_msg4_t) -> SgxError {
+        if self.state != RaSessionState::Pending {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+
+        let mk = rsgx_ra_get_secret_key(self.context, sgx_ra_key_type_t::SGX_RA_KEY_MK)?;
+        let mac = rsgx_rijndael128_cmac_msg(mk.as_raw(), &msg4.status)?;
+        let diff = mac
+            .iter()
+            .zip(msg4.mac.iter())
+            .fold(0_u8, |acc, (x, y)| acc | (x ^ y));
+        if unsafe { ptr::read_volatile(&diff) } != 0 {
+            return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
+        }
+
+        if msg4.status == SGX_RA_MSG4_TRUSTED {
+            self.state = RaSessionState::Trusted;
+            Ok(())
+        } else {
+            self.state = RaSessionState::Rejected;
+            Err(sgx_status_t::SGX_ERROR_AE_SESSION_INVALID)
+        }
+    }
+
+    ///
+    /// Gets the SK, which protects the secrets exchanged with the service provider.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The service provider has not accepted the attestation.
+    ///
+    pub fn secret_key(&self) -> SgxResult<SecretKey128> {
+        self.trusted_key(sgx_ra_key_type_t::SGX_RA_KEY_SK)
+    }
+
+    ///
+    /// Gets the MK, which authenticates the messages exchanged with the service provider.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The service provider has not accepted the attestation.
+    ///
+    pub fn mac_key(&self) -> SgxResult<SecretKey128> {
+        self.trusted_key(sgx_ra_key_type_t::SGX_RA_KEY_MK)
+    }
+
+    fn trusted_key(&self, keytype: sgx_ra_key_type_t) -> SgxResult<SecretKey128> {
+        if self.state != RaSessionState::Trusted {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+        rsgx_ra_get_secret_key(self.context, keytype)
+    }
+}
+
+impl Drop for RaSession {
+    fn drop(&mut self) {
+        let _ = rsgx_ra_close(self.context);
+    }
+}
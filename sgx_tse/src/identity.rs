@@ -0,0 +1,84 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::se::rsgx_self_report;
+use sgx_types::*;
+
+///
+/// The identity of an enclave, as reported by its report body.
+///
+/// Besides the measurement, signer and product fields, it carries the Key Separation and
+/// Sharing (KSS) fields: the CONFIGID and CONFIGSVN supplied when the enclave is created,
+/// and the ISVEXTPRODID and ISVFAMILYID from the enclave signature. These fields are zero
+/// unless the enclave is signed with KSS enabled.
+///
+#[derive(Clone, Copy)]
+pub struct EnclaveIdentity {
+    pub mr_enclave: sgx_measurement_t,
+    pub mr_signer: sgx_measurement_t,
+    pub attributes: sgx_attributes_t,
+    pub misc_select: sgx_misc_select_t,
+    pub cpu_svn: sgx_cpu_svn_t,
+    pub isv_prod_id: sgx_prod_id_t,
+    pub isv_svn: sgx_isv_svn_t,
+    pub config_id: sgx_config_id_t,
+    pub config_svn: sgx_config_svn_t,
+    pub isv_ext_prod_id: sgx_isvext_prod_id_t,
+    pub isv_family_id: sgx_isvfamily_id_t,
+}
+
+impl EnclaveIdentity {
+    pub fn from_report_body(body: &sgx_report_body_t) -> EnclaveIdentity {
+        EnclaveIdentity {
+            mr_enclave: body.mr_enclave,
+            mr_signer: body.mr_signer,
+            attributes: body.attributes,
+            misc_select: body.misc_select,
+            cpu_svn: body.cpu_svn,
+            isv_prod_id: body.isv_prod_id,
+            isv_svn: body.isv_svn,
+            config_id: body.config_id,
+            config_svn: body.config_svn,
+            isv_ext_prod_id: body.isv_ext_prod_id,
+            isv_family_id: body.isv_family_id,
+        }
+    }
+
+    /// Returns true if the enclave was launched in debug mode.
+    pub fn is_debug(&self) -> bool {
+        (self.attributes.flags & SGX_FLAGS_DEBUG) != 0
+    }
+
+    /// Returns true if Key Separation and Sharing is enabled for the enclave.
+    pub fn kss_enabled(&self) -> bool {
+        (self.attributes.flags & SGX_FLAGS_KSS) != 0
+    }
+}
+
+impl From<&sgx_report_body_t> for EnclaveIdentity {
+    fn from(body: &sgx_report_body_t) -> EnclaveIdentity {
+        EnclaveIdentity::from_report_body(body)
+    }
+}
+
+///
+/// rsgx_self_identity returns the identity of the calling enclave, read from the report
+/// returned by rsgx_self_report.
+///
+pub fn rsgx_self_identity() -> EnclaveIdentity {
+    EnclaveIdentity::from_report_body(&rsgx_self_report().body)
+}
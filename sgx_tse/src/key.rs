@@ -0,0 +1,301 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::se::{rsgx_get_secret_key, rsgx_self_report};
+use core::ops::{BitOr, BitOrAssign};
+use sgx_tcrypto::rsgx_sp800_108_cmac_kdf;
+use sgx_types::*;
+
+///
+/// The key requested from EGETKEY.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyName {
+    /// The EINITTOKEN key, only available to the launch enclave.
+    EinitToken,
+    /// The provisioning key, only available to enclaves with the PROVISIONKEY attribute.
+    Provision,
+    /// The provisioning seal key, only available to enclaves with the PROVISIONKEY attribute.
+    ProvisionSeal,
+    /// The report key, which authenticates reports targeted at this enclave.
+    Report,
+    /// The seal key.
+    Seal,
+}
+
+impl KeyName {
+    fn from_raw(key_name: u16) -> SgxResult<KeyName> {
+        match key_name {
+            SGX_KEYSELECT_LICENSE => Ok(KeyName::EinitToken),
+            SGX_KEYSELECT_PROVISION => Ok(KeyName::Provision),
+            SGX_KEYSELECT_PROVISION_SEAL => Ok(KeyName::ProvisionSeal),
+            SGX_KEYSELECT_REPORT => Ok(KeyName::Report),
+            SGX_KEYSELECT_SEAL => Ok(KeyName::Seal),
+            _ => Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
+        }
+    }
+
+    // The seal keys are bound to the enclave identity selected by the key policy.
+    fn uses_policy(self) -> bool {
+        matches!(self, KeyName::Seal | KeyName::ProvisionSeal)
+    }
+}
+
+impl From<KeyName> for u16 {
+    fn from(key_name: KeyName) -> u16 {
+        match key_name {
+            KeyName::EinitToken => SGX_KEYSELECT_LICENSE,
+            KeyName::Provision => SGX_KEYSELECT_PROVISION,
+            KeyName::ProvisionSeal => SGX_KEYSELECT_PROVISION_SEAL,
+            KeyName::Report => SGX_KEYSELECT_REPORT,
+            KeyName::Seal => SGX_KEYSELECT_SEAL,
+        }
+    }
+}
+
+///
+/// The identity fields a seal key is derived from. Policies are combined with `|`.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyPolicy(u16);
+
+impl KeyPolicy {
+    /// Derive the key from the enclave measurement.
+    pub const MRENCLAVE: KeyPolicy = KeyPolicy(SGX_KEYPOLICY_MRENCLAVE);
+    /// Derive the key from the enclave signer.
+    pub const MRSIGNER: KeyPolicy = KeyPolicy(SGX_KEYPOLICY_MRSIGNER);
+    /// Derive the key without the ISVPRODID of the enclave.
+    pub const NOISVPRODID: KeyPolicy = KeyPolicy(SGX_KEYPOLICY_NOISVPRODID);
+    /// Derive the key from the CONFIGID of the enclave.
+    pub const CONFIGID: KeyPolicy = KeyPolicy(SGX_KEYPOLICY_CONFIGID);
+    /// Derive the key from the ISVFAMILYID of the enclave.
+    pub const ISVFAMILYID: KeyPolicy = KeyPolicy(SGX_KEYPOLICY_ISVFAMILYID);
+    /// Derive the key from the ISVEXTPRODID of the enclave.
+    pub const ISVEXTPRODID: KeyPolicy = KeyPolicy(SGX_KEYPOLICY_ISVEXTPRODID);
+    /// All the Key Separation and Sharing fields: CONFIGID, ISVFAMILYID and ISVEXTPRODID.
+    pub const KSS: KeyPolicy =
+        KeyPolicy(SGX_KEYPOLICY_CONFIGID | SGX_KEYPOLICY_ISVFAMILYID | SGX_KEYPOLICY_ISVEXTPRODID);
+
+    const ALL: KeyPolicy = KeyPolicy(
+        SGX_KEYPOLICY_MRENCLAVE
+            | SGX_KEYPOLICY_MRSIGNER
+            | SGX_KEYPOLICY_NOISVPRODID
+            | SGX_KEYPOLICY_CONFIGID
+            | SGX_KEYPOLICY_ISVFAMILYID
+            | SGX_KEYPOLICY_ISVEXTPRODID,
+    );
+
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    /// Returns None if `bits` contains an unknown policy.
+    pub const fn from_bits(bits: u16) -> Option<KeyPolicy> {
+        if bits & !KeyPolicy::ALL.0 != 0 {
+            None
+        } else {
+            Some(KeyPolicy(bits))
+        }
+    }
+
+    pub const fn contains(self, other: KeyPolicy) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn intersects(self, other: KeyPolicy) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for KeyPolicy {
+    type Output = KeyPolicy;
+
+    fn bitor(self, other: KeyPolicy) -> KeyPolicy {
+        KeyPolicy(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for KeyPolicy {
+    fn bitor_assign(&mut self, other: KeyPolicy) {
+        self.0 |= other.0;
+    }
+}
+
+///
+/// A builder for `sgx_key_request_t`.
+///
+/// The builder starts from the choices sgx_tseal makes for its seal keys: the MRSIGNER
+/// policy, with the KSS fields when the enclave has the KSS attribute, the current CPUSVN,
+/// ISVSVN and CONFIGSVN of the enclave, the default attribute and misc masks, and a zero
+/// key ID.
+///
+#[derive(Clone)]
+pub struct KeyRequestBuilder {
+    key_name: KeyName,
+    request: sgx_key_request_t,
+}
+
+impl KeyRequestBuilder {
+    pub fn new(key_name: KeyName) -> KeyRequestBuilder {
+        let report = rsgx_self_report();
+        let mut policy = KeyPolicy::MRSIGNER;
+        if (report.body.attributes.flags & SGX_FLAGS_KSS) != 0 {
+            policy |= KeyPolicy::KSS;
+        }
+
+        let request = sgx_key_request_t {
+            key_name: key_name.into(),
+            key_policy: policy.bits(),
+            isv_svn: report.body.isv_svn,
+            reserved1: 0_u16,
+            cpu_svn: report.body.cpu_svn,
+            attribute_mask: sgx_attributes_t {
+                flags: TSEAL_DEFAULT_FLAGSMASK,
+                xfrm: 0,
+            },
+            key_id: sgx_key_id_t::default(),
+            misc_mask: TSEAL_DEFAULT_MISCMASK,
+            config_svn: report.body.config_svn,
+            reserved2: [0_u8; SGX_KEY_REQUEST_RESERVED2_BYTES],
+        };
+        KeyRequestBuilder { key_name, request }
+    }
+
+    ///
+    /// Starts from an existing key request, e.g. the one stored in a sealed blob.
+    ///
+    /// Returns SGX_ERROR_INVALID_PARAMETER if the key name is unknown.
+    ///
+    pub fn from_request(request: &sgx_key_request_t) -> SgxResult<KeyRequestBuilder> {
+        let key_name = KeyName::from_raw(request.key_name)?;
+        let mut request = *request;
+        request.reserved1 = 0;
+        request.reserved2 = [0_u8; SGX_KEY_REQUEST_RESERVED2_BYTES];
+        Ok(KeyRequestBuilder { key_name, request })
+    }
+
+    pub fn policy(&mut self, policy: KeyPolicy) -> &mut KeyRequestBuilder {
+        self.request.key_policy = policy.bits();
+        self
+    }
+
+    pub fn isv_svn(&mut self, isv_svn: sgx_isv_svn_t) -> &mut KeyRequestBuilder {
+        self.request.isv_svn = isv_svn;
+        self
+    }
+
+    pub fn cpu_svn(&mut self, cpu_svn: sgx_cpu_svn_t) -> &mut KeyRequestBuilder {
+        self.request.cpu_svn = cpu_svn;
+        self
+    }
+
+    pub fn config_svn(&mut self, config_svn: sgx_config_svn_t) -> &mut KeyRequestBuilder {
+        self.request.config_svn = config_svn;
+        self
+    }
+
+    pub fn attribute_mask(&mut self, attribute_mask: sgx_attributes_t) -> &mut KeyRequestBuilder {
+        self.request.attribute_mask = attribute_mask;
+        self
+    }
+
+    pub fn misc_mask(&mut self, misc_mask: sgx_misc_select_t) -> &mut KeyRequestBuilder {
+        self.request.misc_mask = misc_mask;
+        self
+    }
+
+    pub fn key_id(&mut self, key_id: sgx_key_id_t) -> &mut KeyRequestBuilder {
+        self.request.key_id = key_id;
+        self
+    }
+
+    ///
+    /// Sets a random key ID, so that each request yields a different key, as sgx_tseal does
+    /// for every sealed blob.
+    ///
+    pub fn random_key_id(&mut self) -> SgxResult<&mut KeyRequestBuilder> {
+        let mut key_id = sgx_key_id_t::default();
+        let ret = unsafe { sgx_read_rand(key_id.id.as_mut_ptr(), key_id.id.len()) };
+        match ret {
+            sgx_status_t::SGX_SUCCESS => {
+                self.request.key_id = key_id;
+                Ok(self)
+            }
+            _ => Err(ret),
+        }
+    }
+
+    ///
+    /// Validates the request and returns it.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The policy contains unknown bits. Or a seal key is requested with neither MRENCLAVE
+    /// nor MRSIGNER, or without the INITTED and DEBUG bits in the attribute mask.
+    ///
+    /// **SGX_ERROR_INVALID_ISVSVN**
+    ///
+    /// The ISVSVN, or the CONFIGSVN with the CONFIGID policy, is greater than the one of
+    /// the enclave.
+    ///
+    pub fn build(&self) -> SgxResult<sgx_key_request_t> {
+        let policy = KeyPolicy::from_bits(self.request.key_policy)
+            .ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+        if self.key_name.uses_policy() {
+            if !policy.intersects(KeyPolicy::MRENCLAVE | KeyPolicy::MRSIGNER) {
+                return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+            }
+            let required = SGX_FLAGS_INITTED | SGX_FLAGS_DEBUG;
+            if (self.request.attribute_mask.flags & required) != required {
+                return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+            }
+        }
+
+        let report = rsgx_self_report();
+        if self.request.isv_svn > report.body.isv_svn {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_ISVSVN);
+        }
+        if policy.contains(KeyPolicy::CONFIGID) && self.request.config_svn > report.body.config_svn
+        {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_ISVSVN);
+        }
+        Ok(self.request)
+    }
+
+    ///
+    /// Validates the request and gets the key from EGETKEY.
+    ///
+    pub fn get_key(&self) -> SgxResult<SecretKey128> {
+        rsgx_get_secret_key(&self.build()?)
+    }
+
+    ///
+    /// Derives a subkey for one purpose from the requested key.
+    ///
+    /// The subkey is derived with the NIST SP 800-108 KDF in counter mode with AES-CMAC, and
+    /// `label` as the label. Different labels give independent keys, so the same request
+    /// can serve several purposes without exposing the key returned by EGETKEY.
+    ///
+    pub fn derive_key(&self, label: &[u8]) -> SgxResult<SecretKey128> {
+        let key = self.get_key()?;
+        let mut subkey = SecretKey128::default();
+        rsgx_sp800_108_cmac_kdf(key.as_raw(), label, &[], subkey.as_mut_raw())?;
+        Ok(subkey)
+    }
+}
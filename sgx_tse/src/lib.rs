@@ -24,7 +24,19 @@
 #![cfg_attr(target_env = "sgx", feature(rustc_private))]
 #![allow(non_camel_case_types)]
 
+extern crate alloc;
+
+extern crate sgx_tcrypto;
 extern crate sgx_types;
 
 mod se;
 pub use self::se::*;
+
+mod key;
+pub use self::key::*;
+
+mod reportdata;
+pub use self::reportdata::*;
+
+mod identity;
+pub use self::identity::*;
@@ -0,0 +1,193 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::se::rsgx_create_report;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ptr;
+use sgx_tcrypto::{SgxSha384Handle, SgxShaHandle};
+use sgx_types::*;
+
+///
+/// The hash that binds the fields into the report data.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportDataHash {
+    /// The SHA-256 digest fills the first 32 bytes of the report data.
+    Sha256,
+    /// The SHA-384 digest fills the first 48 bytes of the report data.
+    Sha384,
+}
+
+///
+/// Binds a set of labeled fields, such as a public key and a nonce, into the report data of
+/// an enclave report, and checks that a received report binds the same fields.
+///
+/// The report data is the hash of the fields sorted by label, each encoded as the length
+/// of its label as a little-endian u32, the label, the length of its value as a
+/// little-endian u64, and the value. The rest of the report data is zero. The encoding is
+/// unambiguous, so different fields never give the same report data, and the order in
+/// which the fields are added does not matter.
+///
+/// # Examples
+///
+/// ```ignore
+/// // The attesting enclave.
+/// let report = ReportDataBuilder::new(ReportDataHash::Sha256)
+///     .public_key(&public_key)
+///     .nonce(&nonce)
+///     .create_report(&target_info)?;
+///
+/// // The verifying enclave, after rsgx_verify_report.
+/// ReportDataBuilder::new(ReportDataHash::Sha256)
+///     .public_key(&public_key)
+///     .nonce(&nonce)
+///     .verify(&report.body)?;
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct ReportDataBuilder {
+    hash: ReportDataHash,
+    fields: Vec<(String, Vec<u8>)>,
+}
+
+impl ReportDataBuilder {
+    /// The label of the field set by `public_key`.
+    pub const PUBLIC_KEY: &'static str = "public_key";
+    /// The label of the field set by `nonce`.
+    pub const NONCE: &'static str = "nonce";
+    /// The label of the field set by `user_data`.
+    pub const USER_DATA: &'static str = "user_data";
+
+    pub fn new(hash: ReportDataHash) -> ReportDataBuilder {
+        ReportDataBuilder {
+            hash,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Adds a field. The labels must be unique and not empty.
+    pub fn field(&mut self, label: &str, value: &[u8]) -> &mut ReportDataBuilder {
+        self.fields.push((String::from(label), value.to_vec()));
+        self
+    }
+
+    /// Adds the encoded public key the report vouches for, such as a DH or TLS key.
+    pub fn public_key(&mut self, public_key: &[u8]) -> &mut ReportDataBuilder {
+        self.field(Self::PUBLIC_KEY, public_key)
+    }
+
+    /// Adds the nonce of the verifier, which makes the report fresh.
+    pub fn nonce(&mut self, nonce: &[u8]) -> &mut ReportDataBuilder {
+        self.field(Self::NONCE, nonce)
+    }
+
+    /// Adds application data.
+    pub fn user_data(&mut self, user_data: &[u8]) -> &mut ReportDataBuilder {
+        self.field(Self::USER_DATA, user_data)
+    }
+
+    ///
+    /// Computes the report data.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// No field is added, or a label is empty or added twice.
+    ///
+    /// **SGX_ERROR_OUT_OF_MEMORY**
+    ///
+    /// Not enough memory is available to complete this operation.
+    ///
+    pub fn build(&self) -> SgxResult<sgx_report_data_t> {
+        let mut fields: Vec<&(String, Vec<u8>)> = self.fields.iter().collect();
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        if fields.is_empty()
+            || fields.iter().any(|(label, _)| label.is_empty())
+            || fields.windows(2).any(|pair| pair[0].0 == pair[1].0)
+        {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let encode = |update: &mut dyn FnMut(&[u8]) -> SgxError| -> SgxError {
+            for (label, value) in fields.iter() {
+                update(&(label.len() as u32).to_le_bytes())?;
+                update(label.as_bytes())?;
+                update(&(value.len() as u64).to_le_bytes())?;
+                update(value)?;
+            }
+            Ok(())
+        };
+
+        let mut report_data = sgx_report_data_t::default();
+        match self.hash {
+            ReportDataHash::Sha256 => {
+                let handle = SgxShaHandle::new();
+                handle.init()?;
+                encode(&mut |data| handle.update_slice(data))?;
+                report_data.d[..SGX_SHA256_HASH_SIZE].copy_from_slice(&handle.get_hash()?);
+            }
+            ReportDataHash::Sha384 => {
+                let handle = SgxSha384Handle::new();
+                handle.init()?;
+                encode(&mut |data| handle.update_slice(data))?;
+                report_data.d[..SGX_SHA384_HASH_SIZE].copy_from_slice(&handle.get_hash()?);
+            }
+        }
+        Ok(report_data)
+    }
+
+    ///
+    /// Creates a report of the calling enclave for the target enclave, with the report data
+    /// binding the fields.
+    ///
+    /// # Errors
+    ///
+    /// The errors of `build` and of `rsgx_create_report`.
+    ///
+    pub fn create_report(&self, target_info: &sgx_target_info_t) -> SgxResult<sgx_report_t> {
+        rsgx_create_report(target_info, &self.build()?)
+    }
+
+    ///
+    /// Checks that a report binds the fields.
+    ///
+    /// The report itself must be verified first, with rsgx_verify_report for a local
+    /// report or by verifying the quote that carries it.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_MAC_MISMATCH**
+    ///
+    /// The report data does not bind the fields.
+    ///
+    /// The errors of `build`.
+    ///
+    pub fn verify(&self, report: &sgx_report_body_t) -> SgxError {
+        let expected = self.build()?;
+        let diff = expected
+            .d
+            .iter()
+            .zip(report.report_data.d.iter())
+            .fold(0_u8, |acc, (x, y)| acc | (x ^ y));
+        if unsafe { ptr::read_volatile(&diff) } != 0 {
+            return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
+        }
+        Ok(())
+    }
+}
@@ -230,6 +230,24 @@ pub fn rsgx_get_align_key(key_request: &sgx_key_request_t) -> SgxResult<sgx_alig
     }
 }
 
+///
+/// rsgx_get_secret_key generates a 128-bit secret key like rsgx_get_key, wrapped in
+/// SecretKey128, which is cleared when it is dropped.
+///
+pub fn rsgx_get_secret_key(key_request: &sgx_key_request_t) -> SgxResult<SecretKey128> {
+    let mut key = SecretKey128::default();
+    let ret = unsafe {
+        sgx_get_key(
+            key_request as *const sgx_key_request_t,
+            key.as_mut_raw() as *mut sgx_key_128bit_t,
+        )
+    };
+    match ret {
+        sgx_status_t::SGX_SUCCESS => Ok(key),
+        _ => Err(ret),
+    }
+}
+
 pub fn rsgx_self_report() -> sgx_report_t {
     unsafe { *sgx_self_report() }
 }
@@ -25,7 +25,7 @@ use sgx_tse::*;
 use sgx_types::*;
 
 /* intel sgx sdk 2.4 */
-const KEY_POLICY_KSS: uint16_t =
+pub const KEY_POLICY_KSS: uint16_t =
     SGX_KEYPOLICY_CONFIGID | SGX_KEYPOLICY_ISVFAMILYID | SGX_KEYPOLICY_ISVEXTPRODID;
 
 #[derive(Clone, Default)]
@@ -402,6 +402,51 @@ impl SgxInternalSealedData {
         self.unseal_data_helper()
     }
 
+    pub fn reseal_data_ex(
+        &self,
+        key_policy: u16,
+        attribute_mask: sgx_attributes_t,
+        misc_mask: sgx_misc_select_t,
+    ) -> SgxResult<Self> {
+        /* refuse to migrate a blob sealed by a newer enclave */
+        let report = rsgx_self_report();
+        if self.key_request.isv_svn > report.body.isv_svn {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_ISVSVN);
+        }
+        if (self.key_request.key_policy & SGX_KEYPOLICY_CONFIGID) != 0
+            && self.key_request.config_svn > report.body.config_svn
+        {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_ISVSVN);
+        }
+
+        let mut unsealed_data = self.unseal_data()?;
+        let result = Self::seal_data_ex(
+            key_policy,
+            attribute_mask,
+            misc_mask,
+            unsealed_data.get_additional_txt(),
+            unsealed_data.get_decrypt_txt(),
+        );
+
+        unsafe {
+            ptr::write_bytes(
+                unsealed_data.decrypt.as_mut_ptr(),
+                0_u8,
+                unsealed_data.decrypt.len(),
+            );
+        }
+
+        result
+    }
+
+    pub fn upgrade_to_current_svn(&self) -> SgxResult<Self> {
+        self.reseal_data_ex(
+            self.key_request.key_policy,
+            self.key_request.attribute_mask,
+            self.key_request.misc_mask,
+        )
+    }
+
     pub fn mac_aadata(additional_text: &[u8]) -> SgxResult<Self> {
         let attribute_mask = sgx_attributes_t {
             flags: TSEAL_DEFAULT_FLAGSMASK,
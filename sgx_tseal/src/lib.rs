@@ -22,6 +22,7 @@
 //! * Exposes APIs to create sealed data which is both confidentiality andintegrity protected.
 //! * Exposes an API to unseal sealed data inside the enclave.
 //! * Provides APIs to authenticate and verify the input data with AES-GMAC.
+//! * Provides `SealingWriter` and `UnsealingReader` to seal streams in authenticated chunks (feature `stream`).
 //!
 //! The library also provides APIs to help calculate the sealed data size, encrypt text length, and Message Authentication Code (MAC) text length.
 //!
@@ -76,7 +77,6 @@
     all(target_env = "sgx", target_vendor = "mesalock"),
     feature(rustc_private)
 )]
-
 #![allow(non_camel_case_types)]
 #![allow(unused_assignments)]
 #![allow(clippy::missing_safety_doc)]
@@ -84,6 +84,11 @@
 #[macro_use]
 extern crate alloc;
 
+#[cfg(all(feature = "stream", not(target_env = "sgx")))]
+extern crate sgx_tstd as std;
+#[cfg(all(feature = "stream", target_env = "sgx"))]
+extern crate std;
+
 extern crate sgx_tcrypto;
 extern crate sgx_trts;
 extern crate sgx_tse;
@@ -96,3 +101,8 @@ mod aad;
 pub use self::aad::SgxMacAadata;
 
 mod internal;
+
+#[cfg(feature = "stream")]
+mod stream;
+#[cfg(feature = "stream")]
+pub use self::stream::{SealingWriter, UnsealingReader, SGX_SEAL_STREAM_DEFAULT_CHUNK_SIZE};
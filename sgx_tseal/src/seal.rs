@@ -770,6 +770,118 @@ impl<'a, T: 'a + ?Sized> SgxSealedData<'a, T> {
         SgxSealedData::default()
     }
 
+    ///
+    /// This function is used to migrate the sealed data to a new key policy. The sealed
+    /// data is unsealed and sealed again with a seal key derived from the current
+    /// enclave identity, the given key policy and masks.
+    ///
+    /// # Description
+    ///
+    /// The reseal_with function unseals the data blob, checks that the ISVSVN (and the
+    /// CONFIGSVN if the blob uses a KSS policy) in its key request is not newer than the
+    /// current enclave, and seals the decrypted text and the additional text again. A new
+    /// key ID is generated and the current CPUSVN, ISVSVN and CONFIGSVN are recorded. The
+    /// original sealed data is not modified, so the caller can replace the stored blob only
+    /// once the new one is returned.
+    ///
+    /// A blob sealed with the KEYPOLICY_MRENCLAVE policy can only be unsealed by the enclave
+    /// that sealed it. Such blobs must be resealed (e.g. to KEYPOLICY_MRSIGNER) by the old
+    /// enclave before it is replaced by a new version.
+    ///
+    /// # Requirements
+    ///
+    /// Library: libsgx_tservice.a or libsgx_tservice_sim.a (simulation)
+    ///
+    /// # Parameters
+    ///
+    /// **key_policy**
+    ///
+    /// Specifies the policy to use in the key derivation of the new sealed data.
+    ///
+    /// **attribute_mask**
+    ///
+    /// Identifies which platform/enclave attributes to use in the key derivation.
+    ///
+    /// **misc_mask**
+    ///
+    /// The misc mask bits for the enclave. Reserved for future function extension.
+    ///
+    /// # Return value
+    ///
+    /// The resealed data in SgxSealedData.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The key policy or the attribute mask is invalid, or the sealed data is not within the enclave.
+    ///
+    /// **SGX_ERROR_INVALID_CPUSVN**
+    ///
+    /// The CPUSVN in the sealed data blob is beyond the CPUSVN value of the platform.
+    ///
+    /// **SGX_ERROR_INVALID_ISVSVN**
+    ///
+    /// The ISVSVN or CONFIGSVN in the sealed data blob is greater than the value of the enclave.
+    ///
+    /// **SGX_ERROR_MAC_MISMATCH**
+    ///
+    /// The tag verification failed during unsealing.
+    ///
+    /// **SGX_ERROR_OUT_OF_MEMORY**
+    ///
+    /// The enclave is out of memory.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// Indicates a crypto library failure or the RDRAND instruction fails to generate a
+    /// random number.
+    ///
+    pub fn reseal_with(
+        &self,
+        key_policy: u16,
+        attribute_mask: sgx_attributes_t,
+        misc_mask: sgx_misc_select_t,
+    ) -> SgxResult<Self> {
+        let result = self
+            .inner
+            .reseal_data_ex(key_policy, attribute_mask, misc_mask);
+        result.map(|x| SgxSealedData {
+            inner: x,
+            marker: PhantomData,
+        })
+    }
+
+    ///
+    /// This function is used to reseal the sealed data with the same key policy and masks,
+    /// binding it to the current CPUSVN, ISVSVN and CONFIGSVN of the enclave.
+    ///
+    /// # Description
+    ///
+    /// The upgrade_to_current_svn function is a shortcut of `reseal_with` which keeps the
+    /// key policy, attribute mask and misc mask recorded in the sealed data. Once resealed,
+    /// the data can no longer be unsealed by older versions of the enclave.
+    ///
+    /// # Requirements
+    ///
+    /// Library: libsgx_tservice.a or libsgx_tservice_sim.a (simulation)
+    ///
+    /// # Return value
+    ///
+    /// The resealed data in SgxSealedData.
+    ///
+    /// # Errors
+    ///
+    /// See `reseal_with`.
+    ///
+    pub fn upgrade_to_current_svn(&self) -> SgxResult<Self> {
+        let result = self.inner.upgrade_to_current_svn();
+        result.map(|x| SgxSealedData {
+            inner: x,
+            marker: PhantomData,
+        })
+    }
+
     ///
     /// Get the size of payload in SgxSealedData.
     ///
@@ -791,6 +903,30 @@ impl<'a, T: 'a + ?Sized> SgxSealedData<'a, T> {
         self.inner.get_key_request()
     }
 
+    ///
+    /// Get the key policy recorded in the sgx_key_request_t of SgxSealedData.
+    ///
+    /// The policy can be inspected before unsealing to find blobs that are bound
+    /// to MRENCLAVE and must be resealed before the enclave is upgraded.
+    ///
+    pub fn get_key_policy(&self) -> u16 {
+        self.inner.get_key_request().key_policy
+    }
+
+    ///
+    /// Get the ISVSVN recorded in the sgx_key_request_t of SgxSealedData.
+    ///
+    pub fn get_isv_svn(&self) -> sgx_isv_svn_t {
+        self.inner.get_key_request().isv_svn
+    }
+
+    ///
+    /// Get the CPUSVN recorded in the sgx_key_request_t of SgxSealedData.
+    ///
+    pub fn get_cpu_svn(&self) -> &sgx_cpu_svn_t {
+        &self.inner.get_key_request().cpu_svn
+    }
+
     ///
     /// Get a slice of encrypt text in SgxSealedData.
     ///
@@ -0,0 +1,535 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Intel(R) Software Guard Extensions Streaming Sealing and Unsealing
+//!
+//! `SealingWriter` and `UnsealingReader` seal payloads which do not fit in the
+//! enclave heap. One seal key is derived for the whole stream, and the payload is
+//! split into AES-GCM authenticated chunks.
+//!
+//! The stream layout is:
+//!
+//! Field | Size | Description
+//! ---|---|---
+//! magic | 8 | "SGXSEALS"
+//! version | 4 | little-endian, currently 1
+//! chunk_size | 4 | little-endian, maximum plaintext size of a chunk
+//! key_request | 512 | the sgx_key_request_t used to derive the seal key
+//! chunks | ... | a sequence of chunks, the last one carries the final flag
+//!
+//! Each chunk is made of a flags byte, the little-endian u32 length of the encrypted
+//! text, the encrypted text and the 16 bytes MAC. The IV of a chunk is its sequence
+//! number, and the sequence number and the flags are authenticated as additional
+//! data, so dropping, reordering or truncating chunks makes unsealing fail.
+//!
+use crate::internal::KEY_POLICY_KSS;
+use core::cmp;
+use core::mem;
+use core::ptr;
+use core::slice;
+use sgx_tcrypto::*;
+use sgx_trts::trts::*;
+use sgx_tse::*;
+use sgx_types::*;
+use std::io::{self, Read, Write};
+use std::vec::Vec;
+
+/// The default maximum size of the plain text in one chunk.
+pub const SGX_SEAL_STREAM_DEFAULT_CHUNK_SIZE: usize = 0x10000;
+
+const STREAM_MAGIC: [u8; 8] = *b"SGXSEALS";
+const STREAM_VERSION: u32 = 1;
+const STREAM_HEADER_SIZE: usize = 16 + mem::size_of::<sgx_key_request_t>();
+const CHUNK_HEADER_SIZE: usize = 5;
+const CHUNK_FLAG_FINAL: u8 = 0x01;
+const CHUNK_AAD_SIZE: usize = 9;
+
+fn chunk_iv(seq: u64) -> [u8; SGX_AESGCM_IV_SIZE] {
+    let mut iv = [0_u8; SGX_AESGCM_IV_SIZE];
+    iv[..8].copy_from_slice(&seq.to_le_bytes());
+    iv
+}
+
+fn chunk_aad(seq: u64, flags: u8) -> [u8; CHUNK_AAD_SIZE] {
+    let mut aad = [0_u8; CHUNK_AAD_SIZE];
+    aad[..8].copy_from_slice(&seq.to_le_bytes());
+    aad[8] = flags;
+    aad
+}
+
+// The key is kept in the aligned structure it is derived into, rather than copied out.
+fn derive_seal_key(key_request: &sgx_key_request_t) -> SgxResult<sgx_align_key_128bit_t> {
+    rsgx_get_align_key(key_request).map_err(|ret| {
+        if (ret == sgx_status_t::SGX_ERROR_INVALID_CPUSVN)
+            || (ret == sgx_status_t::SGX_ERROR_INVALID_ISVSVN)
+            || (ret == sgx_status_t::SGX_ERROR_OUT_OF_MEMORY)
+        {
+            ret
+        } else {
+            sgx_status_t::SGX_ERROR_UNEXPECTED
+        }
+    })
+}
+
+fn zero_key(key: &mut sgx_align_key_128bit_t) {
+    unsafe { ptr::write_volatile(key, sgx_align_key_128bit_t::default()) };
+}
+
+/// A writer which seals all the data written to it into the inner writer.
+///
+/// The stream must be terminated with `finish`. A stream which is dropped without
+/// being finished has no final chunk and is rejected by `UnsealingReader`.
+pub struct SealingWriter<W: Write> {
+    inner: Option<W>,
+    key_request: sgx_key_request_t,
+    key: sgx_align_key_128bit_t,
+    chunk_size: usize,
+    buf: Vec<u8>,
+    seq: u64,
+    header_written: bool,
+}
+
+impl<W: Write> SealingWriter<W> {
+    ///
+    /// Create a SealingWriter with the same key policy and masks as `SgxSealedData::seal_data`,
+    /// and the default chunk size.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_OUT_OF_MEMORY**
+    ///
+    /// The enclave is out of memory.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// Indicates the seal key derivation failed or the RDRAND instruction fails to generate a
+    /// random number.
+    ///
+    pub fn new(inner: W) -> SgxResult<Self> {
+        let attribute_mask = sgx_attributes_t {
+            flags: TSEAL_DEFAULT_FLAGSMASK,
+            xfrm: 0,
+        };
+        let mut key_policy = SGX_KEYPOLICY_MRSIGNER;
+        let report = rsgx_self_report();
+        if (report.body.attributes.flags & SGX_FLAGS_KSS) != 0 {
+            key_policy = SGX_KEYPOLICY_MRSIGNER | KEY_POLICY_KSS;
+        }
+
+        Self::new_ex(
+            key_policy,
+            attribute_mask,
+            TSEAL_DEFAULT_MISCMASK,
+            SGX_SEAL_STREAM_DEFAULT_CHUNK_SIZE,
+            inner,
+        )
+    }
+
+    ///
+    /// Create a SealingWriter with the given key policy, masks and chunk size. This is the
+    /// expert mode version of function `new`.
+    ///
+    /// # Parameters
+    ///
+    /// **key_policy**
+    ///
+    /// Specifies the policy to use in the key derivation, see `SgxSealedData::seal_data_ex`.
+    ///
+    /// **attribute_mask**
+    ///
+    /// Identifies which platform/enclave attributes to use in the key derivation.
+    ///
+    /// **misc_mask**
+    ///
+    /// The misc mask bits for the enclave. Reserved for future function extension.
+    ///
+    /// **chunk_size**
+    ///
+    /// The maximum size of the plain text in one chunk. Must be non-zero and below 4GB.
+    ///
+    /// **inner**
+    ///
+    /// The writer which receives the sealed stream.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The key policy, the attribute mask or the chunk size is invalid.
+    ///
+    /// **SGX_ERROR_OUT_OF_MEMORY**
+    ///
+    /// The enclave is out of memory.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// Indicates the seal key derivation failed or the RDRAND instruction fails to generate a
+    /// random number.
+    ///
+    pub fn new_ex(
+        key_policy: u16,
+        attribute_mask: sgx_attributes_t,
+        misc_mask: sgx_misc_select_t,
+        chunk_size: usize,
+        inner: W,
+    ) -> SgxResult<Self> {
+        if chunk_size == 0 || chunk_size >= u32::MAX as usize {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        if (key_policy
+            & (!(SGX_KEYPOLICY_MRENCLAVE
+                | SGX_KEYPOLICY_MRSIGNER
+                | KEY_POLICY_KSS
+                | SGX_KEYPOLICY_NOISVPRODID))
+            != 0)
+            || ((key_policy & (SGX_KEYPOLICY_MRENCLAVE | SGX_KEYPOLICY_MRSIGNER)) == 0)
+        {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        if ((attribute_mask.flags & SGX_FLAGS_INITTED) == 0)
+            || ((attribute_mask.flags & SGX_FLAGS_DEBUG) == 0)
+        {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let mut key_id = sgx_key_id_t::default();
+        rsgx_read_rand(&mut key_id.id)?;
+
+        let report = rsgx_self_report();
+        let key_request = sgx_key_request_t {
+            key_name: SGX_KEYSELECT_SEAL,
+            key_policy,
+            isv_svn: report.body.isv_svn,
+            reserved1: 0_u16,
+            cpu_svn: report.body.cpu_svn,
+            attribute_mask,
+            key_id,
+            misc_mask,
+            config_svn: report.body.config_svn,
+            reserved2: [0_u8; SGX_KEY_REQUEST_RESERVED2_BYTES],
+        };
+        let key = derive_seal_key(&key_request)?;
+
+        Ok(SealingWriter {
+            inner: Some(inner),
+            key_request,
+            key,
+            chunk_size,
+            buf: Vec::new(),
+            seq: 0,
+            header_written: false,
+        })
+    }
+
+    ///
+    /// Get the sgx_key_request_t used to derive the seal key of the stream.
+    ///
+    pub fn get_key_request(&self) -> &sgx_key_request_t {
+        &self.key_request
+    }
+
+    ///
+    /// Get a reference to the inner writer.
+    ///
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().unwrap()
+    }
+
+    ///
+    /// Seal the buffered data as the final chunk, flush the inner writer and return it.
+    ///
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_chunk(CHUNK_FLAG_FINAL)?;
+        self.inner_mut().flush()?;
+        Ok(self.inner.take().unwrap())
+    }
+
+    fn inner_mut(&mut self) -> &mut W {
+        self.inner.as_mut().unwrap()
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let mut header = Vec::with_capacity(STREAM_HEADER_SIZE);
+        header.extend_from_slice(&STREAM_MAGIC);
+        header.extend_from_slice(&STREAM_VERSION.to_le_bytes());
+        header.extend_from_slice(&(self.chunk_size as u32).to_le_bytes());
+        header.extend_from_slice(unsafe {
+            slice::from_raw_parts(
+                &self.key_request as *const _ as *const u8,
+                mem::size_of::<sgx_key_request_t>(),
+            )
+        });
+        self.inner_mut().write_all(&header)?;
+        self.header_written = true;
+        Ok(())
+    }
+
+    fn write_chunk(&mut self, flags: u8) -> io::Result<()> {
+        if !self.header_written {
+            self.write_header()?;
+        }
+
+        let seq = self.seq;
+        self.seq = seq
+            .checked_add(1)
+            .ok_or_else(|| io::Error::from(sgx_status_t::SGX_ERROR_INVALID_STATE))?;
+
+        let mut chunk = vec![0_u8; CHUNK_HEADER_SIZE + self.buf.len() + SGX_SEAL_TAG_SIZE];
+        chunk[0] = flags;
+        chunk[1..CHUNK_HEADER_SIZE].copy_from_slice(&(self.buf.len() as u32).to_le_bytes());
+
+        let (body, tag) = chunk[CHUNK_HEADER_SIZE..].split_at_mut(self.buf.len());
+        let mut mac = sgx_aes_gcm_128bit_tag_t::default();
+        rsgx_rijndael128GCM_encrypt(
+            &self.key.key,
+            &self.buf,
+            &chunk_iv(seq),
+            &chunk_aad(seq, flags),
+            body,
+            &mut mac,
+        )?;
+        tag.copy_from_slice(&mac);
+
+        self.buf.iter_mut().for_each(|b| *b = 0);
+        self.buf.clear();
+        self.inner_mut().write_all(&chunk)
+    }
+}
+
+impl<W: Write> Write for SealingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.buf.len() == self.chunk_size {
+            self.write_chunk(0)?;
+        }
+        let len = cmp::min(buf.len(), self.chunk_size - self.buf.len());
+        self.buf.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buf.is_empty() {
+            self.write_chunk(0)?;
+        }
+        self.inner_mut().flush()
+    }
+}
+
+impl<W: Write> Drop for SealingWriter<W> {
+    fn drop(&mut self) {
+        zero_key(&mut self.key);
+        self.buf.iter_mut().for_each(|b| *b = 0);
+    }
+}
+
+/// A reader which unseals a stream produced by `SealingWriter`.
+///
+/// The reader only reports the end of the stream once the final chunk has been
+/// authenticated. A truncated stream yields an `UnexpectedEof` error.
+pub struct UnsealingReader<R: Read> {
+    inner: Option<R>,
+    key_request: sgx_key_request_t,
+    key: sgx_align_key_128bit_t,
+    chunk_size: usize,
+    buf: Vec<u8>,
+    pos: usize,
+    seq: u64,
+    finished: bool,
+}
+
+impl<R: Read> UnsealingReader<R> {
+    ///
+    /// Read the stream header from the inner reader and derive the seal key.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidData` if the header is malformed, or the sgx_status_t error
+    /// (e.g. SGX_ERROR_INVALID_CPUSVN, SGX_ERROR_INVALID_ISVSVN) if the seal key
+    /// cannot be derived.
+    ///
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut header = [0_u8; STREAM_HEADER_SIZE];
+        inner.read_exact(&mut header)?;
+
+        if header[..8] != STREAM_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid sealed stream magic",
+            ));
+        }
+        let mut word = [0_u8; 4];
+        word.copy_from_slice(&header[8..12]);
+        if u32::from_le_bytes(word) != STREAM_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unsupported sealed stream version",
+            ));
+        }
+        word.copy_from_slice(&header[12..16]);
+        let chunk_size = u32::from_le_bytes(word) as usize;
+        if chunk_size == 0 || chunk_size == u32::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid sealed stream chunk size",
+            ));
+        }
+
+        let mut key_request = sgx_key_request_t::default();
+        unsafe {
+            ptr::copy_nonoverlapping(
+                header[16..].as_ptr(),
+                &mut key_request as *mut _ as *mut u8,
+                mem::size_of::<sgx_key_request_t>(),
+            );
+        }
+        if key_request.key_name != SGX_KEYSELECT_SEAL {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid sealed stream key request",
+            ));
+        }
+        let key = derive_seal_key(&key_request)?;
+
+        Ok(UnsealingReader {
+            inner: Some(inner),
+            key_request,
+            key,
+            chunk_size,
+            buf: Vec::new(),
+            pos: 0,
+            seq: 0,
+            finished: false,
+        })
+    }
+
+    ///
+    /// Get the sgx_key_request_t recorded in the stream header.
+    ///
+    pub fn get_key_request(&self) -> &sgx_key_request_t {
+        &self.key_request
+    }
+
+    ///
+    /// Get a reference to the inner reader.
+    ///
+    pub fn get_ref(&self) -> &R {
+        self.inner.as_ref().unwrap()
+    }
+
+    ///
+    /// Consume the UnsealingReader and return the inner reader.
+    ///
+    pub fn into_inner(mut self) -> R {
+        self.inner.take().unwrap()
+    }
+
+    fn inner_mut(&mut self) -> &mut R {
+        self.inner.as_mut().unwrap()
+    }
+
+    fn read_chunk(&mut self) -> io::Result<()> {
+        let mut chunk_header = [0_u8; CHUNK_HEADER_SIZE];
+        self.inner_mut().read_exact(&mut chunk_header)?;
+
+        let flags = chunk_header[0];
+        if (flags & !CHUNK_FLAG_FINAL) != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid sealed chunk flags",
+            ));
+        }
+        let mut word = [0_u8; 4];
+        word.copy_from_slice(&chunk_header[1..]);
+        let len = u32::from_le_bytes(word) as usize;
+        if len > self.chunk_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid sealed chunk size",
+            ));
+        }
+
+        let mut body = vec![0_u8; len];
+        self.inner_mut().read_exact(&mut body)?;
+        let mut mac = sgx_aes_gcm_128bit_tag_t::default();
+        self.inner_mut().read_exact(&mut mac)?;
+
+        let seq = self.seq;
+        self.buf.iter_mut().for_each(|b| *b = 0);
+        self.buf.resize(len, 0);
+        self.pos = 0;
+
+        rsgx_lfence();
+
+        rsgx_rijndael128GCM_decrypt(
+            &self.key.key,
+            &body,
+            &chunk_iv(seq),
+            &chunk_aad(seq, flags),
+            &mac,
+            &mut self.buf,
+        )
+        .map_err(|e| {
+            self.buf.clear();
+            io::Error::from(e)
+        })?;
+
+        self.seq = seq
+            .checked_add(1)
+            .ok_or_else(|| io::Error::from(sgx_status_t::SGX_ERROR_INVALID_STATE))?;
+
+        if (flags & CHUNK_FLAG_FINAL) != 0 {
+            self.finished = true;
+            let mut trailing = [0_u8; 1];
+            if self.inner_mut().read(&mut trailing)? != 0 {
+                self.buf.clear();
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "trailing data after the final sealed chunk",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for UnsealingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while self.pos == self.buf.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            self.read_chunk()?;
+        }
+        let len = cmp::min(buf.len(), self.buf.len() - self.pos);
+        buf[..len].copy_from_slice(&self.buf[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+impl<R: Read> Drop for UnsealingReader<R> {
+    fn drop(&mut self) {
+        zero_key(&mut self.key);
+        self.buf.iter_mut().for_each(|b| *b = 0);
+    }
+}
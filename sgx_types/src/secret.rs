@@ -0,0 +1,119 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Owning wrappers for secret key material.
+//!
+//! The raw key types are `Copy` arrays and structures, so every copy stays in memory
+//! after use. The wrappers below are neither `Copy` nor `Clone` nor `Debug`, compare in
+//! constant time, and clear their memory when they are dropped.
+//!
+use crate::types::*;
+use core::mem;
+use core::ptr;
+use core::slice;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+macro_rules! impl_secret {
+    ($(#[$attr:meta])* $name:ident, $raw:ty) => {
+        $(#[$attr])*
+        pub struct $name {
+            raw: $raw,
+        }
+
+        impl $name {
+            /// Takes ownership of a raw key. The caller should clear its own copy.
+            pub fn new(raw: $raw) -> $name {
+                $name { raw }
+            }
+
+            /// Returns the raw key, for the functions that take the raw type.
+            pub fn as_raw(&self) -> &$raw {
+                &self.raw
+            }
+
+            /// Returns the raw key, for the functions that fill in the raw type.
+            pub fn as_mut_raw(&mut self) -> &mut $raw {
+                &mut self.raw
+            }
+
+            pub fn as_bytes(&self) -> &[u8] {
+                unsafe {
+                    slice::from_raw_parts(&self.raw as *const $raw as *const u8, mem::size_of::<$raw>())
+                }
+            }
+
+            /// Compares two keys in constant time.
+            pub fn ct_eq(&self, other: &$name) -> bool {
+                let diff = self
+                    .as_bytes()
+                    .iter()
+                    .zip(other.as_bytes().iter())
+                    .fold(0_u8, |acc, (x, y)| acc | (x ^ y));
+                unsafe { ptr::read_volatile(&diff) == 0 }
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> $name {
+                $name {
+                    raw: <$raw>::default(),
+                }
+            }
+        }
+
+        impl From<$raw> for $name {
+            fn from(raw: $raw) -> $name {
+                $name::new(raw)
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &$name) -> bool {
+                self.ct_eq(other)
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl Drop for $name {
+            fn drop(&mut self) {
+                let p = &mut self.raw as *mut $raw as *mut u8;
+                for i in 0..mem::size_of::<$raw>() {
+                    unsafe { ptr::write_volatile(p.add(i), 0) };
+                }
+                compiler_fence(Ordering::SeqCst);
+            }
+        }
+    };
+}
+
+impl_secret! {
+    /// A 128-bit secret key: a key from EGETKEY, a remote attestation session key, or
+    /// an AES-GCM or CMAC key.
+    SecretKey128, sgx_key_128bit_t
+}
+
+impl_secret! {
+    /// An EC P-256 private key.
+    EcPrivateKey, sgx_ec256_private_t
+}
+
+impl_secret! {
+    /// An EC P-256 Diffie-Hellman shared secret.
+    EcSharedKey, sgx_ec256_dh_shared_t
+}
@@ -291,6 +291,18 @@ impl_struct_ContiguousMemory! {
     sgx_ra_msg3_t;
 }
 
+// Message 4 is not part of sgx_key_exchange.h. The service provider returns the
+// attestation result to the enclave in it.
+pub const SGX_RA_MSG4_TRUSTED: uint32_t = 0;
+pub const SGX_RA_MSG4_UNTRUSTED: uint32_t = 1;
+
+impl_struct! {
+    pub struct sgx_ra_msg4_t {
+        pub status: uint32_t,
+        pub mac: sgx_mac_t,
+    }
+}
+
 //
 // sgx_quote.h
 //
@@ -663,6 +675,12 @@ pub const SGX_AESCTR_KEY_SIZE: size_t = 16;
 pub const SGX_RSA3072_KEY_SIZE: size_t = 384;
 pub const SGX_RSA3072_PRI_EXP_SIZE: size_t = 384;
 pub const SGX_RSA3072_PUB_EXP_SIZE: size_t = 4;
+pub const SGX_AESGCM256_KEY_SIZE: size_t = 32;
+pub const SGX_CHACHA20POLY1305_KEY_SIZE: size_t = 32;
+pub const SGX_CHACHA20POLY1305_NONCE_SIZE: size_t = 12;
+pub const SGX_CHACHA20POLY1305_MAC_SIZE: size_t = 16;
+pub const SGX_ED25519_KEY_SIZE: size_t = 32;
+pub const SGX_X25519_KEY_SIZE: size_t = 32;
 
 impl_struct! {
     pub struct sgx_ec256_dh_shared_t {
@@ -689,6 +707,31 @@ impl_struct! {
         pub x: [uint32_t; SGX_NISTP_ECP256_KEY_SIZE],
         pub y: [uint32_t; SGX_NISTP_ECP256_KEY_SIZE],
     }
+
+    pub struct sgx_ed25519_private_t {
+        pub seed: [uint8_t; SGX_ED25519_KEY_SIZE],
+    }
+
+    pub struct sgx_ed25519_public_t {
+        pub key: [uint8_t; SGX_ED25519_KEY_SIZE],
+    }
+
+    pub struct sgx_ed25519_signature_t {
+        pub r: [uint8_t; SGX_ED25519_KEY_SIZE],
+        pub s: [uint8_t; SGX_ED25519_KEY_SIZE],
+    }
+
+    pub struct sgx_x25519_private_t {
+        pub scalar: [uint8_t; SGX_X25519_KEY_SIZE],
+    }
+
+    pub struct sgx_x25519_public_t {
+        pub u: [uint8_t; SGX_X25519_KEY_SIZE],
+    }
+
+    pub struct sgx_x25519_shared_t {
+        pub s: [uint8_t; SGX_X25519_KEY_SIZE],
+    }
 }
 
 impl_copy_clone! {
@@ -748,6 +791,9 @@ pub type sgx_hmac_256bit_tag_t = [uint8_t; SGX_HMAC256_MAC_SIZE];
 pub type sgx_cmac_128bit_key_t = [uint8_t; SGX_CMAC_KEY_SIZE];
 pub type sgx_cmac_128bit_tag_t = [uint8_t; SGX_CMAC_MAC_SIZE];
 pub type sgx_aes_ctr_128bit_key_t = [uint8_t; SGX_AESCTR_KEY_SIZE];
+pub type sgx_aes_gcm_256bit_key_t = [uint8_t; SGX_AESGCM256_KEY_SIZE];
+pub type sgx_chacha20_poly1305_key_t = [uint8_t; SGX_CHACHA20POLY1305_KEY_SIZE];
+pub type sgx_chacha20_poly1305_tag_t = [uint8_t; SGX_CHACHA20POLY1305_MAC_SIZE];
 
 impl_enum! {
     #[repr(u32)]
@@ -0,0 +1,260 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Key Derivation Functions
+//!
+//! HKDF (RFC 5869), PBKDF2 (RFC 8018) and the NIST SP800-108 counter-mode KDF
+//! with AES-CMAC as PRF, instantiated with the handles of this crate. See the
+//! kdf module of sgx_crypto_common.
+//!
+use crate::crypto::*;
+use sgx_crypto_common::kdf;
+use sgx_types::*;
+
+///
+/// rsgx_hkdf_sha256_extract performs the HKDF-Extract step of RFC 5869 with SHA256.
+///
+/// # Parameters
+///
+/// **salt**
+///
+/// The optional salt value. The content could be empty.
+///
+/// **ikm**
+///
+/// The input keying material.
+///
+/// # Return value
+///
+/// The pseudorandom key (PRK).
+///
+/// # Errors
+///
+/// **SGX_ERROR_OUT_OF_MEMORY**
+///
+/// Not enough memory is available to complete this operation.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// An internal cryptography library failure occurred.
+///
+pub fn rsgx_hkdf_sha256_extract(salt: &[u8], ikm: &[u8]) -> SgxResult<sgx_sha256_hash_t> {
+    kdf::hkdf_extract::<SgxShaHandle>(salt, ikm)
+}
+
+///
+/// rsgx_hkdf_sha256_expand performs the HKDF-Expand step of RFC 5869 with SHA256.
+///
+/// # Parameters
+///
+/// **prk**
+///
+/// The pseudorandom key, usually the output of rsgx_hkdf_sha256_extract.
+///
+/// **info**
+///
+/// The optional context and application specific information. The content could be empty.
+///
+/// **okm**
+///
+/// The output keying material. The whole buffer is filled.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The PRK is shorter than 32 bytes, or the OKM buffer is empty or longer than 8160 bytes.
+///
+/// **SGX_ERROR_OUT_OF_MEMORY**
+///
+/// Not enough memory is available to complete this operation.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// An internal cryptography library failure occurred.
+///
+pub fn rsgx_hkdf_sha256_expand(prk: &[u8], info: &[u8], okm: &mut [u8]) -> SgxError {
+    kdf::hkdf_expand::<SgxShaHandle>(prk, info, okm)
+}
+
+///
+/// rsgx_hkdf_sha256 performs both HKDF steps of RFC 5869 with SHA256.
+///
+/// # Parameters
+///
+/// **salt**
+///
+/// The optional salt value. The content could be empty.
+///
+/// **ikm**
+///
+/// The input keying material.
+///
+/// **info**
+///
+/// The optional context and application specific information. The content could be empty.
+///
+/// **okm**
+///
+/// The output keying material. The whole buffer is filled.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The OKM buffer is empty or longer than 8160 bytes.
+///
+/// **SGX_ERROR_OUT_OF_MEMORY**
+///
+/// Not enough memory is available to complete this operation.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// An internal cryptography library failure occurred.
+///
+pub fn rsgx_hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8], okm: &mut [u8]) -> SgxError {
+    kdf::hkdf::<SgxShaHandle>(salt, ikm, info, okm)
+}
+
+///
+/// rsgx_hkdf_sha384_extract performs the HKDF-Extract step of RFC 5869 with SHA384.
+///
+/// See rsgx_hkdf_sha256_extract.
+///
+pub fn rsgx_hkdf_sha384_extract(salt: &[u8], ikm: &[u8]) -> SgxResult<sgx_sha384_hash_t> {
+    kdf::hkdf_extract::<SgxSha384Handle>(salt, ikm)
+}
+
+///
+/// rsgx_hkdf_sha384_expand performs the HKDF-Expand step of RFC 5869 with SHA384.
+///
+/// See rsgx_hkdf_sha256_expand. The PRK must be at least 48 bytes and the OKM buffer
+/// at most 12240 bytes.
+///
+pub fn rsgx_hkdf_sha384_expand(prk: &[u8], info: &[u8], okm: &mut [u8]) -> SgxError {
+    kdf::hkdf_expand::<SgxSha384Handle>(prk, info, okm)
+}
+
+///
+/// rsgx_hkdf_sha384 performs both HKDF steps of RFC 5869 with SHA384.
+///
+/// See rsgx_hkdf_sha256. The OKM buffer must be at most 12240 bytes.
+///
+pub fn rsgx_hkdf_sha384(salt: &[u8], ikm: &[u8], info: &[u8], okm: &mut [u8]) -> SgxError {
+    kdf::hkdf::<SgxSha384Handle>(salt, ikm, info, okm)
+}
+
+///
+/// rsgx_pbkdf2_hmac_sha256 derives a key from a password with PBKDF2 (RFC 8018) and HMAC-SHA256.
+///
+/// # Parameters
+///
+/// **password**
+///
+/// The password. The content could be empty.
+///
+/// **salt**
+///
+/// The salt. The content could be empty.
+///
+/// **iterations**
+///
+/// The iteration count. It must not be zero.
+///
+/// **dk**
+///
+/// The derived key. The whole buffer is filled.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The iteration count is zero, or the DK buffer is empty.
+///
+/// **SGX_ERROR_OUT_OF_MEMORY**
+///
+/// Not enough memory is available to complete this operation.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// An internal cryptography library failure occurred.
+///
+pub fn rsgx_pbkdf2_hmac_sha256(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    dk: &mut [u8],
+) -> SgxError {
+    kdf::pbkdf2_hmac::<SgxShaHandle>(password, salt, iterations, dk)
+}
+
+///
+/// rsgx_sp800_108_cmac_kdf derives key material with the NIST SP800-108 KDF in counter mode,
+/// using AES-CMAC with a 128-bit key as PRF.
+///
+/// # Description
+///
+/// Each 16-byte block i (starting from 1) of the output is computed as
+///
+/// CMAC(key, i || label || 0x00 || context || L)
+///
+/// where i and L are 32-bit big-endian integers and L is the output length in bits.
+///
+/// Note that the key derivation of the SGX key exchange libraries uses an 8-bit counter and a
+/// 16-bit length, and is not compatible with this function.
+///
+/// # Parameters
+///
+/// **key**
+///
+/// The key derivation key.
+///
+/// **label**
+///
+/// The label identifying the purpose of the derived key. The content could be empty.
+///
+/// **context**
+///
+/// The context information. The content could be empty.
+///
+/// **okm**
+///
+/// The derived key material. The whole buffer is filled.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The OKM buffer is empty, or its length in bits does not fit in 32 bits.
+///
+/// **SGX_ERROR_OUT_OF_MEMORY**
+///
+/// Not enough memory is available to complete this operation.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// An internal cryptography library failure occurred.
+///
+pub fn rsgx_sp800_108_cmac_kdf(
+    key: &sgx_cmac_128bit_key_t,
+    label: &[u8],
+    context: &[u8],
+    okm: &mut [u8],
+) -> SgxError {
+    kdf::sp800_108_cmac_kdf::<SgxCmacHandle>(key, label, context, okm)
+}
@@ -25,12 +25,28 @@
 #![allow(non_snake_case)]
 #![allow(clippy::too_many_arguments)]
 
+extern crate alloc;
 extern crate libc;
 extern crate rand_core;
 extern crate rdrand;
+extern crate sgx_crypto_common;
 extern crate sgx_types;
 
 mod util;
 pub use util::*;
 mod crypto;
 pub use self::crypto::*;
+
+// The algorithms implemented in Rust are shared with sgx_tcrypto.
+pub use sgx_crypto_common::aead::*;
+pub use sgx_crypto_common::curve25519::*;
+pub use sgx_crypto_common::encoding::*;
+pub use sgx_crypto_common::{Aead, Digest, Mac};
+
+mod traits;
+
+mod kdf;
+pub use self::kdf::*;
+
+mod rsa;
+pub use self::rsa::*;
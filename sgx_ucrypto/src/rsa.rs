@@ -0,0 +1,190 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! RSA Signatures
+//!
+//! RSASSA-PKCS1-v1_5 and RSASSA-PSS with SHA-256 (RFC 8017), for any modulus size
+//! from 1024 to 8192 bits, instantiated with SgxShaHandle. See the rsa module of
+//! sgx_crypto_common.
+//!
+use crate::crypto::SgxShaHandle;
+use alloc::vec::Vec;
+use sgx_crypto_common::rsa;
+use sgx_crypto_common::{SgxRsaPrivateKeyComponents, SgxRsaPublicKeyComponents};
+use sgx_types::*;
+
+///
+/// rsgx_rsa_sign_pkcs1v15_sha256 computes an RSASSA-PKCS1-v1_5 signature with SHA-256.
+///
+/// # Description
+///
+/// Unlike rsgx_rsa3072_sign_msg, the key can be of any size from 1024 to 8192 bits, and
+/// the signature is big-endian. The data set could be empty.
+///
+/// # Parameters
+///
+/// **data**
+///
+/// Data to be signed.
+///
+/// **private**
+///
+/// The RSA private key. Only n, e and the CRT components are used.
+///
+/// # Return value
+///
+/// The signature, as long as the modulus.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The key size is not supported, or the key components are inconsistent.
+///
+pub fn rsgx_rsa_sign_pkcs1v15_sha256(
+    data: &[u8],
+    private: &SgxRsaPrivateKeyComponents,
+) -> SgxResult<Vec<u8>> {
+    rsa::sign_pkcs1v15_sha256::<SgxShaHandle>(data, private)
+}
+
+///
+/// rsgx_rsa_verify_pkcs1v15_sha256 verifies an RSASSA-PKCS1-v1_5 signature with SHA-256.
+///
+/// # Parameters
+///
+/// **data**
+///
+/// Signed data.
+///
+/// **public**
+///
+/// The RSA public key.
+///
+/// **signature**
+///
+/// The big-endian signature.
+///
+/// # Return value
+///
+/// **true**
+///
+/// Digital signature is valid.
+///
+/// **false**
+///
+/// Digital signature is not valid.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The key size is not supported.
+///
+pub fn rsgx_rsa_verify_pkcs1v15_sha256(
+    data: &[u8],
+    public: &SgxRsaPublicKeyComponents,
+    signature: &[u8],
+) -> SgxResult<bool> {
+    rsa::verify_pkcs1v15_sha256::<SgxShaHandle>(data, public, signature)
+}
+
+///
+/// rsgx_rsa_sign_pss_sha256 computes an RSASSA-PSS signature with SHA-256.
+///
+/// # Description
+///
+/// MGF1 with SHA-256 is used as the mask generation function, and the salt is 32 random
+/// bytes. The data set could be empty.
+///
+/// # Parameters
+///
+/// **data**
+///
+/// Data to be signed.
+///
+/// **private**
+///
+/// The RSA private key. Only n, e and the CRT components are used.
+///
+/// # Return value
+///
+/// The signature, as long as the modulus.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The key size is not supported, or the key components are inconsistent.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// The random number generator failed.
+///
+pub fn rsgx_rsa_sign_pss_sha256(
+    data: &[u8],
+    private: &SgxRsaPrivateKeyComponents,
+) -> SgxResult<Vec<u8>> {
+    rsa::sign_pss_sha256::<SgxShaHandle>(data, private)
+}
+
+///
+/// rsgx_rsa_verify_pss_sha256 verifies an RSASSA-PSS signature with SHA-256.
+///
+/// # Description
+///
+/// MGF1 with SHA-256 is expected as the mask generation function. The salt length is
+/// recovered from the signature, so signatures with any salt length are accepted.
+///
+/// # Parameters
+///
+/// **data**
+///
+/// Signed data.
+///
+/// **public**
+///
+/// The RSA public key.
+///
+/// **signature**
+///
+/// The big-endian signature.
+///
+/// # Return value
+///
+/// **true**
+///
+/// Digital signature is valid.
+///
+/// **false**
+///
+/// Digital signature is not valid.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The key size is not supported.
+///
+pub fn rsgx_rsa_verify_pss_sha256(
+    data: &[u8],
+    public: &SgxRsaPublicKeyComponents,
+    signature: &[u8],
+) -> SgxResult<bool> {
+    rsa::verify_pss_sha256::<SgxShaHandle>(data, public, signature)
+}
@@ -0,0 +1,61 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Algorithm Traits
+//!
+//! The `Digest`, `Mac` and `Aead` implementations of the handles of the cryptography
+//! library. The traits are defined in sgx_crypto_common, together with their
+//! implementations for the AEAD handles implemented in Rust.
+//!
+use crate::crypto::*;
+use sgx_types::*;
+
+sgx_crypto_common::impl_digest!(SgxShaHandle, sgx_sha256_hash_t, SGX_SHA256_HASH_SIZE, 64);
+sgx_crypto_common::impl_digest!(
+    SgxSha384Handle,
+    sgx_sha384_hash_t,
+    SGX_SHA384_HASH_SIZE,
+    128
+);
+sgx_crypto_common::impl_digest!(SgxSha1Handle, sgx_sha1_hash_t, SGX_SHA1_HASH_SIZE, 64);
+
+sgx_crypto_common::impl_mac!(
+    SgxCmacHandle,
+    sgx_cmac_128bit_key_t,
+    SGX_CMAC_KEY_SIZE,
+    sgx_cmac_128bit_tag_t,
+    SGX_CMAC_MAC_SIZE
+);
+sgx_crypto_common::impl_mac!(
+    SgxHmacHandle,
+    sgx_hmac_256bit_key_t,
+    SGX_HMAC256_KEY_SIZE,
+    sgx_hmac_256bit_tag_t,
+    SGX_HMAC256_MAC_SIZE
+);
+
+sgx_crypto_common::impl_aead!(
+    SgxAesHandle,
+    sgx_aes_gcm_128bit_key_t,
+    SGX_AESGCM_KEY_SIZE,
+    SGX_AESGCM_IV_SIZE,
+    sgx_aes_gcm_128bit_tag_t,
+    SGX_AESGCM_MAC_SIZE,
+    rsgx_rijndael128GCM_encrypt,
+    rsgx_rijndael128GCM_decrypt
+);
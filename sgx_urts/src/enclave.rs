@@ -15,12 +15,18 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use crate::switchless::SwitchlessSlot;
+use crate::token::{signed_mr_enclave, write_atomically};
+use crate::{LaunchTokenCache, SwitchlessConfig, SwitchlessCounters};
 use sgx_types::*;
 use std::ffi::{CStr, CString};
+use std::fs;
 use std::io;
+use std::mem;
 use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
 use std::ptr;
+use std::sync::Arc;
 
 ///
 /// Loads the enclave using its file name and initializes it using a launch token.
@@ -463,6 +469,84 @@ pub fn rsgx_create_enclave_with_workers(
     }
 }
 
+///
+/// rsgx_create_enclave_with_kss_config loads and initializes an enclave like
+/// rsgx_create_enclave, and supplies the Key Separation and Sharing (KSS) configuration
+/// of the enclave through the extended features of sgx_create_enclave_ex.
+///
+/// The enclave reads the configuration from the CONFIGID and CONFIGSVN fields of its
+/// report, e.g. with rsgx_self_identity.
+///
+/// # Parameters
+///
+/// **config_id**
+///
+/// The CONFIGID of the enclave.
+///
+/// **config_svn**
+///
+/// The CONFIGSVN of the enclave.
+///
+/// The other parameters are the same as for rsgx_create_enclave.
+///
+/// # Requirements
+///
+/// Header: sgx_urts.h
+///
+/// Library: libsgx_urts.a
+///
+/// # Return value
+///
+/// The sgx_enclave_id_t returned.
+///
+/// # Errors
+///
+/// The errors are the same as for rsgx_create_enclave, and:
+///
+/// **SGX_ERROR_FEATURE_NOT_SUPPORTED**
+///
+/// The platform does not support Key Separation and Sharing.
+///
+/// **SGX_ERROR_INVALID_ATTRIBUTE**
+///
+/// The enclave is not signed with Key Separation and Sharing enabled.
+///
+pub fn rsgx_create_enclave_with_kss_config(
+    file_name: &CStr,
+    debug: i32,
+    launch_token: &mut sgx_launch_token_t,
+    launch_token_updated: &mut i32,
+    misc_attr: &mut sgx_misc_attribute_t,
+    config_id: &sgx_config_id_t,
+    config_svn: sgx_config_svn_t,
+) -> SgxResult<sgx_enclave_id_t> {
+    let kss_config = sgx_kss_config_t {
+        config_id: *config_id,
+        config_svn,
+    };
+    let mut enclave_ex_p: [*const c_void; 32] = [ptr::null(); 32];
+    enclave_ex_p[SGX_CREATE_ENCLAVE_EX_KSS_BIT_IDX] =
+        &kss_config as *const sgx_kss_config_t as *const c_void;
+
+    let mut enclave_id: sgx_enclave_id_t = 0;
+    let ret = unsafe {
+        sgx_create_enclave_ex(
+            file_name.as_ptr() as *const c_schar,
+            debug as int32_t,
+            launch_token as *mut sgx_launch_token_t,
+            launch_token_updated as *mut int32_t,
+            &mut enclave_id as *mut sgx_enclave_id_t,
+            misc_attr as *mut sgx_misc_attribute_t,
+            SGX_CREATE_ENCLAVE_EX_KSS,
+            &enclave_ex_p as *const [*const c_void; 32],
+        )
+    };
+    match ret {
+        sgx_status_t::SGX_SUCCESS => Ok(enclave_id),
+        _ => Err(ret),
+    }
+}
+
 pub fn rsgx_create_enclave_from_buffer_ex(
     buffer: &[u8],
     debug: i32,
@@ -556,6 +640,7 @@ pub struct SgxEnclave {
     id: sgx_enclave_id_t,
     debug: i32,
     path: PathBuf,
+    switchless: Option<Arc<SwitchlessSlot>>,
 }
 
 impl SgxEnclave {
@@ -579,12 +664,46 @@ impl SgxEnclave {
             id: eid,
             debug,
             path: file_name.as_ref().to_owned(),
+            switchless: None,
         })?;
 
         enclave.init();
         Ok(enclave)
     }
 
+    ///
+    /// Creates the enclave like `create`, loading its launch token from the cache and
+    /// storing the token back when the uRTS updates it.
+    ///
+    pub fn create_with_token_cache<P: AsRef<Path>>(
+        file_name: P,
+        debug: i32,
+        misc_attr: &mut sgx_misc_attribute_t,
+        cache: &LaunchTokenCache,
+    ) -> SgxResult<SgxEnclave> {
+        let mr_enclave = signed_mr_enclave(file_name.as_ref());
+        let mut launch_token = mr_enclave
+            .and_then(|mr_enclave| cache.get(file_name.as_ref(), &mr_enclave))
+            .unwrap_or([0; 1024]);
+        let mut launch_token_updated: i32 = 0;
+        let enclave = SgxEnclave::create(
+            file_name.as_ref(),
+            debug,
+            &mut launch_token,
+            &mut launch_token_updated,
+            misc_attr,
+        )?;
+        if let Some(mr_enclave) = mr_enclave {
+            cache.update(
+                file_name.as_ref(),
+                &mr_enclave,
+                &launch_token,
+                launch_token_updated != 0,
+            );
+        }
+        Ok(enclave)
+    }
+
     pub fn create_encrypt<P: AsRef<Path>>(
         file_name: P,
         debug: i32,
@@ -607,6 +726,7 @@ impl SgxEnclave {
             id: eid,
             debug,
             path: file_name.as_ref().to_owned(),
+            switchless: None,
         })?;
 
         enclave.init();
@@ -637,6 +757,38 @@ impl SgxEnclave {
             id: eid,
             debug,
             path: file_name.as_ref().to_owned(),
+            switchless: None,
+        })?;
+
+        enclave.init();
+        Ok(enclave)
+    }
+
+    pub fn create_with_kss_config<P: AsRef<Path>>(
+        file_name: P,
+        debug: i32,
+        launch_token: &mut sgx_launch_token_t,
+        launch_token_updated: &mut i32,
+        misc_attr: &mut sgx_misc_attribute_t,
+        config_id: &sgx_config_id_t,
+        config_svn: sgx_config_svn_t,
+    ) -> SgxResult<SgxEnclave> {
+        let path: CString =
+            cstr(file_name.as_ref()).map_err(|_| sgx_status_t::SGX_ERROR_INVALID_ENCLAVE)?;
+        let enclave = rsgx_create_enclave_with_kss_config(
+            path.as_c_str(),
+            debug,
+            launch_token,
+            launch_token_updated,
+            misc_attr,
+            config_id,
+            config_svn,
+        )
+        .map(|eid| SgxEnclave {
+            id: eid,
+            debug,
+            path: file_name.as_ref().to_owned(),
+            switchless: None,
         })?;
 
         enclave.init();
@@ -661,6 +813,7 @@ impl SgxEnclave {
             id: eid,
             debug,
             path: PathBuf::new(),
+            switchless: None,
         })?;
 
         enclave.init();
@@ -688,6 +841,12 @@ impl SgxEnclave {
         self.path.as_path()
     }
 
+    /// The counters of the switchless calls, if the enclave was built with a
+    /// SwitchlessConfig.
+    pub fn switchless_counters(&self) -> Option<SwitchlessCounters> {
+        self.switchless.as_ref().map(|slot| slot.counters())
+    }
+
     pub fn get_target_info(&self) -> SgxResult<sgx_target_info_t> {
         rsgx_get_target_info(self.id)
     }
@@ -733,3 +892,222 @@ impl Drop for SgxEnclave {
         let _ = rsgx_destroy_enclave(self.id);
     }
 }
+
+///
+/// Builds an enclave with any combination of the extended features of
+/// sgx_create_enclave_ex: a PCL sealed key, a switchless configuration and a KSS
+/// configuration.
+///
+/// There are no EDMM options. Whether an enclave uses EDMM is decided by its enclave
+/// configuration, the processor and the driver, and the uRTS does not report it, so the
+/// builder neither enables nor checks it.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut config = SwitchlessConfig::new();
+/// config.num_uworkers(2).retries_before_fallback(1000);
+///
+/// let enclave = EnclaveBuilder::new("enclave.signed.so")
+///     .debug(true)
+///     .launch_token_path("enclave.token")
+///     .switchless(&config)
+///     .kss_config(&config_id, 1)
+///     .build()?;
+/// ```
+///
+pub struct EnclaveBuilder {
+    path: PathBuf,
+    debug: bool,
+    launch_token_path: Option<PathBuf>,
+    launch_token_cache: Option<LaunchTokenCache>,
+    sealed_key: Option<Vec<u8>>,
+    switchless: Option<SwitchlessConfig>,
+    kss_config: Option<sgx_kss_config_t>,
+}
+
+impl EnclaveBuilder {
+    pub fn new<P: AsRef<Path>>(file_name: P) -> EnclaveBuilder {
+        EnclaveBuilder {
+            path: file_name.as_ref().to_owned(),
+            debug: false,
+            launch_token_path: None,
+            launch_token_cache: None,
+            sealed_key: None,
+            switchless: None,
+            kss_config: None,
+        }
+    }
+
+    pub fn debug(&mut self, debug: bool) -> &mut EnclaveBuilder {
+        self.debug = debug;
+        self
+    }
+
+    ///
+    /// Reads the launch token from the file, and writes it back when the uRTS updates it.
+    ///
+    /// A missing or malformed token file is ignored, and so is a failure to write it, as
+    /// the uRTS then simply gets a new launch token.
+    ///
+    pub fn launch_token_path<P: AsRef<Path>>(&mut self, path: P) -> &mut EnclaveBuilder {
+        self.launch_token_path = Some(path.as_ref().to_owned());
+        self
+    }
+
+    ///
+    /// Loads the launch token from the cache, and stores it back when the uRTS updates it.
+    /// The cache takes precedence over `launch_token_path`, unless the MRENCLAVE of the
+    /// enclave file cannot be read, in which case the cache is skipped and the token is
+    /// read from and written to `launch_token_path`, if any.
+    ///
+    pub fn launch_token_cache(&mut self, cache: &LaunchTokenCache) -> &mut EnclaveBuilder {
+        self.launch_token_cache = Some(cache.clone());
+        self
+    }
+
+    /// Loads a PCL encrypted enclave, decrypted with the sealed key.
+    pub fn sealed_key(&mut self, sealed_key: &[u8]) -> &mut EnclaveBuilder {
+        self.sealed_key = Some(sealed_key.to_vec());
+        self
+    }
+
+    /// Enables switchless calls with the configuration.
+    pub fn switchless(&mut self, config: &SwitchlessConfig) -> &mut EnclaveBuilder {
+        self.switchless = Some(config.clone());
+        self
+    }
+
+    /// Supplies the CONFIGID and CONFIGSVN of an enclave signed with KSS enabled.
+    pub fn kss_config(
+        &mut self,
+        config_id: &sgx_config_id_t,
+        config_svn: sgx_config_svn_t,
+    ) -> &mut EnclaveBuilder {
+        self.kss_config = Some(sgx_kss_config_t {
+            config_id: *config_id,
+            config_svn,
+        });
+        self
+    }
+
+    ///
+    /// Creates and initializes the enclave.
+    ///
+    /// # Errors
+    ///
+    /// The errors are the same as for rsgx_create_enclave, and:
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The sealed key is too short to be a sealed data blob.
+    ///
+    /// **SGX_ERROR_FEATURE_NOT_SUPPORTED**
+    ///
+    /// The platform does not support one of the extended features.
+    ///
+    /// **SGX_ERROR_BUSY**
+    ///
+    /// SWITCHLESS_MAX_ENCLAVES enclaves created with a SwitchlessConfig already exist.
+    ///
+    pub fn build(&self) -> SgxResult<SgxEnclave> {
+        let mut misc_attr = sgx_misc_attribute_t::default();
+        self.build_with_misc_attr(&mut misc_attr)
+    }
+
+    ///
+    /// Creates and initializes the enclave like `build`, and returns the misc select and
+    /// attributes of the enclave in `misc_attr`.
+    ///
+    pub fn build_with_misc_attr(
+        &self,
+        misc_attr: &mut sgx_misc_attribute_t,
+    ) -> SgxResult<SgxEnclave> {
+        let path: CString =
+            cstr(&self.path).map_err(|_| sgx_status_t::SGX_ERROR_INVALID_ENCLAVE)?;
+
+        let mut ex_features = 0_u32;
+        let mut enclave_ex_p: [*const c_void; 32] = [ptr::null(); 32];
+        if let Some(ref sealed_key) = self.sealed_key {
+            if sealed_key.len() < mem::size_of::<sgx_sealed_data_t>() {
+                return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+            }
+            ex_features |= SGX_CREATE_ENCLAVE_EX_PCL;
+            enclave_ex_p[SGX_CREATE_ENCLAVE_EX_PCL_BIT_IDX] = sealed_key.as_ptr() as *const c_void;
+        }
+        let switchless = self
+            .switchless
+            .as_ref()
+            .map(SwitchlessConfig::install)
+            .transpose()?;
+        if let Some((ref config, _)) = switchless {
+            ex_features |= SGX_CREATE_ENCLAVE_EX_SWITCHLESS;
+            enclave_ex_p[SGX_CREATE_ENCLAVE_EX_SWITCHLESS_BIT_IDX] =
+                config as *const sgx_uswitchless_config_t as *const c_void;
+        }
+        if let Some(ref config) = self.kss_config {
+            ex_features |= SGX_CREATE_ENCLAVE_EX_KSS;
+            enclave_ex_p[SGX_CREATE_ENCLAVE_EX_KSS_BIT_IDX] =
+                config as *const sgx_kss_config_t as *const c_void;
+        }
+
+        let mut launch_token: sgx_launch_token_t = [0; 1024];
+        // The cached tokens are keyed by the MRENCLAVE of the enclave file.
+        let cache = self
+            .launch_token_cache
+            .as_ref()
+            .and_then(|cache| Some((cache, signed_mr_enclave(&self.path)?)));
+        if let Some((cache, ref mr_enclave)) = cache {
+            if let Some(token) = cache.get(&self.path, mr_enclave) {
+                launch_token = token;
+            }
+        } else if let Some(ref token_path) = self.launch_token_path {
+            if let Ok(token) = fs::read(token_path) {
+                if token.len() == launch_token.len() {
+                    launch_token.copy_from_slice(&token);
+                }
+            }
+        }
+
+        let debug = self.debug as i32;
+        let mut launch_token_updated: i32 = 0;
+        let mut enclave_id: sgx_enclave_id_t = 0;
+        let ret = unsafe {
+            sgx_create_enclave_ex(
+                path.as_ptr() as *const c_schar,
+                debug as int32_t,
+                &mut launch_token as *mut sgx_launch_token_t,
+                &mut launch_token_updated as *mut int32_t,
+                &mut enclave_id as *mut sgx_enclave_id_t,
+                misc_attr as *mut sgx_misc_attribute_t,
+                ex_features,
+                &enclave_ex_p as *const [*const c_void; 32],
+            )
+        };
+        if ret != sgx_status_t::SGX_SUCCESS {
+            return Err(ret);
+        }
+
+        if let Some((cache, ref mr_enclave)) = cache {
+            cache.update(
+                &self.path,
+                mr_enclave,
+                &launch_token,
+                launch_token_updated != 0,
+            );
+        } else if launch_token_updated != 0 {
+            if let Some(ref token_path) = self.launch_token_path {
+                let _ = write_atomically(token_path, &[&launch_token[..]]);
+            }
+        }
+
+        let enclave = SgxEnclave {
+            id: enclave_id,
+            debug,
+            path: self.path.clone(),
+            switchless: switchless.map(|(_, slot)| Arc::new(slot)),
+        };
+        enclave.init();
+        Ok(enclave)
+    }
+}
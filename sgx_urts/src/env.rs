@@ -15,16 +15,22 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use crate::policy;
 use libc::{self, c_char, c_int, passwd, size_t, uid_t};
 use std::io::Error;
+use std::ptr;
 
 #[no_mangle]
 pub extern "C" fn u_getuid_ocall() -> uid_t {
+    policy::audit_other("u_getuid_ocall");
     unsafe { libc::getuid() }
 }
 
 #[no_mangle]
 pub extern "C" fn u_environ_ocall() -> *const *const c_char {
+    if !policy::check_environ("u_environ_ocall") {
+        return ptr::null();
+    }
     extern "C" {
         static environ: *const *const c_char;
     }
@@ -33,6 +39,10 @@ pub extern "C" fn u_environ_ocall() -> *const *const c_char {
 
 #[no_mangle]
 pub extern "C" fn u_getenv_ocall(name: *const c_char) -> *const c_char {
+    // A denied variable reads as unset.
+    if !policy::check_env("u_getenv_ocall", name) {
+        return ptr::null();
+    }
     unsafe { libc::getenv(name) }
 }
 
@@ -43,6 +53,9 @@ pub extern "C" fn u_setenv_ocall(
     value: *const c_char,
     overwrite: c_int,
 ) -> c_int {
+    if !policy::check_env("u_setenv_ocall", name) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::setenv(name, value, overwrite) };
     if ret < 0 {
@@ -58,6 +71,9 @@ pub extern "C" fn u_setenv_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_unsetenv_ocall(error: *mut c_int, name: *const c_char) -> c_int {
+    if !policy::check_env("u_unsetenv_ocall", name) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::unsetenv(name) };
     if ret < 0 {
@@ -73,6 +89,7 @@ pub extern "C" fn u_unsetenv_ocall(error: *mut c_int, name: *const c_char) -> c_
 
 #[no_mangle]
 pub extern "C" fn u_getcwd_ocall(error: *mut c_int, buf: *mut c_char, size: size_t) -> *mut c_char {
+    policy::audit_other("u_getcwd_ocall");
     let mut errno = 0;
     let ret = unsafe { libc::getcwd(buf, size) };
     if ret.is_null() {
@@ -88,6 +105,9 @@ pub extern "C" fn u_getcwd_ocall(error: *mut c_int, buf: *mut c_char, size: size
 
 #[no_mangle]
 pub extern "C" fn u_chdir_ocall(error: *mut c_int, dir: *const c_char) -> c_int {
+    if !policy::check_path("u_chdir_ocall", libc::AT_FDCWD, dir) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::chdir(dir) };
     if ret < 0 {
@@ -109,6 +129,7 @@ pub extern "C" fn u_getpwuid_r_ocall(
     buflen: size_t,
     passwd_result: *mut *mut passwd,
 ) -> c_int {
+    policy::audit_other("u_getpwuid_r_ocall");
     let ret = unsafe { libc::getpwuid_r(uid, pwd, buf, buflen, passwd_result) };
     if ret == 0 {
         let pwd_ret = unsafe { *passwd_result };
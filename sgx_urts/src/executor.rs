@@ -0,0 +1,596 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::{rsgx_get_metadata, rsgx_metadata_tcs_num, SgxEnclave};
+use sgx_types::*;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::ptr;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+
+type Job = Box<dyn FnOnce(sgx_enclave_id_t) + Send>;
+
+thread_local! {
+    // The executor whose worker runs on this thread, to catch spawn from a worker.
+    static WORKER_OF: Cell<*const Shared> = const { Cell::new(ptr::null()) };
+}
+
+///
+/// Runs ECALLs on a bounded pool of threads, one per TCS of the enclave, so that callers
+/// wait for a free TCS instead of failing with SGX_ERROR_OUT_OF_TCS.
+///
+/// The ECALLs are closures given the enclave ID. They are queued, up to the queue capacity,
+/// and each returns an EcallHandle that can be joined or awaited. When the queue is full,
+/// `spawn` blocks, `try_spawn` gives the closure back and `spawn_async` waits without
+/// blocking the async runtime.
+///
+/// The TCS budget is read from the metadata of the enclave file. An ECALL that makes an
+/// OCALL which calls back into the enclave uses a second TCS, so such enclaves need a
+/// smaller budget than the TCS of the enclave, see `with_tcs_num`.
+///
+/// Dropping the executor runs the queued ECALLs, then joins the worker threads.
+///
+/// # Examples
+///
+/// ```ignore
+/// let executor = EnclaveExecutor::new(Arc::new(enclave), 64)?;
+/// let handle = executor.spawn_async(|eid| {
+///     let mut retval = sgx_status_t::SGX_SUCCESS;
+///     let ret = unsafe { say_something(eid, &mut retval, input.as_ptr(), input.len()) };
+///     (ret, retval)
+/// }).await;
+/// let (ret, retval) = handle.await?;
+/// ```
+///
+pub struct EnclaveExecutor {
+    enclave: Arc<SgxEnclave>,
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    queue_capacity: usize,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+struct Queue {
+    jobs: VecDeque<Job>,
+    // The spawn_async callers waiting for room in the queue.
+    waiters: VecDeque<Waker>,
+    shutdown: bool,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn run(&self, enclave_id: sgx_enclave_id_t) {
+        WORKER_OF.with(|worker_of| worker_of.set(self));
+        loop {
+            let job = {
+                let mut queue = self.lock();
+                loop {
+                    if let Some(job) = queue.jobs.pop_front() {
+                        // Wake all the waiters, as some of them may have been dropped.
+                        for waker in queue.waiters.drain(..) {
+                            waker.wake();
+                        }
+                        self.not_full.notify_one();
+                        break job;
+                    }
+                    if queue.shutdown {
+                        return;
+                    }
+                    queue = self
+                        .not_empty
+                        .wait(queue)
+                        .unwrap_or_else(|e| e.into_inner());
+                }
+            };
+            job(enclave_id);
+        }
+    }
+}
+
+impl EnclaveExecutor {
+    ///
+    /// Starts a worker thread per TCS of the enclave, with room for `queue_capacity` queued
+    /// ECALLs.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// `queue_capacity` is zero.
+    ///
+    /// The errors of rsgx_get_metadata and rsgx_metadata_tcs_num for the enclave file.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a worker thread cannot be spawned.
+    ///
+    pub fn new(enclave: Arc<SgxEnclave>, queue_capacity: usize) -> SgxResult<EnclaveExecutor> {
+        let tcs_num = rsgx_metadata_tcs_num(&*rsgx_get_metadata(enclave.path())?)?;
+        EnclaveExecutor::with_tcs_num(enclave, tcs_num, queue_capacity)
+    }
+
+    ///
+    /// Starts `tcs_num` worker threads, leaving the other TCS of the enclave to the ECALLs
+    /// made from OCALLs.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// `tcs_num` or `queue_capacity` is zero, or `tcs_num` is larger than the number of TCS
+    /// of the enclave.
+    ///
+    /// The errors of rsgx_get_metadata and rsgx_metadata_tcs_num for the enclave file.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a worker thread cannot be spawned.
+    ///
+    pub fn with_tcs_num(
+        enclave: Arc<SgxEnclave>,
+        tcs_num: usize,
+        queue_capacity: usize,
+    ) -> SgxResult<EnclaveExecutor> {
+        let enclave_tcs_num = rsgx_metadata_tcs_num(&*rsgx_get_metadata(enclave.path())?)?;
+        if tcs_num == 0 || tcs_num > enclave_tcs_num || queue_capacity == 0 {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        Ok(EnclaveExecutor::start(enclave, tcs_num, queue_capacity))
+    }
+
+    fn start(enclave: Arc<SgxEnclave>, tcs_num: usize, queue_capacity: usize) -> EnclaveExecutor {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue {
+                jobs: VecDeque::with_capacity(queue_capacity),
+                waiters: VecDeque::new(),
+                shutdown: false,
+            }),
+            queue_capacity,
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        });
+        let enclave_id = enclave.geteid();
+        let workers = (0..tcs_num)
+            .map(|i| {
+                let shared = shared.clone();
+                thread::Builder::new()
+                    .name(format!("ecall-{}-{}", enclave_id, i))
+                    .spawn(move || shared.run(enclave_id))
+                    .expect("failed to spawn an ECALL worker thread")
+            })
+            .collect();
+        EnclaveExecutor {
+            enclave,
+            shared,
+            workers,
+        }
+    }
+
+    #[inline]
+    pub fn enclave(&self) -> &SgxEnclave {
+        &self.enclave
+    }
+
+    /// The number of worker threads, which is the TCS budget.
+    #[inline]
+    pub fn tcs_num(&self) -> usize {
+        self.workers.len()
+    }
+
+    #[inline]
+    pub fn queue_capacity(&self) -> usize {
+        self.shared.queue_capacity
+    }
+
+    /// The number of ECALLs waiting for a worker thread.
+    pub fn queued(&self) -> usize {
+        self.shared.lock().jobs.len()
+    }
+
+    ///
+    /// Queues the ECALL, blocking while the queue is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from an ECALL of this executor: the worker thread would wait for
+    /// room in the queue, which only the worker threads make, and could deadlock. Use
+    /// `try_spawn` or `spawn_async` there.
+    ///
+    pub fn spawn<F, R>(&self, ecall: F) -> EcallHandle<R>
+    where
+        F: FnOnce(sgx_enclave_id_t) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        assert!(
+            !WORKER_OF.with(|worker_of| ptr::eq(worker_of.get(), &*self.shared)),
+            "spawn called from a worker thread of the executor, use try_spawn"
+        );
+        let (job, handle) = job(ecall);
+        let mut queue = self.shared.lock();
+        while queue.jobs.len() >= self.shared.queue_capacity {
+            queue = self
+                .shared
+                .not_full
+                .wait(queue)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+        self.push(queue, job);
+        handle
+    }
+
+    ///
+    /// Queues the ECALL, or gives it back if the queue is full.
+    ///
+    pub fn try_spawn<F, R>(&self, ecall: F) -> Result<EcallHandle<R>, F>
+    where
+        F: FnOnce(sgx_enclave_id_t) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let queue = self.shared.lock();
+        if queue.jobs.len() >= self.shared.queue_capacity {
+            return Err(ecall);
+        }
+        let (job, handle) = job(ecall);
+        self.push(queue, job);
+        Ok(handle)
+    }
+
+    ///
+    /// Queues the ECALL once the queue has room, without blocking the thread.
+    ///
+    pub fn spawn_async<F, R>(&self, ecall: F) -> SpawnEcall<'_, F>
+    where
+        F: FnOnce(sgx_enclave_id_t) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        SpawnEcall {
+            executor: self,
+            ecall: Some(Box::new(ecall)),
+        }
+    }
+
+    fn push(&self, mut queue: MutexGuard<'_, Queue>, job: Job) {
+        queue.jobs.push_back(job);
+        drop(queue);
+        self.shared.not_empty.notify_one();
+    }
+}
+
+impl Drop for EnclaveExecutor {
+    fn drop(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.not_empty.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+///
+/// The future returned by `EnclaveExecutor::spawn_async`, which resolves to the handle of
+/// the ECALL once it is queued.
+///
+pub struct SpawnEcall<'a, F> {
+    executor: &'a EnclaveExecutor,
+    // Boxed, so that the future is Unpin whatever the closure.
+    ecall: Option<Box<F>>,
+}
+
+impl<'a, F, R> Future for SpawnEcall<'a, F>
+where
+    F: FnOnce(sgx_enclave_id_t) -> R + Send + 'static,
+    R: Send + 'static,
+{
+    type Output = EcallHandle<R>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<EcallHandle<R>> {
+        let shared = &self.executor.shared;
+        let mut queue = shared.lock();
+        if queue.jobs.len() >= shared.queue_capacity {
+            // A future polled again before being woken is already waiting.
+            if !queue.waiters.iter().any(|w| w.will_wake(cx.waker())) {
+                queue.waiters.push_back(cx.waker().clone());
+            }
+            return Poll::Pending;
+        }
+        let ecall = self
+            .ecall
+            .take()
+            .expect("SpawnEcall polled after completion");
+        let (job, handle) = job(*ecall);
+        self.executor.push(queue, job);
+        Poll::Ready(handle)
+    }
+}
+
+///
+/// The result of a queued ECALL, which can be joined or awaited.
+///
+/// The result is Err(SGX_ERROR_UNEXPECTED) if the ECALL closure panicked.
+///
+pub struct EcallHandle<R> {
+    slot: Arc<Slot<R>>,
+}
+
+struct Slot<R> {
+    state: Mutex<SlotState<R>>,
+    ready: Condvar,
+}
+
+struct SlotState<R> {
+    result: Option<SgxResult<R>>,
+    waker: Option<Waker>,
+}
+
+impl<R> Slot<R> {
+    fn lock(&self) -> MutexGuard<'_, SlotState<R>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn job<F, R>(ecall: F) -> (Job, EcallHandle<R>)
+where
+    F: FnOnce(sgx_enclave_id_t) -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let slot = Arc::new(Slot {
+        state: Mutex::new(SlotState {
+            result: None,
+            waker: None,
+        }),
+        ready: Condvar::new(),
+    });
+    let handle = EcallHandle { slot: slot.clone() };
+    let job = Box::new(move |enclave_id| {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| ecall(enclave_id)))
+            .map_err(|_| sgx_status_t::SGX_ERROR_UNEXPECTED);
+        let waker = {
+            let mut state = slot.lock();
+            state.result = Some(result);
+            state.waker.take()
+        };
+        slot.ready.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    });
+    (job, handle)
+}
+
+impl<R> EcallHandle<R> {
+    /// Returns true if the ECALL has completed.
+    pub fn is_finished(&self) -> bool {
+        self.slot.lock().result.is_some()
+    }
+
+    /// Blocks until the ECALL has completed, and returns its result.
+    pub fn join(self) -> SgxResult<R> {
+        let mut state = self.slot.lock();
+        loop {
+            if let Some(result) = state.result.take() {
+                return result;
+            }
+            state = self
+                .slot
+                .ready
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+impl<R> Future for EcallHandle<R> {
+    type Output = SgxResult<R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<SgxResult<R>> {
+        let mut state = self.slot.lock();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::task::Wake;
+    use std::time::Duration;
+
+    // The executor only destroys the enclave when the last reference is dropped, which
+    // the tests do without a uRTS.
+    #[no_mangle]
+    extern "C" fn sgx_destroy_enclave(_enclave_id: sgx_enclave_id_t) -> sgx_status_t {
+        sgx_status_t::SGX_SUCCESS
+    }
+
+    #[cfg(feature = "global_exit")]
+    #[no_mangle]
+    extern "C" fn t_global_exit_ecall(_enclave_id: sgx_enclave_id_t) -> sgx_status_t {
+        sgx_status_t::SGX_SUCCESS
+    }
+
+    fn executor(tcs_num: usize, queue_capacity: usize) -> EnclaveExecutor {
+        EnclaveExecutor::start(Arc::new(SgxEnclave::default()), tcs_num, queue_capacity)
+    }
+
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<T>(future: impl Future<Output = T>) -> T {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    // Occupies the single worker until the returned sender is dropped.
+    fn occupy(executor: &EnclaveExecutor) -> (mpsc::Sender<()>, EcallHandle<()>) {
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let handle = executor.spawn(move |_| {
+            started_tx.send(()).unwrap();
+            let _ = release_rx.recv();
+        });
+        started_rx.recv().unwrap();
+        (release_tx, handle)
+    }
+
+    #[test]
+    fn spawn_blocks_while_the_queue_is_full() {
+        let executor = executor(1, 1);
+        let (release, busy) = occupy(&executor);
+        let queued = executor.spawn(|eid| eid + 1);
+        assert_eq!(executor.queued(), 1);
+
+        let spawned = AtomicBool::new(false);
+        thread::scope(|s| {
+            let blocked = s.spawn(|| {
+                let handle = executor.spawn(|_| 7);
+                spawned.store(true, Ordering::SeqCst);
+                handle.join()
+            });
+            thread::sleep(Duration::from_millis(100));
+            assert!(!spawned.load(Ordering::SeqCst));
+
+            drop(release);
+            assert_eq!(blocked.join().unwrap(), Ok(7));
+        });
+        assert_eq!(busy.join(), Ok(()));
+        assert_eq!(queued.join(), Ok(1));
+    }
+
+    #[test]
+    fn try_spawn_gives_the_ecall_back_when_full() {
+        let executor = executor(1, 1);
+        let (release, busy) = occupy(&executor);
+        let queued = executor.try_spawn(|_| 1).ok().unwrap();
+        let rejected = executor.try_spawn(|_| 2).err().unwrap();
+        assert_eq!(rejected(0), 2);
+
+        drop(release);
+        busy.join().unwrap();
+        assert_eq!(queued.join(), Ok(1));
+        assert_eq!(executor.try_spawn(|_| 3).ok().unwrap().join(), Ok(3));
+    }
+
+    #[test]
+    fn spawn_async_waits_for_room() {
+        let executor = executor(1, 1);
+        let (release, busy) = occupy(&executor);
+        let queued = executor.spawn(|_| 1);
+
+        thread::scope(|s| {
+            let waiting = s.spawn(|| block_on(async { executor.spawn_async(|_| 2).await.await }));
+            thread::sleep(Duration::from_millis(100));
+            assert!(!waiting.is_finished());
+
+            drop(release);
+            assert_eq!(waiting.join().unwrap(), Ok(2));
+        });
+        busy.join().unwrap();
+        assert_eq!(block_on(queued), Ok(1));
+    }
+
+    #[test]
+    fn spawn_async_registers_its_waker_once() {
+        let executor = executor(1, 1);
+        let (release, busy) = occupy(&executor);
+        let queued = executor.spawn(|_| 1);
+
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut spawning = executor.spawn_async(|_| 2);
+        for _ in 0..3 {
+            assert!(Pin::new(&mut spawning).poll(&mut cx).is_pending());
+        }
+        assert_eq!(executor.shared.lock().waiters.len(), 1);
+
+        drop(release);
+        busy.join().unwrap();
+        assert_eq!(queued.join(), Ok(1));
+        assert_eq!(block_on(spawning).join(), Ok(2));
+    }
+
+    #[test]
+    fn drop_runs_the_queued_ecalls() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let executor = executor(2, 16);
+        let handles: Vec<_> = (0..16)
+            .map(|i| {
+                let runs = runs.clone();
+                executor.spawn(move |_| {
+                    thread::sleep(Duration::from_millis(1));
+                    runs.fetch_add(1, Ordering::SeqCst);
+                    i
+                })
+            })
+            .collect();
+        drop(executor);
+
+        assert_eq!(runs.load(Ordering::SeqCst), 16);
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert!(handle.is_finished());
+            assert_eq!(handle.join(), Ok(i));
+        }
+    }
+
+    #[test]
+    fn panics_are_reported_as_errors() {
+        let executor = Arc::new(executor(1, 4));
+        let panicked = executor.spawn(|_| -> u32 { panic!("ECALL failed") });
+        assert_eq!(panicked.join(), Err(sgx_status_t::SGX_ERROR_UNEXPECTED));
+
+        // spawn from a worker thread panics instead of risking a deadlock.
+        let inner = executor.clone();
+        let nested = executor.spawn(move |_| inner.spawn(|_| ()).join());
+        assert_eq!(nested.join(), Err(sgx_status_t::SGX_ERROR_UNEXPECTED));
+
+        // try_spawn is allowed there.
+        let inner = executor.clone();
+        let nested = executor.spawn(move |_| inner.try_spawn(|_| 5).is_ok());
+        assert_eq!(nested.join(), Ok(true));
+    }
+}
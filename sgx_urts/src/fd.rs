@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use crate::policy;
 use libc::{self, c_int, c_uint, c_ulong, c_void, iovec, loff_t, off64_t, off_t, size_t, ssize_t, timespec};
 use std::io::Error;
 
@@ -25,6 +26,7 @@ pub extern "C" fn u_read_ocall(
     buf: *mut c_void,
     count: size_t,
 ) -> ssize_t {
+    policy::audit_fd("u_read_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::read(fd, buf, count) };
     if ret < 0 {
@@ -46,6 +48,7 @@ pub extern "C" fn u_pread64_ocall(
     count: size_t,
     offset: off64_t,
 ) -> ssize_t {
+    policy::audit_fd("u_pread64_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::pread64(fd, buf, count, offset) };
     if ret < 0 {
@@ -66,6 +69,7 @@ pub extern "C" fn u_readv_ocall(
     iov: *const iovec,
     iovcnt: c_int,
 ) -> ssize_t {
+    policy::audit_fd("u_readv_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::readv(fd, iov, iovcnt) };
     if ret < 0 {
@@ -87,6 +91,7 @@ pub extern "C" fn u_preadv64_ocall(
     iovcnt: c_int,
     offset: off64_t,
 ) -> ssize_t {
+    policy::audit_fd("u_preadv64_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::preadv64(fd, iov, iovcnt, offset) };
     if ret < 0 {
@@ -107,6 +112,7 @@ pub extern "C" fn u_write_ocall(
     buf: *const c_void,
     count: size_t,
 ) -> ssize_t {
+    policy::audit_fd("u_write_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::write(fd, buf, count) };
     if ret < 0 {
@@ -128,6 +134,7 @@ pub extern "C" fn u_pwrite64_ocall(
     count: size_t,
     offset: off64_t,
 ) -> ssize_t {
+    policy::audit_fd("u_pwrite64_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::pwrite64(fd, buf, count, offset) };
     if ret < 0 {
@@ -148,6 +155,7 @@ pub extern "C" fn u_writev_ocall(
     iov: *const iovec,
     iovcnt: c_int,
 ) -> ssize_t {
+    policy::audit_fd("u_writev_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::writev(fd, iov, iovcnt) };
     if ret < 0 {
@@ -169,6 +177,7 @@ pub extern "C" fn u_pwritev64_ocall(
     iovcnt: c_int,
     offset: off64_t,
 ) -> ssize_t {
+    policy::audit_fd("u_pwritev64_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::pwritev64(fd, iov, iovcnt, offset) };
     if ret < 0 {
@@ -190,6 +199,8 @@ pub extern "C" fn u_sendfile_ocall(
     offset: *mut off_t,
     count: size_t,
 ) -> ssize_t {
+    policy::audit_fd("u_sendfile_ocall", out_fd);
+    policy::audit_fd("u_sendfile_ocall", in_fd);
     let mut errno = 0;
     let ret = unsafe { libc::sendfile(out_fd, in_fd, offset, count) };
     if ret < 0 {
@@ -213,6 +224,8 @@ pub extern "C" fn u_copy_file_range_ocall(
     len: size_t,
     flags: c_uint,
 ) -> ssize_t {
+    policy::audit_fd("u_copy_file_range_ocall", fd_in);
+    policy::audit_fd("u_copy_file_range_ocall", fd_out);
     let mut errno = 0;
     let ret = unsafe {
         libc::syscall(
@@ -246,6 +259,8 @@ pub extern "C" fn u_splice_ocall(
     len: size_t,
     flags: c_uint,
 ) -> ssize_t {
+    policy::audit_fd("u_splice_ocall", fd_in);
+    policy::audit_fd("u_splice_ocall", fd_out);
     let mut errno = 0;
     let ret = unsafe { libc::splice(fd_in, off_in, fd_out, off_out, len, flags) };
     if ret < 0 {
@@ -261,6 +276,7 @@ pub extern "C" fn u_splice_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_fcntl_arg0_ocall(error: *mut c_int, fd: c_int, cmd: c_int) -> c_int {
+    policy::audit_fd("u_fcntl_arg0_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::fcntl(fd, cmd) };
     if ret < 0 {
@@ -281,6 +297,7 @@ pub extern "C" fn u_fcntl_arg1_ocall(
     cmd: c_int,
     arg: c_int,
 ) -> c_int {
+    policy::audit_fd("u_fcntl_arg1_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::fcntl(fd, cmd, arg) };
     if ret < 0 {
@@ -296,6 +313,7 @@ pub extern "C" fn u_fcntl_arg1_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_ioctl_arg0_ocall(error: *mut c_int, fd: c_int, request: c_int) -> c_int {
+    policy::audit_fd("u_ioctl_arg0_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::ioctl(fd, request as c_ulong) };
     if ret < 0 {
@@ -316,6 +334,7 @@ pub extern "C" fn u_ioctl_arg1_ocall(
     request: c_int,
     arg: *mut c_int,
 ) -> c_int {
+    policy::audit_fd("u_ioctl_arg1_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::ioctl(fd, request as c_ulong, arg) };
     if ret < 0 {
@@ -331,6 +350,7 @@ pub extern "C" fn u_ioctl_arg1_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_close_ocall(error: *mut c_int, fd: c_int) -> c_int {
+    policy::audit_fd("u_close_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::close(fd) };
     if ret < 0 {
@@ -346,6 +366,7 @@ pub extern "C" fn u_close_ocall(error: *mut c_int, fd: c_int) -> c_int {
 
 #[no_mangle]
 pub extern "C" fn u_isatty_ocall(error: *mut c_int, fd: c_int) -> c_int {
+    policy::audit_fd("u_isatty_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::isatty(fd) };
     if ret == 0 {
@@ -361,6 +382,7 @@ pub extern "C" fn u_isatty_ocall(error: *mut c_int, fd: c_int) -> c_int {
 
 #[no_mangle]
 pub extern "C" fn u_dup_ocall(error: *mut c_int, oldfd: c_int) -> c_int {
+    policy::audit_fd("u_dup_ocall", oldfd);
     let mut errno = 0;
     let ret = unsafe { libc::dup(oldfd) };
     if ret < 0 {
@@ -376,6 +398,7 @@ pub extern "C" fn u_dup_ocall(error: *mut c_int, oldfd: c_int) -> c_int {
 
 #[no_mangle]
 pub extern "C" fn u_eventfd_ocall(error: *mut c_int, initval: c_uint, flags: c_int) -> c_int {
+    policy::audit_other("u_eventfd_ocall");
     let mut errno = 0;
     let ret = unsafe { libc::eventfd(initval, flags) };
     if ret < 0 {
@@ -391,6 +414,7 @@ pub extern "C" fn u_eventfd_ocall(error: *mut c_int, initval: c_uint, flags: c_i
 
 #[no_mangle]
 pub extern "C" fn u_futimens_ocall(error: *mut c_int, fd: c_int, times: *const timespec) -> c_int {
+    policy::audit_fd("u_futimens_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::futimens(fd, times) };
     if ret < 0 {
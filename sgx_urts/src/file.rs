@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use crate::policy;
 use libc::{
     self, c_char, c_int, dirent64, mode_t, off64_t, off_t, size_t, ssize_t, stat, stat64, DIR,
 };
@@ -23,6 +24,9 @@ use std::ptr;
 
 #[no_mangle]
 pub extern "C" fn u_open_ocall(error: *mut c_int, pathname: *const c_char, flags: c_int) -> c_int {
+    if !policy::check_path("u_open_ocall", libc::AT_FDCWD, pathname) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::open(pathname, flags) };
     if ret < 0 {
@@ -43,6 +47,9 @@ pub extern "C" fn u_open64_ocall(
     oflag: c_int,
     mode: c_int,
 ) -> c_int {
+    if !policy::check_path("u_open64_ocall", libc::AT_FDCWD, path) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::open64(path, oflag, mode) };
     if ret < 0 {
@@ -63,6 +70,9 @@ pub extern "C" fn u_openat_ocall(
     pathname: *const c_char,
     flags: c_int,
 ) -> c_int {
+    if !policy::check_path("u_openat_ocall", dirfd, pathname) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::openat(dirfd, pathname, flags) };
     if ret < 0 {
@@ -78,6 +88,7 @@ pub extern "C" fn u_openat_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_fstat_ocall(error: *mut c_int, fd: c_int, buf: *mut stat) -> c_int {
+    policy::audit_fd("u_fstat_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::fstat(fd, buf) };
     if ret < 0 {
@@ -93,6 +104,7 @@ pub extern "C" fn u_fstat_ocall(error: *mut c_int, fd: c_int, buf: *mut stat) ->
 
 #[no_mangle]
 pub extern "C" fn u_fstat64_ocall(error: *mut c_int, fd: c_int, buf: *mut stat64) -> c_int {
+    policy::audit_fd("u_fstat64_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::fstat64(fd, buf) };
     if ret < 0 {
@@ -108,6 +120,9 @@ pub extern "C" fn u_fstat64_ocall(error: *mut c_int, fd: c_int, buf: *mut stat64
 
 #[no_mangle]
 pub extern "C" fn u_stat_ocall(error: *mut c_int, path: *const c_char, buf: *mut stat) -> c_int {
+    if !policy::check_path("u_stat_ocall", libc::AT_FDCWD, path) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::stat(path, buf) };
     if ret < 0 {
@@ -127,6 +142,9 @@ pub extern "C" fn u_stat64_ocall(
     path: *const c_char,
     buf: *mut stat64,
 ) -> c_int {
+    if !policy::check_path("u_stat64_ocall", libc::AT_FDCWD, path) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::stat64(path, buf) };
     if ret < 0 {
@@ -142,6 +160,9 @@ pub extern "C" fn u_stat64_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_lstat_ocall(error: *mut c_int, path: *const c_char, buf: *mut stat) -> c_int {
+    if !policy::check_path("u_lstat_ocall", libc::AT_FDCWD, path) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::lstat(path, buf) };
     if ret < 0 {
@@ -161,6 +182,9 @@ pub extern "C" fn u_lstat64_ocall(
     path: *const c_char,
     buf: *mut stat64,
 ) -> c_int {
+    if !policy::check_path("u_lstat64_ocall", libc::AT_FDCWD, path) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::lstat64(path, buf) };
     if ret < 0 {
@@ -181,6 +205,7 @@ pub extern "C" fn u_lseek_ocall(
     offset: off_t,
     whence: c_int,
 ) -> off_t {
+    policy::audit_fd("u_lseek_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::lseek(fd, offset, whence) };
     if ret < 0 {
@@ -201,6 +226,7 @@ pub extern "C" fn u_lseek64_ocall(
     offset: off64_t,
     whence: c_int,
 ) -> off64_t {
+    policy::audit_fd("u_lseek64_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::lseek64(fd, offset, whence) };
     if ret < 0 {
@@ -216,6 +242,7 @@ pub extern "C" fn u_lseek64_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_ftruncate_ocall(error: *mut c_int, fd: c_int, length: off_t) -> c_int {
+    policy::audit_fd("u_ftruncate_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::ftruncate(fd, length) };
     if ret < 0 {
@@ -231,6 +258,7 @@ pub extern "C" fn u_ftruncate_ocall(error: *mut c_int, fd: c_int, length: off_t)
 
 #[no_mangle]
 pub extern "C" fn u_ftruncate64_ocall(error: *mut c_int, fd: c_int, length: off64_t) -> c_int {
+    policy::audit_fd("u_ftruncate64_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::ftruncate64(fd, length) };
     if ret < 0 {
@@ -246,6 +274,9 @@ pub extern "C" fn u_ftruncate64_ocall(error: *mut c_int, fd: c_int, length: off6
 
 #[no_mangle]
 pub extern "C" fn u_truncate_ocall(error: *mut c_int, path: *const c_char, length: off_t) -> c_int {
+    if !policy::check_path("u_truncate_ocall", libc::AT_FDCWD, path) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::truncate(path, length) };
     if ret < 0 {
@@ -265,6 +296,9 @@ pub extern "C" fn u_truncate64_ocall(
     path: *const c_char,
     length: off64_t,
 ) -> c_int {
+    if !policy::check_path("u_truncate64_ocall", libc::AT_FDCWD, path) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::truncate64(path, length) };
     if ret < 0 {
@@ -280,6 +314,7 @@ pub extern "C" fn u_truncate64_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_fsync_ocall(error: *mut c_int, fd: c_int) -> c_int {
+    policy::audit_fd("u_fsync_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::fsync(fd) };
     if ret < 0 {
@@ -295,6 +330,7 @@ pub extern "C" fn u_fsync_ocall(error: *mut c_int, fd: c_int) -> c_int {
 
 #[no_mangle]
 pub extern "C" fn u_fdatasync_ocall(error: *mut c_int, fd: c_int) -> c_int {
+    policy::audit_fd("u_fdatasync_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::fdatasync(fd) };
     if ret < 0 {
@@ -310,6 +346,7 @@ pub extern "C" fn u_fdatasync_ocall(error: *mut c_int, fd: c_int) -> c_int {
 
 #[no_mangle]
 pub extern "C" fn u_fchmod_ocall(error: *mut c_int, fd: c_int, mode: mode_t) -> c_int {
+    policy::audit_fd("u_fchmod_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::fchmod(fd, mode) };
     if ret < 0 {
@@ -325,6 +362,9 @@ pub extern "C" fn u_fchmod_ocall(error: *mut c_int, fd: c_int, mode: mode_t) ->
 
 #[no_mangle]
 pub extern "C" fn u_unlink_ocall(error: *mut c_int, pathname: *const c_char) -> c_int {
+    if !policy::check_path("u_unlink_ocall", libc::AT_FDCWD, pathname) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::unlink(pathname) };
     if ret < 0 {
@@ -344,6 +384,11 @@ pub extern "C" fn u_link_ocall(
     oldpath: *const c_char,
     newpath: *const c_char,
 ) -> c_int {
+    if !policy::check_path("u_link_ocall", libc::AT_FDCWD, oldpath)
+        || !policy::check_path("u_link_ocall", libc::AT_FDCWD, newpath)
+    {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::link(oldpath, newpath) };
     if ret < 0 {
@@ -364,6 +409,9 @@ pub extern "C" fn u_unlinkat_ocall(
     pathname: *const c_char,
     flags: c_int,
 ) -> c_int {
+    if !policy::check_path("u_unlinkat_ocall", dirfd, pathname) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::unlinkat(dirfd, pathname, flags) };
     if ret < 0 {
@@ -386,6 +434,11 @@ pub extern "C" fn u_linkat_ocall(
     newpath: *const c_char,
     flags: c_int,
 ) -> c_int {
+    if !policy::check_path("u_linkat_ocall", olddirfd, oldpath)
+        || !policy::check_path("u_linkat_ocall", newdirfd, newpath)
+    {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::linkat(olddirfd, oldpath, newdirfd, newpath, flags) };
     if ret < 0 {
@@ -405,6 +458,11 @@ pub extern "C" fn u_rename_ocall(
     oldpath: *const c_char,
     newpath: *const c_char,
 ) -> c_int {
+    if !policy::check_path("u_rename_ocall", libc::AT_FDCWD, oldpath)
+        || !policy::check_path("u_rename_ocall", libc::AT_FDCWD, newpath)
+    {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::rename(oldpath, newpath) };
     if ret < 0 {
@@ -420,6 +478,9 @@ pub extern "C" fn u_rename_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_chmod_ocall(error: *mut c_int, path: *const c_char, mode: mode_t) -> c_int {
+    if !policy::check_path("u_chmod_ocall", libc::AT_FDCWD, path) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::chmod(path, mode) };
     if ret < 0 {
@@ -440,6 +501,9 @@ pub extern "C" fn u_readlink_ocall(
     buf: *mut c_char,
     bufsz: size_t,
 ) -> ssize_t {
+    if !policy::check_path("u_readlink_ocall", libc::AT_FDCWD, path) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::readlink(path, buf, bufsz) };
     if ret < 0 {
@@ -459,6 +523,9 @@ pub extern "C" fn u_symlink_ocall(
     path1: *const c_char,
     path2: *const c_char,
 ) -> c_int {
+    if !policy::check_symlink("u_symlink_ocall", path1, path2) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::symlink(path1, path2) };
     if ret < 0 {
@@ -474,6 +541,9 @@ pub extern "C" fn u_symlink_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_realpath_ocall(error: *mut c_int, pathname: *const c_char) -> *mut c_char {
+    if !policy::check_path("u_realpath_ocall", libc::AT_FDCWD, pathname) {
+        return policy::deny(error, ptr::null_mut());
+    }
     let mut errno = 0;
     let ret = unsafe { libc::realpath(pathname, ptr::null_mut()) };
     if ret.is_null() {
@@ -489,6 +559,9 @@ pub extern "C" fn u_realpath_ocall(error: *mut c_int, pathname: *const c_char) -
 
 #[no_mangle]
 pub extern "C" fn u_mkdir_ocall(error: *mut c_int, pathname: *const c_char, mode: mode_t) -> c_int {
+    if !policy::check_path("u_mkdir_ocall", libc::AT_FDCWD, pathname) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::mkdir(pathname, mode) };
     if ret < 0 {
@@ -504,6 +577,9 @@ pub extern "C" fn u_mkdir_ocall(error: *mut c_int, pathname: *const c_char, mode
 
 #[no_mangle]
 pub extern "C" fn u_rmdir_ocall(error: *mut c_int, pathname: *const c_char) -> c_int {
+    if !policy::check_path("u_rmdir_ocall", libc::AT_FDCWD, pathname) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::rmdir(pathname) };
     if ret < 0 {
@@ -519,6 +595,7 @@ pub extern "C" fn u_rmdir_ocall(error: *mut c_int, pathname: *const c_char) -> c
 
 #[no_mangle]
 pub extern "C" fn u_fdopendir_ocall(error: *mut c_int, fd: c_int) -> *mut DIR {
+    policy::audit_fd("u_fdopendir_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::fdopendir(fd) };
     if ret.is_null() {
@@ -534,6 +611,9 @@ pub extern "C" fn u_fdopendir_ocall(error: *mut c_int, fd: c_int) -> *mut DIR {
 
 #[no_mangle]
 pub extern "C" fn u_opendir_ocall(error: *mut c_int, pathname: *const c_char) -> *mut DIR {
+    if !policy::check_path("u_opendir_ocall", libc::AT_FDCWD, pathname) {
+        return policy::deny(error, ptr::null_mut());
+    }
     let mut errno = 0;
     let ret = unsafe { libc::opendir(pathname) };
     if ret.is_null() {
@@ -553,11 +633,13 @@ pub extern "C" fn u_readdir64_r_ocall(
     entry: *mut dirent64,
     result: *mut *mut dirent64,
 ) -> c_int {
+    policy::audit_other("u_readdir64_r_ocall");
     unsafe { libc::readdir64_r(dirp, entry, result) }
 }
 
 #[no_mangle]
 pub extern "C" fn u_closedir_ocall(error: *mut c_int, dirp: *mut DIR) -> c_int {
+    policy::audit_other("u_closedir_ocall");
     let mut errno = 0;
     let ret = unsafe { libc::closedir(dirp) };
     if ret < 0 {
@@ -573,6 +655,7 @@ pub extern "C" fn u_closedir_ocall(error: *mut c_int, dirp: *mut DIR) -> c_int {
 
 #[no_mangle]
 pub extern "C" fn u_dirfd_ocall(error: *mut c_int, dirp: *mut DIR) -> c_int {
+    policy::audit_other("u_dirfd_ocall");
     let mut errno = 0;
     let ret = unsafe { libc::dirfd(dirp) };
     if ret < 0 {
@@ -594,6 +677,9 @@ pub extern "C" fn u_fstatat64_ocall(
     buf: *mut stat64,
     flags: c_int,
 ) -> c_int {
+    if !policy::check_path("u_fstatat64_ocall", dirfd, pathname) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::fstatat64(dirfd, pathname, buf, flags) };
     if ret < 0 {
@@ -38,3 +38,28 @@ pub mod time;
 
 mod enclave;
 pub use enclave::*;
+
+mod metadata;
+pub use metadata::*;
+
+mod token;
+pub use token::*;
+
+mod switchless;
+pub use switchless::*;
+
+mod executor;
+pub use executor::*;
+
+mod policy;
+pub use policy::{OcallEvent, OcallPolicy, OcallTarget};
+
+#[cfg(feature = "dcap")]
+mod quote;
+#[cfg(feature = "dcap")]
+pub use quote::*;
+
+#[cfg(feature = "ra")]
+mod ra;
+#[cfg(feature = "ra")]
+pub use ra::*;
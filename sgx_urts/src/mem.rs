@@ -15,11 +15,13 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use crate::policy;
 use libc::{self, c_int, c_void, off_t, size_t};
 use std::io::Error;
 
 #[no_mangle]
 pub extern "C" fn u_malloc_ocall(error: *mut c_int, size: size_t) -> *mut c_void {
+    policy::audit_other("u_malloc_ocall");
     let mut errno = 0;
     let ret = unsafe { libc::malloc(size) };
     if ret.is_null() {
@@ -35,6 +37,7 @@ pub extern "C" fn u_malloc_ocall(error: *mut c_int, size: size_t) -> *mut c_void
 
 #[no_mangle]
 pub extern "C" fn u_free_ocall(p: *mut c_void) {
+    policy::audit_other("u_free_ocall");
     unsafe { libc::free(p) }
 }
 
@@ -48,6 +51,7 @@ pub extern "C" fn u_mmap_ocall(
     fd: c_int,
     offset: off_t,
 ) -> *mut c_void {
+    policy::audit_fd("u_mmap_ocall", fd);
     let mut errno = 0;
     let ret = unsafe { libc::mmap(start, length, prot, flags, fd, offset) };
     if ret as isize == -1 {
@@ -63,6 +67,7 @@ pub extern "C" fn u_mmap_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_munmap_ocall(error: *mut c_int, start: *mut c_void, length: size_t) -> c_int {
+    policy::audit_other("u_munmap_ocall");
     let mut errno = 0;
     let ret = unsafe { libc::munmap(start, length) };
     if ret < 0 {
@@ -83,6 +88,7 @@ pub extern "C" fn u_msync_ocall(
     length: size_t,
     flags: c_int,
 ) -> c_int {
+    policy::audit_other("u_msync_ocall");
     let mut errno = 0;
     let ret = unsafe { libc::msync(addr, length, flags) };
     if ret < 0 {
@@ -103,6 +109,7 @@ pub extern "C" fn u_mprotect_ocall(
     length: size_t,
     prot: c_int,
 ) -> c_int {
+    policy::audit_other("u_mprotect_ocall");
     let mut errno = 0;
     let ret = unsafe { libc::mprotect(addr, length, prot) };
     if ret < 0 {
@@ -0,0 +1,194 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use sgx_types::metadata::*;
+use sgx_types::*;
+use std::ffi::CString;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::ptr;
+use std::slice;
+
+///
+/// Reads the metadata of a signed enclave file, which holds the SIGSTRUCT and the layout
+/// of the enclave.
+///
+/// # Requirements
+///
+/// Header: sgx_urts.h
+///
+/// Library: libsgx_urts.a
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The path contains a NUL byte.
+///
+/// **SGX_ERROR_INVALID_ENCLAVE**
+///
+/// The enclave file is not a signed enclave, or its metadata is invalid.
+///
+pub fn rsgx_get_metadata<P: AsRef<Path>>(enclave_file: P) -> SgxResult<Box<metadata_t>> {
+    let path = CString::new(enclave_file.as_ref().as_os_str().as_bytes())
+        .map_err(|_| sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+    let mut metadata: Box<metadata_t> = Box::new(unsafe { mem::zeroed() });
+    let ret = unsafe { sgx_get_metadata(path.as_ptr(), &mut *metadata as *mut metadata_t) };
+    match ret {
+        sgx_status_t::SGX_SUCCESS => Ok(metadata),
+        _ => Err(ret),
+    }
+}
+
+///
+/// The number of TCS of the enclave, which is TCSMaxNum of the enclave configuration:
+/// the TCS added when the enclave is loaded, and the TCS added later with EDMM.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_ENCLAVE**
+///
+/// The layout directory of the metadata is out of bounds.
+///
+pub fn rsgx_metadata_tcs_num(metadata: &metadata_t) -> SgxResult<usize> {
+    const LAYOUT_SIZE: usize = mem::size_of::<layout_t>();
+
+    let bytes = unsafe {
+        slice::from_raw_parts(
+            metadata as *const metadata_t as *const u8,
+            mem::size_of::<metadata_t>(),
+        )
+    };
+    let dir = metadata.dirs[dir_index_t::DIR_LAYOUT as usize];
+    let (offset, size) = (dir.offset as usize, dir.size as usize);
+    let layouts = offset
+        .checked_add(size)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or(sgx_status_t::SGX_ERROR_INVALID_ENCLAVE)?;
+
+    // A group repeats the `entry_count` entries before it `load_times` times, so keep the
+    // number of TCS each entry stands for.
+    let mut tcs_nums: Vec<usize> = Vec::with_capacity(layouts.len() / LAYOUT_SIZE);
+    for layout in layouts.chunks_exact(LAYOUT_SIZE) {
+        let entry = unsafe { ptr::read_unaligned(layout.as_ptr() as *const layout_entry_t) };
+        let tcs_num = if u32::from(entry.id) & GROUP_FLAG != 0 {
+            let group = unsafe { ptr::read_unaligned(layout.as_ptr() as *const layout_group_t) };
+            let first = tcs_nums
+                .len()
+                .checked_sub(group.entry_count as usize)
+                .ok_or(sgx_status_t::SGX_ERROR_INVALID_ENCLAVE)?;
+            tcs_nums[first..].iter().sum::<usize>() * group.load_times as usize
+        } else {
+            match u32::from(entry.id) {
+                LAYOUT_ID_TCS | LAYOUT_ID_TCS_DYN => 1,
+                _ => 0,
+            }
+        };
+        tcs_nums.push(tcs_num);
+    }
+    Ok(tcs_nums.iter().sum())
+}
+
+///
+/// The MRENCLAVE the enclave will have, as signed in its SIGSTRUCT.
+///
+pub fn rsgx_metadata_mrenclave(metadata: &metadata_t) -> sgx_measurement_t {
+    metadata.enclave_css.body.enclave_hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata_with_layouts(layouts: &[layout_t]) -> Box<metadata_t> {
+        let mut metadata: Box<metadata_t> = Box::new(unsafe { mem::zeroed() });
+        let offset = mem::size_of::<metadata_t>() - metadata.data.len();
+        let size = mem::size_of_val(layouts);
+        metadata.dirs[dir_index_t::DIR_LAYOUT as usize] = data_directory_t {
+            offset: offset as u32,
+            size: size as u32,
+        };
+        let bytes = unsafe { slice::from_raw_parts(layouts.as_ptr() as *const u8, size) };
+        metadata.data[..size].copy_from_slice(bytes);
+        metadata
+    }
+
+    fn entry(id: u32) -> layout_t {
+        layout_t {
+            entry: layout_entry_t {
+                id: id as u16,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn group(id: u32, entry_count: u16, load_times: u32) -> layout_t {
+        layout_t {
+            group: layout_group_t {
+                id: id as u16,
+                entry_count,
+                load_times,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn tcs_num_counts_thread_groups() {
+        // The layout of the signing tool for TCSNum 4 and TCSMaxNum 6: the first thread and
+        // a group of 3 more, then the first dynamic thread and a group of 1 more.
+        let metadata = metadata_with_layouts(&[
+            entry(LAYOUT_ID_HEAP_MIN),
+            entry(LAYOUT_ID_GUARD),
+            entry(LAYOUT_ID_STACK_MAX),
+            entry(LAYOUT_ID_TCS),
+            entry(LAYOUT_ID_SSA),
+            entry(LAYOUT_ID_GUARD),
+            entry(LAYOUT_ID_TD),
+            group(LAYOUT_ID_THREAD_GROUP, 6, 3),
+            entry(LAYOUT_ID_STACK_DYN_MAX),
+            entry(LAYOUT_ID_TCS_DYN),
+            entry(LAYOUT_ID_SSA_DYN),
+            group(LAYOUT_ID_THREAD_GROUP_DYN, 3, 1),
+        ]);
+        assert_eq!(rsgx_metadata_tcs_num(&metadata), Ok(6));
+
+        let metadata = metadata_with_layouts(&[entry(LAYOUT_ID_TCS)]);
+        assert_eq!(rsgx_metadata_tcs_num(&metadata), Ok(1));
+    }
+
+    #[test]
+    fn tcs_num_rejects_bad_layouts() {
+        let metadata =
+            metadata_with_layouts(&[entry(LAYOUT_ID_TCS), group(LAYOUT_ID_THREAD_GROUP, 2, 1)]);
+        assert_eq!(
+            rsgx_metadata_tcs_num(&metadata),
+            Err(sgx_status_t::SGX_ERROR_INVALID_ENCLAVE)
+        );
+
+        let mut metadata = metadata_with_layouts(&[]);
+        metadata.dirs[dir_index_t::DIR_LAYOUT as usize] = data_directory_t {
+            offset: METADATA_SIZE as u32,
+            size: 32,
+        };
+        assert_eq!(
+            rsgx_metadata_tcs_num(&metadata),
+            Err(sgx_status_t::SGX_ERROR_INVALID_ENCLAVE)
+        );
+    }
+}
@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use crate::policy;
 use libc::{self, addrinfo, c_char, c_int};
 use std::io::Error;
 
@@ -26,6 +27,9 @@ pub extern "C" fn u_getaddrinfo_ocall(
     hints: *const addrinfo,
     res: *mut *mut addrinfo,
 ) -> c_int {
+    if !policy::check_host("u_getaddrinfo_ocall", node) {
+        return policy::deny(error, libc::EAI_SYSTEM);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::getaddrinfo(node, service, hints, res) };
     if ret == libc::EAI_SYSTEM {
@@ -41,10 +45,12 @@ pub extern "C" fn u_getaddrinfo_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_freeaddrinfo_ocall(res: *mut addrinfo) {
+    policy::audit_other("u_freeaddrinfo_ocall");
     unsafe { libc::freeaddrinfo(res) }
 }
 
 #[no_mangle]
 pub extern "C" fn u_gai_strerror_ocall(errcode: c_int) -> *const c_char {
+    policy::audit_other("u_gai_strerror_ocall");
     unsafe { libc::gai_strerror(errcode) }
 }
@@ -0,0 +1,844 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use libc::{self, c_char, c_int, sa_family_t, sockaddr, sockaddr_in, sockaddr_in6, socklen_t};
+use std::env;
+use std::ffi::{CStr, OsStr};
+use std::fs;
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+type PathFilter = Arc<dyn Fn(&Path) -> bool + Send + Sync>;
+type AddrFilter = Arc<dyn Fn(&SocketAddr) -> bool + Send + Sync>;
+type HostFilter = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+type EnvFilter = Arc<dyn Fn(&OsStr) -> bool + Send + Sync>;
+type AuditSink = Arc<dyn Fn(&OcallEvent<'_>) + Send + Sync>;
+
+// The OCALLs are not told which enclave calls them, so the policy is shared by all the
+// enclaves of the process.
+static POLICY: RwLock<Option<Arc<OcallPolicy>>> = RwLock::new(None);
+
+///
+/// What a checked OCALL is about to access.
+///
+#[derive(Clone, Copy, Debug)]
+pub enum OcallTarget<'a> {
+    /// A filesystem path, made absolute with its symbolic links and `..` resolved,
+    /// including the path of a Unix domain socket.
+    Path(&'a Path),
+    /// A path whose directory cannot be found, or a dangling symbolic link, which is
+    /// denied.
+    Unresolved(&'a Path),
+    /// An IPv4 or IPv6 socket address.
+    Addr(&'a SocketAddr),
+    /// A socket address of another family, such as an abstract Unix domain socket.
+    OtherAddr(sa_family_t),
+    /// A host name, or a numeric address, passed to the resolver.
+    Host(&'a str),
+    /// An environment variable.
+    Env(&'a OsStr),
+    /// The whole environment, which is denied whenever the environment is restricted.
+    Environ,
+    /// A file descriptor, which is audited but not checked.
+    Fd(c_int),
+    /// Nothing that a filter applies to, as for `u_malloc_ocall`, which is audited but
+    /// not checked.
+    Other,
+}
+
+///
+/// An OCALL, as passed to the audit sink.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct OcallEvent<'a> {
+    /// The name of the OCALL, e.g. `u_open_ocall`.
+    pub ocall: &'static str,
+    pub target: OcallTarget<'a>,
+    pub allowed: bool,
+}
+
+///
+/// A host-side policy for the OCALLs that access the filesystem, bind, connect or send to
+/// socket addresses, look up host names, or read and write the environment.
+///
+/// Each family is unrestricted until a filter is added for it, and then a call is allowed
+/// if any of the filters of its family allows it. A denied call fails with EPERM without
+/// being made.
+///
+/// The checked OCALLs are the filesystem OCALLs that take a path, `u_chdir_ocall`,
+/// `u_bind_ocall`, `u_connect_ocall`, `u_sendto_ocall`, `u_sendmsg_ocall`,
+/// `u_getaddrinfo_ocall` and the environment OCALLs. The audit sink receives every call of
+/// them, allowed or denied, and every call of the other OCALLs of the filesystem, socket,
+/// network, memory and environment families, such as `u_read_ocall` or `u_malloc_ocall`,
+/// which are always allowed.
+///
+/// The paths are resolved on the host before being checked: the symbolic links and `..`
+/// are followed, so a path is checked as the file it names, and a symbolic link as the
+/// file it points to, even by the OCALLs that do not follow it. The check and the call
+/// are not atomic, so a host process that replaces a directory with a symbolic link in
+/// between can still lead a call elsewhere.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut policy = OcallPolicy::new();
+/// policy
+///     .allow_paths(&["/var/lib/my_app"])
+///     .allow_socket_addrs(&["10.0.0.2:443".parse().unwrap()])
+///     .allow_hosts(&["example.com"])
+///     .allow_env_keys(&["RUST_LOG"])
+///     .audit(|event| log::info!("{:?}", event));
+/// policy.install();
+/// ```
+///
+#[derive(Clone, Default)]
+pub struct OcallPolicy {
+    path_filters: Vec<PathFilter>,
+    addr_filters: Vec<AddrFilter>,
+    host_filters: Vec<HostFilter>,
+    env_filters: Vec<EnvFilter>,
+    audit: Option<AuditSink>,
+}
+
+impl OcallPolicy {
+    pub fn new() -> OcallPolicy {
+        OcallPolicy::default()
+    }
+
+    /// Allows the paths under any of the directories, or equal to any of the files.
+    pub fn allow_paths<I, P>(&mut self, paths: I) -> &mut OcallPolicy
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
+        let allowed: Vec<PathBuf> = paths
+            .into_iter()
+            .map(|path| {
+                let path = cwd.join(path);
+                fs::canonicalize(&path).unwrap_or_else(|_| normalize(&path))
+            })
+            .collect();
+        self.path_filter(move |path| allowed.iter().any(|allowed| path.starts_with(allowed)))
+    }
+
+    /// Allows the paths for which the closure returns true.
+    pub fn path_filter<F>(&mut self, filter: F) -> &mut OcallPolicy
+    where
+        F: Fn(&Path) -> bool + Send + Sync + 'static,
+    {
+        self.path_filters.push(Arc::new(filter));
+        self
+    }
+
+    /// Allows the socket addresses. An address with port 0 allows any port of its IP.
+    pub fn allow_socket_addrs<'a, I>(&mut self, addrs: I) -> &mut OcallPolicy
+    where
+        I: IntoIterator<Item = &'a SocketAddr>,
+    {
+        let allowed: Vec<SocketAddr> = addrs.into_iter().copied().collect();
+        self.socket_filter(move |addr| {
+            allowed.iter().any(|allowed| {
+                allowed.ip() == addr.ip() && (allowed.port() == 0 || allowed.port() == addr.port())
+            })
+        })
+    }
+
+    /// Allows the socket addresses for which the closure returns true.
+    pub fn socket_filter<F>(&mut self, filter: F) -> &mut OcallPolicy
+    where
+        F: Fn(&SocketAddr) -> bool + Send + Sync + 'static,
+    {
+        self.addr_filters.push(Arc::new(filter));
+        self
+    }
+
+    /// Allows the host names, compared without regard to ASCII case.
+    pub fn allow_hosts<I, H>(&mut self, hosts: I) -> &mut OcallPolicy
+    where
+        I: IntoIterator<Item = H>,
+        H: AsRef<str>,
+    {
+        let allowed: Vec<String> = hosts
+            .into_iter()
+            .map(|host| host.as_ref().to_owned())
+            .collect();
+        self.host_filter(move |host| {
+            allowed
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(host))
+        })
+    }
+
+    /// Allows the host names for which the closure returns true.
+    pub fn host_filter<F>(&mut self, filter: F) -> &mut OcallPolicy
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.host_filters.push(Arc::new(filter));
+        self
+    }
+
+    /// Allows the environment variables.
+    pub fn allow_env_keys<I, K>(&mut self, keys: I) -> &mut OcallPolicy
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<OsStr>,
+    {
+        let allowed: Vec<_> = keys
+            .into_iter()
+            .map(|key| key.as_ref().to_owned())
+            .collect();
+        self.env_filter(move |key| allowed.iter().any(|allowed| allowed == key))
+    }
+
+    /// Allows the environment variables for which the closure returns true.
+    pub fn env_filter<F>(&mut self, filter: F) -> &mut OcallPolicy
+    where
+        F: Fn(&OsStr) -> bool + Send + Sync + 'static,
+    {
+        self.env_filters.push(Arc::new(filter));
+        self
+    }
+
+    /// Passes every OCALL to the sink, which must not make OCALLs itself.
+    pub fn audit<F>(&mut self, sink: F) -> &mut OcallPolicy
+    where
+        F: Fn(&OcallEvent<'_>) + Send + Sync + 'static,
+    {
+        self.audit = Some(Arc::new(sink));
+        self
+    }
+
+    /// Applies the policy to the OCALLs of all the enclaves, replacing the previous one.
+    pub fn install(&self) {
+        *POLICY.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(self.clone()));
+    }
+
+    /// Removes the installed policy, so that the OCALLs are unrestricted again.
+    pub fn uninstall() {
+        *POLICY.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    fn decide(&self, ocall: &'static str, target: OcallTarget<'_>) -> bool {
+        let allowed = match target {
+            OcallTarget::Path(path) => allows(&self.path_filters, |filter| filter(path)),
+            OcallTarget::Unresolved(_) => false,
+            OcallTarget::Addr(addr) => allows(&self.addr_filters, |filter| filter(addr)),
+            OcallTarget::OtherAddr(_) => self.addr_filters.is_empty(),
+            OcallTarget::Host(host) => allows(&self.host_filters, |filter| filter(host)),
+            OcallTarget::Env(key) => allows(&self.env_filters, |filter| filter(key)),
+            OcallTarget::Environ => self.env_filters.is_empty(),
+            OcallTarget::Fd(_) | OcallTarget::Other => true,
+        };
+        if let Some(ref audit) = self.audit {
+            audit(&OcallEvent {
+                ocall,
+                target,
+                allowed,
+            });
+        }
+        allowed
+    }
+}
+
+fn allows<T: ?Sized, F: Fn(&T) -> bool>(filters: &[Arc<T>], allow: F) -> bool {
+    filters.is_empty() || filters.iter().any(|filter| allow(filter))
+}
+
+fn installed() -> Option<Arc<OcallPolicy>> {
+    POLICY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+// Resolves `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+// Resolves the path relative to the directory `dirfd` like the *at functions.
+fn resolve(dirfd: c_int, path: &Path) -> Option<PathBuf> {
+    if path.is_absolute() {
+        return resolve_in(Path::new("/"), path);
+    }
+    let base = if dirfd == libc::AT_FDCWD {
+        env::current_dir().ok()?
+    } else {
+        fs::read_link(format!("/proc/self/fd/{}", dirfd)).ok()?
+    };
+    resolve_in(&base, path)
+}
+
+// Resolves the path relative to `base`, following the symbolic links and `..` on the
+// host. The last component need not exist, as for a file about to be created, but its
+// directory must, and a dangling symbolic link is not resolved.
+fn resolve_in(base: &Path, path: &Path) -> Option<PathBuf> {
+    let path = base.join(path);
+    if let Ok(path) = fs::canonicalize(&path) {
+        return Some(path);
+    }
+    let name = match path.components().next_back()? {
+        Component::Normal(name) => name,
+        _ => return None,
+    };
+    let path = fs::canonicalize(path.parent()?).ok()?.join(name);
+    // A link that points to a missing file, which open would create wherever it points.
+    if fs::symlink_metadata(&path).is_ok() {
+        return None;
+    }
+    Some(path)
+}
+
+fn c_path<'a>(path: *const c_char) -> &'a Path {
+    Path::new(OsStr::from_bytes(
+        unsafe { CStr::from_ptr(path) }.to_bytes(),
+    ))
+}
+
+///
+/// Checks a path relative to `dirfd`. A null path is left to fail in the OCALL itself,
+/// and a path that cannot be made absolute is denied.
+///
+pub(crate) fn check_path(ocall: &'static str, dirfd: c_int, path: *const c_char) -> bool {
+    let policy = match installed() {
+        Some(policy) => policy,
+        None => return true,
+    };
+    if path.is_null() {
+        return true;
+    }
+    let path = c_path(path);
+    match resolve(dirfd, path) {
+        Some(path) => policy.decide(ocall, OcallTarget::Path(&path)),
+        None => policy.decide(ocall, OcallTarget::Unresolved(path)),
+    }
+}
+
+///
+/// Checks a symbolic link and its target, which is relative to the directory of the link.
+///
+pub(crate) fn check_symlink(
+    ocall: &'static str,
+    target: *const c_char,
+    linkpath: *const c_char,
+) -> bool {
+    let policy = match installed() {
+        Some(policy) => policy,
+        None => return true,
+    };
+    if target.is_null() || linkpath.is_null() {
+        return true;
+    }
+    let linkpath = match resolve(libc::AT_FDCWD, c_path(linkpath)) {
+        Some(linkpath) => linkpath,
+        None => return policy.decide(ocall, OcallTarget::Unresolved(c_path(linkpath))),
+    };
+    if !policy.decide(ocall, OcallTarget::Path(&linkpath)) {
+        return false;
+    }
+    match resolve_in(linkpath.parent().unwrap_or(&linkpath), c_path(target)) {
+        Some(target) => policy.decide(ocall, OcallTarget::Path(&target)),
+        None => policy.decide(ocall, OcallTarget::Unresolved(c_path(target))),
+    }
+}
+
+///
+/// Checks a socket address. A null address is left to fail in the OCALL itself.
+///
+pub(crate) fn check_addr(ocall: &'static str, addr: *const sockaddr, addrlen: socklen_t) -> bool {
+    let policy = match installed() {
+        Some(policy) => policy,
+        None => return true,
+    };
+    if addr.is_null() || (addrlen as usize) < mem::size_of::<sa_family_t>() {
+        return true;
+    }
+    let family = unsafe { (*addr).sa_family };
+    match c_int::from(family) {
+        libc::AF_INET if addrlen as usize >= mem::size_of::<sockaddr_in>() => {
+            let addr = unsafe { &*(addr as *const sockaddr_in) };
+            let addr = SocketAddr::V4(SocketAddrV4::new(
+                Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)),
+                u16::from_be(addr.sin_port),
+            ));
+            policy.decide(ocall, OcallTarget::Addr(&addr))
+        }
+        libc::AF_INET6 if addrlen as usize >= mem::size_of::<sockaddr_in6>() => {
+            let addr = unsafe { &*(addr as *const sockaddr_in6) };
+            let addr = SocketAddr::V6(SocketAddrV6::new(
+                Ipv6Addr::from(addr.sin6_addr.s6_addr),
+                u16::from_be(addr.sin6_port),
+                addr.sin6_flowinfo,
+                addr.sin6_scope_id,
+            ));
+            policy.decide(ocall, OcallTarget::Addr(&addr))
+        }
+        libc::AF_UNIX => {
+            let offset = mem::size_of::<sa_family_t>();
+            let path = unsafe {
+                std::slice::from_raw_parts(
+                    (addr as *const u8).add(offset),
+                    addrlen as usize - offset,
+                )
+            };
+            // A pathname socket, rather than an unnamed or abstract one.
+            match path.first() {
+                Some(&b) if b != 0 => {
+                    let len = path.iter().position(|&b| b == 0).unwrap_or(path.len());
+                    let path = Path::new(OsStr::from_bytes(&path[..len]));
+                    match resolve(libc::AT_FDCWD, path) {
+                        Some(path) => policy.decide(ocall, OcallTarget::Path(&path)),
+                        None => policy.decide(ocall, OcallTarget::Unresolved(path)),
+                    }
+                }
+                _ => policy.decide(ocall, OcallTarget::OtherAddr(family)),
+            }
+        }
+        _ => policy.decide(ocall, OcallTarget::OtherAddr(family)),
+    }
+}
+
+///
+/// Checks a host name passed to the resolver. A null name, which looks up the local
+/// addresses of a service, is not checked.
+///
+pub(crate) fn check_host(ocall: &'static str, node: *const c_char) -> bool {
+    let policy = match installed() {
+        Some(policy) => policy,
+        None => return true,
+    };
+    if node.is_null() {
+        return true;
+    }
+    let node = unsafe { CStr::from_ptr(node) }.to_string_lossy();
+    policy.decide(ocall, OcallTarget::Host(&node))
+}
+
+///
+/// Checks an environment variable. A null name is left to fail in the OCALL itself.
+///
+pub(crate) fn check_env(ocall: &'static str, name: *const c_char) -> bool {
+    let policy = match installed() {
+        Some(policy) => policy,
+        None => return true,
+    };
+    if name.is_null() {
+        return true;
+    }
+    let name = OsStr::from_bytes(unsafe { CStr::from_ptr(name) }.to_bytes());
+    policy.decide(ocall, OcallTarget::Env(name))
+}
+
+/// Checks a read of the whole environment.
+pub(crate) fn check_environ(ocall: &'static str) -> bool {
+    match installed() {
+        Some(policy) => policy.decide(ocall, OcallTarget::Environ),
+        None => true,
+    }
+}
+
+/// Audits an OCALL on a file descriptor, which is not checked.
+pub(crate) fn audit_fd(ocall: &'static str, fd: c_int) {
+    audit(ocall, OcallTarget::Fd(fd));
+}
+
+/// Audits an OCALL that names no file descriptor, path, address or name.
+pub(crate) fn audit_other(ocall: &'static str) {
+    audit(ocall, OcallTarget::Other);
+}
+
+fn audit(ocall: &'static str, target: OcallTarget<'_>) {
+    if let Some(policy) = installed() {
+        policy.decide(ocall, target);
+    }
+}
+
+/// Fails a denied OCALL with EPERM.
+pub(crate) fn deny<T>(error: *mut c_int, ret: T) -> T {
+    if !error.is_null() {
+        unsafe {
+            *error = libc::EPERM;
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libc::sockaddr_un;
+    use std::ffi::CString;
+    use std::os::unix::fs::symlink;
+    use std::os::unix::io::AsRawFd;
+    use std::process;
+    use std::ptr;
+    use std::sync::Mutex;
+
+    // The tests install the process-wide policy, so they take turns.
+    static INSTALLED: Mutex<()> = Mutex::new(());
+
+    // Installs the policy, recording the audited events as (ocall, target, allowed).
+    fn install(policy: &mut OcallPolicy) -> Arc<Mutex<Vec<(String, String, bool)>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        policy
+            .audit(move |event| {
+                sink.lock().unwrap().push((
+                    event.ocall.to_owned(),
+                    format!("{:?}", event.target),
+                    event.allowed,
+                ))
+            })
+            .install();
+        events
+    }
+
+    // A fresh directory, with its symbolic links resolved.
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Scratch {
+            let dir = env::temp_dir().join(format!("sgx_urts-policy-{}-{}", name, process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Scratch(fs::canonicalize(dir).unwrap())
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn c_string(path: &Path) -> CString {
+        CString::new(path.as_os_str().as_bytes()).unwrap()
+    }
+
+    fn sockaddr_of<T>(addr: &T) -> (*const sockaddr, socklen_t) {
+        (
+            addr as *const T as *const sockaddr,
+            mem::size_of::<T>() as socklen_t,
+        )
+    }
+
+    fn unix_addr(path: &[u8]) -> sockaddr_un {
+        let mut addr: sockaddr_un = unsafe { mem::zeroed() };
+        addr.sun_family = libc::AF_UNIX as sa_family_t;
+        for (dst, &src) in addr.sun_path.iter_mut().zip(path) {
+            *dst = src as c_char;
+        }
+        addr
+    }
+
+    #[test]
+    fn normalize_resolves_parent_dirs() {
+        assert_eq!(normalize(Path::new("/a/./b/../c")), Path::new("/a/c"));
+        assert_eq!(normalize(Path::new("/a/b/../../..")), Path::new("/"));
+        assert_eq!(
+            normalize(Path::new("/srv/data/../../etc/passwd")),
+            Path::new("/etc/passwd")
+        );
+    }
+
+    #[test]
+    fn resolve_follows_the_dirfd() {
+        let scratch = Scratch::new("resolve");
+        let base = &scratch.0;
+        fs::create_dir(base.join("a")).unwrap();
+        assert_eq!(
+            resolve(libc::AT_FDCWD, &base.join("a/../b")),
+            Some(base.join("b"))
+        );
+        // The directory of a file to be created must exist.
+        assert_eq!(resolve(libc::AT_FDCWD, &base.join("x/../b")), None);
+
+        let dir = fs::File::open(base).unwrap();
+        assert_eq!(resolve(dir.as_raw_fd(), Path::new("a/./b/../c")), None);
+        assert_eq!(
+            resolve(dir.as_raw_fd(), Path::new("a/./c")),
+            Some(base.join("a/c"))
+        );
+        assert_eq!(
+            resolve(dir.as_raw_fd(), Path::new("..")),
+            base.parent().map(Path::to_path_buf)
+        );
+        assert_eq!(resolve(-1, Path::new("a")), None);
+    }
+
+    #[test]
+    fn resolve_follows_symlinks() {
+        let scratch = Scratch::new("symlinks");
+        let base = &scratch.0;
+        fs::create_dir(base.join("a")).unwrap();
+        symlink("a", base.join("link")).unwrap();
+        symlink("missing", base.join("dangling")).unwrap();
+        assert_eq!(
+            resolve(libc::AT_FDCWD, &base.join("link/app.db")),
+            Some(base.join("a/app.db"))
+        );
+        assert_eq!(
+            resolve(libc::AT_FDCWD, &base.join("link")),
+            Some(base.join("a"))
+        );
+        assert_eq!(resolve(libc::AT_FDCWD, &base.join("dangling")), None);
+    }
+
+    #[test]
+    fn check_path_denies_traversal() {
+        let scratch = Scratch::new("traversal");
+        let data = scratch.0.join("data");
+        fs::create_dir_all(data.join("logs")).unwrap();
+
+        let _installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+        let events = install(OcallPolicy::new().allow_paths([&data]));
+
+        let allowed = c_string(&data.join("logs/../app.db"));
+        let escaped = c_string(&data.join("logs/../../outside.db"));
+        let relative = CString::new("app.db").unwrap();
+        assert!(check_path("u_open_ocall", libc::AT_FDCWD, allowed.as_ptr()));
+        assert!(!check_path(
+            "u_open_ocall",
+            libc::AT_FDCWD,
+            escaped.as_ptr()
+        ));
+        // The directory of a bad dirfd cannot be found.
+        assert!(!check_path("u_openat_ocall", -1, relative.as_ptr()));
+        OcallPolicy::uninstall();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (
+                    "u_open_ocall".to_owned(),
+                    format!("Path({:?})", data.join("app.db")),
+                    true
+                ),
+                (
+                    "u_open_ocall".to_owned(),
+                    format!("Path({:?})", scratch.0.join("outside.db")),
+                    false
+                ),
+                (
+                    "u_openat_ocall".to_owned(),
+                    r#"Unresolved("app.db")"#.to_owned(),
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_path_resolves_a_link_to_the_current_dir() {
+        let scratch = Scratch::new("dot_link");
+        let data = scratch.0.join("data");
+        fs::create_dir(&data).unwrap();
+
+        let _installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+        install(OcallPolicy::new().allow_paths([&data]));
+
+        // The link itself points under the allowed directory.
+        let dot = CString::new(".").unwrap();
+        let parent = CString::new("..").unwrap();
+        let link = c_string(&data.join("d"));
+        assert!(check_symlink(
+            "u_symlink_ocall",
+            dot.as_ptr(),
+            link.as_ptr()
+        ));
+        assert!(!check_symlink(
+            "u_symlink_ocall",
+            parent.as_ptr(),
+            link.as_ptr()
+        ));
+        symlink(".", data.join("d")).unwrap();
+
+        // Lexically d/d/d/../../.. is the allowed directory, but on the host it climbs
+        // out of it.
+        let depth = data.components().count();
+        let mut path = data.clone();
+        for _ in 0..depth {
+            path.push("d");
+        }
+        for _ in 0..depth {
+            path.push("..");
+        }
+        path.push("etc/passwd");
+        assert!(normalize(&path).starts_with(&data));
+        let path = c_string(&path);
+        assert!(!check_path("u_open_ocall", libc::AT_FDCWD, path.as_ptr()));
+
+        let inside = c_string(&data.join("d/d/app.db"));
+        assert!(check_path("u_open_ocall", libc::AT_FDCWD, inside.as_ptr()));
+        OcallPolicy::uninstall();
+    }
+
+    #[test]
+    fn check_addr_handles_each_family() {
+        let _installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+        let scratch = Scratch::new("addr");
+        let sock = scratch.0.join("app.sock");
+        let cwd = env::current_dir().unwrap();
+        let mut policy = OcallPolicy::new();
+        policy
+            .allow_socket_addrs(&[
+                "10.0.0.2:443".parse().unwrap(),
+                "[2001:db8::1]:0".parse().unwrap(),
+            ])
+            .allow_paths([&sock]);
+        let events = install(&mut policy);
+
+        let mut v4: sockaddr_in = unsafe { mem::zeroed() };
+        v4.sin_family = libc::AF_INET as sa_family_t;
+        v4.sin_addr.s_addr = u32::from(Ipv4Addr::new(10, 0, 0, 2)).to_be();
+        v4.sin_port = 443_u16.to_be();
+        let (addr, len) = sockaddr_of(&v4);
+        assert!(check_addr("u_connect_ocall", addr, len));
+        v4.sin_port = 80_u16.to_be();
+        let (addr, len) = sockaddr_of(&v4);
+        assert!(!check_addr("u_connect_ocall", addr, len));
+        // A truncated address is not read.
+        assert!(!check_addr("u_connect_ocall", addr, len - 1));
+
+        let mut v6: sockaddr_in6 = unsafe { mem::zeroed() };
+        v6.sin6_family = libc::AF_INET6 as sa_family_t;
+        v6.sin6_addr.s6_addr = "2001:db8::1".parse::<Ipv6Addr>().unwrap().octets();
+        v6.sin6_port = 8443_u16.to_be();
+        let (addr, len) = sockaddr_of(&v6);
+        assert!(check_addr("u_sendto_ocall", addr, len));
+        v6.sin6_addr.s6_addr = Ipv6Addr::LOCALHOST.octets();
+        let (addr, len) = sockaddr_of(&v6);
+        assert!(!check_addr("u_sendto_ocall", addr, len));
+
+        let unix = unix_addr(sock.as_os_str().as_bytes());
+        let (addr, len) = sockaddr_of(&unix);
+        assert!(check_addr("u_bind_ocall", addr, len));
+        let unix = unix_addr(b"app.sock");
+        let (addr, len) = sockaddr_of(&unix);
+        assert!(!check_addr("u_bind_ocall", addr, len));
+        let unix = unix_addr(b"\0abstract");
+        let (addr, len) = sockaddr_of(&unix);
+        assert!(!check_addr("u_bind_ocall", addr, len));
+        OcallPolicy::uninstall();
+
+        let unix = libc::AF_UNIX as sa_family_t;
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (
+                    "u_connect_ocall".to_owned(),
+                    "Addr(10.0.0.2:443)".to_owned(),
+                    true
+                ),
+                (
+                    "u_connect_ocall".to_owned(),
+                    "Addr(10.0.0.2:80)".to_owned(),
+                    false
+                ),
+                (
+                    "u_connect_ocall".to_owned(),
+                    format!("OtherAddr({})", libc::AF_INET),
+                    false
+                ),
+                (
+                    "u_sendto_ocall".to_owned(),
+                    "Addr([2001:db8::1]:8443)".to_owned(),
+                    true
+                ),
+                (
+                    "u_sendto_ocall".to_owned(),
+                    "Addr([::1]:8443)".to_owned(),
+                    false
+                ),
+                ("u_bind_ocall".to_owned(), format!("Path({:?})", sock), true),
+                (
+                    "u_bind_ocall".to_owned(),
+                    format!("Path({:?})", cwd.join("app.sock")),
+                    false
+                ),
+                (
+                    "u_bind_ocall".to_owned(),
+                    format!("OtherAddr({})", unix),
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_host_and_env() {
+        let _installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+        let events = install(
+            OcallPolicy::new()
+                .allow_hosts(["example.com"])
+                .allow_env_keys(["RUST_LOG"]),
+        );
+
+        let host = CString::new("EXAMPLE.com").unwrap();
+        let other = CString::new("leak.example.net").unwrap();
+        let key = CString::new("RUST_LOG").unwrap();
+        let other_key = CString::new("HOME").unwrap();
+        assert!(check_host("u_getaddrinfo_ocall", host.as_ptr()));
+        assert!(!check_host("u_getaddrinfo_ocall", other.as_ptr()));
+        assert!(check_host("u_getaddrinfo_ocall", ptr::null()));
+        assert!(check_env("u_getenv_ocall", key.as_ptr()));
+        assert!(!check_env("u_getenv_ocall", other_key.as_ptr()));
+        assert!(!check_environ("u_environ_ocall"));
+        OcallPolicy::uninstall();
+        assert!(check_environ("u_environ_ocall"));
+
+        assert_eq!(events.lock().unwrap().len(), 5);
+        assert_eq!(
+            events.lock().unwrap()[1],
+            (
+                "u_getaddrinfo_ocall".to_owned(),
+                r#"Host("leak.example.net")"#.to_owned(),
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn audit_records_unchecked_ocalls() {
+        let _installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+        audit_fd("u_read_ocall", 3);
+        let events = install(OcallPolicy::new().allow_paths(["/srv/data"]));
+        audit_fd("u_read_ocall", 3);
+        audit_other("u_malloc_ocall");
+        OcallPolicy::uninstall();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ("u_read_ocall".to_owned(), "Fd(3)".to_owned(), true),
+                ("u_malloc_ocall".to_owned(), "Other".to_owned(), true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,448 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use sgx_types::*;
+use std::ffi::{CStr, CString};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::sync::RwLock;
+
+/// A logger for the messages of the quote library and the quote provider library.
+///
+/// The quote provider library has a single logging callback for the whole process, so
+/// there is a single logger too: it is shared by every enclave and QuoteGenerator of the
+/// process. A function pointer carries no state; a logger that needs state keeps it in
+/// a static.
+pub type QuoteLogger = fn(level: sgx_ql_log_level_t, message: &str);
+
+static QUOTE_LOGGER: RwLock<Option<QuoteLogger>> = RwLock::new(None);
+
+extern "C" fn quote_logging_callback(level: sgx_ql_log_level_t, message: *const c_char) {
+    if message.is_null() {
+        return;
+    }
+    let logger = match QUOTE_LOGGER.read() {
+        Ok(logger) => *logger,
+        Err(_) => return,
+    };
+    if let Some(logger) = logger {
+        let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+        logger(level, message.trim_end());
+    }
+}
+
+///
+/// Sets whether the Quoting Enclave and the PCE are kept loaded between quotes.
+///
+/// # Parameters
+///
+/// **policy**
+///
+/// SGX_QL_PERSISTENT loads the enclaves on first use and keeps them until the process ends.
+/// SGX_QL_EPHEMERAL loads and unloads them for every quote.
+///
+/// # Requirements
+///
+/// Header: sgx_dcap_ql_wrapper.h
+///
+/// Library: libsgx_dcap_ql.so
+///
+/// # Errors
+///
+/// **SGX_QL_UNSUPPORTED_LOADING_POLICY**
+///
+/// The policy is not supported.
+///
+/// **SGX_QL_UNSUPPORTED_MODE**
+///
+/// The quote library runs out of process, where the policy cannot be set.
+///
+pub fn rsgx_qe_set_enclave_load_policy(policy: sgx_ql_request_policy_t) -> SgxQuote3Result<()> {
+    let ret = unsafe { sgx_qe_set_enclave_load_policy(policy) };
+    match ret {
+        sgx_quote3_error_t::SGX_QL_SUCCESS => Ok(()),
+        _ => Err(ret),
+    }
+}
+
+///
+/// Gets the target info of the Quoting Enclave, which an application enclave uses to
+/// create a report for the Quoting Enclave.
+///
+/// # Requirements
+///
+/// Header: sgx_dcap_ql_wrapper.h
+///
+/// Library: libsgx_dcap_ql.so
+///
+/// # Return value
+///
+/// The target info of the Quoting Enclave.
+///
+/// # Errors
+///
+/// **SGX_QL_OUT_OF_EPC**, **SGX_QL_ENCLAVE_LOAD_ERROR**, **SGX_QL_ENCLAVE_LOST**
+///
+/// The Quoting Enclave could not be loaded, or it was lost.
+///
+/// **SGX_QL_ATT_KEY_NOT_INITIALIZED**, **SGX_QL_ATT_KEY_CERT_DATA_INVALID**
+///
+/// The attestation key could not be generated, or the PCK certificate data for it could
+/// not be retrieved.
+///
+/// **SGX_QL_NO_PLATFORM_CERT_DATA**
+///
+/// The quote provider library has no PCK certificate for the platform.
+///
+/// **SGX_QL_ERROR_UNEXPECTED**
+///
+/// An unexpected internal error occurred.
+///
+pub fn rsgx_qe_get_target_info() -> SgxQuote3Result<sgx_target_info_t> {
+    let mut target_info = sgx_target_info_t::default();
+    let ret = unsafe { sgx_qe_get_target_info(&mut target_info as *mut sgx_target_info_t) };
+    match ret {
+        sgx_quote3_error_t::SGX_QL_SUCCESS => Ok(target_info),
+        _ => Err(ret),
+    }
+}
+
+///
+/// Gets the size of the buffer for the quote of the platform.
+///
+/// # Requirements
+///
+/// Header: sgx_dcap_ql_wrapper.h
+///
+/// Library: libsgx_dcap_ql.so
+///
+/// # Return value
+///
+/// The size of the quote, in bytes.
+///
+/// # Errors
+///
+/// **SGX_QL_ATT_KEY_NOT_INITIALIZED**
+///
+/// The attestation key is not generated yet. Call rsgx_qe_get_target_info first.
+///
+/// **SGX_QL_ATT_KEY_CERT_DATA_INVALID**, **SGX_QL_NO_PLATFORM_CERT_DATA**
+///
+/// The certification data of the attestation key could not be retrieved.
+///
+/// **SGX_QL_ERROR_UNEXPECTED**
+///
+/// An unexpected internal error occurred.
+///
+pub fn rsgx_qe_get_quote_size() -> SgxQuote3Result<u32> {
+    let mut quote_size: u32 = 0;
+    let ret = unsafe { sgx_qe_get_quote_size(&mut quote_size as *mut uint32_t) };
+    match ret {
+        sgx_quote3_error_t::SGX_QL_SUCCESS => Ok(quote_size),
+        _ => Err(ret),
+    }
+}
+
+///
+/// Gets the quote for a report of an application enclave.
+///
+/// # Parameters
+///
+/// **report**
+///
+/// The report of the application enclave, targeted at the Quoting Enclave.
+///
+/// # Requirements
+///
+/// Header: sgx_dcap_ql_wrapper.h
+///
+/// Library: libsgx_dcap_ql.so
+///
+/// # Return value
+///
+/// The quote, in a buffer of the size returned by rsgx_qe_get_quote_size.
+///
+/// # Errors
+///
+/// **SGX_QL_ERROR_INVALID_PARAMETER**
+///
+/// The size of the quote is invalid.
+///
+/// **SGX_QL_INVALID_REPORT**
+///
+/// The report is not targeted at the Quoting Enclave, or it is invalid.
+///
+/// **SGX_QL_ATT_KEY_NOT_INITIALIZED**, **SGX_QL_ATT_KEY_CERT_DATA_INVALID**
+///
+/// The attestation key is not generated, or its certification data is invalid.
+///
+/// **SGX_QL_OUT_OF_EPC**, **SGX_QL_ENCLAVE_LOAD_ERROR**, **SGX_QL_ENCLAVE_LOST**
+///
+/// The Quoting Enclave could not be loaded, or it was lost.
+///
+/// **SGX_QL_ERROR_UNEXPECTED**
+///
+/// An unexpected internal error occurred.
+///
+pub fn rsgx_qe_get_quote(report: &sgx_report_t) -> SgxQuote3Result<Vec<u8>> {
+    let quote_size = rsgx_qe_get_quote_size()?;
+    let mut quote = vec![0_u8; quote_size as usize];
+    let ret = unsafe {
+        sgx_qe_get_quote(
+            report as *const sgx_report_t,
+            quote_size,
+            quote.as_mut_ptr() as *mut uint8_t,
+        )
+    };
+    match ret {
+        sgx_quote3_error_t::SGX_QL_SUCCESS => Ok(quote),
+        _ => Err(ret),
+    }
+}
+
+///
+/// Unloads the Quoting Enclave and the PCE when the load policy is SGX_QL_PERSISTENT.
+///
+/// # Requirements
+///
+/// Header: sgx_dcap_ql_wrapper.h
+///
+/// Library: libsgx_dcap_ql.so
+///
+/// # Errors
+///
+/// **SGX_QL_ERROR_UNEXPECTED**
+///
+/// An unexpected internal error occurred.
+///
+pub fn rsgx_qe_cleanup_by_policy() -> SgxQuote3Result<()> {
+    let ret = unsafe { sgx_qe_cleanup_by_policy() };
+    match ret {
+        sgx_quote3_error_t::SGX_QL_SUCCESS => Ok(()),
+        _ => Err(ret),
+    }
+}
+
+///
+/// Sets the path of the Quoting Enclave, the PCE, the quote provider library or the ID
+/// enclave, instead of searching the default library paths.
+///
+/// # Parameters
+///
+/// **path_type**
+///
+/// The component whose path is set.
+///
+/// **path**
+///
+/// The full path of the component.
+///
+/// # Requirements
+///
+/// Header: sgx_dcap_ql_wrapper.h
+///
+/// Library: libsgx_dcap_ql.so
+///
+/// # Errors
+///
+/// **SGX_QL_ERROR_INVALID_PARAMETER**
+///
+/// The path contains a NUL byte, is too long, or the path type is invalid.
+///
+/// **SGX_QL_UNSUPPORTED_MODE**
+///
+/// The quote library runs out of process, where the paths cannot be set.
+///
+pub fn rsgx_ql_set_path<P: AsRef<Path>>(
+    path_type: sgx_ql_path_type_t,
+    path: P,
+) -> SgxQuote3Result<()> {
+    let path = CString::new(path.as_ref().as_os_str().as_bytes())
+        .map_err(|_| sgx_quote3_error_t::SGX_QL_ERROR_INVALID_PARAMETER)?;
+    let ret = unsafe { sgx_ql_set_path(path_type, path.as_ptr()) };
+    match ret {
+        sgx_quote3_error_t::SGX_QL_SUCCESS => Ok(()),
+        _ => Err(ret),
+    }
+}
+
+///
+/// Sets the logger for the messages of the quote provider library.
+///
+/// # Parameters
+///
+/// **logger**
+///
+/// The logger, which receives every message of the given level or more severe. The
+/// trailing newline of a message is trimmed.
+///
+/// The logger is process-wide: a later call, from any thread or through any
+/// QuoteGenerator, replaces it for the whole process.
+///
+/// **level**
+///
+/// The least severe level that is logged.
+///
+/// # Requirements
+///
+/// Header: sgx_default_quote_provider.h
+///
+/// Library: libdcap_quoteprov.so
+///
+/// # Errors
+///
+/// **SGX_QL_ERROR_UNEXPECTED**
+///
+/// The quote provider library does not support logging callbacks.
+///
+pub fn rsgx_ql_set_logging_callback(
+    logger: QuoteLogger,
+    level: sgx_ql_log_level_t,
+) -> SgxQuote3Result<()> {
+    if let Ok(mut current) = QUOTE_LOGGER.write() {
+        *current = Some(logger);
+    }
+    let ret = unsafe { sgx_ql_set_logging_callback(quote_logging_callback, level) };
+    match ret {
+        sgx_quote3_error_t::SGX_QL_SUCCESS => Ok(()),
+        _ => Err(ret),
+    }
+}
+
+///
+/// Generates DCAP quotes for the reports of application enclaves.
+///
+/// The settings are applied to the quote library when the generator is built, and stay
+/// in effect for the whole process.
+///
+/// # Examples
+///
+/// ```no_run
+/// use sgx_types::*;
+/// use sgx_urts::QuoteGenerator;
+///
+/// fn log(level: sgx_ql_log_level_t, message: &str) {
+///     println!("[{:?}] {}", level, message);
+/// }
+///
+/// let generator = QuoteGenerator::new()
+///     .load_policy(sgx_ql_request_policy_t::SGX_QL_PERSISTENT)
+///     .path(sgx_ql_path_type_t::SGX_QL_QPL_PATH, "/usr/lib/x86_64-linux-gnu/libdcap_quoteprov.so.1")
+///     .logging_callback(log, sgx_ql_log_level_t::SGX_QL_LOG_ERROR)
+///     .build()?;
+///
+/// let qe_target_info = generator.target_info()?;
+/// // Create a report for qe_target_info in the enclave, then:
+/// # let report = sgx_report_t::default();
+/// let quote = generator.quote_for(&report)?;
+/// # Ok::<(), sgx_quote3_error_t>(())
+/// ```
+///
+#[derive(Clone, Default)]
+pub struct QuoteGenerator {
+    load_policy: Option<sgx_ql_request_policy_t>,
+    paths: Vec<(sgx_ql_path_type_t, CString)>,
+    logger: Option<(QuoteLogger, sgx_ql_log_level_t)>,
+    invalid_path: bool,
+}
+
+impl QuoteGenerator {
+    pub fn new() -> QuoteGenerator {
+        QuoteGenerator::default()
+    }
+
+    /// Sets whether the Quoting Enclave and the PCE are kept loaded between quotes.
+    pub fn load_policy(&mut self, policy: sgx_ql_request_policy_t) -> &mut Self {
+        self.load_policy = Some(policy);
+        self
+    }
+
+    /// Sets the path of the Quoting Enclave, the PCE, the quote provider library or the
+    /// ID enclave.
+    pub fn path<P: AsRef<Path>>(&mut self, path_type: sgx_ql_path_type_t, path: P) -> &mut Self {
+        match CString::new(path.as_ref().as_os_str().as_bytes()) {
+            Ok(path) => self.paths.push((path_type, path)),
+            Err(_) => self.invalid_path = true,
+        }
+        self
+    }
+
+    /// Sets the logger for the messages of the quote provider library. The logger is
+    /// process-wide, see rsgx_ql_set_logging_callback.
+    pub fn logging_callback(
+        &mut self,
+        logger: QuoteLogger,
+        level: sgx_ql_log_level_t,
+    ) -> &mut Self {
+        self.logger = Some((logger, level));
+        self
+    }
+
+    ///
+    /// Applies the settings to the quote library.
+    ///
+    /// The paths are set before the logging callback, so that the callback is set on the
+    /// configured quote provider library.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_QL_ERROR_INVALID_PARAMETER**
+    ///
+    /// A path contains a NUL byte or is invalid.
+    ///
+    /// The errors of rsgx_qe_set_enclave_load_policy, rsgx_ql_set_path and
+    /// rsgx_ql_set_logging_callback.
+    ///
+    pub fn build(&self) -> SgxQuote3Result<QuoteGenerator> {
+        if self.invalid_path {
+            return Err(sgx_quote3_error_t::SGX_QL_ERROR_INVALID_PARAMETER);
+        }
+        if let Some(policy) = self.load_policy {
+            rsgx_qe_set_enclave_load_policy(policy)?;
+        }
+        for (path_type, path) in self.paths.iter() {
+            let ret = unsafe { sgx_ql_set_path(*path_type, path.as_ptr()) };
+            if ret != sgx_quote3_error_t::SGX_QL_SUCCESS {
+                return Err(ret);
+            }
+        }
+        if let Some((logger, level)) = self.logger {
+            rsgx_ql_set_logging_callback(logger, level)?;
+        }
+        Ok(self.clone())
+    }
+
+    /// Gets the target info of the Quoting Enclave. See rsgx_qe_get_target_info.
+    pub fn target_info(&self) -> SgxQuote3Result<sgx_target_info_t> {
+        rsgx_qe_get_target_info()
+    }
+
+    /// Gets the size of the quote of the platform. See rsgx_qe_get_quote_size.
+    pub fn quote_size(&self) -> SgxQuote3Result<u32> {
+        rsgx_qe_get_quote_size()
+    }
+
+    /// Gets the quote for a report targeted at the Quoting Enclave. See rsgx_qe_get_quote.
+    pub fn quote_for(&self, report: &sgx_report_t) -> SgxQuote3Result<Vec<u8>> {
+        rsgx_qe_get_quote(report)
+    }
+
+    /// Unloads the Quoting Enclave and the PCE. See rsgx_qe_cleanup_by_policy.
+    pub fn cleanup(&self) -> SgxQuote3Result<()> {
+        rsgx_qe_cleanup_by_policy()
+    }
+}
@@ -0,0 +1,214 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use sgx_types::*;
+use std::mem;
+use std::ptr;
+use std::slice;
+
+///
+/// Gets the extended EPID group ID of the platform, which the application sends to the
+/// service provider as message 0 of the remote attestation.
+///
+/// # Requirements
+///
+/// Header: sgx_uae_epid.h
+///
+/// Library: libsgx_epid.so
+///
+/// # Return value
+///
+/// The extended EPID group ID.
+///
+/// # Errors
+///
+/// **SGX_ERROR_SERVICE_UNAVAILABLE**
+///
+/// The AE service did not respond or the requested service is not supported.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// Indicates that an unexpected error occurred.
+///
+pub fn rsgx_get_extended_epid_group_id() -> SgxResult<u32> {
+    let mut extended_epid_group_id: u32 = 0;
+    let ret = unsafe { sgx_get_extended_epid_group_id(&mut extended_epid_group_id as *mut u32) };
+    match ret {
+        sgx_status_t::SGX_SUCCESS => Ok(extended_epid_group_id),
+        _ => Err(ret),
+    }
+}
+
+///
+/// Creates message 1 of the remote attestation, which carries the public key of the enclave
+/// for the key exchange.
+///
+/// # Parameters
+///
+/// **context**
+///
+/// The context returned by rsgx_ra_init in the enclave, for example by RaSession::context.
+///
+/// **eid**
+///
+/// The ID of the enclave.
+///
+/// **p_get_ga**
+///
+/// The untrusted proxy of sgx_ra_get_ga, generated from sgx_tkey_exchange.edl.
+///
+/// # Requirements
+///
+/// Header: sgx_ukey_exchange.h
+///
+/// Library: libsgx_ukey_exchange.a
+///
+/// # Return value
+///
+/// Message 1.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The context is invalid.
+///
+/// **SGX_ERROR_AE_SESSION_INVALID**, **SGX_ERROR_SERVICE_UNAVAILABLE**
+///
+/// The AE service did not respond, or the session is invalid.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// Indicates that an unexpected error occurred.
+///
+pub fn rsgx_ra_get_msg1(
+    context: sgx_ra_context_t,
+    eid: sgx_enclave_id_t,
+    p_get_ga: sgx_ecall_get_ga_trusted_t,
+) -> SgxResult<sgx_ra_msg1_t> {
+    let mut msg1 = sgx_ra_msg1_t::default();
+    let ret = unsafe { sgx_ra_get_msg1(context, eid, p_get_ga, &mut msg1 as *mut sgx_ra_msg1_t) };
+    match ret {
+        sgx_status_t::SGX_SUCCESS => Ok(msg1),
+        _ => Err(ret),
+    }
+}
+
+///
+/// Processes message 2 of the service provider, and creates message 3 with a quote of the
+/// enclave that binds the keys of the session.
+///
+/// # Parameters
+///
+/// **context**
+///
+/// The context returned by rsgx_ra_init in the enclave.
+///
+/// **eid**
+///
+/// The ID of the enclave.
+///
+/// **p_proc_msg2**, **p_get_msg3**
+///
+/// The untrusted proxies of sgx_ra_proc_msg2_trusted and sgx_ra_get_msg3_trusted, generated
+/// from sgx_tkey_exchange.edl.
+///
+/// **msg2**
+///
+/// Message 2, as sent by the service provider: a sgx_ra_msg2_t followed by the signature
+/// revocation list.
+///
+/// # Requirements
+///
+/// Header: sgx_ukey_exchange.h
+///
+/// Library: libsgx_ukey_exchange.a
+///
+/// # Return value
+///
+/// Message 3: a sgx_ra_msg3_t followed by the quote.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// Message 2 is shorter than a sgx_ra_msg2_t, or its signature revocation list size is
+/// inconsistent, or the context is invalid.
+///
+/// **SGX_ERROR_INVALID_SIGNATURE**
+///
+/// Message 2 is not signed by the service provider the session was created for.
+///
+/// **SGX_ERROR_MAC_MISMATCH**
+///
+/// The MAC of message 2 is invalid.
+///
+/// **SGX_ERROR_OUT_OF_MEMORY**
+///
+/// Not enough memory is available to complete this operation.
+///
+/// **SGX_ERROR_AE_SESSION_INVALID**, **SGX_ERROR_SERVICE_UNAVAILABLE**
+///
+/// The AE service did not respond, or the session is invalid.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// Indicates that an unexpected error occurred.
+///
+pub fn rsgx_ra_proc_msg2(
+    context: sgx_ra_context_t,
+    eid: sgx_enclave_id_t,
+    p_proc_msg2: sgx_ecall_proc_msg2_trusted_t,
+    p_get_msg3: sgx_ecall_get_msg3_trusted_t,
+    msg2: &[u8],
+) -> SgxResult<Vec<u8>> {
+    if msg2.len() < mem::size_of::<sgx_ra_msg2_t>() || msg2.len() > u32::MAX as usize {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+
+    // Copy the message into an allocation aligned for sgx_ra_msg2_t.
+    let mut aligned = vec![0_u32; msg2.len() / 4 + 1];
+    unsafe {
+        ptr::copy_nonoverlapping(msg2.as_ptr(), aligned.as_mut_ptr() as *mut u8, msg2.len());
+    }
+
+    let mut p_msg3: *mut sgx_ra_msg3_t = ptr::null_mut();
+    let mut msg3_size: u32 = 0;
+    let ret = unsafe {
+        sgx_ra_proc_msg2(
+            context,
+            eid,
+            p_proc_msg2,
+            p_get_msg3,
+            aligned.as_ptr() as *const sgx_ra_msg2_t,
+            msg2.len() as u32,
+            &mut p_msg3 as *mut *mut sgx_ra_msg3_t,
+            &mut msg3_size as *mut u32,
+        )
+    };
+    if ret != sgx_status_t::SGX_SUCCESS {
+        return Err(ret);
+    }
+    if p_msg3.is_null() {
+        return Err(sgx_status_t::SGX_ERROR_UNEXPECTED);
+    }
+
+    // The library allocates message 3 with malloc.
+    let msg3 = unsafe { slice::from_raw_parts(p_msg3 as *const u8, msg3_size as usize) }.to_vec();
+    unsafe { libc::free(p_msg3 as *mut libc::c_void) };
+    Ok(msg3)
+}
@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use crate::policy;
 use libc::{self, c_int, c_void, iovec, msghdr, size_t, sockaddr, socklen_t, ssize_t};
 use std::io::Error;
 
@@ -25,6 +26,7 @@ pub extern "C" fn u_socket_ocall(
     ty: c_int,
     protocol: c_int,
 ) -> c_int {
+    policy::audit_other("u_socket_ocall");
     let mut errno = 0;
     let ret = unsafe { libc::socket(domain, ty, protocol) };
     if ret < 0 {
@@ -46,6 +48,7 @@ pub extern "C" fn u_socketpair_ocall(
     protocol: c_int,
     sv: *mut c_int,
 ) -> c_int {
+    policy::audit_other("u_socketpair_ocall");
     let mut errno = 0;
     let ret = unsafe { libc::socketpair(domain, ty, protocol, sv) };
     if ret < 0 {
@@ -66,6 +69,9 @@ pub extern "C" fn u_bind_ocall(
     address: *const sockaddr,
     addrlen: socklen_t,
 ) -> c_int {
+    if !policy::check_addr("u_bind_ocall", address, addrlen) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::bind(sockfd, address, addrlen) };
     if ret < 0 {
@@ -81,6 +87,7 @@ pub extern "C" fn u_bind_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_listen_ocall(error: *mut c_int, sockfd: c_int, backlog: c_int) -> c_int {
+    policy::audit_fd("u_listen_ocall", sockfd);
     let mut errno = 0;
     let ret = unsafe { libc::listen(sockfd, backlog) };
     if ret < 0 {
@@ -102,6 +109,7 @@ pub extern "C" fn u_accept_ocall(
     addrlen_in: socklen_t,
     addrlen_out: *mut socklen_t,
 ) -> c_int {
+    policy::audit_fd("u_accept_ocall", sockfd);
     let mut errno = 0;
     unsafe { *addrlen_out = addrlen_in };
     let ret = unsafe { libc::accept(sockfd, addr, addrlen_out) };
@@ -125,6 +133,7 @@ pub extern "C" fn u_accept4_ocall(
     addrlen_out: *mut socklen_t,
     flags: c_int,
 ) -> c_int {
+    policy::audit_fd("u_accept4_ocall", sockfd);
     let mut errno = 0;
     unsafe { *addrlen_out = addrlen_in };
     let ret = unsafe { libc::accept4(sockfd, addr, addrlen_out, flags) };
@@ -146,6 +155,9 @@ pub extern "C" fn u_connect_ocall(
     address: *const sockaddr,
     addrlen: socklen_t,
 ) -> c_int {
+    if !policy::check_addr("u_connect_ocall", address, addrlen) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::connect(sockfd, address, addrlen) };
     if ret < 0 {
@@ -167,6 +179,7 @@ pub extern "C" fn u_recv_ocall(
     len: size_t,
     flags: c_int,
 ) -> ssize_t {
+    policy::audit_fd("u_recv_ocall", sockfd);
     let mut errno = 0;
     let ret = unsafe { libc::recv(sockfd, buf, len, flags) };
     if ret < 0 {
@@ -191,6 +204,7 @@ pub extern "C" fn u_recvfrom_ocall(
     addrlen_in: socklen_t,
     addrlen_out: *mut socklen_t,
 ) -> ssize_t {
+    policy::audit_fd("u_recvfrom_ocall", sockfd);
     let mut errno = 0;
     unsafe { *addrlen_out = addrlen_in };
     let ret = unsafe { libc::recvfrom(sockfd, buf, len, flags, src_addr, addrlen_out) };
@@ -220,6 +234,7 @@ pub extern "C" fn u_recvmsg_ocall(
     msg_flags: *mut c_int,
     flags: c_int,
 ) -> ssize_t {
+    policy::audit_fd("u_recvmsg_ocall", sockfd);
     if msg_namelen_out.is_null() || msg_controllen_out.is_null() || msg_flags.is_null() {
         if !error.is_null() {
             unsafe {
@@ -266,6 +281,7 @@ pub extern "C" fn u_send_ocall(
     len: size_t,
     flags: c_int,
 ) -> ssize_t {
+    policy::audit_fd("u_send_ocall", sockfd);
     let mut errno = 0;
     let ret = unsafe { libc::send(sockfd, buf, len, flags) };
     if ret < 0 {
@@ -289,6 +305,9 @@ pub extern "C" fn u_sendto_ocall(
     dest_addr: *const sockaddr,
     addrlen: socklen_t,
 ) -> ssize_t {
+    if !policy::check_addr("u_sendto_ocall", dest_addr, addrlen) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let ret = unsafe { libc::sendto(sockfd, buf, len, flags, dest_addr, addrlen) };
     if ret < 0 {
@@ -314,6 +333,9 @@ pub extern "C" fn u_sendmsg_ocall(
     msg_controllen: usize,
     flags: c_int,
 ) -> ssize_t {
+    if !policy::check_addr("u_sendmsg_ocall", msg_name as *const sockaddr, msg_namelen) {
+        return policy::deny(error, -1);
+    }
     let mut errno = 0;
     let msg = msghdr {
         msg_name,
@@ -346,6 +368,7 @@ pub extern "C" fn u_getsockopt_ocall(
     optlen_in: socklen_t,
     optlen_out: *mut socklen_t,
 ) -> c_int {
+    policy::audit_fd("u_getsockopt_ocall", sockfd);
     let mut errno = 0;
     unsafe { *optlen_out = optlen_in };
     let ret = unsafe { libc::getsockopt(sockfd, level, optname, optval, optlen_out) };
@@ -369,6 +392,7 @@ pub extern "C" fn u_setsockopt_ocall(
     optval: *const c_void,
     optlen: socklen_t,
 ) -> c_int {
+    policy::audit_fd("u_setsockopt_ocall", sockfd);
     let mut errno = 0;
     let ret = unsafe { libc::setsockopt(sockfd, level, optname, optval, optlen) };
     if ret < 0 {
@@ -390,6 +414,7 @@ pub extern "C" fn u_getsockname_ocall(
     addrlen_in: socklen_t,
     addrlen_out: *mut socklen_t,
 ) -> c_int {
+    policy::audit_fd("u_getsockname_ocall", sockfd);
     let mut errno = 0;
     unsafe { *addrlen_out = addrlen_in };
     let ret = unsafe { libc::getsockname(sockfd, address, addrlen_out) };
@@ -412,6 +437,7 @@ pub extern "C" fn u_getpeername_ocall(
     addrlen_in: socklen_t,
     addrlen_out: *mut socklen_t,
 ) -> c_int {
+    policy::audit_fd("u_getpeername_ocall", sockfd);
     let mut errno = 0;
     unsafe { *addrlen_out = addrlen_in };
     let ret = unsafe { libc::getpeername(sockfd, address, addrlen_out) };
@@ -428,6 +454,7 @@ pub extern "C" fn u_getpeername_ocall(
 
 #[no_mangle]
 pub extern "C" fn u_shutdown_ocall(error: *mut c_int, sockfd: c_int, how: c_int) -> c_int {
+    policy::audit_fd("u_shutdown_ocall", sockfd);
     let mut errno = 0;
     let ret = unsafe { libc::shutdown(sockfd, how) };
     if ret < 0 {
@@ -0,0 +1,481 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use sgx_types::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+
+type WorkerCallback =
+    Arc<dyn Fn(sgx_uswitchless_worker_type_t, &sgx_uswitchless_worker_stats_t) + Send + Sync>;
+
+/// The number of enclaves created with a SwitchlessConfig that may exist at the same time.
+pub const SWITCHLESS_MAX_ENCLAVES: usize = 8;
+
+// The uRTS passes no context to the worker callbacks, so every enclave gets a slot, with a
+// callback of its own that finds the closures and the counters of the enclave in the slot.
+const NO_SLOT: Option<Arc<SwitchlessState>> = None;
+static SLOTS: Mutex<[Option<Arc<SwitchlessState>>; SWITCHLESS_MAX_ENCLAVES]> =
+    Mutex::new([NO_SLOT; SWITCHLESS_MAX_ENCLAVES]);
+const SLOT_CALLBACKS: [sgx_uswitchless_worker_callback_t; SWITCHLESS_MAX_ENCLAVES] = [
+    slot_worker_callback::<0>,
+    slot_worker_callback::<1>,
+    slot_worker_callback::<2>,
+    slot_worker_callback::<3>,
+    slot_worker_callback::<4>,
+    slot_worker_callback::<5>,
+    slot_worker_callback::<6>,
+    slot_worker_callback::<7>,
+];
+
+///
+/// The switchless configuration of an enclave, with the worker callbacks as closures.
+///
+/// A zero pool size or retry count selects the default of the uRTS.
+///
+/// The uRTS calls the closures from the worker threads of the enclave. A configuration may
+/// be used for several enclaves: each gets its own closures and counters, see
+/// SgxEnclave::switchless_counters.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut config = SwitchlessConfig::new();
+/// config
+///     .num_uworkers(2)
+///     .retries_before_fallback(1000)
+///     .on_worker_miss(|worker_type, stats| println!("{:?} missed {}", worker_type, stats.missed));
+///
+/// let enclave = EnclaveBuilder::new("enclave.signed.so").switchless(&config).build()?;
+/// let counters = enclave.switchless_counters().unwrap();
+/// // ... run the workload ...
+/// let stats = counters.snapshot();
+/// ```
+///
+#[derive(Clone)]
+pub struct SwitchlessConfig {
+    pool_size_qwords: u64,
+    num_uworkers: u64,
+    num_tworkers: u64,
+    retries_before_fallback: u64,
+    retries_before_sleep: u64,
+    callbacks: [Option<WorkerCallback>; SGX_USWITCHLESS_WORKER_EVENT_NUM],
+}
+
+impl Default for SwitchlessConfig {
+    fn default() -> SwitchlessConfig {
+        SwitchlessConfig {
+            pool_size_qwords: 0,
+            num_uworkers: 1,
+            num_tworkers: 1,
+            retries_before_fallback: 0,
+            retries_before_sleep: 0,
+            callbacks: [None, None, None, None],
+        }
+    }
+}
+
+impl SwitchlessConfig {
+    pub fn new() -> SwitchlessConfig {
+        SwitchlessConfig::default()
+    }
+
+    /// The number of 64-bit words of the task pool, each word holding 64 tasks.
+    pub fn pool_size_qwords(&mut self, pool_size_qwords: u32) -> &mut SwitchlessConfig {
+        self.pool_size_qwords = u64::from(pool_size_qwords);
+        self
+    }
+
+    /// The number of untrusted workers, which serve the switchless OCALLs.
+    pub fn num_uworkers(&mut self, num_uworkers: u32) -> &mut SwitchlessConfig {
+        self.num_uworkers = u64::from(num_uworkers);
+        self
+    }
+
+    /// The number of trusted workers, which serve the switchless ECALLs.
+    pub fn num_tworkers(&mut self, num_tworkers: u32) -> &mut SwitchlessConfig {
+        self.num_tworkers = u64::from(num_tworkers);
+        self
+    }
+
+    /// How many times a caller retries before falling back to a regular call.
+    pub fn retries_before_fallback(&mut self, retries: u32) -> &mut SwitchlessConfig {
+        self.retries_before_fallback = u64::from(retries);
+        self
+    }
+
+    /// How many times an idle worker retries before going to sleep.
+    pub fn retries_before_sleep(&mut self, retries: u32) -> &mut SwitchlessConfig {
+        self.retries_before_sleep = u64::from(retries);
+        self
+    }
+
+    pub fn on_worker_start<F>(&mut self, callback: F) -> &mut SwitchlessConfig
+    where
+        F: Fn(sgx_uswitchless_worker_type_t, &sgx_uswitchless_worker_stats_t)
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.callback(
+            sgx_uswitchless_worker_event_t::SGX_USWITCHLESS_WORKER_EVENT_START,
+            Arc::new(callback),
+        )
+    }
+
+    pub fn on_worker_idle<F>(&mut self, callback: F) -> &mut SwitchlessConfig
+    where
+        F: Fn(sgx_uswitchless_worker_type_t, &sgx_uswitchless_worker_stats_t)
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.callback(
+            sgx_uswitchless_worker_event_t::SGX_USWITCHLESS_WORKER_EVENT_IDLE,
+            Arc::new(callback),
+        )
+    }
+
+    pub fn on_worker_miss<F>(&mut self, callback: F) -> &mut SwitchlessConfig
+    where
+        F: Fn(sgx_uswitchless_worker_type_t, &sgx_uswitchless_worker_stats_t)
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.callback(
+            sgx_uswitchless_worker_event_t::SGX_USWITCHLESS_WORKER_EVENT_MISS,
+            Arc::new(callback),
+        )
+    }
+
+    pub fn on_worker_exit<F>(&mut self, callback: F) -> &mut SwitchlessConfig
+    where
+        F: Fn(sgx_uswitchless_worker_type_t, &sgx_uswitchless_worker_stats_t)
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.callback(
+            sgx_uswitchless_worker_event_t::SGX_USWITCHLESS_WORKER_EVENT_EXIT,
+            Arc::new(callback),
+        )
+    }
+
+    fn callback(
+        &mut self,
+        event: sgx_uswitchless_worker_event_t,
+        callback: WorkerCallback,
+    ) -> &mut SwitchlessConfig {
+        self.callbacks[event as usize] = Some(callback);
+        self
+    }
+
+    ///
+    /// Registers the closures and fresh counters in a free slot, and returns the
+    /// configuration to pass to the uRTS, which calls back into the slot. The slot is freed
+    /// when the returned SwitchlessSlot is dropped, after the enclave is destroyed.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_BUSY**
+    ///
+    /// SWITCHLESS_MAX_ENCLAVES enclaves created with a SwitchlessConfig already exist.
+    ///
+    pub(crate) fn install(&self) -> SgxResult<(sgx_uswitchless_config_t, SwitchlessSlot)> {
+        let state = Arc::new(SwitchlessState {
+            callbacks: self.callbacks.clone(),
+            untrusted: WorkerCounters::default(),
+            trusted: WorkerCounters::default(),
+        });
+        let mut slots = SLOTS.lock().unwrap_or_else(|e| e.into_inner());
+        let index = slots
+            .iter()
+            .position(Option::is_none)
+            .ok_or(sgx_status_t::SGX_ERROR_BUSY)?;
+        slots[index] = Some(state.clone());
+
+        let config = sgx_uswitchless_config_t {
+            switchless_calls_pool_size_qwords: self.pool_size_qwords,
+            num_uworkers: self.num_uworkers,
+            num_tworkers: self.num_tworkers,
+            retries_before_fallback: self.retries_before_fallback,
+            retries_before_sleep: self.retries_before_sleep,
+            callback_func: [SLOT_CALLBACKS[index]; SGX_USWITCHLESS_WORKER_EVENT_NUM],
+        };
+        Ok((config, SwitchlessSlot { index, state }))
+    }
+}
+
+/// The closures and the counters of one enclave.
+struct SwitchlessState {
+    callbacks: [Option<WorkerCallback>; SGX_USWITCHLESS_WORKER_EVENT_NUM],
+    untrusted: WorkerCounters,
+    trusted: WorkerCounters,
+}
+
+///
+/// The slot of an enclave created with a SwitchlessConfig, held by the enclave.
+///
+pub(crate) struct SwitchlessSlot {
+    index: usize,
+    state: Arc<SwitchlessState>,
+}
+
+impl SwitchlessSlot {
+    pub(crate) fn counters(&self) -> SwitchlessCounters {
+        SwitchlessCounters {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for SwitchlessSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SwitchlessSlot")
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+impl Drop for SwitchlessSlot {
+    fn drop(&mut self) {
+        SLOTS.lock().unwrap_or_else(|e| e.into_inner())[self.index] = None;
+    }
+}
+
+extern "C" fn slot_worker_callback<const SLOT: usize>(
+    worker_type: sgx_uswitchless_worker_type_t,
+    worker_event: sgx_uswitchless_worker_event_t,
+    worker_stats: *const sgx_uswitchless_worker_stats_t,
+) {
+    if worker_stats.is_null() {
+        return;
+    }
+    let stats = unsafe { &*worker_stats };
+    let state = match SLOTS.lock().unwrap_or_else(|e| e.into_inner())[SLOT].clone() {
+        Some(state) => state,
+        None => return,
+    };
+    let counters = match worker_type {
+        sgx_uswitchless_worker_type_t::SGX_USWITCHLESS_WORKER_TYPE_UNTRUSTED => &state.untrusted,
+        sgx_uswitchless_worker_type_t::SGX_USWITCHLESS_WORKER_TYPE_TRUSTED => &state.trusted,
+    };
+    counters.record(thread::current().id(), worker_event, stats);
+
+    let callback = state
+        .callbacks
+        .get(worker_event as usize)
+        .cloned()
+        .flatten();
+    if let Some(callback) = callback {
+        // Unwinding into the uRTS is undefined behavior.
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| callback(worker_type, stats)));
+    }
+}
+
+#[derive(Default)]
+struct WorkerCounters {
+    // The counters last reported by each worker, which runs the callback on its own thread.
+    workers: Mutex<HashMap<ThreadId, (u64, u64)>>,
+    miss_events: AtomicU64,
+}
+
+impl WorkerCounters {
+    fn record(
+        &self,
+        worker: ThreadId,
+        event: sgx_uswitchless_worker_event_t,
+        stats: &sgx_uswitchless_worker_stats_t,
+    ) {
+        self.workers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(worker, (stats.processed, stats.missed));
+        if event == sgx_uswitchless_worker_event_t::SGX_USWITCHLESS_WORKER_EVENT_MISS {
+            self.miss_events.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn snapshot(&self) -> SwitchlessCallStats {
+        let workers = self.workers.lock().unwrap_or_else(|e| e.into_inner());
+        SwitchlessCallStats {
+            hits: workers.values().map(|&(processed, _)| processed).sum(),
+            misses: self.miss_events.load(Ordering::Relaxed),
+            fallbacks: workers.values().map(|&(_, missed)| missed).sum(),
+        }
+    }
+}
+
+///
+/// The switchless calls of one direction, as reported by its workers.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwitchlessCallStats {
+    /// The calls processed by a worker.
+    pub hits: u64,
+    /// The times a worker found tasks missed by the workers.
+    pub misses: u64,
+    /// The calls missed by the workers, which fell back to regular calls.
+    pub fallbacks: u64,
+}
+
+impl SwitchlessCallStats {
+    /// The switchless calls made, served by a worker or not.
+    pub fn calls(&self) -> u64 {
+        self.hits + self.fallbacks
+    }
+}
+
+///
+/// A snapshot of the switchless calls of an enclave.
+///
+/// The uRTS only reports the counters to the worker callbacks, so the snapshot is as of the
+/// last worker event: a worker starting, going idle, missing tasks or exiting.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwitchlessStats {
+    /// The OCALLs, served by the untrusted workers.
+    pub ocalls: SwitchlessCallStats,
+    /// The ECALLs, served by the trusted workers.
+    pub ecalls: SwitchlessCallStats,
+}
+
+///
+/// The counters of the switchless calls of an enclave, which start from zero when the
+/// enclave is created. They remain readable after the enclave is destroyed, when the
+/// workers have reported their final counters.
+///
+#[derive(Clone)]
+pub struct SwitchlessCounters {
+    state: Arc<SwitchlessState>,
+}
+
+impl SwitchlessCounters {
+    pub fn snapshot(&self) -> SwitchlessStats {
+        SwitchlessStats {
+            ocalls: self.state.untrusted.snapshot(),
+            ecalls: self.state.trusted.snapshot(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    fn report(
+        config: &sgx_uswitchless_config_t,
+        worker_type: sgx_uswitchless_worker_type_t,
+        worker_event: sgx_uswitchless_worker_event_t,
+        processed: u64,
+        missed: u64,
+    ) {
+        let stats = sgx_uswitchless_worker_stats_t { processed, missed };
+        (config.callback_func[worker_event as usize])(worker_type, worker_event, &stats);
+    }
+
+    #[test]
+    fn counters_are_per_enclave_and_per_worker() {
+        use sgx_uswitchless_worker_event_t::*;
+        use sgx_uswitchless_worker_type_t::*;
+
+        let misses = Arc::new(AtomicUsize::new(0));
+        let mut config = SwitchlessConfig::new();
+        let observed = misses.clone();
+        config.on_worker_miss(move |_, _| {
+            observed.fetch_add(1, Ordering::Relaxed);
+        });
+        let (first, first_slot) = config.install().unwrap();
+        let (second, second_slot) = SwitchlessConfig::new().install().unwrap();
+
+        // Two untrusted workers of the first enclave, each reporting its own counters.
+        thread::scope(|s| {
+            s.spawn(|| {
+                report(
+                    &first,
+                    SGX_USWITCHLESS_WORKER_TYPE_UNTRUSTED,
+                    SGX_USWITCHLESS_WORKER_EVENT_IDLE,
+                    3,
+                    0,
+                );
+                report(
+                    &first,
+                    SGX_USWITCHLESS_WORKER_TYPE_UNTRUSTED,
+                    SGX_USWITCHLESS_WORKER_EVENT_MISS,
+                    5,
+                    1,
+                );
+            });
+            s.spawn(|| {
+                report(
+                    &first,
+                    SGX_USWITCHLESS_WORKER_TYPE_UNTRUSTED,
+                    SGX_USWITCHLESS_WORKER_EVENT_EXIT,
+                    4,
+                    2,
+                );
+            });
+        });
+        report(
+            &second,
+            SGX_USWITCHLESS_WORKER_TYPE_TRUSTED,
+            SGX_USWITCHLESS_WORKER_EVENT_EXIT,
+            7,
+            0,
+        );
+
+        let stats = first_slot.counters().snapshot();
+        assert_eq!(
+            stats.ocalls,
+            SwitchlessCallStats {
+                hits: 9,
+                misses: 1,
+                fallbacks: 3,
+            }
+        );
+        assert_eq!(stats.ecalls, SwitchlessCallStats::default());
+        assert_eq!(second_slot.counters().snapshot().ecalls.hits, 7);
+        assert_eq!(
+            second_slot.counters().snapshot().ocalls,
+            SwitchlessCallStats::default()
+        );
+        // The closures of the first enclave are not replaced by the second.
+        assert_eq!(misses.load(Ordering::Relaxed), 1);
+
+        // A new enclave reuses the freed slot, with fresh counters.
+        let counters = first_slot.counters();
+        let index = first_slot.index;
+        drop(first_slot);
+        let (third, third_slot) = SwitchlessConfig::new().install().unwrap();
+        assert_eq!(third_slot.index, index);
+        assert_eq!(third_slot.counters().snapshot(), SwitchlessStats::default());
+        report(
+            &third,
+            SGX_USWITCHLESS_WORKER_TYPE_UNTRUSTED,
+            SGX_USWITCHLESS_WORKER_EVENT_MISS,
+            1,
+            1,
+        );
+        assert_eq!(misses.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.snapshot().ocalls.hits, 9);
+    }
+}
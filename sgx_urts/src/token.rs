@@ -0,0 +1,330 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::{rsgx_get_metadata, rsgx_metadata_mrenclave};
+use sgx_types::*;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const LAUNCH_TOKEN_SIZE: usize = mem::size_of::<sgx_launch_token_t>();
+const ENTRY_SIZE: usize = SGX_HASH_SIZE + LAUNCH_TOKEN_SIZE;
+
+// Numbers the temporary files of write_atomically, which may run on several threads.
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+///
+/// A directory of launch tokens, keyed by the enclave path and the MRENCLAVE the token was
+/// issued for.
+///
+/// Each enclave has one entry, holding its MRENCLAVE followed by its launch token. The entry
+/// is rewritten atomically, through a temporary file renamed over it, whenever the uRTS
+/// updates the launch token or the enclave was rebuilt with a new MRENCLAVE.
+///
+/// When an enclave is created with the cache, the MRENCLAVE is read from the SIGSTRUCT of
+/// the enclave file, so that the token of a previous build of the enclave is not used.
+///
+/// # Examples
+///
+/// ```ignore
+/// let cache = LaunchTokenCache::new("/var/cache/my_app");
+/// let mut misc_attr = sgx_misc_attribute_t::default();
+/// let enclave =
+///     SgxEnclave::create_with_token_cache("enclave.signed.so", debug, &mut misc_attr, &cache)?;
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct LaunchTokenCache {
+    dir: PathBuf,
+}
+
+impl LaunchTokenCache {
+    pub fn new<P: AsRef<Path>>(dir: P) -> LaunchTokenCache {
+        LaunchTokenCache {
+            dir: dir.as_ref().to_owned(),
+        }
+    }
+
+    #[inline]
+    pub fn dir(&self) -> &Path {
+        self.dir.as_path()
+    }
+
+    ///
+    /// Returns the cached launch token of the enclave, with the MRENCLAVE it was issued for.
+    ///
+    /// Returns None if the enclave has no entry, or the entry cannot be read.
+    ///
+    pub fn load<P: AsRef<Path>>(
+        &self,
+        enclave_path: P,
+    ) -> Option<(sgx_measurement_t, sgx_launch_token_t)> {
+        let entry = fs::read(self.entry_path(enclave_path.as_ref())).ok()?;
+        if entry.len() != ENTRY_SIZE {
+            return None;
+        }
+        let mut mr_enclave = sgx_measurement_t::default();
+        mr_enclave.m.copy_from_slice(&entry[..SGX_HASH_SIZE]);
+        let mut launch_token: sgx_launch_token_t = [0; LAUNCH_TOKEN_SIZE];
+        launch_token.copy_from_slice(&entry[SGX_HASH_SIZE..]);
+        Some((mr_enclave, launch_token))
+    }
+
+    ///
+    /// Returns the cached launch token of the enclave, if it was issued for `mr_enclave`.
+    ///
+    pub fn get<P: AsRef<Path>>(
+        &self,
+        enclave_path: P,
+        mr_enclave: &sgx_measurement_t,
+    ) -> Option<sgx_launch_token_t> {
+        self.load(enclave_path)
+            .filter(|(cached, _)| cached.m == mr_enclave.m)
+            .map(|(_, launch_token)| launch_token)
+    }
+
+    ///
+    /// Atomically replaces the entry of the enclave, creating the cache directory if needed.
+    ///
+    pub fn store<P: AsRef<Path>>(
+        &self,
+        enclave_path: P,
+        mr_enclave: &sgx_measurement_t,
+        launch_token: &sgx_launch_token_t,
+    ) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        write_atomically(
+            &self.entry_path(enclave_path.as_ref()),
+            &[&mr_enclave.m[..], &launch_token[..]],
+        )
+    }
+
+    ///
+    /// Stores the launch token after the enclave is created, unless the cached entry is
+    /// already up to date. Failures are ignored, as the uRTS then simply gets a new token.
+    ///
+    pub(crate) fn update(
+        &self,
+        enclave_path: &Path,
+        mr_enclave: &sgx_measurement_t,
+        launch_token: &sgx_launch_token_t,
+        launch_token_updated: bool,
+    ) {
+        if launch_token_updated || self.get(enclave_path, mr_enclave).is_none() {
+            let _ = self.store(enclave_path, mr_enclave, launch_token);
+        }
+    }
+
+    // The entry is named after the enclave file, with a hash of its canonical path so that
+    // enclaves with the same file name in different directories do not share an entry.
+    fn entry_path(&self, enclave_path: &Path) -> PathBuf {
+        let path = fs::canonicalize(enclave_path).unwrap_or_else(|_| enclave_path.to_owned());
+        // 64-bit FNV-1a, which is stable across builds unlike the std hashers.
+        let hash = path
+            .as_os_str()
+            .as_bytes()
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, &b| {
+                (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+            });
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.dir.join(format!("{}-{:016x}.token", name, hash))
+    }
+}
+
+///
+/// The MRENCLAVE in the SIGSTRUCT of the enclave file, or None if the file cannot be read.
+///
+pub(crate) fn signed_mr_enclave(enclave_path: &Path) -> Option<sgx_measurement_t> {
+    rsgx_get_metadata(enclave_path)
+        .ok()
+        .map(|metadata| rsgx_metadata_mrenclave(&metadata))
+}
+
+///
+/// Writes the file through a temporary file in the same directory, renamed over it once
+/// its contents are synced, so that readers never see a partially written file.
+///
+pub(crate) fn write_atomically(path: &Path, contents: &[&[u8]]) -> io::Result<()> {
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(format!(
+        ".tmp.{}.{}",
+        process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp_path = PathBuf::from(tmp_name);
+
+    let result = File::create(&tmp_path).and_then(|mut file| {
+        for part in contents {
+            file.write_all(part)?;
+        }
+        file.sync_all()
+    });
+    match result.and_then(|_| fs::rename(&tmp_path, path)) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::sync::Arc;
+    use std::thread;
+
+    // A fresh directory holding an enclave file and a cache directory.
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Scratch {
+            let dir = env::temp_dir().join(format!("sgx_urts-{}-{}", name, process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(dir.join("enclaves")).unwrap();
+            fs::write(dir.join("enclaves/enclave.signed.so"), b"enclave").unwrap();
+            Scratch(dir)
+        }
+
+        fn enclave(&self) -> PathBuf {
+            self.0.join("enclaves/enclave.signed.so")
+        }
+
+        fn cache(&self) -> LaunchTokenCache {
+            LaunchTokenCache::new(self.0.join("cache"))
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn mr_enclave(b: u8) -> sgx_measurement_t {
+        sgx_measurement_t {
+            m: [b; SGX_HASH_SIZE],
+        }
+    }
+
+    #[test]
+    fn get_checks_the_mr_enclave() {
+        let scratch = Scratch::new("token-get");
+        let cache = scratch.cache();
+        assert!(cache.load(scratch.enclave()).is_none());
+
+        let token = [7; LAUNCH_TOKEN_SIZE];
+        cache
+            .store(scratch.enclave(), &mr_enclave(1), &token)
+            .unwrap();
+        let (cached, cached_token) = cache.load(scratch.enclave()).unwrap();
+        assert_eq!(cached.m, mr_enclave(1).m);
+        assert_eq!(cached_token[..], token[..]);
+        assert_eq!(
+            cache.get(scratch.enclave(), &mr_enclave(1)).unwrap()[..],
+            token[..]
+        );
+        // The token of a previous build is not used.
+        assert!(cache.get(scratch.enclave(), &mr_enclave(2)).is_none());
+    }
+
+    #[test]
+    fn entries_are_per_enclave_path() {
+        let scratch = Scratch::new("token-path");
+        let cache = scratch.cache();
+        let other = scratch.0.join("enclave.signed.so");
+        fs::write(&other, b"other enclave").unwrap();
+
+        cache
+            .store(scratch.enclave(), &mr_enclave(1), &[1; LAUNCH_TOKEN_SIZE])
+            .unwrap();
+        cache
+            .store(&other, &mr_enclave(2), &[2; LAUNCH_TOKEN_SIZE])
+            .unwrap();
+        assert_eq!(cache.load(scratch.enclave()).unwrap().1[0], 1);
+        assert_eq!(cache.load(&other).unwrap().1[0], 2);
+        // A relative path of the same file shares the entry.
+        let relative = scratch.0.join("enclaves/../enclaves/enclave.signed.so");
+        assert_eq!(cache.load(relative).unwrap().1[0], 1);
+    }
+
+    #[test]
+    fn malformed_entries_are_ignored() {
+        let scratch = Scratch::new("token-malformed");
+        let cache = scratch.cache();
+        cache
+            .store(scratch.enclave(), &mr_enclave(1), &[1; LAUNCH_TOKEN_SIZE])
+            .unwrap();
+        let entry = cache.entry_path(&scratch.enclave());
+        fs::write(&entry, [0; ENTRY_SIZE - 1]).unwrap();
+        assert!(cache.load(scratch.enclave()).is_none());
+    }
+
+    #[test]
+    fn update_stores_new_and_updated_tokens() {
+        let scratch = Scratch::new("token-update");
+        let cache = scratch.cache();
+        let enclave = scratch.enclave();
+
+        cache.update(&enclave, &mr_enclave(1), &[1; LAUNCH_TOKEN_SIZE], false);
+        assert_eq!(cache.get(&enclave, &mr_enclave(1)).unwrap()[0], 1);
+        // An up to date entry is left alone unless the uRTS updated the token.
+        cache.update(&enclave, &mr_enclave(1), &[2; LAUNCH_TOKEN_SIZE], false);
+        assert_eq!(cache.get(&enclave, &mr_enclave(1)).unwrap()[0], 1);
+        cache.update(&enclave, &mr_enclave(1), &[3; LAUNCH_TOKEN_SIZE], true);
+        assert_eq!(cache.get(&enclave, &mr_enclave(1)).unwrap()[0], 3);
+        // A rebuilt enclave replaces the entry.
+        cache.update(&enclave, &mr_enclave(2), &[4; LAUNCH_TOKEN_SIZE], false);
+        assert_eq!(cache.get(&enclave, &mr_enclave(2)).unwrap()[0], 4);
+    }
+
+    #[test]
+    fn concurrent_stores_leave_a_whole_entry() {
+        let scratch = Scratch::new("token-concurrent");
+        let cache = Arc::new(scratch.cache());
+        let enclave = Arc::new(scratch.enclave());
+        let writers: Vec<_> = (0..8_u8)
+            .map(|i| {
+                let (cache, enclave) = (cache.clone(), enclave.clone());
+                thread::spawn(move || {
+                    for _ in 0..16 {
+                        cache
+                            .store(&*enclave, &mr_enclave(i), &[i; LAUNCH_TOKEN_SIZE])
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let (cached, token) = cache.load(&*enclave).unwrap();
+        assert!(token.iter().all(|&b| b == cached.m[0]));
+        // No temporary file is left behind.
+        assert_eq!(fs::read_dir(cache.dir()).unwrap().count(), 1);
+    }
+}